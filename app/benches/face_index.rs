@@ -0,0 +1,86 @@
+//! Gallery search throughput at 10k and 100k identities.
+//!
+//!     cargo bench --bench face_index
+
+use app::face_index::{FaceIndex, HnswParams, IndexEntry, EMBEDDING_DIM};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+const GALLERY_SIZES: [usize; 2] = [10_000, 100_000];
+
+/// Deterministic xorshift so runs are comparable without pulling in `rand`.
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+    }
+
+    fn vector(&mut self) -> Vec<f32> {
+        (0..EMBEDDING_DIM).map(|_| self.next_f32()).collect()
+    }
+}
+
+fn build(index: &mut FaceIndex, size: usize, rng: &mut Xorshift) {
+    for id in 0..size as u32 {
+        let entry = IndexEntry {
+            embedding_id: id,
+            criminal_id: id,
+            photo_id: id,
+        };
+        index.insert(entry, &rng.vector()).unwrap();
+    }
+}
+
+fn bench_search(c: &mut Criterion) {
+    let mut group = c.benchmark_group("face_index_top10");
+    group.sample_size(20);
+
+    for size in GALLERY_SIZES {
+        let mut rng = Xorshift(0x5EED_u64 + size as u64);
+        let query = rng.vector();
+
+        let mut brute = FaceIndex::new(EMBEDDING_DIM);
+        build(&mut brute, size, &mut rng);
+        group.bench_with_input(BenchmarkId::new("brute_force", size), &query, |b, q| {
+            b.iter(|| brute.search(black_box(q), 10).unwrap())
+        });
+        drop(brute);
+
+        let params = HnswParams {
+            ef_construction: 100,
+            ..HnswParams::default()
+        };
+        let mut hnsw = FaceIndex::new(EMBEDDING_DIM).with_hnsw(params);
+        build(&mut hnsw, size, &mut rng);
+        group.bench_with_input(BenchmarkId::new("hnsw", size), &query, |b, q| {
+            b.iter(|| hnsw.search(black_box(q), 10).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_insert_remove(c: &mut Criterion) {
+    let mut rng = Xorshift(0xC0FFEE);
+    let mut index = FaceIndex::new(EMBEDDING_DIM);
+    build(&mut index, GALLERY_SIZES[0], &mut rng);
+    let vector = rng.vector();
+    let entry = IndexEntry {
+        embedding_id: u32::MAX,
+        criminal_id: u32::MAX,
+        photo_id: u32::MAX,
+    };
+
+    c.bench_function("face_index_insert_remove_10000", |b| {
+        b.iter(|| {
+            index.insert(entry, black_box(&vector)).unwrap();
+            index.remove(entry.embedding_id)
+        })
+    });
+}
+
+criterion_group!(benches, bench_search, bench_insert_remove);
+criterion_main!(benches);
@@ -0,0 +1,235 @@
+use crate::components::{GlassButton, GlassTextInput};
+use crate::config::AlertConfig;
+use crate::database::{AlertEntry, CriminalDB};
+use crate::entities::watchlist;
+use crate::error::{self, AppError};
+use crate::Message;
+
+use iced::widget::{column, container, row, space, text};
+use iced::{Alignment, Background, Border, Color, Element, Length, Task, Theme};
+use std::io::Write;
+use std::sync::Arc;
+
+/// Watchlist alerts waiting for the operator, shown as a banner over every page.
+pub struct AlertCenter {
+    pub pending: Vec<AlertEntry>,
+    pub note: String,
+    pub error: Option<String>,
+    sound: AlertConfig,
+}
+
+impl AlertCenter {
+    pub fn new(sound: AlertConfig) -> Self {
+        Self {
+            pending: Vec::new(),
+            note: String::new(),
+            error: None,
+            sound,
+        }
+    }
+
+    pub fn update(&mut self, message: Message, db: Option<Arc<CriminalDB>>) -> Task<Message> {
+        match message {
+            Message::CheckWatchlists(criminal_id, source) => {
+                let Some(db) = db else {
+                    return Task::none();
+                };
+                return Task::perform(
+                    async move {
+                        db.raise_watchlist_alert(criminal_id, &source)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::AlertRaised,
+                );
+            }
+
+            Message::AlertRaised(Ok(Some(entry))) => {
+                play_alert_sound(&self.sound);
+                self.pending.push(entry);
+                self.sort();
+            }
+
+            Message::AlertRaised(Ok(None)) => {}
+
+            Message::AlertRaised(Err(e)) => {
+                return error::report(
+                    AppError::Database(format!("Watchlist check failed: {}", e)),
+                    None,
+                );
+            }
+
+            Message::LoadPendingAlerts => {
+                let Some(db) = db else {
+                    return Task::none();
+                };
+                return Task::perform(
+                    async move { db.get_pending_alerts().await.map_err(|e| e.to_string()) },
+                    Message::PendingAlertsLoaded,
+                );
+            }
+
+            Message::PendingAlertsLoaded(Ok(entries)) => {
+                // Left over from a previous session; still needs acknowledging
+                if !entries.is_empty() {
+                    play_alert_sound(&self.sound);
+                }
+                self.pending = entries;
+                self.sort();
+            }
+
+            Message::PendingAlertsLoaded(Err(e)) => {
+                return error::report(
+                    AppError::Database(format!("Could not load pending alerts: {}", e)),
+                    Some(Message::LoadPendingAlerts),
+                );
+            }
+
+            Message::AlertNoteChanged(value) => {
+                self.note = value;
+            }
+
+            Message::AcknowledgeAlert(alert_id) => {
+                let Some(db) = db else {
+                    self.error = Some("Database not connected".to_string());
+                    return Task::none();
+                };
+                let note = self.note.trim();
+                let note = (!note.is_empty()).then(|| note.to_string());
+                return Task::perform(
+                    async move {
+                        db.acknowledge_alert(alert_id, note)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    move |result| Message::AlertAcknowledged(alert_id, result),
+                );
+            }
+
+            Message::AlertAcknowledged(alert_id, Ok(())) => {
+                self.pending
+                    .retain(|entry| entry.alert.alert_id != alert_id);
+                self.note = String::new();
+                self.error = None;
+            }
+
+            Message::AlertAcknowledged(_, Err(e)) => {
+                self.error = Some(e);
+            }
+
+            _ => {}
+        }
+        Task::none()
+    }
+
+    /// Most urgent first, then oldest first.
+    fn sort(&mut self) {
+        self.pending.sort_by_key(|entry| {
+            (
+                watchlist::priority_rank(&entry.watchlist.priority),
+                entry.alert.alert_id,
+            )
+        });
+    }
+
+    /// Banner for the most urgent pending alert, if there is one.
+    pub fn view(&self) -> Option<Element<'_, Message>> {
+        let entry = self.pending.first()?;
+        let color = priority_color(&entry.watchlist.priority);
+        let alert = &entry.alert;
+
+        let mut headline = format!(
+            "WATCHLIST MATCH · {}",
+            watchlist::priority_label(&entry.watchlist.priority).to_uppercase()
+        );
+        if self.pending.len() > 1 {
+            headline.push_str(&format!("  (+{} more pending)", self.pending.len() - 1));
+        }
+
+        let details = column![
+            text(headline).size(14).color(color),
+            text(format!("#{}  {}", alert.criminal_id, entry.criminal_name))
+                .size(24)
+                .color(Color::WHITE),
+            text(format!(
+                "On \"{}\" · identified via {} at {}",
+                entry.watchlist.name,
+                alert.source,
+                alert.created_at.format("%Y-%m-%d %H:%M:%S")
+            ))
+            .size(13)
+            .color(Color::from_rgba(1.0, 1.0, 1.0, 0.7)),
+        ]
+        .spacing(4)
+        .width(Length::Fill);
+
+        let error: Element<Message> = match &self.error {
+            Some(e) => text(e.clone())
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.4, 0.4))
+                .into(),
+            None => space().height(0.0).into(),
+        };
+
+        let actions = column![
+            GlassTextInput::new("Action taken (optional)", &self.note)
+                .on_input(Message::AlertNoteChanged)
+                .on_submit(Message::AcknowledgeAlert(alert.alert_id)),
+            GlassButton::new("Acknowledge").on_press(Message::AcknowledgeAlert(alert.alert_id)),
+            error,
+        ]
+        .spacing(8)
+        .width(Length::Fixed(320.0));
+
+        Some(
+            container(
+                row![details, actions]
+                    .spacing(20)
+                    .align_y(Alignment::Center),
+            )
+            .padding(16)
+            .width(Length::Fill)
+            .style(move |_theme: &Theme| container::Style {
+                background: Some(Background::Color(Color { a: 0.25, ..color })),
+                border: Border {
+                    color,
+                    width: 2.0,
+                    radius: 12.0.into(),
+                },
+                ..Default::default()
+            })
+            .into(),
+        )
+    }
+}
+
+/// Banner / label colour for a watchlist priority.
+pub fn priority_color(priority: &str) -> Color {
+    match priority {
+        watchlist::CRITICAL => Color::from_rgb(0.95, 0.25, 0.25),
+        watchlist::HIGH => Color::from_rgb(0.95, 0.55, 0.2),
+        watchlist::MEDIUM => Color::from_rgb(0.95, 0.8, 0.3),
+        _ => Color::from_rgb(0.4, 0.7, 1.0),
+    }
+}
+
+/// Plays the configured alert sound without waiting for it to finish; falls
+/// back to the terminal bell when there is no player.
+fn play_alert_sound(config: &AlertConfig) {
+    if !config.sound_player.is_empty() {
+        match std::process::Command::new(&config.sound_player)
+            .arg(&config.sound_file)
+            .spawn()
+        {
+            Ok(mut child) => {
+                // Reap it so finished players don't linger as zombies
+                std::thread::spawn(move || child.wait());
+                return;
+            }
+            Err(e) => eprintln!("Could not play alert sound: {}", e),
+        }
+    }
+    let mut stdout = std::io::stdout();
+    let _ = stdout.write_all(b"\x07");
+    let _ = stdout.flush();
+}
@@ -0,0 +1,166 @@
+//! Embeds registered photos that have no face embedding yet.
+//!
+//!     cargo run --release --bin backfill_embeddings -- [--dry-run]
+//!
+//! People enrolled while the gallery lived in the engine's `faces_db.npz`
+//! have photos in `criminal_photos` but nothing in `face_embeddings`, so they
+//! never match. This runs every such photo through the engine configured in
+//! `config.toml` and stores the result, the same way enrollment does. Photos
+//! where the engine finds no face are listed and left as they are; running
+//! it again only picks up what is still missing.
+
+use app::config::AppConfig;
+use app::database::{sealed_data_stored, CriminalDB};
+use app::engine::spawn_engine;
+use app::python_process::{decode_embedding, encode_path, format_box, next_engine_line};
+use app::vault::{self, Vault};
+use app::workspace;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
+
+/// How long the engine may stay silent (model loading included).
+const REPLY_TIMEOUT: Duration = Duration::from_secs(300);
+
+#[tokio::main]
+async fn main() {
+    let dry_run = match std::env::args().skip(1).collect::<Vec<_>>().as_slice() {
+        [] => false,
+        [flag] if flag == "--dry-run" => true,
+        _ => {
+            eprintln!("usage: backfill_embeddings [--dry-run]");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(dry_run).await {
+        eprintln!("❌ {e}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(dry_run: bool) -> Result<(), String> {
+    let config = AppConfig::load();
+    if let Some(keys) = Vault::load(&config.encryption, || sealed_data_stored(&config))? {
+        vault::install(keys);
+    }
+    workspace::install(config.storage.workspace_root());
+    let db = CriminalDB::new(&config.database_url)
+        .await
+        .map_err(|e| e.to_string())?;
+    let db = match config.storage.photo_store() {
+        Some(store) => db.with_photo_store(store),
+        None => db,
+    };
+
+    let mut missing: BTreeMap<u32, HashSet<u32>> = BTreeMap::new();
+    for (criminal_id, photo_id) in db
+        .get_photos_without_embeddings()
+        .await
+        .map_err(|e| e.to_string())?
+    {
+        missing.entry(criminal_id).or_default().insert(photo_id);
+    }
+    let total: usize = missing.values().map(HashSet::len).sum();
+    println!(
+        "{} photos of {} people have no face embedding",
+        total,
+        missing.len()
+    );
+    if dry_run || total == 0 {
+        return Ok(());
+    }
+
+    let engine = spawn_engine(&config.engine)?;
+    engine.send("start").map_err(|e| e.to_string())?;
+    while next_line().await? != "ready" {}
+
+    let (mut stored, mut no_face, mut failed) = (0, Vec::new(), 0);
+    for (criminal_id, photo_ids) in missing {
+        let photos = match db.get_criminal_photos(criminal_id).await {
+            Ok(photos) => photos,
+            Err(e) => {
+                eprintln!("❌ Photos of #{}: {}", criminal_id, e);
+                failed += photo_ids.len();
+                continue;
+            }
+        };
+
+        // The engine reads each photo from a temp file, deleted once it's embedded
+        let mut pending = HashMap::new();
+        let mut commands = Vec::new();
+        for photo in photos {
+            if !photo_ids.contains(&photo.photo_id) || photo.photo.is_none() {
+                continue;
+            }
+            let file = workspace::current().allocate("backfill", "jpg");
+            if let Err(e) = vault::write(file.path(), photo.bytes().to_vec()) {
+                eprintln!("❌ Photo {}: {}", photo.photo_id, e);
+                failed += 1;
+                continue;
+            }
+            let tag = format!("backfill-{}", photo.photo_id);
+            commands.push(format!(
+                "embed {} {} {}",
+                tag,
+                encode_path(&file.path().to_string_lossy()),
+                format_box(photo.face_box())
+            ));
+            pending.insert(tag, (photo.photo_id, file));
+        }
+        failed += photo_ids.len() - pending.len();
+        engine.send_batch(commands);
+
+        while !pending.is_empty() {
+            let line = next_line().await?;
+            let ["probe", tag, vector] = line.split_whitespace().collect::<Vec<_>>()[..] else {
+                continue;
+            };
+            let Some((photo_id, _file)) = pending.remove(tag) else {
+                continue;
+            };
+            let Some(vector) = decode_embedding(vector) else {
+                no_face.push(photo_id);
+                continue;
+            };
+            match db
+                .add_face_embedding(
+                    photo_id,
+                    criminal_id,
+                    config.engine.model_name.clone(),
+                    &vector,
+                )
+                .await
+            {
+                Ok(_) => stored += 1,
+                Err(e) => {
+                    eprintln!("❌ Embedding of photo {}: {}", photo_id, e);
+                    failed += 1;
+                }
+            }
+        }
+        println!("  #{} done, {} / {} stored", criminal_id, stored, total);
+    }
+    let _ = engine.send("exit");
+    let _ = workspace::current().clear();
+
+    println!("Stored {} embeddings, {} failed", stored, failed);
+    if !no_face.is_empty() {
+        println!(
+            "No face found in {} photos (ids {:?}); enroll better photos of these people",
+            no_face.len(),
+            no_face
+        );
+    }
+    Ok(())
+}
+
+async fn next_line() -> Result<String, String> {
+    match tokio::time::timeout(REPLY_TIMEOUT, next_engine_line()).await {
+        Ok(Some(line)) => Ok(line),
+        Ok(None) => Err("the engine closed its output".to_string()),
+        Err(_) => Err(format!(
+            "no reply from the engine in {}s",
+            REPLY_TIMEOUT.as_secs()
+        )),
+    }
+}
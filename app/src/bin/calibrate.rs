@@ -0,0 +1,468 @@
+//! Measures the match threshold against a labelled evaluation set.
+//!
+//!     cargo run --release --bin calibrate -- <eval_dir> [--target-far 0.001]
+//!         [--out <dir>] [--write-config]
+//!
+//! `<eval_dir>/gallery/<person>/` holds the photos enrolled for each person
+//! and `<eval_dir>/probes/<person>/` the photos to identify. Probes of people
+//! with no gallery folder only ever produce impostor scores. Every image is
+//! embedded by the engine configured in `config.toml`, so the numbers match
+//! what the app sees.
+
+use app::calibration::{self, OperatingPoint};
+use app::config::AppConfig;
+use app::engine::spawn_engine;
+use app::face_index::{FaceIndex, IndexEntry, EMBEDDING_DIM};
+use app::preprocess::{prepare_image, IMAGE_EXTENSIONS};
+use app::python_process::{decode_embedding, encode_path, next_engine_line};
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How long the engine may stay silent (model loading included).
+const REPLY_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// Thresholds always listed in the report next to the current and recommended ones.
+const CANDIDATES: [f32; 7] = [0.30, 0.35, 0.40, 0.45, 0.50, 0.55, 0.60];
+
+struct Options {
+    eval_dir: PathBuf,
+    out_dir: PathBuf,
+    target_far: f32,
+    write_config: bool,
+}
+
+struct Sample {
+    person: String,
+    path: PathBuf,
+}
+
+/// What the engine made of the evaluation set.
+struct Scores {
+    enrolled: usize,
+    gallery_images: usize,
+    probes: usize,
+    no_face: Vec<PathBuf>,
+    genuine: Vec<f32>,
+    impostor: Vec<f32>,
+    // Rank of the probe's own identity, for probes of enrolled people
+    ranks: Vec<Option<usize>>,
+}
+
+#[tokio::main]
+async fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!(
+                "usage: calibrate <eval_dir> [--target-far 0.001] [--out <dir>] [--write-config]"
+            );
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(options).await {
+        eprintln!("❌ {e}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(options: Options) -> Result<(), String> {
+    let config = AppConfig::load();
+    let gallery = list_samples(&options.eval_dir.join("gallery"))?;
+    let probes = list_samples(&options.eval_dir.join("probes"))?;
+    if gallery.is_empty() || probes.is_empty() {
+        return Err(format!(
+            "{} needs gallery/<person>/ and probes/<person>/ folders with images",
+            options.eval_dir.display()
+        ));
+    }
+
+    println!(
+        "Embedding {} gallery and {} probe images with the {} engine...",
+        gallery.len(),
+        probes.len(),
+        config.engine.model_name
+    );
+    let paths: Vec<&Path> = gallery
+        .iter()
+        .chain(&probes)
+        .map(|s| s.path.as_path())
+        .collect();
+    let embeddings = embed_all(&config, &paths).await?;
+    let (gallery_embeddings, probe_embeddings) = embeddings.split_at(gallery.len());
+
+    let scores = score(&gallery, gallery_embeddings, &probes, probe_embeddings);
+    if scores.genuine.is_empty() || scores.impostor.is_empty() {
+        return Err(format!(
+            "need both genuine and impostor scores (got {} and {}); add probes of enrolled and of other people",
+            scores.genuine.len(),
+            scores.impostor.len()
+        ));
+    }
+
+    let curve = calibration::sweep(&scores.genuine, &scores.impostor);
+    let recommended = calibration::recommend(&curve, options.target_far);
+    let report = report(&scores, &curve, recommended, &config, &options);
+
+    std::fs::create_dir_all(&options.out_dir).map_err(|e| e.to_string())?;
+    let report_path = options.out_dir.join("calibration_report.md");
+    std::fs::write(&report_path, &report).map_err(|e| e.to_string())?;
+    let curve_path = options.out_dir.join("roc.csv");
+    std::fs::write(&curve_path, curve_csv(&curve)).map_err(|e| e.to_string())?;
+    println!("{report}");
+    println!(
+        "Report written to {}, curves to {}",
+        report_path.display(),
+        curve_path.display()
+    );
+
+    match (recommended, options.write_config) {
+        (Some(point), true) => {
+            let path = AppConfig::path();
+            AppConfig::save_match_threshold(&path, point.threshold)?;
+            println!(
+                "✅ matching.threshold = {:.3} written to {}",
+                point.threshold,
+                path.display()
+            );
+        }
+        (None, true) => {
+            return Err(format!(
+                "no threshold reaches a false accept rate of {}; config left unchanged",
+                options.target_far
+            ))
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut eval_dir = None;
+    let mut out_dir = None;
+    let mut target_far = 0.001;
+    let mut write_config = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--target-far" => {
+                let value = args.next().ok_or("--target-far needs a value")?;
+                target_far = value
+                    .parse()
+                    .ok()
+                    .filter(|far| (0.0..=1.0).contains(far))
+                    .ok_or_else(|| format!("invalid false accept rate: {value}"))?;
+            }
+            "--out" => out_dir = Some(PathBuf::from(args.next().ok_or("--out needs a folder")?)),
+            "--write-config" => write_config = true,
+            flag if flag.starts_with("--") => return Err(format!("unknown option {flag}")),
+            path => eval_dir = Some(PathBuf::from(path)),
+        }
+    }
+
+    let eval_dir = eval_dir.ok_or("missing the evaluation folder")?;
+    Ok(Options {
+        out_dir: out_dir.unwrap_or_else(|| eval_dir.join("calibration")),
+        eval_dir,
+        target_far,
+        write_config,
+    })
+}
+
+/// Images in each `<root>/<person>/` folder, sorted for a stable report.
+fn list_samples(root: &Path) -> Result<Vec<Sample>, String> {
+    let mut samples = Vec::new();
+    let people = std::fs::read_dir(root).map_err(|e| format!("{}: {e}", root.display()))?;
+    for person in people.flatten().filter(|e| e.path().is_dir()) {
+        let name = person.file_name().to_string_lossy().to_string();
+        let files = std::fs::read_dir(person.path()).map_err(|e| e.to_string())?;
+        for file in files.flatten() {
+            let path = file.path();
+            let is_image = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+            if is_image {
+                samples.push(Sample {
+                    person: name.clone(),
+                    path,
+                });
+            }
+        }
+    }
+    samples.sort_by(|a, b| (&a.person, &a.path).cmp(&(&b.person, &b.path)));
+    Ok(samples)
+}
+
+/// One embedding per path, `None` where the engine found no face.
+async fn embed_all(config: &AppConfig, paths: &[&Path]) -> Result<Vec<Option<Vec<f32>>>, String> {
+    let engine = spawn_engine(&config.engine)?;
+    engine.send("start").map_err(|e| e.to_string())?;
+    wait_for(|line| line == "ready").await?;
+
+    let mut commands = Vec::new();
+    let mut pending = HashMap::new();
+    // Converted copies are deleted when dropped, so hold them until the engine is done
+    let mut prepared_images = Vec::new();
+    for (i, path) in paths.iter().enumerate() {
+        // The engine's working directory differs
+        let prepared = prepare_image(path)
+            .map_err(|e| e.to_string())
+            .and_then(|image| {
+                let canonical = std::fs::canonicalize(image.path()).map_err(|e| e.to_string())?;
+                prepared_images.push(image);
+                Ok(canonical)
+            });
+        match prepared {
+            Ok(prepared) => {
+                let tag = format!("eval-{i}");
+                let path = encode_path(&prepared.to_string_lossy());
+                commands.push(format!("embed {} {}", tag, path));
+                pending.insert(tag, i);
+            }
+            Err(e) => eprintln!("Skipping {}: {e}", path.display()),
+        }
+    }
+    engine.send_batch(commands);
+
+    let mut embeddings = vec![None; paths.len()];
+    while !pending.is_empty() {
+        let line = next_line().await?;
+        if let ["probe", tag, vector] = line.split_whitespace().collect::<Vec<_>>()[..] {
+            if let Some(i) = pending.remove(tag) {
+                embeddings[i] = decode_embedding(vector);
+                let done = paths.len() - pending.len();
+                if done.is_multiple_of(50) {
+                    println!("  {done} / {}", paths.len());
+                }
+            }
+        }
+    }
+    let _ = engine.send("exit");
+    Ok(embeddings)
+}
+
+async fn wait_for(done: impl Fn(&str) -> bool) -> Result<(), String> {
+    while !done(&next_line().await?) {}
+    Ok(())
+}
+
+async fn next_line() -> Result<String, String> {
+    match tokio::time::timeout(REPLY_TIMEOUT, next_engine_line()).await {
+        Ok(Some(line)) => Ok(line),
+        Ok(None) => Err("the engine closed its output".to_string()),
+        Err(_) => Err(format!(
+            "no reply from the engine in {}s",
+            REPLY_TIMEOUT.as_secs()
+        )),
+    }
+}
+
+/// Scores every probe against every enrolled person, the way the app does:
+/// a person's score is their best-matching gallery photo.
+fn score(
+    gallery: &[Sample],
+    gallery_embeddings: &[Option<Vec<f32>>],
+    probes: &[Sample],
+    probe_embeddings: &[Option<Vec<f32>>],
+) -> Scores {
+    let mut ids: BTreeMap<&str, u32> = BTreeMap::new();
+    let mut index = FaceIndex::new(EMBEDDING_DIM);
+    let mut no_face = Vec::new();
+
+    for (i, (sample, embedding)) in gallery.iter().zip(gallery_embeddings).enumerate() {
+        let Some(embedding) = embedding else {
+            no_face.push(sample.path.clone());
+            continue;
+        };
+        let next_id = ids.len() as u32;
+        let criminal_id = *ids.entry(sample.person.as_str()).or_insert(next_id);
+        let entry = IndexEntry {
+            embedding_id: i as u32,
+            criminal_id,
+            photo_id: i as u32,
+        };
+        if let Err(e) = index.insert(entry, embedding) {
+            eprintln!("Skipping {}: {e}", sample.path.display());
+        }
+    }
+
+    let mut scores = Scores {
+        enrolled: ids.len(),
+        gallery_images: index.len(),
+        probes: 0,
+        no_face: Vec::new(),
+        genuine: Vec::new(),
+        impostor: Vec::new(),
+        ranks: Vec::new(),
+    };
+
+    for (sample, embedding) in probes.iter().zip(probe_embeddings) {
+        let Some(embedding) = embedding else {
+            no_face.push(sample.path.clone());
+            continue;
+        };
+        scores.probes += 1;
+        let own = ids.get(sample.person.as_str()).copied();
+
+        let mut ranked: Vec<(u32, f32)> = ids
+            .values()
+            .filter_map(|&id| {
+                let best = index.scores_for_criminal(embedding, id).ok()?;
+                best.first().map(|hit| (id, hit.score))
+            })
+            .collect();
+        ranked.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+        for &(id, score) in &ranked {
+            if Some(id) == own {
+                scores.genuine.push(score);
+            } else {
+                scores.impostor.push(score);
+            }
+        }
+        if let Some(own) = own {
+            scores
+                .ranks
+                .push(ranked.iter().position(|&(id, _)| id == own).map(|r| r + 1));
+        }
+    }
+
+    scores.no_face = no_face;
+    scores
+}
+
+fn report(
+    scores: &Scores,
+    curve: &[OperatingPoint],
+    recommended: Option<OperatingPoint>,
+    config: &AppConfig,
+    options: &Options,
+) -> String {
+    let current = OperatingPoint::at(&scores.genuine, &scores.impostor, config.matching.threshold);
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# Threshold calibration\n");
+    let _ = writeln!(
+        out,
+        "Evaluation set `{}`, model `{}`, {}\n",
+        options.eval_dir.display(),
+        config.engine.model_name,
+        chrono::Local::now().format("%Y-%m-%d %H:%M")
+    );
+    let _ = writeln!(out, "- People enrolled: {}", scores.enrolled);
+    let _ = writeln!(out, "- Gallery images: {}", scores.gallery_images);
+    let _ = writeln!(out, "- Probes scored: {}", scores.probes);
+    let _ = writeln!(
+        out,
+        "- Images skipped or with no face: {}",
+        scores.no_face.len()
+    );
+    let _ = writeln!(out, "- Genuine scores: {}", scores.genuine.len());
+    let _ = writeln!(out, "- Impostor scores: {}\n", scores.impostor.len());
+
+    let _ = writeln!(out, "## Identification\n");
+    let _ = writeln!(
+        out,
+        "Over {} probes of enrolled people:\n",
+        scores.ranks.len()
+    );
+    for k in [1, 5] {
+        let _ = writeln!(
+            out,
+            "- Rank-{k} accuracy: {:.1}%",
+            calibration::rank_accuracy(&scores.ranks, k) * 100.0
+        );
+    }
+
+    let _ = writeln!(out, "\n## Verification\n");
+    if let Some(eer) = calibration::equal_error_point(curve) {
+        let _ = writeln!(
+            out,
+            "Equal error rate ≈ {:.2}% at threshold {:.3}\n",
+            (eer.far + eer.frr) / 2.0 * 100.0,
+            eer.threshold
+        );
+    }
+
+    let mut rows: Vec<(OperatingPoint, &str)> = CANDIDATES
+        .iter()
+        .map(|&t| (OperatingPoint::at(&scores.genuine, &scores.impostor, t), ""))
+        .collect();
+    rows.push((current, "current"));
+    if let Some(point) = recommended {
+        rows.push((point, "recommended"));
+    }
+    rows.sort_by(|a, b| a.0.threshold.total_cmp(&b.0.threshold));
+
+    let _ = writeln!(out, "| Threshold | FAR | FRR | TAR | |");
+    let _ = writeln!(out, "|---|---|---|---|---|");
+    for (point, note) in rows {
+        let _ = writeln!(
+            out,
+            "| {:.3} | {:.3}% | {:.2}% | {:.2}% | {} |",
+            point.threshold,
+            point.far * 100.0,
+            point.frr * 100.0,
+            point.tar() * 100.0,
+            note
+        );
+    }
+
+    let _ = writeln!(out, "\n## Recommendation\n");
+    match recommended {
+        Some(point) => {
+            let _ = writeln!(
+                out,
+                "Threshold **{:.3}** keeps false accepts at or below {}% ({:.3}%) \
+                 while rejecting {:.2}% of genuine matches.",
+                point.threshold,
+                options.target_far * 100.0,
+                point.far * 100.0,
+                point.frr * 100.0
+            );
+        }
+        None => {
+            let _ = writeln!(
+                out,
+                "No threshold reaches a false accept rate of {}%.",
+                options.target_far * 100.0
+            );
+        }
+    }
+    let floor = 1.0 / scores.impostor.len() as f32;
+    if options.target_far < floor {
+        let _ = writeln!(
+            out,
+            "\nWith {} impostor scores the smallest measurable FAR is {:.4}%; \
+             add more people to trust the target.",
+            scores.impostor.len(),
+            floor * 100.0
+        );
+    }
+
+    let _ = writeln!(
+        out,
+        "\nroc.csv has the full sweep: plot `tar` against `far` for the ROC \
+         curve, `frr` against `far` for the DET curve."
+    );
+    out
+}
+
+fn curve_csv(curve: &[OperatingPoint]) -> String {
+    let mut csv = String::from("threshold,far,frr,tar\n");
+    for point in curve {
+        let _ = writeln!(
+            csv,
+            "{:.3},{:.6},{:.6},{:.6}",
+            point.threshold,
+            point.far,
+            point.frr,
+            point.tar()
+        );
+    }
+    csv
+}
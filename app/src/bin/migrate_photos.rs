@@ -0,0 +1,221 @@
+//! Moves photos out of `criminal_photos.photo` into the photo store.
+//!
+//!     cargo run --release --bin migrate_photos -- [--batch 100] [--prune] [--dry-run]
+//!
+//! Needs `[storage] photo_dir` in `config.toml` and the `photo_hash` and
+//! `photo_size` columns from `mysql.sql`; on an older database:
+//!
+//!     ALTER TABLE criminal_photos
+//!         MODIFY photo LONGBLOB NULL,
+//!         ADD COLUMN photo_hash CHAR(64) NULL AFTER photo,
+//!         ADD COLUMN photo_size INT(10) UNSIGNED NULL AFTER photo_hash,
+//!         ADD KEY photo_hash (photo_hash);
+//!
+//! Each blob is written to the store and read back before it is cleared in
+//! SQL, so an interrupted run can simply be started again. `--prune` deletes
+//! store files no photo refers to any more; run it with the app closed. With
+//! `[encryption]` configured the files are written sealed.
+
+use app::config::AppConfig;
+use app::database::{sealed_data_stored, CriminalDB};
+use app::entities::criminal_photo;
+use app::photo_store::PhotoStore;
+use app::vault::{self, Vault};
+use sea_orm::*;
+use std::collections::HashSet;
+
+struct Options {
+    batch: u64,
+    prune: bool,
+    dry_run: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("usage: migrate_photos [--batch 100] [--prune] [--dry-run]");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(options).await {
+        eprintln!("❌ {e}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(options: Options) -> Result<(), String> {
+    let config = AppConfig::load();
+    if let Some(keys) = Vault::load(&config.encryption, || sealed_data_stored(&config))? {
+        vault::install(keys);
+        // Blobs from before encryption was turned on are moved too, and sealed
+        vault::allow_plaintext();
+    }
+    let store = config
+        .storage
+        .photo_store()
+        .ok_or("set [storage] photo_dir in the config first")?;
+    let db = CriminalDB::new(&config.database_url)
+        .await
+        .map_err(|e| e.to_string())?;
+
+    migrate(&db, &store, &options).await?;
+    if options.prune {
+        prune(&db, &store, &options).await?;
+    }
+    Ok(())
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        batch: 100,
+        prune: false,
+        dry_run: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--batch" => {
+                let value = args.next().ok_or("--batch needs a value")?;
+                options.batch = value
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid batch size: {value}"))?;
+            }
+            "--prune" => options.prune = true,
+            "--dry-run" => options.dry_run = true,
+            other => return Err(format!("unknown option {other}")),
+        }
+    }
+    Ok(options)
+}
+
+async fn migrate(db: &CriminalDB, store: &PhotoStore, options: &Options) -> Result<(), String> {
+    let connection = db.connection.as_ref();
+    let (mut moved, mut deduplicated, mut failed, mut bytes) = (0, 0, 0, 0u64);
+    let mut last_id = 0;
+
+    loop {
+        // Paged by id so a photo that fails isn't picked up again
+        let photos = criminal_photo::Entity::find()
+            .filter(criminal_photo::Column::PhotoId.gt(last_id))
+            .filter(criminal_photo::Column::PhotoHash.is_null())
+            .filter(criminal_photo::Column::Photo.is_not_null())
+            .order_by_asc(criminal_photo::Column::PhotoId)
+            .limit(options.batch)
+            .all(connection)
+            .await
+            .map_err(|e| e.to_string())?;
+        let Some(last) = photos.last() else {
+            break;
+        };
+        last_id = last.photo_id;
+
+        for photo in photos {
+            let photo_id = photo.photo_id;
+            let plain = match vault::open(photo.bytes().to_vec()) {
+                Ok(plain) => plain,
+                Err(e) => {
+                    eprintln!("❌ Photo {}: {}", photo_id, e);
+                    failed += 1;
+                    continue;
+                }
+            };
+            let size = plain.len();
+            let hash = PhotoStore::hash(&plain);
+            let already_stored = store.contains(&hash);
+
+            if options.dry_run {
+                println!("would move photo {} ({} bytes) to {}", photo_id, size, hash);
+            } else if let Err(e) = move_photo(connection, store, photo, &plain).await {
+                eprintln!("❌ Photo {}: {}", photo_id, e);
+                failed += 1;
+                continue;
+            }
+            moved += 1;
+            bytes += size as u64;
+            if already_stored {
+                deduplicated += 1;
+            }
+        }
+        println!("  {} photos so far", moved);
+    }
+
+    println!(
+        "{} {} photos ({:.1} MB), {} identical to one already stored, {} failed",
+        if options.dry_run {
+            "Would move"
+        } else {
+            "Moved"
+        },
+        moved,
+        bytes as f64 / 1_048_576.0,
+        deduplicated,
+        failed
+    );
+    Ok(())
+}
+
+async fn move_photo(
+    connection: &DatabaseConnection,
+    store: &PhotoStore,
+    photo: criminal_photo::Model,
+    bytes: &[u8],
+) -> Result<(), String> {
+    let hash = store.put(bytes).await.map_err(|e| e.to_string())?;
+    // Only clear the blob once the stored copy reads back intact
+    store.get(&hash).await.map_err(|e| e.to_string())?;
+
+    let size = u32::try_from(bytes.len()).ok();
+    let mut active: criminal_photo::ActiveModel = photo.into();
+    active.photo = Set(None);
+    active.photo_hash = Set(Some(hash));
+    active.photo_size = Set(size);
+    active
+        .update(connection)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+async fn prune(db: &CriminalDB, store: &PhotoStore, options: &Options) -> Result<(), String> {
+    let referenced: HashSet<String> = criminal_photo::Entity::find()
+        .select_only()
+        .column(criminal_photo::Column::PhotoHash)
+        .filter(criminal_photo::Column::PhotoHash.is_not_null())
+        .distinct()
+        .into_tuple::<String>()
+        .all(db.connection.as_ref())
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .collect();
+
+    let mut removed = 0;
+    for hash in store.hashes().map_err(|e| e.to_string())? {
+        if referenced.contains(&hash) {
+            continue;
+        }
+        if options.dry_run {
+            println!("would delete {}", store.path(&hash).display());
+        } else if let Err(e) = store.remove(&hash).await {
+            eprintln!("❌ {}: {}", store.path(&hash).display(), e);
+            continue;
+        }
+        removed += 1;
+    }
+    println!(
+        "{} {} unreferenced files from {}",
+        if options.dry_run {
+            "Would delete"
+        } else {
+            "Deleted"
+        },
+        removed,
+        store.root().display()
+    );
+    Ok(())
+}
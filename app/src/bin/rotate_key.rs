@@ -0,0 +1,339 @@
+//! Re-encrypts everything stored at rest under a new data key.
+//!
+//!     cargo run --release --bin rotate_key -- [--batch 100] [--no-new-key] [--dry-run]
+//!
+//! Needs `[encryption]` in `config.toml`. The new key is saved in front of the
+//! old ones before anything is touched, so every photo stays readable however
+//! far a run gets. Photos, thumbnails, probe images, embeddings, photo store
+//! files and records waiting in the outbox not yet sealed with the new key are
+//! sealed with it, including ones stored before encryption was turned on; once
+//! none are left under the old keys, those are dropped.
+//!
+//! `--no-new-key` only does the re-encrypting, to finish an interrupted run or
+//! to encrypt an existing database the first time. Run it with the app closed.
+
+use app::config::AppConfig;
+use app::database::{sealed_data_stored, CriminalDB};
+use app::entities::{criminal_photo, face_embedding, identification};
+use app::photo_store::PhotoStore;
+use app::vault::{self, Vault};
+use sea_orm::sea_query::Expr;
+use sea_orm::*;
+use std::path::Path;
+
+struct Options {
+    batch: u64,
+    new_key: bool,
+    dry_run: bool,
+}
+
+/// What happened to one kind of data.
+#[derive(Default)]
+struct Counts {
+    current: usize,
+    resealed: usize,
+    failed: usize,
+}
+
+#[tokio::main]
+async fn main() {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("usage: rotate_key [--batch 100] [--no-new-key] [--dry-run]");
+            std::process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(options).await {
+        eprintln!("❌ {e}");
+        std::process::exit(1);
+    }
+}
+
+async fn run(options: Options) -> Result<(), String> {
+    let config = AppConfig::load();
+    let existing = Vault::load(&config.encryption, || sealed_data_stored(&config))?
+        .ok_or("set [encryption] key_file or keyring in the config first")?;
+
+    let keys = if options.new_key && !options.dry_run {
+        let rotated = existing.rotated();
+        rotated.save(&config.encryption)?;
+        println!(
+            "New data key {}; keeping {} until everything is re-encrypted",
+            rotated.key_ids()[0],
+            existing.key_ids().join(", ")
+        );
+        rotated
+    } else {
+        existing
+    };
+    vault::install(keys.clone());
+    // Data stored before encryption was turned on is sealed here too
+    vault::allow_plaintext();
+
+    let db = CriminalDB::new(&config.database_url)
+        .await
+        .map_err(|e| e.to_string())?;
+    let connection = db.connection.as_ref();
+
+    let mut failed = 0;
+    let tables = [
+        (
+            "photos",
+            reseal_column::<criminal_photo::Entity>(
+                connection,
+                &keys,
+                &options,
+                criminal_photo::Column::PhotoId,
+                criminal_photo::Column::Photo,
+            )
+            .await?,
+        ),
+        (
+            "thumbnails",
+            reseal_column::<criminal_photo::Entity>(
+                connection,
+                &keys,
+                &options,
+                criminal_photo::Column::PhotoId,
+                criminal_photo::Column::Thumbnail,
+            )
+            .await?,
+        ),
+        (
+            "probe images",
+            reseal_column::<identification::Entity>(
+                connection,
+                &keys,
+                &options,
+                identification::Column::IdentificationId,
+                identification::Column::Probe,
+            )
+            .await?,
+        ),
+        (
+            "embeddings",
+            reseal_column::<face_embedding::Entity>(
+                connection,
+                &keys,
+                &options,
+                face_embedding::Column::EmbeddingId,
+                face_embedding::Column::Vector,
+            )
+            .await?,
+        ),
+    ];
+    for (what, counts) in &tables {
+        print_counts(what, counts, &options);
+        failed += counts.failed;
+    }
+    if let Some(store) = config.storage.photo_store() {
+        let counts = reseal_store(&store, &keys, &options).await?;
+        print_counts("photo store files", &counts, &options);
+        failed += counts.failed;
+    }
+    // Queued records, including ones set aside in failed/, sync after the old keys go
+    let mut counts = Counts::default();
+    reseal_dir(&config.storage.outbox_dir(), &keys, &options, &mut counts);
+    print_counts("outbox files", &counts, &options);
+    failed += counts.failed;
+
+    if options.dry_run {
+        return Ok(());
+    }
+    if failed > 0 {
+        println!(
+            "{} items could not be re-encrypted; the old keys are kept. Fix them and run again with --no-new-key",
+            failed
+        );
+    } else if keys.key_ids().len() > 1 {
+        keys.current_only().save(&config.encryption)?;
+        println!("Dropped the old keys; restart the app to pick up the new one");
+    }
+    Ok(())
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut options = Options {
+        batch: 100,
+        new_key: true,
+        dry_run: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--batch" => {
+                let value = args.next().ok_or("--batch needs a value")?;
+                options.batch = value
+                    .parse()
+                    .ok()
+                    .filter(|&n| n > 0)
+                    .ok_or_else(|| format!("invalid batch size: {value}"))?;
+            }
+            "--no-new-key" => options.new_key = false,
+            "--dry-run" => options.dry_run = true,
+            other => return Err(format!("unknown option {other}")),
+        }
+    }
+    Ok(options)
+}
+
+/// Seals every `blob` of `E` not already under the current key, paged by `id`.
+async fn reseal_column<E: EntityTrait>(
+    connection: &DatabaseConnection,
+    keys: &Vault,
+    options: &Options,
+    id: E::Column,
+    blob: E::Column,
+) -> Result<Counts, String> {
+    let mut counts = Counts::default();
+    let mut last_id = 0;
+
+    loop {
+        let rows: Vec<(u32, Option<Vec<u8>>)> = E::find()
+            .select_only()
+            .column(id)
+            .column(blob)
+            .filter(id.gt(last_id))
+            .order_by_asc(id)
+            .limit(options.batch)
+            .into_tuple()
+            .all(connection)
+            .await
+            .map_err(|e| e.to_string())?;
+        let Some(&(last, _)) = rows.last() else {
+            break;
+        };
+        last_id = last;
+
+        for (row_id, bytes) in rows {
+            let Some(bytes) = bytes else {
+                continue;
+            };
+            let sealed = match keys.reseal(bytes) {
+                Ok(Some(sealed)) => sealed,
+                Ok(None) => {
+                    counts.current += 1;
+                    continue;
+                }
+                Err(e) => {
+                    eprintln!("❌ {} {}: {}", E::default().table_name(), row_id, e);
+                    counts.failed += 1;
+                    continue;
+                }
+            };
+            if !options.dry_run {
+                let updated = E::update_many()
+                    .col_expr(blob, Expr::value(sealed))
+                    .filter(id.eq(row_id))
+                    .exec(connection)
+                    .await;
+                if let Err(e) = updated {
+                    eprintln!("❌ {} {}: {}", E::default().table_name(), row_id, e);
+                    counts.failed += 1;
+                    continue;
+                }
+            }
+            counts.resealed += 1;
+        }
+    }
+    Ok(counts)
+}
+
+async fn reseal_store(
+    store: &PhotoStore,
+    keys: &Vault,
+    options: &Options,
+) -> Result<Counts, String> {
+    let mut counts = Counts::default();
+    for hash in store.hashes().map_err(|e| e.to_string())? {
+        let path = store.path(&hash);
+        let stored = match tokio::fs::read(&path).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                eprintln!("❌ {}: {}", path.display(), e);
+                counts.failed += 1;
+                continue;
+            }
+        };
+        if keys.is_current(&stored) {
+            counts.current += 1;
+            continue;
+        }
+        if !options.dry_run {
+            // `get` also checks the photo against its hash before it's rewritten
+            let rewritten = match store.get(&hash).await {
+                Ok(photo) => store.rewrite(&hash, &photo).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = rewritten {
+                eprintln!("❌ {}: {}", path.display(), e);
+                counts.failed += 1;
+                continue;
+            }
+        }
+        counts.resealed += 1;
+    }
+    Ok(counts)
+}
+
+/// Seals every file under `dir` not already under the current key. Each is
+/// replaced through a temp file, so a crash leaves the old or the new one.
+fn reseal_dir(dir: &Path, keys: &Vault, options: &Options, counts: &mut Counts) {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return,
+        Err(e) => {
+            eprintln!("❌ {}: {}", dir.display(), e);
+            counts.failed += 1;
+            return;
+        }
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.is_dir() {
+            reseal_dir(&path, keys, options, counts);
+            continue;
+        }
+        let resealed = std::fs::read(&path).and_then(|bytes| keys.reseal(bytes));
+        let sealed = match resealed {
+            Ok(Some(sealed)) => sealed,
+            Ok(None) => {
+                counts.current += 1;
+                continue;
+            }
+            Err(e) => {
+                eprintln!("❌ {}: {}", path.display(), e);
+                counts.failed += 1;
+                continue;
+            }
+        };
+        if !options.dry_run {
+            let temp = path.with_extension("reseal");
+            let written =
+                std::fs::write(&temp, sealed).and_then(|()| std::fs::rename(&temp, &path));
+            if let Err(e) = written {
+                let _ = std::fs::remove_file(&temp);
+                eprintln!("❌ {}: {}", path.display(), e);
+                counts.failed += 1;
+                continue;
+            }
+        }
+        counts.resealed += 1;
+    }
+}
+
+fn print_counts(what: &str, counts: &Counts, options: &Options) {
+    println!(
+        "{}: {} {}, {} already current, {} failed",
+        what,
+        if options.dry_run {
+            "would re-encrypt"
+        } else {
+            "re-encrypted"
+        },
+        counts.resealed,
+        counts.current,
+        counts.failed
+    );
+}
@@ -0,0 +1,73 @@
+//! Error rates of the match threshold on a labelled evaluation set.
+//!
+//! A genuine score compares a probe with the right person's gallery photos, an
+//! impostor score with anyone else's. The `calibrate` binary collects both
+//! from the engine and uses these functions for the curves and the report.
+
+/// Spacing of the thresholds swept for the ROC/DET curves.
+pub const SWEEP_STEP: f32 = 0.005;
+
+/// Error rates if `threshold` were the match threshold.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OperatingPoint {
+    pub threshold: f32,
+    // Share of impostor scores at or above the threshold (false accepts)
+    pub far: f32,
+    // Share of genuine scores below it (false rejects)
+    pub frr: f32,
+}
+
+impl OperatingPoint {
+    pub fn at(genuine: &[f32], impostor: &[f32], threshold: f32) -> Self {
+        Self {
+            threshold,
+            far: share(impostor, |s| s >= threshold),
+            frr: share(genuine, |s| s < threshold),
+        }
+    }
+
+    /// True accept rate, the ROC curve's y axis.
+    pub fn tar(&self) -> f32 {
+        1.0 - self.frr
+    }
+}
+
+/// Operating points from 0 to 1 in `SWEEP_STEP`s; plotting `tar()` against
+/// `far` gives the ROC curve, `frr` against `far` the DET curve.
+pub fn sweep(genuine: &[f32], impostor: &[f32]) -> Vec<OperatingPoint> {
+    let steps = (1.0 / SWEEP_STEP).round() as u32;
+    (0..=steps)
+        .map(|i| OperatingPoint::at(genuine, impostor, i as f32 * SWEEP_STEP))
+        .collect()
+}
+
+/// The swept point where false accepts and false rejects are closest.
+pub fn equal_error_point(curve: &[OperatingPoint]) -> Option<OperatingPoint> {
+    curve
+        .iter()
+        .min_by(|a, b| (a.far - a.frr).abs().total_cmp(&(b.far - b.frr).abs()))
+        .copied()
+}
+
+/// Lowest swept threshold whose false accept rate is at most `target_far`;
+/// lower thresholds reject fewer genuine matches. `None` if none qualifies.
+pub fn recommend(curve: &[OperatingPoint], target_far: f32) -> Option<OperatingPoint> {
+    curve.iter().find(|point| point.far <= target_far).copied()
+}
+
+/// Share of probes whose own identity ranked within the top `k`. `ranks` are
+/// 1-based, `None` where the identity wasn't scored at all.
+pub fn rank_accuracy(ranks: &[Option<usize>], k: usize) -> f32 {
+    share_of(ranks, |rank| rank.is_some_and(|r| r <= k))
+}
+
+fn share(scores: &[f32], counts: impl Fn(f32) -> bool) -> f32 {
+    share_of(scores, |&s| counts(s))
+}
+
+fn share_of<T>(items: &[T], counts: impl Fn(&T) -> bool) -> f32 {
+    if items.is_empty() {
+        return 0.0;
+    }
+    items.iter().filter(|item| counts(item)).count() as f32 / items.len() as f32
+}
@@ -0,0 +1,354 @@
+use crate::database::CriminalDB;
+use crate::face_index::SearchHit;
+use crate::preprocess::load_image;
+use crate::python_process::encode_path;
+use crate::quality::face_crop;
+use crate::Message;
+
+use iced::widget::{button, column, container, image, row, scrollable, slider, space, text};
+use iced::{Alignment, Background, Border, Color, Element, Length, Task, Theme};
+use std::path::Path;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
+
+/// Side of the probe and registered crops, in pixels.
+const CROP_SIZE: u32 = 224;
+const PANE_SIZE: f32 = 260.0;
+const STRIP_SIZE: f32 = 64.0;
+const MAX_ZOOM: f32 = 4.0;
+
+static NEXT_COMPARISON: AtomicU32 = AtomicU32::new(1);
+
+/// The probe face next to the registered photos of the person it matched,
+/// each scored against the probe, for an operator to confirm the match.
+pub struct FaceComparison {
+    tag: String,
+    probe_path: String,
+    probe: Option<image::Handle>,
+    probe_error: Option<String>,
+    photos: Vec<(u32, image::Handle)>, // photo id, face crop
+    photos_error: Option<String>,
+    scores: Vec<SearchHit>, // one per embedded photo, best first
+    selected: Option<u32>,
+    zoom: f32,
+    match_threshold: f32, // scores at or above it are shown as matching
+}
+
+impl FaceComparison {
+    /// Asks the engine for the probe's face and embedding and loads the
+    /// matched person's photos.
+    pub fn start(
+        probe_path: String,
+        criminal_id: u32,
+        match_threshold: f32,
+        db: Option<Arc<CriminalDB>>,
+    ) -> (Self, Task<Message>) {
+        let tag = format!(
+            "cmp-{}-{}",
+            NEXT_COMPARISON.fetch_add(1, Ordering::Relaxed),
+            criminal_id
+        );
+
+        let engine = Task::batch(vec![
+            Task::done(Message::PythonInput(format!(
+                "embed {} {}",
+                tag,
+                encode_path(&probe_path)
+            ))),
+            Task::done(Message::PythonInput(format!(
+                "analyze {} {}",
+                tag,
+                encode_path(&probe_path)
+            ))),
+        ]);
+        let photos = match db {
+            Some(db) => {
+                let tag = tag.clone();
+                Task::perform(
+                    async move {
+                        let photos = db
+                            .get_criminal_photos(criminal_id)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        tokio::task::spawn_blocking(move || registered_crops(photos))
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    move |result| Message::ComparisonPhotosLoaded(tag.clone(), result),
+                )
+            }
+            None => Task::done(Message::ComparisonPhotosLoaded(
+                tag.clone(),
+                Err("Database not connected".to_string()),
+            )),
+        };
+
+        let comparison = Self {
+            tag,
+            probe_path,
+            probe: None,
+            probe_error: None,
+            photos: Vec::new(),
+            photos_error: None,
+            scores: Vec::new(),
+            selected: None,
+            zoom: 1.0,
+            match_threshold,
+        };
+        (comparison, Task::batch(vec![engine, photos]))
+    }
+
+    /// Criminal id encoded in a `cmp-{n}-{criminal_id}` request tag.
+    pub fn criminal_for_tag(tag: &str) -> Option<u32> {
+        tag.strip_prefix("cmp-")?.split_once('-')?.1.parse().ok()
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::FacesDetected(tag, faces) if tag == self.tag => {
+                // The engine embeds the first face, so that's the one to show
+                let Some(face) = faces.first().copied() else {
+                    self.probe_error = Some("No face found in the probe".to_string());
+                    return Task::none();
+                };
+                let path = self.probe_path.clone();
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            let probe = load_image(Path::new(&path)).map_err(|e| e.to_string())?;
+                            let crop = face.aligned(&probe, CROP_SIZE);
+                            Ok(image::Handle::from_rgba(
+                                crop.width(),
+                                crop.height(),
+                                crop.into_raw(),
+                            ))
+                        })
+                        .await
+                        .unwrap_or_else(|e| Err(e.to_string()))
+                    },
+                    move |result| Message::ComparisonProbeReady(tag.clone(), result),
+                );
+            }
+
+            Message::ComparisonProbeReady(tag, result) if tag == self.tag => match result {
+                Ok(handle) => self.probe = Some(handle),
+                Err(e) => self.probe_error = Some(e),
+            },
+
+            Message::ComparisonScores(tag, scores) if tag == self.tag => {
+                self.scores = scores;
+                if let Some(best) = self.scores.first() {
+                    self.selected = Some(best.photo_id);
+                }
+            }
+
+            Message::ComparisonPhotosLoaded(tag, result) if tag == self.tag => match result {
+                Ok(photos) => {
+                    self.photos = photos;
+                    if self.selected.is_none() {
+                        self.selected = self.photos.first().map(|(id, _)| *id);
+                    }
+                }
+                Err(e) => self.photos_error = Some(e),
+            },
+
+            Message::SelectComparisonPhoto(photo_id) => self.selected = Some(photo_id),
+
+            Message::ComparisonZoomChanged(zoom) => self.zoom = zoom.clamp(1.0, MAX_ZOOM),
+
+            _ => {}
+        }
+        Task::none()
+    }
+
+    fn score_color(&self, score: f32) -> Color {
+        if score >= self.match_threshold {
+            Color::from_rgb(0.4, 0.9, 0.5)
+        } else {
+            Color::from_rgb(0.9, 0.7, 0.3)
+        }
+    }
+
+    fn score_for(&self, photo_id: u32) -> Option<f32> {
+        self.scores
+            .iter()
+            .find(|hit| hit.photo_id == photo_id)
+            .map(|hit| hit.score)
+    }
+
+    pub fn view(&self) -> Element<'static, Message> {
+        let registered = self
+            .selected
+            .and_then(|id| self.photos.iter().find(|(photo_id, _)| *photo_id == id));
+
+        let probe_pane = self.pane(
+            "PROBE",
+            self.probe.clone(),
+            self.probe_error
+                .clone()
+                .unwrap_or_else(|| "Aligning face...".to_string()),
+        );
+        let registered_pane = self.pane(
+            "REGISTERED PHOTO",
+            registered.map(|(_, handle)| handle.clone()),
+            self.photos_error
+                .clone()
+                .unwrap_or_else(|| "Loading photos...".to_string()),
+        );
+
+        let similarity: Element<Message> = match self.selected.and_then(|id| self.score_for(id)) {
+            Some(score) => text(format!("SIMILARITY {:.2}", score))
+                .size(16)
+                .color(self.score_color(score))
+                .into(),
+            None => text("SIMILARITY —")
+                .size(16)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.5))
+                .into(),
+        };
+
+        let zoom = row![
+            text("ZOOM")
+                .size(11)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.4)),
+            slider(1.0..=MAX_ZOOM, self.zoom, Message::ComparisonZoomChanged)
+                .step(0.1)
+                .width(Length::Fixed(200.0)),
+            text(format!("{:.1}×", self.zoom))
+                .size(12)
+                .color(Color::WHITE),
+        ]
+        .spacing(10)
+        .align_y(Alignment::Center);
+
+        column![
+            row![probe_pane, registered_pane].spacing(16),
+            space().height(12.0),
+            row![similarity, space().width(Length::Fill), zoom].align_y(Alignment::Center),
+            space().height(12.0),
+            self.photo_strip(),
+        ]
+        .width(Length::Shrink)
+        .into()
+    }
+
+    fn pane(
+        &self,
+        label: &'static str,
+        handle: Option<image::Handle>,
+        placeholder: String,
+    ) -> Element<'static, Message> {
+        // Both panes share one zoom so the faces stay at the same scale
+        let content: Element<Message> = match handle {
+            Some(handle) => image(handle)
+                .width(Length::Fixed(PANE_SIZE))
+                .height(Length::Fixed(PANE_SIZE))
+                .scale(self.zoom)
+                .into(),
+            None => text(placeholder)
+                .size(13)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.4))
+                .into(),
+        };
+
+        column![
+            text(label)
+                .size(11)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.4)),
+            space().height(6.0),
+            container(content)
+                .width(Length::Fixed(PANE_SIZE))
+                .height(Length::Fixed(PANE_SIZE))
+                .center_x(Length::Fixed(PANE_SIZE))
+                .center_y(Length::Fixed(PANE_SIZE))
+                .clip(true)
+                .style(|_theme| container::Style {
+                    background: Some(Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.4))),
+                    border: Border {
+                        color: Color::from_rgba(1.0, 1.0, 1.0, 0.15),
+                        width: 1.0,
+                        radius: 8.0.into(),
+                    },
+                    ..container::Style::default()
+                }),
+        ]
+        .into()
+    }
+
+    /// Every registered photo with its score against the probe; click to compare.
+    fn photo_strip(&self) -> Element<'static, Message> {
+        let photos = row(self.photos.iter().map(|(photo_id, handle)| {
+            let selected = self.selected == Some(*photo_id);
+            let score = self.score_for(*photo_id);
+
+            button(
+                column![
+                    image(handle.clone())
+                        .width(Length::Fixed(STRIP_SIZE))
+                        .height(Length::Fixed(STRIP_SIZE)),
+                    text(
+                        score
+                            .map(|s| format!("{:.2}", s))
+                            .unwrap_or_else(|| "—".to_string())
+                    )
+                    .size(11)
+                    .color(
+                        score
+                            .map(|s| self.score_color(s))
+                            .unwrap_or(Color::from_rgba(1.0, 1.0, 1.0, 0.5))
+                    ),
+                ]
+                .spacing(4)
+                .align_x(Alignment::Center),
+            )
+            .padding(4)
+            .on_press(Message::SelectComparisonPhoto(*photo_id))
+            .style(
+                move |_theme: &Theme, status: button::Status| button::Style {
+                    background: Some(Background::Color(
+                        if selected || matches!(status, button::Status::Hovered) {
+                            Color::from_rgba(0.4, 0.9, 0.5, 0.15)
+                        } else {
+                            Color::TRANSPARENT
+                        },
+                    )),
+                    border: Border {
+                        color: Color::from_rgba(0.4, 0.9, 0.5, if selected { 0.6 } else { 0.0 }),
+                        width: 1.0,
+                        radius: 6.0.into(),
+                    },
+                    ..Default::default()
+                },
+            )
+            .into()
+        }))
+        .spacing(8);
+
+        scrollable(photos)
+            .direction(scrollable::Direction::Horizontal(
+                scrollable::Scrollbar::default(),
+            ))
+            .width(Length::Fixed(PANE_SIZE * 2.0 + 16.0))
+            .into()
+    }
+}
+
+/// Face crops of the registered photos; the whole photo when no box was stored.
+fn registered_crops(
+    photos: Vec<crate::entities::criminal_photo::Model>,
+) -> Vec<(u32, image::Handle)> {
+    photos
+        .into_iter()
+        .filter_map(|photo| {
+            let decoded = ::image::load_from_memory(photo.bytes()).ok()?;
+            let crop = match photo.face_box() {
+                Some(bbox) => face_crop(&decoded, bbox, CROP_SIZE),
+                None => decoded.thumbnail(CROP_SIZE, CROP_SIZE).to_rgba8(),
+            };
+            Some((
+                photo.photo_id,
+                image::Handle::from_rgba(crop.width(), crop.height(), crop.into_raw()),
+            ))
+        })
+        .collect()
+}
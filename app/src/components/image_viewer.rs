@@ -1,24 +1,58 @@
+use crate::vault;
 use crate::Message;
 use iced::widget::{button, column, container, image, row, space, text};
 use iced::{Alignment, Background, Border, Color, Element, Length, Theme};
+use std::path::Path;
 
 pub struct GlassImageViewer {
-    images: Vec<String>,
+    images: Vec<image::Handle>,
     current_index: usize,
+    badge: Option<(String, Color)>,
+    notes: Vec<String>,
+}
+
+/// An image file read into memory, so sealed temp images show too. An
+/// unreadable file falls back to loading by path, which shows as broken.
+pub fn file_handle(path: impl AsRef<Path>) -> image::Handle {
+    let path = path.as_ref();
+    match vault::read_input(path) {
+        Ok(bytes) => image::Handle::from_bytes(bytes),
+        Err(_) => image::Handle::from_path(path),
+    }
 }
 
 impl GlassImageViewer {
+    /// Viewer over image files.
     pub fn new(images: Vec<String>, current_index: usize) -> Self {
+        Self::from_handles(images.iter().map(file_handle).collect(), current_index)
+    }
+
+    /// Viewer over images already in memory, e.g. photos from the database.
+    pub fn from_handles(images: Vec<image::Handle>, current_index: usize) -> Self {
         Self {
             images,
             current_index,
+            badge: None,
+            notes: Vec::new(),
         }
     }
 
+    /// Tag shown above the current image, e.g. its quality grade.
+    pub fn badge(mut self, label: impl Into<String>, color: Color) -> Self {
+        self.badge = Some((label.into(), color));
+        self
+    }
+
+    /// Small lines of detail under the current image.
+    pub fn notes(mut self, notes: Vec<String>) -> Self {
+        self.notes = notes;
+        self
+    }
+
     pub fn view(self, next_msg: Message, prev_msg: Message) -> Element<'static, Message> {
         let image_content: Element<Message> =
-            if let Some(path) = self.images.get(self.current_index) {
-                image(path.clone())
+            if let Some(handle) = self.images.get(self.current_index) {
+                image(handle.clone())
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .content_fit(iced::ContentFit::Contain)
@@ -43,12 +77,38 @@ impl GlassImageViewer {
         .padding(10)
         .align_y(Alignment::Center);
 
+        let badge: Element<Message> = match self.badge {
+            Some((label, color)) => container(text(label).size(12).color(color))
+                .padding([4, 10])
+                .style(move |_theme: &Theme| container::Style {
+                    background: Some(Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.5))),
+                    border: Border {
+                        color,
+                        width: 1.0,
+                        radius: 8.0.into(),
+                    },
+                    ..Default::default()
+                })
+                .into(),
+            None => space().height(0.0).into(),
+        };
+
+        let notes = column(self.notes.into_iter().map(|note| {
+            text(note)
+                .size(12)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.7))
+                .into()
+        }))
+        .spacing(2);
+
         let viewer_layout = column![
+            container(badge).padding([8, 10]),
             container(image_content)
                 .width(Length::Fill)
                 .height(Length::Fill)
                 .center_x(Length::Fill)
                 .center_y(Length::Fill),
+            container(notes).padding([0, 10]),
             space().height(20.0),
             container(controls)
                 .width(Length::Fill)
@@ -1,11 +1,15 @@
 mod button;
+mod face_comparison;
 mod image_viewer;
 mod input_label;
 mod text_input;
+mod toast;
 mod video_viewer;
 
 pub use button::GlassButton;
-pub use image_viewer::GlassImageViewer;
+pub use face_comparison::FaceComparison;
+pub use image_viewer::{file_handle, GlassImageViewer};
 pub use input_label::GlassInputLabel;
 pub use text_input::GlassTextInput;
+pub use toast::{Notice, Toasts};
 pub use video_viewer::VideoViewer;
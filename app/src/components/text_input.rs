@@ -5,6 +5,7 @@ pub struct GlassTextInput<'a, Message> {
     placeholder: &'a str,
     value: &'a str,
     on_input: Option<Box<dyn Fn(String) -> Message + 'a>>,
+    on_submit: Option<Message>,
 }
 
 impl<'a, Message> GlassTextInput<'a, Message>
@@ -16,6 +17,7 @@ where
             placeholder,
             value,
             on_input: None,
+            on_submit: None,
         }
     }
 
@@ -27,6 +29,11 @@ where
         self
     }
 
+    pub fn on_submit(mut self, msg: Message) -> Self {
+        self.on_submit = Some(msg);
+        self
+    }
+
     pub fn view(self) -> TextInput<'a, Message> {
         let mut input = text_input(self.placeholder, self.value)
             .padding(12)
@@ -64,6 +71,9 @@ where
         if let Some(on_input) = self.on_input {
             input = input.on_input(on_input);
         }
+        if let Some(on_submit) = self.on_submit {
+            input = input.on_submit(on_submit);
+        }
 
         input
     }
@@ -0,0 +1,283 @@
+use super::GlassButton;
+use crate::error::AppError;
+use crate::Message;
+
+use chrono::{DateTime, Local};
+use iced::widget::{button, column, container, row, scrollable, space, text};
+use iced::{Alignment, Background, Border, Color, Element, Length, Task, Theme};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// How long a toast without a retry stays up; ones with a retry wait for the operator.
+const TOAST_LIFETIME: Duration = Duration::from_secs(8);
+/// Toasts on screen at once; older ones are still in the history.
+const MAX_VISIBLE: usize = 4;
+const HISTORY_LEN: usize = 200;
+
+const ERROR_COLOR: Color = Color::from_rgb(0.9, 0.4, 0.4);
+
+/// One reported error, as kept in the history.
+#[derive(Debug, Clone)]
+pub struct Notice {
+    pub id: u64,
+    pub error: AppError,
+    pub retry: Option<Message>,
+    pub time: DateTime<Local>, // last time it was reported
+    pub repeats: u32,          // further reports while its toast was still up
+}
+
+/// Errors shown as toasts over every page, with a history panel of everything
+/// reported this session.
+#[derive(Default)]
+pub struct Toasts {
+    next_id: u64,
+    visible: Vec<(u64, Instant)>, // notice id, when it was last reported
+    history: VecDeque<Notice>,    // newest first
+    pub show_history: bool,
+}
+
+impl Toasts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Shows `error` and returns its id. The same error again while its toast
+    /// is up counts as a repeat rather than stacking another toast.
+    pub fn push(&mut self, error: AppError, retry: Option<Message>) -> u64 {
+        let now = Instant::now();
+        let repeated = self.visible.iter_mut().find(|(id, _)| {
+            self.history
+                .iter()
+                .any(|notice| notice.id == *id && notice.error == error)
+        });
+        if let Some((id, shown)) = repeated {
+            *shown = now;
+            let id = *id;
+            if let Some(notice) = self.history.iter_mut().find(|n| n.id == id) {
+                notice.repeats += 1;
+                notice.time = Local::now();
+                notice.retry = retry;
+            }
+            return id;
+        }
+
+        self.next_id += 1;
+        let id = self.next_id;
+        self.history.push_front(Notice {
+            id,
+            error,
+            retry,
+            time: Local::now(),
+            repeats: 0,
+        });
+        self.history.truncate(HISTORY_LEN);
+        self.visible.push((id, now));
+        if self.visible.len() > MAX_VISIBLE {
+            self.visible.remove(0);
+        }
+        id
+    }
+
+    pub fn dismiss(&mut self, id: u64) {
+        self.visible.retain(|(shown, _)| *shown != id);
+    }
+
+    /// Takes the toast down and returns the message that retries its error.
+    pub fn retry(&mut self, id: u64) -> Option<Message> {
+        self.dismiss(id);
+        self.get(id)?.retry.clone()
+    }
+
+    /// Takes down toasts without a retry once they've been up long enough.
+    pub fn expire(&mut self, now: Instant) {
+        let history = &self.history;
+        self.visible.retain(|(id, shown)| {
+            now.duration_since(*shown) < TOAST_LIFETIME
+                || history
+                    .iter()
+                    .any(|notice| notice.id == *id && notice.retry.is_some())
+        });
+    }
+
+    /// Whether a toast is up that `expire` may take down.
+    pub fn is_expiring(&self) -> bool {
+        self.visible
+            .iter()
+            .any(|(id, _)| self.get(*id).is_some_and(|notice| notice.retry.is_none()))
+    }
+
+    /// Ids of the toasts on screen, oldest first.
+    pub fn visible(&self) -> impl Iterator<Item = u64> + '_ {
+        self.visible.iter().map(|(id, _)| *id)
+    }
+
+    /// Everything reported, newest first.
+    pub fn history(&self) -> impl Iterator<Item = &Notice> {
+        self.history.iter()
+    }
+
+    pub fn get(&self, id: u64) -> Option<&Notice> {
+        self.history.iter().find(|notice| notice.id == id)
+    }
+
+    pub fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::ShowError(error, retry) => {
+                eprintln!("❌ {}: {}", error.title(), error);
+                self.push(error, retry.map(|retry| *retry));
+            }
+            Message::DismissToast(id) => self.dismiss(id),
+            Message::RetryToast(id) => {
+                if let Some(retry) = self.retry(id) {
+                    return Task::done(retry);
+                }
+            }
+            Message::ExpireToasts => self.expire(Instant::now()),
+            Message::ToggleNotificationHistory => self.show_history = !self.show_history,
+            Message::ClearNotificationHistory => {
+                self.history.clear();
+                self.visible.clear();
+                self.show_history = false;
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    /// Toasts, the history panel when open and the button that opens it,
+    /// stacked in the bottom-right corner; meant to be layered over the page.
+    pub fn view(&self) -> Element<'_, Message> {
+        let mut overlay = column![].spacing(10).align_x(Alignment::End);
+        if self.show_history {
+            overlay = overlay.push(self.history_view());
+        }
+        for notice in self.visible().filter_map(|id| self.get(id)) {
+            overlay = overlay.push(toast_view(notice));
+        }
+        if !self.history.is_empty() {
+            let label = if self.show_history {
+                "Hide Notifications".to_string()
+            } else {
+                format!("Notifications ({})", self.history.len())
+            };
+            overlay = overlay.push(
+                button(text(label).size(13))
+                    .on_press(Message::ToggleNotificationHistory)
+                    .style(button::text),
+            );
+        }
+
+        container(overlay)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(20)
+            .align_right(Length::Fill)
+            .align_bottom(Length::Fill)
+            .into()
+    }
+
+    fn history_view(&self) -> Element<'_, Message> {
+        let entries = column(self.history.iter().map(|notice| {
+            let retry: Element<Message> = match &notice.retry {
+                Some(retry) => button(text("Retry").size(12))
+                    .on_press(retry.clone())
+                    .style(button::text)
+                    .into(),
+                None => space().width(0.0).into(),
+            };
+            row![
+                column![
+                    text(format!(
+                        "{} · {}",
+                        notice.time.format("%H:%M:%S"),
+                        notice.error.title()
+                    ))
+                    .size(12)
+                    .color(ERROR_COLOR),
+                    text(notice_detail(notice))
+                        .size(13)
+                        .color(Color::from_rgba(1.0, 1.0, 1.0, 0.8)),
+                ]
+                .spacing(2)
+                .width(Length::Fill),
+                retry,
+            ]
+            .spacing(10)
+            .align_y(Alignment::Center)
+            .into()
+        }))
+        .spacing(12);
+
+        panel(
+            column![
+                row![
+                    text("NOTIFICATIONS").size(16).color(Color::WHITE),
+                    space().width(Length::Fill),
+                    button(text("Clear").size(12))
+                        .on_press(Message::ClearNotificationHistory)
+                        .style(button::text),
+                    button(text("Close").size(12))
+                        .on_press(Message::ToggleNotificationHistory)
+                        .style(button::text),
+                ]
+                .align_y(Alignment::Center),
+                scrollable(entries).height(Length::Fixed(320.0)),
+            ]
+            .spacing(12),
+        )
+    }
+}
+
+fn toast_view(notice: &Notice) -> Element<'_, Message> {
+    let mut actions = row![].spacing(8).align_y(Alignment::Center);
+    if notice.retry.is_some() {
+        actions = actions.push(GlassButton::new("Retry").on_press(Message::RetryToast(notice.id)));
+    }
+    actions = actions.push(
+        button(text("Dismiss").size(12))
+            .on_press(Message::DismissToast(notice.id))
+            .style(button::text),
+    );
+
+    panel(
+        row![
+            column![
+                text(notice.error.title().to_uppercase())
+                    .size(13)
+                    .color(ERROR_COLOR),
+                text(notice_detail(notice)).size(15).color(Color::WHITE),
+            ]
+            .spacing(4)
+            .width(Length::Fill),
+            actions,
+        ]
+        .spacing(16)
+        .align_y(Alignment::Center),
+    )
+}
+
+fn notice_detail(notice: &Notice) -> String {
+    match notice.repeats {
+        0 => notice.error.to_string(),
+        n => format!("{} (×{})", notice.error, n + 1),
+    }
+}
+
+fn panel<'a>(content: impl Into<Element<'a, Message>>) -> Element<'a, Message> {
+    container(content)
+        .padding(14)
+        .width(Length::Fixed(420.0))
+        .style(|_theme: &Theme| container::Style {
+            background: Some(Background::Color(Color::from_rgba(0.12, 0.06, 0.06, 0.92))),
+            border: Border {
+                color: Color {
+                    a: 0.6,
+                    ..ERROR_COLOR
+                },
+                width: 1.0,
+                radius: 10.0.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+}
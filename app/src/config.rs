@@ -0,0 +1,322 @@
+use crate::face_index::MATCH_THRESHOLD;
+use crate::outbox;
+use crate::photo_store::PhotoStore;
+use crate::workspace;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// Overrides where the config file is read from.
+pub const CONFIG_PATH_ENV: &str = "CFR_CONFIG";
+const DEFAULT_CONFIG_PATH: &str = "config.toml";
+
+/// Station settings, read from `config.toml` (or `$CFR_CONFIG`).
+/// Every field has a default, so a missing file or section is fine.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    pub database_url: String,
+    pub engine: EngineConfig,
+    pub matching: MatchingConfig,
+    pub storage: StorageConfig,
+    pub encryption: EncryptionConfig,
+    pub retention: RetentionConfig,
+    pub alerts: AlertConfig,
+    pub notifications: NotificationConfig,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EngineBackend {
+    /// `model_engine/main.py` in a child process (needs CUDA)
+    Python,
+    /// In-process CPU inference over ONNX models (needs the `onnx` feature)
+    Onnx,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineConfig {
+    pub backend: EngineBackend,
+    pub python_dir: PathBuf,
+    pub python_script: String,
+    // Wrapper the interpreter is started through (e.g. "prime-run"); empty for none
+    pub python_launcher: String,
+    // Stored with each embedding so galleries from different models never mix
+    pub model_name: String,
+    pub detector_model: PathBuf,
+    pub recognizer_model: PathBuf,
+}
+
+/// When a similarity score counts as the same person. `calibrate` measures
+/// these against a labelled evaluation set and can write `threshold` back.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MatchingConfig {
+    // Minimum cosine similarity for an image or webcam match
+    pub threshold: f32,
+    // A video stops early once someone has this many matching frames...
+    pub video_min_hits: u32,
+    // ...averaging above this score
+    pub video_confirm_score: f32,
+}
+
+impl MatchingConfig {
+    /// The engine command that applies these settings.
+    pub fn engine_command(&self) -> String {
+        format!(
+            "thresholds {:.4} {} {:.4}",
+            self.threshold, self.video_min_hits, self.video_confirm_score
+        )
+    }
+}
+
+/// Where enrolled photos, temp images and offline writes are kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StorageConfig {
+    // Content-addressed photo folder; empty keeps photos in the database
+    pub photo_dir: PathBuf,
+    // Temp workspace for prepared probes and captured frames; empty uses the system temp folder
+    pub runtime_dir: PathBuf,
+    // Enrollments and matches waiting for the database; empty uses "outbox".
+    // A relative folder is taken from where the app starts
+    pub outbox_dir: PathBuf,
+}
+
+impl StorageConfig {
+    pub fn photo_store(&self) -> Option<PhotoStore> {
+        (!self.photo_dir.as_os_str().is_empty()).then(|| PhotoStore::new(&self.photo_dir))
+    }
+
+    pub fn workspace_root(&self) -> PathBuf {
+        if self.runtime_dir.as_os_str().is_empty() {
+            workspace::default_root()
+        } else {
+            self.runtime_dir.clone()
+        }
+    }
+
+    pub fn outbox_dir(&self) -> PathBuf {
+        if self.outbox_dir.as_os_str().is_empty() {
+            PathBuf::from(outbox::DEFAULT_DIR)
+        } else {
+            self.outbox_dir.clone()
+        }
+    }
+}
+
+/// Where the data keys for encryption at rest are kept; see `vault`.
+/// Encryption is off when neither is set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EncryptionConfig {
+    // Hex keys, current first; created with a new key if it doesn't exist and
+    // nothing is encrypted yet
+    pub key_file: PathBuf,
+    // Keep the keys in the OS keyring instead (needs the `keyring` feature)
+    pub keyring: bool,
+}
+
+/// How long data about people is kept; see `retention`. A limit of 0 keeps
+/// that data forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RetentionConfig {
+    // Probe images of identifications are cleared after this many days
+    pub probe_days: u32,
+    // Identifications nobody confirmed are deleted after this many days
+    pub unconfirmed_days: u32,
+    // Sightings and confirmed identifications are deleted after this many days
+    pub sighting_days: u32,
+    // Leftover temp images (ours and the engine's) are deleted after this many hours
+    pub temp_hours: u32,
+    // How often the rules are applied while the app runs; 0 only applies them at startup
+    pub interval_minutes: u32,
+}
+
+/// How watchlist matches are announced.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlertConfig {
+    // Program that plays `sound_file`; empty to only ring the terminal bell
+    pub sound_player: String,
+    pub sound_file: PathBuf,
+}
+
+/// Outbound notifications about identifications, for people away from the screen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    // Identification sources that notify ("image", "video", "webcam"); empty turns notifications off
+    pub sources: Vec<String>,
+    // Only notify for people on a watchlist at least this urgent; empty for every identification
+    pub min_priority: String,
+    // Attach the probe image (base64) when there is one
+    pub include_snapshot: bool,
+    pub max_attempts: u32,
+    // Wait before the first retry; doubles after each failed attempt
+    pub retry_delay_secs: u64,
+    pub webhooks: Vec<WebhookConfig>,
+    pub smtp: Option<SmtpConfig>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    pub url: String,
+    // Signs each payload (HMAC-SHA256) so the receiver can verify it
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: String,
+    pub port: u16,
+    // Leave empty for relays that don't need a login
+    pub username: String,
+    pub password: String,
+    pub starttls: bool,
+    pub from: String,
+    pub to: Vec<String>,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            database_url: "mysql://crim_user:@localhost:3306/criminal_recognizer".to_string(),
+            engine: EngineConfig::default(),
+            matching: MatchingConfig::default(),
+            storage: StorageConfig::default(),
+            encryption: EncryptionConfig::default(),
+            retention: RetentionConfig::default(),
+            alerts: AlertConfig::default(),
+            notifications: NotificationConfig::default(),
+        }
+    }
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            sources: Vec::new(),
+            min_priority: "high".to_string(),
+            include_snapshot: true,
+            max_attempts: 5,
+            retry_delay_secs: 30,
+            webhooks: Vec::new(),
+            smtp: None,
+        }
+    }
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 25,
+            username: String::new(),
+            password: String::new(),
+            starttls: false,
+            from: "recognizer@localhost".to_string(),
+            to: Vec::new(),
+        }
+    }
+}
+
+impl Default for MatchingConfig {
+    fn default() -> Self {
+        Self {
+            threshold: MATCH_THRESHOLD,
+            video_min_hits: 5,
+            video_confirm_score: 0.6,
+        }
+    }
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            probe_days: 30,
+            unconfirmed_days: 90,
+            sighting_days: 0,
+            temp_hours: 24,
+            interval_minutes: 60,
+        }
+    }
+}
+
+impl Default for AlertConfig {
+    fn default() -> Self {
+        Self {
+            sound_player: "paplay".to_string(),
+            sound_file: PathBuf::from(
+                "/usr/share/sounds/freedesktop/stereo/alarm-clock-elapsed.oga",
+            ),
+        }
+    }
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            backend: EngineBackend::Python,
+            python_dir: PathBuf::from(
+                "/home/NEW_VOLUME-d/developer/criminal_face_recog/model_engine",
+            ),
+            python_script: "main.py".to_string(),
+            python_launcher: "prime-run".to_string(),
+            model_name: "buffalo_l".to_string(),
+            // The buffalo_l pack ships these two files
+            detector_model: PathBuf::from("models/buffalo_l/det_10g.onnx"),
+            recognizer_model: PathBuf::from("models/buffalo_l/w600k_r50.onnx"),
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn path() -> PathBuf {
+        std::env::var_os(CONFIG_PATH_ENV)
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(DEFAULT_CONFIG_PATH))
+    }
+
+    /// Loads the config file, falling back to defaults if it is missing or invalid.
+    pub fn load() -> Self {
+        let path = Self::path();
+        let text = match std::fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => {
+                println!("No config at {}, using defaults", path.display());
+                return Self::default();
+            }
+        };
+
+        toml::from_str(&text).unwrap_or_else(|e| {
+            eprintln!("❌ Invalid config {}: {e}", path.display());
+            Self::default()
+        })
+    }
+
+    /// Sets `matching.threshold` in the config file at `path`, creating it if
+    /// needed. Other keys are kept as written; comments are not.
+    pub fn save_match_threshold(path: &Path, threshold: f32) -> Result<(), String> {
+        let mut table: toml::Table = match std::fs::read_to_string(path) {
+            Ok(text) => toml::from_str(&text).map_err(|e| e.to_string())?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => toml::Table::new(),
+            Err(e) => return Err(e.to_string()),
+        };
+
+        let matching = table
+            .entry("matching")
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()));
+        let Some(matching) = matching.as_table_mut() else {
+            return Err("`matching` in the config is not a table".to_string());
+        };
+        // Round so the file doesn't end up with 0.41999998
+        let rounded = (f64::from(threshold) * 1e4).round() / 1e4;
+        matching.insert("threshold".to_string(), toml::Value::Float(rounded));
+
+        let text = toml::to_string_pretty(&table).map_err(|e| e.to_string())?;
+        std::fs::write(path, text).map_err(|e| e.to_string())
+    }
+}
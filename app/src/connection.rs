@@ -0,0 +1,170 @@
+//! The database connection as the operator sees it: a status badge on every
+//! page, and reconnection with backoff while it's down.
+//!
+//! The app starts without a database and keeps working: enrollments and
+//! matches made while offline go to the `outbox` and are written once the
+//! connection is back. A periodic ping notices a connection that drops later.
+
+use crate::Message;
+
+use chrono::{DateTime, Local};
+use iced::widget::{button, container, row, text};
+use iced::{Alignment, Background, Border, Color, Element, Length, Theme};
+use std::time::Duration;
+
+/// Wait after the first failed attempt; doubled after each further one.
+const FIRST_RETRY: Duration = Duration::from_secs(2);
+const MAX_RETRY: Duration = Duration::from_secs(60);
+/// How often a live connection is checked.
+pub const PING_INTERVAL: Duration = Duration::from_secs(30);
+
+const ONLINE_COLOR: Color = Color::from_rgb(0.4, 0.85, 0.5);
+const PENDING_COLOR: Color = Color::from_rgb(0.95, 0.75, 0.3);
+const OFFLINE_COLOR: Color = Color::from_rgb(0.9, 0.4, 0.4);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DbStatus {
+    Connecting,
+    Connected,
+    Offline {
+        error: String,
+        retry_at: DateTime<Local>,
+    },
+}
+
+pub struct Connection {
+    pub status: DbStatus,
+    pub queued: usize, // outbox entries waiting for the database
+    attempting: bool,
+    failures: u32, // attempts failed in a row
+    generation: u64,
+}
+
+impl Default for Connection {
+    fn default() -> Self {
+        Self {
+            status: DbStatus::Connecting,
+            queued: 0,
+            attempting: false,
+            failures: 0,
+            generation: 0,
+        }
+    }
+}
+
+impl Connection {
+    pub fn new(queued: usize) -> Self {
+        Self {
+            queued,
+            ..Self::default()
+        }
+    }
+
+    /// Starts an attempt; false if one is already running or we're connected.
+    pub fn begin(&mut self) -> bool {
+        if self.attempting || self.status == DbStatus::Connected {
+            return false;
+        }
+        self.attempting = true;
+        self.status = DbStatus::Connecting;
+        true
+    }
+
+    pub fn connected(&mut self) {
+        self.attempting = false;
+        self.failures = 0;
+        self.status = DbStatus::Connected;
+    }
+
+    /// Records a failed attempt. Returns how long to wait before the next one
+    /// and the generation to hand back in `Message::ReconnectDue`.
+    pub fn failed(&mut self, error: String) -> (Duration, u64) {
+        self.attempting = false;
+        self.failures += 1;
+        let delay = backoff(self.failures);
+        self.generation += 1;
+        self.status = DbStatus::Offline {
+            error,
+            retry_at: Local::now() + delay,
+        };
+        (delay, self.generation)
+    }
+
+    /// A live connection stopped answering; retries start over from the
+    /// shortest wait.
+    pub fn lost(&mut self, error: String) -> (Duration, u64) {
+        self.failures = 0;
+        self.failed(error)
+    }
+
+    /// Whether the retry timer of `generation` is still the one to act on;
+    /// a manual reconnect or a newer failure supersedes it.
+    pub fn is_due(&self, generation: u64) -> bool {
+        generation == self.generation
+            && !self.attempting
+            && matches!(self.status, DbStatus::Offline { .. })
+    }
+
+    /// Attempts failed in a row; only the first is worth a toast.
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// A small badge for the top-right corner; meant to be layered over the page.
+    pub fn view(&self) -> Element<'_, Message> {
+        let queued = match self.queued {
+            0 => String::new(),
+            1 => " · 1 change waiting to sync".to_string(),
+            n => format!(" · {} changes waiting to sync", n),
+        };
+        let (color, label) = match &self.status {
+            DbStatus::Connecting => (PENDING_COLOR, format!("Connecting to database…{}", queued)),
+            DbStatus::Connected => (ONLINE_COLOR, format!("Database connected{}", queued)),
+            DbStatus::Offline { retry_at, .. } => (
+                OFFLINE_COLOR,
+                format!(
+                    "Database offline · retrying at {}{}",
+                    retry_at.format("%H:%M:%S"),
+                    queued
+                ),
+            ),
+        };
+
+        let mut badge = row![text("●").size(12).color(color), text(label).size(12)]
+            .spacing(6)
+            .align_y(Alignment::Center);
+        // The reason is in the toast and the notification history
+        if matches!(self.status, DbStatus::Offline { .. }) {
+            badge = badge.push(
+                button(text("Reconnect now").size(12))
+                    .on_press(Message::ConnectDatabase)
+                    .style(button::text),
+            );
+        }
+
+        container(
+            container(badge)
+                .padding([4, 12])
+                .style(move |_theme: &Theme| container::Style {
+                    text_color: Some(Color::from_rgba(1.0, 1.0, 1.0, 0.85)),
+                    background: Some(Background::Color(Color::from_rgba(0.05, 0.05, 0.08, 0.8))),
+                    border: Border {
+                        color: Color { a: 0.5, ..color },
+                        width: 1.0,
+                        radius: 12.0.into(),
+                    },
+                    ..Default::default()
+                }),
+        )
+        .width(Length::Fill)
+        .padding(10)
+        .align_right(Length::Fill)
+        .into()
+    }
+}
+
+/// `FIRST_RETRY` doubled for each failure after the first, up to `MAX_RETRY`.
+pub fn backoff(failures: u32) -> Duration {
+    let doublings = failures.saturating_sub(1).min(16);
+    (FIRST_RETRY * 2u32.pow(doublings)).min(MAX_RETRY)
+}
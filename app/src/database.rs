@@ -1,12 +1,161 @@
-use crate::entities::{criminal, criminal_photo};
-use chrono::Utc;
+use crate::config::AppConfig;
+use crate::entities::{
+    alert, audit_log, criminal, criminal_alias, criminal_photo, face_embedding, identification,
+    notification_delivery, offence, offence_type, sighting, watchlist, watchlist_member,
+};
+use crate::notify::DeliveryRecord;
+use crate::photo_store::PhotoStore;
+use crate::preprocess::make_thumbnail;
+use crate::vault;
+use chrono::{DateTime, NaiveDate, Utc};
+use sea_orm::sea_query::{Expr, Query};
 use sea_orm::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 
+/// Optional identity attributes captured at enrollment.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct CriminalDetails {
+    pub date_of_birth: Option<NaiveDate>,
+    pub gender: Option<String>,
+    pub national_ids: Option<String>,
+    pub height_cm: Option<u16>,
+    pub distinguishing_marks: Option<String>,
+    pub notes: Option<String>,
+}
+
+/// An offence entered at enrollment, not yet saved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NewOffence {
+    pub offence_type_id: u32,
+    pub offence_date: Option<NaiveDate>,
+    pub location: Option<String>,
+    pub case_number: Option<String>,
+    pub status: String,
+}
+
+/// A recorded offence with its type name, for details views.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OffenceEntry {
+    pub offence: offence::Model,
+    pub offence_type: String,
+}
+
+impl OffenceEntry {
+    /// One line: date, type, location, case number and status.
+    pub fn summary(&self) -> String {
+        let offence = &self.offence;
+        let mut parts = vec![
+            offence
+                .offence_date
+                .map(|d| d.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| "Undated".to_string()),
+            self.offence_type.clone(),
+        ];
+        parts.extend(offence.location.clone());
+        parts.extend(offence.case_number.as_ref().map(|c| format!("Case {}", c)));
+        parts.push(offence::status_label(&offence.status).to_string());
+        parts.join(" · ")
+    }
+}
+
+/// A person and everything entered with them on the registry page, for
+/// `enroll`.
+#[derive(Debug, Clone, Default)]
+pub struct NewEnrollment {
+    pub name: String,
+    pub fathers_name: Option<String>,
+    pub arrested_location: Option<String>,
+    pub details: CriminalDetails,
+    pub aliases: Vec<String>,
+    pub offences: Vec<NewOffence>,
+    pub photos: Vec<(Vec<u8>, Option<[f32; 4]>)>, // image bytes and face box
+    pub audit: Option<PendingAudit>,
+    pub outbox_entry: Option<String>, // key of the outbox entry it was queued as
+}
+
+/// History entry written alongside an enrollment.
+#[derive(Debug, Clone)]
+pub struct PendingAudit {
+    pub action: &'static str,
+    pub related_criminal_id: Option<u32>,
+    pub details: String,
+}
+
+/// What `enroll` wrote.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnrollmentSummary {
+    pub criminal_id: u32,
+    pub photo_ids: Vec<u32>, // in the order the photos were given
+}
+
+/// An alert with what the banner needs to describe it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertEntry {
+    pub alert: alert::Model,
+    pub criminal_name: String,
+    pub watchlist: watchlist::Model,
+}
+
+/// An engine match to be put in front of an operator.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewIdentification {
+    pub criminal_id: u32,
+    pub photo_id: Option<u32>,
+    pub source: String,
+    pub score: Option<f32>,
+    pub probe: Option<Vec<u8>>,
+    pub captured_at: DateTime<Utc>, // earlier than the insert for matches queued offline
+}
+
+/// An identification with the candidate's name and best-matching photo,
+/// for the review queue.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IdentificationEntry {
+    pub identification: identification::Model,
+    pub criminal_name: String,
+    // Thumbnail of the registered photo that matched
+    pub candidate_photo: Option<Vec<u8>>,
+}
+
+/// Rows moved by `merge_criminals`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MergeSummary {
+    pub photos: u64,
+    pub embeddings: u64,
+    pub sightings: u64,
+    pub identifications: u64,
+    pub offences: u64,
+    pub aliases: u64,
+}
+
+/// Rows removed by `purge_criminal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PurgeSummary {
+    pub photos: u64,
+    pub embeddings: u64,
+    pub sightings: u64,
+    pub identifications: u64,
+    pub alerts: u64,
+    pub offences: u64,
+    pub aliases: u64,
+}
+
+/// Rows affected by one pass of `apply_retention`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionSummary {
+    pub probes_cleared: u64,
+    pub unconfirmed_deleted: u64,
+    pub sightings_deleted: u64,
+}
+
 #[derive(Debug, Clone)]
 pub struct CriminalDB {
     pub connection: Arc<DatabaseConnection>,
+    // Where photo files go; photos stay in `criminal_photos.photo` without one
+    photo_store: Option<PhotoStore>,
 }
 
 impl CriminalDB {
@@ -29,30 +178,157 @@ impl CriminalDB {
 
         Ok(Self {
             connection: Arc::new(connection),
+            photo_store: None,
         })
     }
 
-    pub async fn add_criminal(
+    /// Checks the connection is still usable.
+    pub async fn ping(&self) -> Result<(), DbErr> {
+        self.connection.ping().await
+    }
+
+    /// Whether any photo, thumbnail, probe image or embedding is sealed.
+    pub async fn holds_sealed_data(&self) -> Result<bool, DbErr> {
+        let magic = std::str::from_utf8(vault::SEALED_MAGIC).expect("ASCII magic");
+        let db = self.connection.as_ref();
+        let photo = criminal_photo::Entity::find()
+            .select_only()
+            .column(criminal_photo::Column::PhotoId)
+            .filter(
+                Condition::any()
+                    .add(criminal_photo::Column::Photo.starts_with(magic))
+                    .add(criminal_photo::Column::Thumbnail.starts_with(magic)),
+            )
+            .into_tuple::<u32>()
+            .one(db)
+            .await?;
+        let probe = identification::Entity::find()
+            .select_only()
+            .column(identification::Column::IdentificationId)
+            .filter(identification::Column::Probe.starts_with(magic))
+            .into_tuple::<u32>()
+            .one(db)
+            .await?;
+        let embedding = face_embedding::Entity::find()
+            .select_only()
+            .column(face_embedding::Column::EmbeddingId)
+            .filter(face_embedding::Column::Vector.starts_with(magic))
+            .into_tuple::<u32>()
+            .one(db)
+            .await?;
+        Ok(photo.is_some() || probe.is_some() || embedding.is_some())
+    }
+
+    /// Stores new photos as files in `store` rather than in the database.
+    pub fn with_photo_store(mut self, store: PhotoStore) -> Self {
+        self.photo_store = Some(store);
+        self
+    }
+
+    pub fn photo_store(&self) -> Option<&PhotoStore> {
+        self.photo_store.as_ref()
+    }
+
+    /// Fills in `photo` for a photo kept in the store and decrypts the photo
+    /// and thumbnail. A missing or damaged file is logged and leaves it empty
+    /// rather than failing the whole record.
+    async fn load_photo(&self, mut photo: criminal_photo::Model) -> criminal_photo::Model {
+        let photo_id = photo.photo_id;
+        photo.thumbnail = photo.thumbnail.take().and_then(|bytes| {
+            vault::open(bytes)
+                .map_err(|e| eprintln!("❌ Thumbnail of photo {}: {}", photo_id, e))
+                .ok()
+        });
+        if let Some(bytes) = photo.photo.take() {
+            photo.photo = vault::open(bytes)
+                .map_err(|e| eprintln!("❌ Photo {}: {}", photo_id, e))
+                .ok();
+            return photo;
+        }
+
+        let Some(hash) = &photo.photo_hash else {
+            return photo;
+        };
+        match &self.photo_store {
+            Some(store) => match store.get(hash).await {
+                Ok(bytes) => photo.photo = Some(bytes),
+                Err(e) => eprintln!("❌ Photo {} ({}): {}", photo_id, hash, e),
+            },
+            None => eprintln!(
+                "❌ Photo {} is in the photo store but no photo_dir is configured",
+                photo_id
+            ),
+        }
+        photo
+    }
+
+    /// Generates and saves the thumbnail of a photo enrolled before
+    /// thumbnails existed. `photo` must already be loaded.
+    async fn backfill_thumbnail(
         &self,
-        name: String,
-        fathers_name: Option<String>,
-        arrested_location: Option<String>,
-        no_of_crimes: u32,
-    ) -> Result<u32, DbErr> {
-        let new_criminal = criminal::ActiveModel {
-            name: Set(name),
-            fathers_name: Set(fathers_name),
-            arrested_location: Set(arrested_location),
-            no_of_crimes: Set(no_of_crimes),
-            date_of_arrest: Set(Utc::now()),
-            ..Default::default()
+        mut photo: criminal_photo::Model,
+    ) -> Result<criminal_photo::Model, DbErr> {
+        if photo.thumbnail.is_some() || photo.photo.is_none() {
+            return Ok(photo);
+        }
+        let Some(thumbnail) = thumbnail_of(photo.bytes().to_vec()).await else {
+            return Ok(photo);
         };
 
-        let result = criminal::Entity::insert(new_criminal)
+        criminal_photo::Entity::update_many()
+            .col_expr(
+                criminal_photo::Column::Thumbnail,
+                Expr::value(vault::seal(thumbnail.clone())),
+            )
+            .filter(criminal_photo::Column::PhotoId.eq(photo.photo_id))
             .exec(self.connection.as_ref())
             .await?;
+        photo.thumbnail = Some(thumbnail);
+        Ok(photo)
+    }
+
+    /// Thumbnails of a person's photos as (photo id, JPEG), without pulling
+    /// the originals unless a thumbnail still has to be made.
+    pub async fn get_photo_thumbnails(
+        &self,
+        criminal_id: u32,
+    ) -> Result<Vec<(u32, Vec<u8>)>, DbErr> {
+        let stored: Vec<(u32, Option<Vec<u8>>)> = criminal_photo::Entity::find()
+            .select_only()
+            .column(criminal_photo::Column::PhotoId)
+            .column(criminal_photo::Column::Thumbnail)
+            .filter(criminal_photo::Column::CriminalId.eq(criminal_id))
+            .order_by_asc(criminal_photo::Column::PhotoId)
+            .into_tuple()
+            .all(self.connection.as_ref())
+            .await?;
 
-        Ok(result.last_insert_id)
+        let mut thumbnails = Vec::new();
+        for (photo_id, thumbnail) in stored {
+            if let Some(thumbnail) = self.thumbnail(photo_id, thumbnail).await? {
+                thumbnails.push((photo_id, thumbnail));
+            }
+        }
+        Ok(thumbnails)
+    }
+
+    /// `stored`, or the photo's thumbnail made now if it had none.
+    async fn thumbnail(
+        &self,
+        photo_id: u32,
+        stored: Option<Vec<u8>>,
+    ) -> Result<Option<Vec<u8>>, DbErr> {
+        if let Some(stored) = stored {
+            return open_blob("thumbnail", stored).map(Some);
+        }
+        let Some(photo) = criminal_photo::Entity::find_by_id(photo_id)
+            .one(self.connection.as_ref())
+            .await?
+        else {
+            return Ok(None);
+        };
+        let photo = self.load_photo(photo).await;
+        Ok(self.backfill_thumbnail(photo).await?.thumbnail)
     }
 
     pub async fn get_criminal(&self, id: u32) -> Result<Option<criminal::Model>, DbErr> {
@@ -62,9 +338,454 @@ impl CriminalDB {
             .await
     }
 
+    pub async fn get_aliases(&self, criminal_id: u32) -> Result<Vec<String>, DbErr> {
+        Ok(criminal_alias::Entity::find()
+            .filter(criminal_alias::Column::CriminalId.eq(criminal_id))
+            .order_by_asc(criminal_alias::Column::AliasId)
+            .all(self.connection.as_ref())
+            .await?
+            .into_iter()
+            .map(|a| a.alias)
+            .collect())
+    }
+
+    pub async fn get_offence_types(&self) -> Result<Vec<offence_type::Model>, DbErr> {
+        offence_type::Entity::find()
+            .order_by_asc(offence_type::Column::Name)
+            .all(self.connection.as_ref())
+            .await
+    }
+
+    /// Adds an offence type to the managed list, or returns the existing one
+    /// with that name.
+    pub async fn add_offence_type(&self, name: &str) -> Result<offence_type::Model, DbErr> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(DbErr::Custom("offence type name is empty".to_string()));
+        }
+        if let Some(existing) = offence_type::Entity::find()
+            .filter(offence_type::Column::Name.eq(name))
+            .one(self.connection.as_ref())
+            .await?
+        {
+            return Ok(existing);
+        }
+
+        offence_type::ActiveModel {
+            name: Set(name.to_string()),
+            ..Default::default()
+        }
+        .insert(self.connection.as_ref())
+        .await
+    }
+
+    /// The record's offences, most recent first; undated ones last.
+    pub async fn get_offences(&self, criminal_id: u32) -> Result<Vec<OffenceEntry>, DbErr> {
+        let rows = offence::Entity::find()
+            .filter(offence::Column::CriminalId.eq(criminal_id))
+            .find_also_related(offence_type::Entity)
+            .order_by_desc(offence::Column::OffenceDate)
+            .order_by_desc(offence::Column::OffenceId)
+            .all(self.connection.as_ref())
+            .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(offence, offence_type)| OffenceEntry {
+                offence,
+                offence_type: offence_type
+                    .map(|t| t.name)
+                    .unwrap_or_else(|| "Unknown".to_string()),
+            })
+            .collect())
+    }
+
+    /// Every watchlist, most urgent first.
+    pub async fn get_watchlists(&self) -> Result<Vec<watchlist::Model>, DbErr> {
+        let mut watchlists = watchlist::Entity::find()
+            .order_by_asc(watchlist::Column::Name)
+            .all(self.connection.as_ref())
+            .await?;
+        watchlists.sort_by_key(|w| watchlist::priority_rank(&w.priority));
+        Ok(watchlists)
+    }
+
+    pub async fn create_watchlist(
+        &self,
+        name: &str,
+        priority: &str,
+        description: Option<String>,
+    ) -> Result<watchlist::Model, DbErr> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(DbErr::Custom("watchlist name is empty".to_string()));
+        }
+        if !watchlist::PRIORITIES.contains(&priority) {
+            return Err(DbErr::Custom(format!("unknown priority {}", priority)));
+        }
+
+        watchlist::ActiveModel {
+            name: Set(name.to_string()),
+            priority: Set(priority.to_string()),
+            description: Set(description),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(self.connection.as_ref())
+        .await
+    }
+
+    /// Members of a watchlist with their records, most recently added first.
+    pub async fn get_watchlist_members(
+        &self,
+        watchlist_id: u32,
+    ) -> Result<Vec<(watchlist_member::Model, criminal::Model)>, DbErr> {
+        Ok(watchlist_member::Entity::find()
+            .filter(watchlist_member::Column::WatchlistId.eq(watchlist_id))
+            .find_also_related(criminal::Entity)
+            .order_by_desc(watchlist_member::Column::AddedAt)
+            .all(self.connection.as_ref())
+            .await?
+            .into_iter()
+            .filter_map(|(member, criminal)| Some((member, criminal?)))
+            .collect())
+    }
+
+    pub async fn add_watchlist_member(
+        &self,
+        watchlist_id: u32,
+        criminal_id: u32,
+        reason: Option<String>,
+    ) -> Result<watchlist_member::Model, DbErr> {
+        if self.get_criminal(criminal_id).await?.is_none() {
+            return Err(DbErr::RecordNotFound(format!("criminal {}", criminal_id)));
+        }
+        if watchlist_member::Entity::find()
+            .filter(watchlist_member::Column::WatchlistId.eq(watchlist_id))
+            .filter(watchlist_member::Column::CriminalId.eq(criminal_id))
+            .one(self.connection.as_ref())
+            .await?
+            .is_some()
+        {
+            return Err(DbErr::Custom(format!(
+                "criminal {} is already on this watchlist",
+                criminal_id
+            )));
+        }
+
+        watchlist_member::ActiveModel {
+            watchlist_id: Set(watchlist_id),
+            criminal_id: Set(criminal_id),
+            reason: Set(reason),
+            added_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(self.connection.as_ref())
+        .await
+    }
+
+    pub async fn remove_watchlist_member(&self, member_id: u32) -> Result<(), DbErr> {
+        watchlist_member::Entity::delete_by_id(member_id)
+            .exec(self.connection.as_ref())
+            .await?;
+        Ok(())
+    }
+
+    /// The most urgent watchlist `criminal_id` is on, if any.
+    pub async fn most_urgent_watchlist(
+        &self,
+        criminal_id: u32,
+    ) -> Result<Option<watchlist::Model>, DbErr> {
+        Ok(watchlist::Entity::find()
+            .inner_join(watchlist_member::Entity)
+            .filter(watchlist_member::Column::CriminalId.eq(criminal_id))
+            .all(self.connection.as_ref())
+            .await?
+            .into_iter()
+            .min_by_key(|w| watchlist::priority_rank(&w.priority)))
+    }
+
+    /// Raises an alert if `criminal_id` is on any watchlist, against the most
+    /// urgent one. Returns `None` when they aren't listed, or when an earlier
+    /// alert for them is still waiting to be acknowledged (so a webcam that
+    /// keeps seeing the same face doesn't stack up alerts).
+    pub async fn raise_watchlist_alert(
+        &self,
+        criminal_id: u32,
+        source: &str,
+    ) -> Result<Option<AlertEntry>, DbErr> {
+        let Some(criminal) = self.get_criminal(criminal_id).await? else {
+            return Ok(None);
+        };
+
+        let Some(watchlist) = self.most_urgent_watchlist(criminal_id).await? else {
+            return Ok(None);
+        };
+
+        let pending = alert::Entity::find()
+            .filter(alert::Column::CriminalId.eq(criminal_id))
+            .filter(alert::Column::AcknowledgedAt.is_null())
+            .one(self.connection.as_ref())
+            .await?;
+        if pending.is_some() {
+            return Ok(None);
+        }
+
+        let alert = alert::ActiveModel {
+            criminal_id: Set(criminal_id),
+            watchlist_id: Set(watchlist.watchlist_id),
+            source: Set(source.to_string()),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(self.connection.as_ref())
+        .await?;
+
+        Ok(Some(AlertEntry {
+            alert,
+            criminal_name: criminal.name,
+            watchlist,
+        }))
+    }
+
+    /// Alerts nobody has acknowledged yet, oldest first.
+    pub async fn get_pending_alerts(&self) -> Result<Vec<AlertEntry>, DbErr> {
+        let alerts = alert::Entity::find()
+            .filter(alert::Column::AcknowledgedAt.is_null())
+            .find_also_related(watchlist::Entity)
+            .order_by_asc(alert::Column::AlertId)
+            .all(self.connection.as_ref())
+            .await?;
+
+        let mut entries = Vec::new();
+        for (alert, watchlist) in alerts {
+            let (Some(watchlist), Some(criminal)) =
+                (watchlist, self.get_criminal(alert.criminal_id).await?)
+            else {
+                continue;
+            };
+            entries.push(AlertEntry {
+                alert,
+                criminal_name: criminal.name,
+                watchlist,
+            });
+        }
+        Ok(entries)
+    }
+
+    pub async fn acknowledge_alert(
+        &self,
+        alert_id: u32,
+        note: Option<String>,
+    ) -> Result<(), DbErr> {
+        alert::Entity::update_many()
+            .col_expr(alert::Column::AcknowledgedAt, Expr::value(Utc::now()))
+            .col_expr(alert::Column::AcknowledgementNote, Expr::value(note))
+            .filter(alert::Column::AlertId.eq(alert_id))
+            .exec(self.connection.as_ref())
+            .await?;
+        Ok(())
+    }
+
+    pub async fn log_delivery(&self, record: &DeliveryRecord) -> Result<u32, DbErr> {
+        let entry = notification_delivery::ActiveModel {
+            channel: Set(record.channel.to_string()),
+            target: Set(record.target.clone()),
+            criminal_id: Set(record.criminal_id),
+            attempt: Set(record.attempt),
+            status: Set(record.status.to_string()),
+            error: Set(record.error.clone()),
+            attempted_at: Set(Utc::now()),
+            ..Default::default()
+        };
+
+        let result = notification_delivery::Entity::insert(entry)
+            .exec(self.connection.as_ref())
+            .await?;
+
+        Ok(result.last_insert_id)
+    }
+
+    /// Stores a match as pending; nothing downstream happens until it is confirmed.
+    pub async fn record_identification(
+        &self,
+        new: NewIdentification,
+    ) -> Result<identification::Model, DbErr> {
+        identification::ActiveModel {
+            criminal_id: Set(new.criminal_id),
+            photo_id: Set(new.photo_id),
+            source: Set(new.source),
+            score: Set(new.score),
+            probe: Set(new.probe.clone().map(vault::seal)),
+            status: Set(identification::PENDING.to_string()),
+            created_at: Set(new.captured_at),
+            ..Default::default()
+        }
+        .insert(self.connection.as_ref())
+        .await
+        .map(|recorded| identification::Model {
+            probe: new.probe,
+            ..recorded
+        })
+    }
+
+    /// Records an operator's verdict. The first confirmation also logs the
+    /// sighting and moves the person's `last_seen`.
+    pub async fn review_identification(
+        &self,
+        identification_id: u32,
+        status: &str,
+        comment: Option<String>,
+    ) -> Result<identification::Model, DbErr> {
+        let txn = self.connection.begin().await?;
+
+        let existing = identification::Entity::find_by_id(identification_id)
+            .one(&txn)
+            .await?
+            .ok_or_else(|| {
+                DbErr::RecordNotFound(format!("identification {}", identification_id))
+            })?;
+        let newly_confirmed =
+            status == identification::CONFIRMED && existing.status != identification::CONFIRMED;
+
+        let mut active: identification::ActiveModel = existing.into();
+        active.status = Set(status.to_string());
+        active.comment = Set(comment.filter(|c| !c.trim().is_empty()));
+        active.reviewed_at = Set(Some(Utc::now()));
+        let reviewed = active.update(&txn).await?;
+
+        if newly_confirmed {
+            sighting::ActiveModel {
+                criminal_id: Set(reviewed.criminal_id),
+                source: Set(reviewed.source.clone()),
+                score: Set(reviewed.score),
+                seen_at: Set(reviewed.created_at),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+            criminal::Entity::update_many()
+                .col_expr(criminal::Column::LastSeen, Expr::value(reviewed.created_at))
+                .filter(criminal::Column::CriminalId.eq(reviewed.criminal_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        txn.commit().await?;
+        open_probe(reviewed)
+    }
+
+    /// Identifications with `status` (all when `None`), newest first.
+    pub async fn get_identifications(
+        &self,
+        status: Option<&str>,
+    ) -> Result<Vec<IdentificationEntry>, DbErr> {
+        let mut query = identification::Entity::find()
+            .find_also_related(criminal::Entity)
+            .order_by_desc(identification::Column::IdentificationId);
+        if let Some(status) = status {
+            query = query.filter(identification::Column::Status.eq(status));
+        }
+
+        let mut entries = Vec::new();
+        for (identification, criminal) in query.all(self.connection.as_ref()).await? {
+            let Some(criminal) = criminal else {
+                continue;
+            };
+            let candidate_photo = match identification.photo_id {
+                Some(photo_id) => self.thumbnail(photo_id, None).await?,
+                None => None,
+            };
+            entries.push(IdentificationEntry {
+                identification: open_probe(identification)?,
+                criminal_name: criminal.name,
+                candidate_photo,
+            });
+        }
+        Ok(entries)
+    }
+
+    /// Records matching `query` in the name, father's name, aliases, national
+    /// ids, marks or notes (or by exact id), newest first, with their aliases.
+    /// An empty query lists the newest records.
+    pub async fn search_criminals(
+        &self,
+        query: &str,
+        limit: u64,
+    ) -> Result<Vec<(criminal::Model, Vec<String>)>, DbErr> {
+        let query = query.trim();
+        let mut select = criminal::Entity::find();
+
+        if !query.is_empty() {
+            let pattern = format!("%{}%", query);
+            let mut condition = Condition::any()
+                .add(criminal::Column::Name.like(&pattern))
+                .add(criminal::Column::FathersName.like(&pattern))
+                .add(criminal::Column::NationalIds.like(&pattern))
+                .add(criminal::Column::DistinguishingMarks.like(&pattern))
+                .add(criminal::Column::Notes.like(&pattern))
+                .add(
+                    criminal::Column::CriminalId.in_subquery(
+                        Query::select()
+                            .column(criminal_alias::Column::CriminalId)
+                            .from(criminal_alias::Entity)
+                            .and_where(criminal_alias::Column::Alias.like(&pattern))
+                            .to_owned(),
+                    ),
+                );
+            if let Ok(id) = query.trim_start_matches('#').parse::<u32>() {
+                condition = condition.add(criminal::Column::CriminalId.eq(id));
+            }
+            select = select.filter(condition);
+        }
+
+        let criminals = select
+            .order_by_desc(criminal::Column::CriminalId)
+            .limit(limit)
+            .all(self.connection.as_ref())
+            .await?;
+
+        let ids: Vec<u32> = criminals.iter().map(|c| c.criminal_id).collect();
+        let mut aliases: HashMap<u32, Vec<String>> = HashMap::new();
+        for alias in criminal_alias::Entity::find()
+            .filter(criminal_alias::Column::CriminalId.is_in(ids))
+            .order_by_asc(criminal_alias::Column::AliasId)
+            .all(self.connection.as_ref())
+            .await?
+        {
+            aliases
+                .entry(alias.criminal_id)
+                .or_default()
+                .push(alias.alias);
+        }
+
+        Ok(criminals
+            .into_iter()
+            .map(|c| {
+                let names = aliases.remove(&c.criminal_id).unwrap_or_default();
+                (c, names)
+            })
+            .collect())
+    }
+
+    /// A person's photos with their image bytes, decrypted.
     pub async fn get_criminal_photos(
         &self,
         criminal_id: u32,
+    ) -> Result<Vec<criminal_photo::Model>, DbErr> {
+        let mut photos = Vec::new();
+        for photo in self.get_criminal_photo_records(criminal_id).await? {
+            let photo = self.load_photo(photo).await;
+            photos.push(self.backfill_thumbnail(photo).await?);
+        }
+        Ok(photos)
+    }
+
+    /// A person's photo rows as stored: photos kept in the photo store come
+    /// without their bytes, and blobs are still sealed when encryption is on.
+    pub async fn get_criminal_photo_records(
+        &self,
+        criminal_id: u32,
     ) -> Result<Vec<criminal_photo::Model>, DbErr> {
         criminal_photo::Entity::find()
             .filter(criminal_photo::Column::CriminalId.eq(criminal_id))
@@ -85,21 +806,727 @@ impl CriminalDB {
         Ok(Some((criminal, photos)))
     }
 
-    pub async fn add_criminal_photo(
+    /// A photo row without its owner, with the image sealed or in the store.
+    async fn new_photo(
         &self,
-        criminal_id: u32,
         photo_bytes: Vec<u8>,
+        face_box: Option<[f32; 4]>,
+    ) -> Result<criminal_photo::ActiveModel, DbErr> {
+        let photo_size = u32::try_from(photo_bytes.len()).ok();
+        let thumbnail = thumbnail_of(photo_bytes.clone()).await;
+        let (photo, photo_hash) = match &self.photo_store {
+            Some(store) => {
+                let hash = store
+                    .put(&photo_bytes)
+                    .await
+                    .map_err(|e| DbErr::Custom(format!("photo store: {}", e)))?;
+                (None, Some(hash))
+            }
+            None => (Some(vault::seal(photo_bytes)), None),
+        };
+
+        Ok(criminal_photo::ActiveModel {
+            photo: Set(photo),
+            photo_hash: Set(photo_hash),
+            photo_size: Set(photo_size),
+            thumbnail: Set(thumbnail.map(vault::seal)),
+            face_x1: Set(face_box.map(|b| b[0])),
+            face_y1: Set(face_box.map(|b| b[1])),
+            face_x2: Set(face_box.map(|b| b[2])),
+            face_y2: Set(face_box.map(|b| b[3])),
+            ..Default::default()
+        })
+    }
+
+    pub async fn add_face_embedding(
+        &self,
+        photo_id: u32,
+        criminal_id: u32,
+        model_name: String,
+        vector: &[f32],
+    ) -> Result<u32, DbErr> {
+        let new_embedding = face_embedding::ActiveModel {
+            photo_id: Set(photo_id),
+            criminal_id: Set(criminal_id),
+            model_name: Set(model_name),
+            vector: Set(vault::seal(face_embedding::vector_to_blob(vector))),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        };
+
+        let result = face_embedding::Entity::insert(new_embedding)
+            .exec(self.connection.as_ref())
+            .await?;
+
+        Ok(result.last_insert_id)
+    }
+
+    pub async fn get_face_embeddings(
+        &self,
+        criminal_id: u32,
+    ) -> Result<Vec<face_embedding::Model>, DbErr> {
+        face_embedding::Entity::find()
+            .filter(face_embedding::Column::CriminalId.eq(criminal_id))
+            .all(self.connection.as_ref())
+            .await?
+            .into_iter()
+            .map(open_embedding)
+            .collect()
+    }
+
+    /// Photos with no face embedding, as (criminal id, photo id): those
+    /// enrolled while the gallery lived in the engine's `faces_db.npz`, or
+    /// where the engine found no face.
+    pub async fn get_photos_without_embeddings(&self) -> Result<Vec<(u32, u32)>, DbErr> {
+        criminal_photo::Entity::find()
+            .select_only()
+            .column(criminal_photo::Column::CriminalId)
+            .column(criminal_photo::Column::PhotoId)
+            .filter(
+                criminal_photo::Column::PhotoId.not_in_subquery(
+                    Query::select()
+                        .column(face_embedding::Column::PhotoId)
+                        .from(face_embedding::Entity)
+                        .to_owned(),
+                ),
+            )
+            .order_by_asc(criminal_photo::Column::CriminalId)
+            .order_by_asc(criminal_photo::Column::PhotoId)
+            .into_tuple()
+            .all(self.connection.as_ref())
+            .await
+    }
+
+    /// Every stored embedding, used to (re)load the engine's gallery.
+    pub async fn get_all_face_embeddings(&self) -> Result<Vec<face_embedding::Model>, DbErr> {
+        face_embedding::Entity::find()
+            .order_by_asc(face_embedding::Column::EmbeddingId)
+            .all(self.connection.as_ref())
+            .await?
+            .into_iter()
+            .map(open_embedding)
+            .collect()
+    }
+
+    pub async fn add_sighting(
+        &self,
+        criminal_id: u32,
+        source: &str,
+        score: Option<f32>,
     ) -> Result<u32, DbErr> {
-        let new_photo = criminal_photo::ActiveModel {
+        let new_sighting = sighting::ActiveModel {
             criminal_id: Set(criminal_id),
-            photo: Set(photo_bytes),
+            source: Set(source.to_string()),
+            score: Set(score),
+            seen_at: Set(Utc::now()),
             ..Default::default()
         };
 
-        let result = criminal_photo::Entity::insert(new_photo)
+        let result = sighting::Entity::insert(new_sighting)
             .exec(self.connection.as_ref())
             .await?;
 
+        if let Some(criminal) = self.get_criminal(criminal_id).await? {
+            let mut active: criminal::ActiveModel = criminal.into();
+            active.last_seen = Set(Some(Utc::now()));
+            active.update(self.connection.as_ref()).await?;
+        }
+
         Ok(result.last_insert_id)
     }
+
+    /// What `enroll` wrote for outbox entry `key`, if it got as far as
+    /// committing. The photo ids are those of the record, oldest first.
+    pub async fn synced_enrollment(&self, key: &str) -> Result<Option<EnrollmentSummary>, DbErr> {
+        let db = self.connection.as_ref();
+        let marker = audit_log::Entity::find()
+            .filter(audit_log::Column::Action.eq(audit_log::SYNCED_OUTBOX_ENTRY))
+            .filter(audit_log::Column::Details.eq(key))
+            .one(db)
+            .await?;
+        let Some(criminal_id) = marker.and_then(|m| m.criminal_id) else {
+            return Ok(None);
+        };
+        let photo_ids = criminal_photo::Entity::find()
+            .select_only()
+            .column(criminal_photo::Column::PhotoId)
+            .filter(criminal_photo::Column::CriminalId.eq(criminal_id))
+            .order_by_asc(criminal_photo::Column::PhotoId)
+            .into_tuple()
+            .all(db)
+            .await?;
+        Ok(Some(EnrollmentSummary {
+            criminal_id,
+            photo_ids,
+        }))
+    }
+
+    /// Saves a new record, or adds to `existing`, with its photos, aliases,
+    /// offences and audit entry, and marks its outbox entry as synced. All or
+    /// nothing, apart from photo store files, which are written first;
+    /// they're content-addressed, so saving again reuses them.
+    pub async fn enroll(
+        &self,
+        existing: Option<u32>,
+        enrollment: NewEnrollment,
+    ) -> Result<EnrollmentSummary, DbErr> {
+        // Thumbnails and store writes are slow; keep them out of the transaction
+        let mut photos = Vec::with_capacity(enrollment.photos.len());
+        for (bytes, face_box) in enrollment.photos {
+            photos.push(self.new_photo(bytes, face_box).await?);
+        }
+
+        let txn = self.connection.begin().await?;
+
+        let (criminal_id, name) = match existing {
+            Some(id) => {
+                let criminal = criminal::Entity::find_by_id(id)
+                    .one(&txn)
+                    .await?
+                    .ok_or_else(|| DbErr::RecordNotFound(format!("criminal {}", id)))?;
+                (id, criminal.name)
+            }
+            None => {
+                let details = enrollment.details;
+                let new_criminal = criminal::ActiveModel {
+                    name: Set(enrollment.name.clone()),
+                    fathers_name: Set(enrollment.fathers_name),
+                    arrested_location: Set(enrollment.arrested_location),
+                    // Counted from the offences below
+                    no_of_crimes: Set(0),
+                    date_of_arrest: Set(Utc::now()),
+                    date_of_birth: Set(details.date_of_birth),
+                    gender: Set(details.gender),
+                    national_ids: Set(details.national_ids),
+                    height_cm: Set(details.height_cm),
+                    distinguishing_marks: Set(details.distinguishing_marks),
+                    notes: Set(details.notes),
+                    ..Default::default()
+                };
+                let id = criminal::Entity::insert(new_criminal)
+                    .exec(&txn)
+                    .await?
+                    .last_insert_id;
+                (id, enrollment.name)
+            }
+        };
+
+        let mut photo_ids = Vec::with_capacity(photos.len());
+        for mut photo in photos {
+            photo.criminal_id = Set(criminal_id);
+            let result = criminal_photo::Entity::insert(photo).exec(&txn).await?;
+            photo_ids.push(result.last_insert_id);
+        }
+
+        // Only aliases the record doesn't have yet, ignoring case and its name
+        let mut known: Vec<String> = criminal_alias::Entity::find()
+            .filter(criminal_alias::Column::CriminalId.eq(criminal_id))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|a| a.alias.to_lowercase())
+            .collect();
+        known.push(name.to_lowercase());
+        for alias in enrollment.aliases.iter().map(|a| a.trim()) {
+            if alias.is_empty() || known.contains(&alias.to_lowercase()) {
+                continue;
+            }
+            known.push(alias.to_lowercase());
+            criminal_alias::ActiveModel {
+                criminal_id: Set(criminal_id),
+                alias: Set(alias.to_string()),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        if !enrollment.offences.is_empty() {
+            for new in enrollment.offences {
+                offence::ActiveModel {
+                    criminal_id: Set(criminal_id),
+                    offence_type_id: Set(new.offence_type_id),
+                    offence_date: Set(new.offence_date),
+                    location: Set(new.location),
+                    case_number: Set(new.case_number),
+                    status: Set(new.status),
+                    ..Default::default()
+                }
+                .insert(&txn)
+                .await?;
+            }
+            let count = offence::Entity::find()
+                .filter(offence::Column::CriminalId.eq(criminal_id))
+                .count(&txn)
+                .await? as u32;
+            criminal::Entity::update_many()
+                .col_expr(criminal::Column::NoOfCrimes, Expr::value(count))
+                .filter(criminal::Column::CriminalId.eq(criminal_id))
+                .exec(&txn)
+                .await?;
+        }
+
+        if let Some(audit) = enrollment.audit {
+            audit_log::ActiveModel {
+                action: Set(audit.action.to_string()),
+                criminal_id: Set(Some(criminal_id)),
+                related_criminal_id: Set(audit.related_criminal_id),
+                details: Set(Some(audit.details)),
+                created_at: Set(Utc::now()),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+        if let Some(key) = enrollment.outbox_entry {
+            audit_log::ActiveModel {
+                action: Set(audit_log::SYNCED_OUTBOX_ENTRY.to_string()),
+                criminal_id: Set(Some(criminal_id)),
+                details: Set(Some(key)),
+                created_at: Set(Utc::now()),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+        }
+
+        txn.commit().await?;
+
+        Ok(EnrollmentSummary {
+            criminal_id,
+            photo_ids,
+        })
+    }
+
+    /// Folds `source_id` into `target_id`: photos, embeddings, sightings and
+    /// offences move over, crime counts add up, blank details are filled from the
+    /// source, the source row is deleted and the merge is written to the
+    /// audit log. All or nothing.
+    pub async fn merge_criminals(
+        &self,
+        source_id: u32,
+        target_id: u32,
+        reason: Option<String>,
+    ) -> Result<MergeSummary, DbErr> {
+        if source_id == target_id {
+            return Err(DbErr::Custom(
+                "cannot merge a record into itself".to_string(),
+            ));
+        }
+
+        let txn = self.connection.begin().await?;
+
+        let source = criminal::Entity::find_by_id(source_id)
+            .one(&txn)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("criminal {}", source_id)))?;
+        let target = criminal::Entity::find_by_id(target_id)
+            .one(&txn)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("criminal {}", target_id)))?;
+
+        let photos = criminal_photo::Entity::update_many()
+            .col_expr(criminal_photo::Column::CriminalId, Expr::value(target_id))
+            .filter(criminal_photo::Column::CriminalId.eq(source_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        let embeddings = face_embedding::Entity::update_many()
+            .col_expr(face_embedding::Column::CriminalId, Expr::value(target_id))
+            .filter(face_embedding::Column::CriminalId.eq(source_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        let sightings = sighting::Entity::update_many()
+            .col_expr(sighting::Column::CriminalId, Expr::value(target_id))
+            .filter(sighting::Column::CriminalId.eq(source_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        let offences = offence::Entity::update_many()
+            .col_expr(offence::Column::CriminalId, Expr::value(target_id))
+            .filter(offence::Column::CriminalId.eq(source_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+
+        // Watchlist memberships follow the person; drop ones the target already has
+        let target_lists: Vec<u32> = watchlist_member::Entity::find()
+            .filter(watchlist_member::Column::CriminalId.eq(target_id))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|m| m.watchlist_id)
+            .collect();
+        watchlist_member::Entity::delete_many()
+            .filter(watchlist_member::Column::CriminalId.eq(source_id))
+            .filter(watchlist_member::Column::WatchlistId.is_in(target_lists))
+            .exec(&txn)
+            .await?;
+        watchlist_member::Entity::update_many()
+            .col_expr(watchlist_member::Column::CriminalId, Expr::value(target_id))
+            .filter(watchlist_member::Column::CriminalId.eq(source_id))
+            .exec(&txn)
+            .await?;
+        alert::Entity::update_many()
+            .col_expr(alert::Column::CriminalId, Expr::value(target_id))
+            .filter(alert::Column::CriminalId.eq(source_id))
+            .exec(&txn)
+            .await?;
+        let identifications = identification::Entity::update_many()
+            .col_expr(identification::Column::CriminalId, Expr::value(target_id))
+            .filter(identification::Column::CriminalId.eq(source_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+
+        // The source's aliases, plus its primary name, become target aliases
+        let mut known: Vec<String> = criminal_alias::Entity::find()
+            .filter(criminal_alias::Column::CriminalId.eq(target_id))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|a| a.alias.to_lowercase())
+            .collect();
+        known.push(target.name.to_lowercase());
+
+        let mut aliases = 0;
+        let source_aliases = criminal_alias::Entity::find()
+            .filter(criminal_alias::Column::CriminalId.eq(source_id))
+            .all(&txn)
+            .await?;
+        let incoming = std::iter::once(source.name.clone())
+            .chain(source_aliases.iter().map(|a| a.alias.clone()));
+        for alias in incoming {
+            if known.contains(&alias.to_lowercase()) {
+                continue;
+            }
+            known.push(alias.to_lowercase());
+            criminal_alias::ActiveModel {
+                criminal_id: Set(target_id),
+                alias: Set(alias),
+                ..Default::default()
+            }
+            .insert(&txn)
+            .await?;
+            aliases += 1;
+        }
+
+        let mut merged: criminal::ActiveModel = target.clone().into();
+        merged.no_of_crimes = Set(target.no_of_crimes + source.no_of_crimes);
+        merged.last_seen = Set(target.last_seen.max(source.last_seen));
+        merged.date_of_arrest = Set(target.date_of_arrest.max(source.date_of_arrest));
+        if target.fathers_name.is_none() {
+            merged.fathers_name = Set(source.fathers_name.clone());
+        }
+        if target.date_of_birth.is_none() {
+            merged.date_of_birth = Set(source.date_of_birth);
+        }
+        if target.gender.is_none() {
+            merged.gender = Set(source.gender.clone());
+        }
+        if target.height_cm.is_none() {
+            merged.height_cm = Set(source.height_cm);
+        }
+        merged.national_ids = Set(join_text(&target.national_ids, &source.national_ids, ", "));
+        merged.distinguishing_marks = Set(join_text(
+            &target.distinguishing_marks,
+            &source.distinguishing_marks,
+            "; ",
+        ));
+        merged.notes = Set(join_text(&target.notes, &source.notes, "\n\n"));
+        // "Last arrested location" follows whichever arrest is more recent
+        let source_is_newer = source.date_of_arrest > target.date_of_arrest;
+        if target.arrested_location.is_none()
+            || (source_is_newer && source.arrested_location.is_some())
+        {
+            merged.arrested_location = Set(source.arrested_location.clone());
+        }
+        merged.update(&txn).await?;
+
+        criminal::Entity::delete_by_id(source_id).exec(&txn).await?;
+
+        let mut details = format!(
+            "Merged #{} \"{}\" ({} photos, {} embeddings, {} sightings, {} identifications, {} offences, {} aliases)",
+            source_id, source.name, photos, embeddings, sightings, identifications, offences, aliases
+        );
+        if let Some(reason) = reason.filter(|r| !r.trim().is_empty()) {
+            details.push_str(": ");
+            details.push_str(reason.trim());
+        }
+        audit_log::ActiveModel {
+            action: Set(audit_log::MERGED_RECORDS.to_string()),
+            criminal_id: Set(Some(target_id)),
+            related_criminal_id: Set(Some(source_id)),
+            details: Set(Some(details)),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        Ok(MergeSummary {
+            photos,
+            embeddings,
+            sightings,
+            identifications,
+            offences,
+            aliases,
+        })
+    }
+
+    /// Removes every trace of a person: the record, photos, embeddings,
+    /// sightings, identifications, alerts, offences, aliases, watchlist
+    /// memberships and notification deliveries, then the photo store files
+    /// no other photo uses. Earlier audit entries about them keep their ids
+    /// but lose their details; the purge itself is audited with `reason`.
+    /// All or nothing, apart from the store files.
+    pub async fn purge_criminal(
+        &self,
+        criminal_id: u32,
+        reason: String,
+    ) -> Result<PurgeSummary, DbErr> {
+        let reason = reason.trim();
+        if reason.is_empty() {
+            return Err(DbErr::Custom("a purge needs a reason".to_string()));
+        }
+
+        let txn = self.connection.begin().await?;
+
+        criminal::Entity::find_by_id(criminal_id)
+            .one(&txn)
+            .await?
+            .ok_or_else(|| DbErr::RecordNotFound(format!("criminal {}", criminal_id)))?;
+        let hashes: Vec<String> = criminal_photo::Entity::find()
+            .select_only()
+            .column(criminal_photo::Column::PhotoHash)
+            .filter(criminal_photo::Column::CriminalId.eq(criminal_id))
+            .filter(criminal_photo::Column::PhotoHash.is_not_null())
+            .into_tuple()
+            .all(&txn)
+            .await?;
+
+        // The foreign keys would cascade, but the counts go in the audit entry
+        let identifications = identification::Entity::delete_many()
+            .filter(identification::Column::CriminalId.eq(criminal_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        let alerts = alert::Entity::delete_many()
+            .filter(alert::Column::CriminalId.eq(criminal_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        let sightings = sighting::Entity::delete_many()
+            .filter(sighting::Column::CriminalId.eq(criminal_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        let embeddings = face_embedding::Entity::delete_many()
+            .filter(face_embedding::Column::CriminalId.eq(criminal_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        let photos = criminal_photo::Entity::delete_many()
+            .filter(criminal_photo::Column::CriminalId.eq(criminal_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        let offences = offence::Entity::delete_many()
+            .filter(offence::Column::CriminalId.eq(criminal_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        let aliases = criminal_alias::Entity::delete_many()
+            .filter(criminal_alias::Column::CriminalId.eq(criminal_id))
+            .exec(&txn)
+            .await?
+            .rows_affected;
+        watchlist_member::Entity::delete_many()
+            .filter(watchlist_member::Column::CriminalId.eq(criminal_id))
+            .exec(&txn)
+            .await?;
+        // No foreign key here: the delivery log outlives its records otherwise
+        notification_delivery::Entity::delete_many()
+            .filter(notification_delivery::Column::CriminalId.eq(criminal_id))
+            .exec(&txn)
+            .await?;
+        criminal::Entity::delete_by_id(criminal_id)
+            .exec(&txn)
+            .await?;
+
+        // Merge and enrollment entries can quote names
+        audit_log::Entity::update_many()
+            .col_expr(
+                audit_log::Column::Details,
+                Expr::value(audit_log::REDACTED_DETAILS),
+            )
+            .filter(
+                Condition::any()
+                    .add(audit_log::Column::CriminalId.eq(criminal_id))
+                    .add(audit_log::Column::RelatedCriminalId.eq(criminal_id)),
+            )
+            .exec(&txn)
+            .await?;
+        audit_log::ActiveModel {
+            action: Set(audit_log::PURGED_RECORD.to_string()),
+            criminal_id: Set(Some(criminal_id)),
+            details: Set(Some(format!(
+                "Purged #{} ({} photos, {} embeddings, {} sightings, {} identifications, {} alerts, {} offences, {} aliases): {}",
+                criminal_id, photos, embeddings, sightings, identifications, alerts, offences, aliases, reason
+            ))),
+            created_at: Set(Utc::now()),
+            ..Default::default()
+        }
+        .insert(&txn)
+        .await?;
+
+        txn.commit().await?;
+
+        if let Some(store) = &self.photo_store {
+            for hash in hashes {
+                // The same photo may be enrolled for someone else too
+                let still_used = criminal_photo::Entity::find()
+                    .filter(criminal_photo::Column::PhotoHash.eq(hash.as_str()))
+                    .count(self.connection.as_ref())
+                    .await?
+                    > 0;
+                if !still_used {
+                    if let Err(e) = store.remove(&hash).await {
+                        eprintln!("❌ Purge of #{}: {}: {}", criminal_id, hash, e);
+                    }
+                }
+            }
+        }
+
+        Ok(PurgeSummary {
+            photos,
+            embeddings,
+            sightings,
+            identifications,
+            alerts,
+            offences,
+            aliases,
+        })
+    }
+
+    /// One pass of the retention rules: probe images recorded before
+    /// `probes_before` are cleared, identifications never confirmed from
+    /// before `unconfirmed_before` are deleted, and so are sightings (with
+    /// the confirmed identifications behind them) from before
+    /// `sightings_before`. `None` keeps that data forever.
+    pub async fn apply_retention(
+        &self,
+        probes_before: Option<DateTime<Utc>>,
+        unconfirmed_before: Option<DateTime<Utc>>,
+        sightings_before: Option<DateTime<Utc>>,
+    ) -> Result<RetentionSummary, DbErr> {
+        let connection = self.connection.as_ref();
+        let mut summary = RetentionSummary::default();
+
+        if let Some(cutoff) = probes_before {
+            summary.probes_cleared = identification::Entity::update_many()
+                .col_expr(
+                    identification::Column::Probe,
+                    Expr::value(Option::<Vec<u8>>::None),
+                )
+                .filter(identification::Column::Probe.is_not_null())
+                .filter(identification::Column::CreatedAt.lt(cutoff))
+                .exec(connection)
+                .await?
+                .rows_affected;
+        }
+        if let Some(cutoff) = unconfirmed_before {
+            summary.unconfirmed_deleted = identification::Entity::delete_many()
+                .filter(identification::Column::Status.ne(identification::CONFIRMED))
+                .filter(identification::Column::CreatedAt.lt(cutoff))
+                .exec(connection)
+                .await?
+                .rows_affected;
+        }
+        if let Some(cutoff) = sightings_before {
+            summary.sightings_deleted = sighting::Entity::delete_many()
+                .filter(sighting::Column::SeenAt.lt(cutoff))
+                .exec(connection)
+                .await?
+                .rows_affected;
+            identification::Entity::delete_many()
+                .filter(identification::Column::Status.eq(identification::CONFIRMED))
+                .filter(identification::Column::CreatedAt.lt(cutoff))
+                .exec(connection)
+                .await?;
+        }
+        Ok(summary)
+    }
+}
+
+/// Both texts when both are set and differ, otherwise whichever is set.
+fn join_text(keep: &Option<String>, other: &Option<String>, separator: &str) -> Option<String> {
+    match (keep, other) {
+        (Some(a), Some(b)) if a != b => Some(format!("{}{}{}", a, separator, b)),
+        (Some(a), _) => Some(a.clone()),
+        (None, b) => b.clone(),
+    }
+}
+
+/// Decrypts a blob read straight from a column.
+fn open_blob(what: &str, bytes: Vec<u8>) -> Result<Vec<u8>, DbErr> {
+    vault::open(bytes).map_err(|e| DbErr::Custom(format!("{}: {}", what, e)))
+}
+
+fn open_probe(mut identification: identification::Model) -> Result<identification::Model, DbErr> {
+    identification.probe = identification
+        .probe
+        .map(|probe| open_blob("probe image", probe))
+        .transpose()?;
+    Ok(identification)
+}
+
+fn open_embedding(mut embedding: face_embedding::Model) -> Result<face_embedding::Model, DbErr> {
+    embedding.vector = open_blob("embedding", embedding.vector)?;
+    Ok(embedding)
+}
+
+/// `make_thumbnail` off the async runtime; `None` (logged) if the photo
+/// can't be decoded.
+async fn thumbnail_of(bytes: Vec<u8>) -> Option<Vec<u8>> {
+    match tokio::task::spawn_blocking(move || make_thumbnail(&bytes)).await {
+        Ok(Ok(thumbnail)) => Some(thumbnail),
+        Ok(Err(e)) => {
+            eprintln!("❌ Thumbnail failed: {}", e);
+            None
+        }
+        Err(e) => {
+            eprintln!("❌ Thumbnail task failed: {}", e);
+            None
+        }
+    }
+}
+
+/// Whether anything the data keys are needed for is stored: in the photo
+/// store, the outbox or the database. For `Vault::load` at startup; the
+/// database is checked on a thread of its own, so this works both inside and
+/// outside an async runtime.
+pub fn sealed_data_stored(config: &AppConfig) -> Result<bool, String> {
+    let store = config.storage.photo_store().map(|s| s.root().to_path_buf());
+    for dir in store.into_iter().chain([config.storage.outbox_dir()]) {
+        if vault::holds_sealed_files(&dir).map_err(|e| format!("{}: {}", dir.display(), e))? {
+            return Ok(true);
+        }
+    }
+
+    let url = config.database_url.clone();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .map_err(|e| e.to_string())?;
+        runtime
+            .block_on(async { CriminalDB::new(&url).await?.holds_sealed_data().await })
+            .map_err(|e| format!("database: {}", e))
+    })
+    .join()
+    .map_err(|_| "checking the database for encrypted data panicked".to_string())?
 }
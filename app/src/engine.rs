@@ -0,0 +1,46 @@
+use crate::config::{EngineBackend, EngineConfig};
+use crate::python_process::PythonProcess;
+
+/// A recognition backend speaking the line protocol of `model_engine/main.py`.
+///
+/// Commands go in through `send`; replies are written to
+/// `python_process::engine_output()` and reach the app as `Message::PythonOutput`,
+/// so pages never need to know which backend is running.
+pub trait RecognitionEngine: Send + Sync {
+    fn send(&self, message: &str) -> std::io::Result<()>;
+
+    /// Sends a block of commands without blocking the caller on a full pipe.
+    fn send_batch(&self, messages: Vec<String>);
+}
+
+impl RecognitionEngine for PythonProcess {
+    fn send(&self, message: &str) -> std::io::Result<()> {
+        PythonProcess::send(self, message)
+    }
+
+    fn send_batch(&self, messages: Vec<String>) {
+        PythonProcess::send_batch(self, messages)
+    }
+}
+
+/// Starts the backend chosen in the config, or says why it can't run here.
+pub fn spawn_engine(config: &EngineConfig) -> Result<Box<dyn RecognitionEngine>, String> {
+    match config.backend {
+        EngineBackend::Python => {
+            let launcher = Some(config.python_launcher.as_str()).filter(|l| !l.is_empty());
+            match PythonProcess::spawn(launcher, &config.python_script, &config.python_dir) {
+                Ok(process) => Ok(Box::new(process)),
+                Err(e) => Err(format!("Failed to start the Python engine: {e}")),
+            }
+        }
+
+        #[cfg(feature = "onnx")]
+        EngineBackend::Onnx => Ok(Box::new(crate::onnx_engine::OnnxEngine::spawn(config))),
+
+        #[cfg(not(feature = "onnx"))]
+        EngineBackend::Onnx => Err(
+            "The ONNX backend was selected but the app was built without the `onnx` feature"
+                .to_string(),
+        ),
+    }
+}
@@ -0,0 +1,50 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "alerts")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub alert_id: u32,
+    pub criminal_id: u32,
+    // The most urgent list the person was on when matched
+    pub watchlist_id: u32,
+    // Where the match came from: "image", "video" or "webcam"
+    pub source: String,
+    pub created_at: DateTime<Utc>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub acknowledgement_note: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::criminal::Entity",
+        from = "Column::CriminalId",
+        to = "super::criminal::Column::CriminalId",
+        on_delete = "Cascade"
+    )]
+    Criminal,
+    #[sea_orm(
+        belongs_to = "super::watchlist::Entity",
+        from = "Column::WatchlistId",
+        to = "super::watchlist::Column::WatchlistId",
+        on_delete = "Cascade"
+    )]
+    Watchlist,
+}
+
+impl Related<super::criminal::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Criminal.def()
+    }
+}
+
+impl Related<super::watchlist::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Watchlist.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
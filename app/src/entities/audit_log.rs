@@ -0,0 +1,33 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+
+// Actions recorded in `audit_log.action`
+pub const MERGED_ENROLLMENT: &str = "merged_enrollment";
+pub const FORCED_NEW_RECORD: &str = "forced_new_record";
+pub const SKIPPED_DUPLICATE_CHECK: &str = "skipped_duplicate_check";
+pub const MERGED_RECORDS: &str = "merged_records";
+pub const PURGED_RECORD: &str = "purged_record";
+// Details hold the outbox entry's key, so a replayed entry isn't written twice
+pub const SYNCED_OUTBOX_ENTRY: &str = "synced_outbox_entry";
+
+/// What's left of an entry's details once the person it's about is purged.
+pub const REDACTED_DETAILS: &str = "[redacted: record purged]";
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "audit_log")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub audit_id: u32,
+    pub action: String,
+    // No foreign keys: history outlives the records it mentions
+    pub criminal_id: Option<u32>,
+    pub related_criminal_id: Option<u32>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use chrono::{DateTime, Duration, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use sea_orm::Set;
 use sea_orm::entity::prelude::*;
 
@@ -12,14 +12,62 @@ pub struct Model {
     pub fathers_name: Option<String>,
     pub date_of_arrest: DateTime<Utc>,
     pub last_seen: Option<DateTime<Utc>>,
+    // Kept in step with the `offences` rows by `CriminalDB::enroll` and merges
     pub no_of_crimes: u32,
     pub arrested_location: Option<String>,
+    pub date_of_birth: Option<NaiveDate>,
+    pub gender: Option<String>,
+    // Comma-separated, as entered
+    pub national_ids: Option<String>,
+    pub height_cm: Option<u16>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub distinguishing_marks: Option<String>,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub notes: Option<String>,
+}
+
+impl Model {
+    /// Optional identity attributes that are filled in, as (label, value) for details views.
+    pub fn attribute_fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        if let Some(dob) = self.date_of_birth {
+            fields.push(("DATE OF BIRTH", dob.format("%Y-%m-%d").to_string()));
+        }
+        if let Some(gender) = &self.gender {
+            fields.push(("GENDER", gender.clone()));
+        }
+        if let Some(ids) = &self.national_ids {
+            fields.push(("NATIONAL ID", ids.clone()));
+        }
+        if let Some(height) = self.height_cm {
+            fields.push(("HEIGHT", format!("{} cm", height)));
+        }
+        if let Some(marks) = &self.distinguishing_marks {
+            fields.push(("MARKS / TATTOOS", marks.clone()));
+        }
+        if let Some(notes) = &self.notes {
+            fields.push(("NOTES", notes.clone()));
+        }
+        fields
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
 pub enum Relation {
     #[sea_orm(has_many = "super::criminal_photo::Entity")]
     Photos,
+    #[sea_orm(has_many = "super::face_embedding::Entity")]
+    Embeddings,
+    #[sea_orm(has_many = "super::sighting::Entity")]
+    Sightings,
+    #[sea_orm(has_many = "super::criminal_alias::Entity")]
+    Aliases,
+    #[sea_orm(has_many = "super::offence::Entity")]
+    Offences,
+    #[sea_orm(has_many = "super::watchlist_member::Entity")]
+    WatchlistMemberships,
+    #[sea_orm(has_many = "super::identification::Entity")]
+    Identifications,
 }
 
 impl Related<super::criminal_photo::Entity> for Entity {
@@ -28,6 +76,42 @@ impl Related<super::criminal_photo::Entity> for Entity {
     }
 }
 
+impl Related<super::face_embedding::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Embeddings.def()
+    }
+}
+
+impl Related<super::sighting::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Sightings.def()
+    }
+}
+
+impl Related<super::criminal_alias::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Aliases.def()
+    }
+}
+
+impl Related<super::offence::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Offences.def()
+    }
+}
+
+impl Related<super::watchlist_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::WatchlistMemberships.def()
+    }
+}
+
+impl Related<super::identification::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Identifications.def()
+    }
+}
+
 #[async_trait]
 impl ActiveModelBehavior for ActiveModel {
     async fn before_save<C>(mut self, _db: &C, _insert: bool) -> Result<Self, DbErr>
@@ -1,13 +1,41 @@
 use sea_orm::entity::prelude::*;
 
+/// A registered photo. With `[encryption]` on, `photo` and `thumbnail` are
+/// sealed in the table; `CriminalDB` hands them out opened.
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
 #[sea_orm(table_name = "criminal_photos")]
 pub struct Model {
     #[sea_orm(primary_key)]
     pub photo_id: u32,
     pub criminal_id: u32,
-    // Vec<u8> auto maps to a binary/blob column
-    pub photo: Vec<u8>,
+    // The image itself; NULL when it lives in the photo store instead
+    pub photo: Option<Vec<u8>>,
+    // SHA-256 of the image, its file name in the photo store
+    pub photo_hash: Option<String>,
+    pub photo_size: Option<u32>,
+    // Small JPEG for lists and viewers (see preprocess::make_thumbnail)
+    pub thumbnail: Option<Vec<u8>>,
+    // Box of the suspect's face in the photo (x1, y1, x2, y2), when known
+    pub face_x1: Option<f32>,
+    pub face_y1: Option<f32>,
+    pub face_x2: Option<f32>,
+    pub face_y2: Option<f32>,
+}
+
+impl Model {
+    /// The image bytes, once loaded from the database or the photo store.
+    pub fn bytes(&self) -> &[u8] {
+        self.photo.as_deref().unwrap_or_default()
+    }
+
+    /// The thumbnail, or the full image for a photo that has none yet.
+    pub fn preview(&self) -> &[u8] {
+        self.thumbnail.as_deref().unwrap_or_else(|| self.bytes())
+    }
+
+    pub fn face_box(&self) -> Option<[f32; 4]> {
+        Some([self.face_x1?, self.face_y1?, self.face_x2?, self.face_y2?])
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
@@ -19,6 +47,8 @@ pub enum Relation {
         on_delete = "Cascade"
     )]
     Criminal,
+    #[sea_orm(has_many = "super::face_embedding::Entity")]
+    Embeddings,
 }
 
 impl Related<super::criminal::Entity> for Entity {
@@ -27,4 +57,10 @@ impl Related<super::criminal::Entity> for Entity {
     }
 }
 
+impl Related<super::face_embedding::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Embeddings.def()
+    }
+}
+
 impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "face_embeddings")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub embedding_id: u32,
+    pub photo_id: u32,
+    pub criminal_id: u32,
+    // Which engine model produced the vector (e.g. "buffalo_l")
+    pub model_name: String,
+    // Little-endian f32 values, see `vector_to_blob` (sealed in the table
+    // with encryption on; `CriminalDB` opens it)
+    pub vector: Vec<u8>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::criminal::Entity",
+        from = "Column::CriminalId",
+        to = "super::criminal::Column::CriminalId",
+        on_delete = "Cascade"
+    )]
+    Criminal,
+    #[sea_orm(
+        belongs_to = "super::criminal_photo::Entity",
+        from = "Column::PhotoId",
+        to = "super::criminal_photo::Column::PhotoId",
+        on_delete = "Cascade"
+    )]
+    Photo,
+}
+
+impl Related<super::criminal::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Criminal.def()
+    }
+}
+
+impl Related<super::criminal_photo::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Photo.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl Model {
+    pub fn to_vector(&self) -> Vec<f32> {
+        blob_to_vector(&self.vector)
+    }
+}
+
+pub fn vector_to_blob(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+pub fn blob_to_vector(blob: &[u8]) -> Vec<f32> {
+    blob.chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
@@ -0,0 +1,63 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+
+// Values of `identifications.status`
+pub const PENDING: &str = "pending";
+pub const CONFIRMED: &str = "confirmed";
+pub const REJECTED: &str = "rejected"; // false positive
+pub const UNSURE: &str = "unsure";
+
+/// What an operator can mark a pending identification as.
+pub const VERDICTS: [&str; 3] = [CONFIRMED, REJECTED, UNSURE];
+
+/// Human-readable form of a stored status.
+pub fn status_label(status: &str) -> &str {
+    match status {
+        PENDING => "Pending Review",
+        CONFIRMED => "Confirmed",
+        REJECTED => "Rejected",
+        UNSURE => "Unsure",
+        other => other,
+    }
+}
+
+/// An engine match awaiting (or given) an operator's verdict. Only confirmed
+/// ones count as sightings.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "identifications")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub identification_id: u32,
+    // The candidate the engine proposed
+    pub criminal_id: u32,
+    // Their registered photo that scored best, when known
+    pub photo_id: Option<u32>,
+    // "image", "video" or "webcam"
+    pub source: String,
+    pub score: Option<f32>,
+    // The probe image as the engine saw it (sealed in the table with encryption on)
+    pub probe: Option<Vec<u8>>,
+    pub status: String,
+    pub comment: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::criminal::Entity",
+        from = "Column::CriminalId",
+        to = "super::criminal::Column::CriminalId",
+        on_delete = "Cascade"
+    )]
+    Criminal,
+}
+
+impl Related<super::criminal::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Criminal.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
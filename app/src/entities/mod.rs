@@ -1,2 +1,13 @@
+pub mod alert;
+pub mod audit_log;
 pub mod criminal;
+pub mod criminal_alias;
 pub mod criminal_photo;
+pub mod face_embedding;
+pub mod identification;
+pub mod notification_delivery;
+pub mod offence;
+pub mod offence_type;
+pub mod sighting;
+pub mod watchlist;
+pub mod watchlist_member;
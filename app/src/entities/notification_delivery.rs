@@ -0,0 +1,26 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "notification_deliveries")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub delivery_id: u32,
+    // "webhook" or "email"
+    pub channel: String,
+    // Webhook URL or recipient list
+    pub target: String,
+    // No foreign key: the log outlives the records it mentions
+    pub criminal_id: u32,
+    pub attempt: u32,
+    // One of `notify::DELIVERED`, `notify::RETRYING`, `notify::FAILED`
+    pub status: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub error: Option<String>,
+    pub attempted_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,68 @@
+use chrono::NaiveDate;
+use sea_orm::entity::prelude::*;
+
+// Values of `offences.status`
+pub const OPEN: &str = "open";
+pub const UNDER_TRIAL: &str = "under_trial";
+pub const CONVICTED: &str = "convicted";
+pub const ACQUITTED: &str = "acquitted";
+pub const CLOSED: &str = "closed";
+
+pub const STATUSES: [&str; 5] = [OPEN, UNDER_TRIAL, CONVICTED, ACQUITTED, CLOSED];
+
+/// Human-readable form of a stored status.
+pub fn status_label(status: &str) -> &str {
+    match status {
+        OPEN => "Open",
+        UNDER_TRIAL => "Under Trial",
+        CONVICTED => "Convicted",
+        ACQUITTED => "Acquitted",
+        CLOSED => "Closed",
+        other => other,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "offences")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub offence_id: u32,
+    pub criminal_id: u32,
+    pub offence_type_id: u32,
+    pub offence_date: Option<NaiveDate>,
+    pub location: Option<String>,
+    // Case / FIR number
+    pub case_number: Option<String>,
+    pub status: String,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::criminal::Entity",
+        from = "Column::CriminalId",
+        to = "super::criminal::Column::CriminalId",
+        on_delete = "Cascade"
+    )]
+    Criminal,
+    #[sea_orm(
+        belongs_to = "super::offence_type::Entity",
+        from = "Column::OffenceTypeId",
+        to = "super::offence_type::Column::OffenceTypeId"
+    )]
+    OffenceType,
+}
+
+impl Related<super::criminal::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Criminal.def()
+    }
+}
+
+impl Related<super::offence_type::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::OffenceType.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,31 @@
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "offence_types")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub offence_type_id: u32,
+    #[sea_orm(unique)]
+    pub name: String,
+}
+
+// Shown as-is in the registry's offence type picker
+impl std::fmt::Display for Model {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::offence::Entity")]
+    Offences,
+}
+
+impl Related<super::offence::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Offences.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
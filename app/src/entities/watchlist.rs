@@ -0,0 +1,56 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+
+// Values of `watchlists.priority`, most urgent first
+pub const CRITICAL: &str = "critical";
+pub const HIGH: &str = "high";
+pub const MEDIUM: &str = "medium";
+pub const LOW: &str = "low";
+
+pub const PRIORITIES: [&str; 4] = [CRITICAL, HIGH, MEDIUM, LOW];
+
+/// Sort key for a stored priority; lower is more urgent.
+pub fn priority_rank(priority: &str) -> usize {
+    PRIORITIES
+        .iter()
+        .position(|p| *p == priority)
+        .unwrap_or(PRIORITIES.len())
+}
+
+/// Human-readable form of a stored priority.
+pub fn priority_label(priority: &str) -> &str {
+    match priority {
+        CRITICAL => "Critical",
+        HIGH => "High",
+        MEDIUM => "Medium",
+        LOW => "Low",
+        other => other,
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "watchlists")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub watchlist_id: u32,
+    #[sea_orm(unique)]
+    pub name: String,
+    pub priority: String,
+    #[sea_orm(column_type = "Text", nullable)]
+    pub description: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::watchlist_member::Entity")]
+    Members,
+}
+
+impl Related<super::watchlist_member::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Members.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
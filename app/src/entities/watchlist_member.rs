@@ -0,0 +1,46 @@
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel)]
+#[sea_orm(table_name = "watchlist_members")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub member_id: u32,
+    pub watchlist_id: u32,
+    pub criminal_id: u32,
+    // Why they were listed, e.g. "absconding since March"
+    pub reason: Option<String>,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::watchlist::Entity",
+        from = "Column::WatchlistId",
+        to = "super::watchlist::Column::WatchlistId",
+        on_delete = "Cascade"
+    )]
+    Watchlist,
+    #[sea_orm(
+        belongs_to = "super::criminal::Entity",
+        from = "Column::CriminalId",
+        to = "super::criminal::Column::CriminalId",
+        on_delete = "Cascade"
+    )]
+    Criminal,
+}
+
+impl Related<super::watchlist::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Watchlist.def()
+    }
+}
+
+impl Related<super::criminal::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Criminal.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,59 @@
+//! Failures the operator needs to know about, whatever part of the app hit them.
+//!
+//! Pages and background tasks report one with `Message::ShowError`, optionally
+//! with the message that retries it; the app logs it and shows it as a toast
+//! (see `components::Toasts`), which is also kept in the notification history.
+
+use crate::Message;
+use iced::Task;
+use std::fmt;
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AppError {
+    /// Connecting to or querying MySQL failed.
+    Database(String),
+    /// The recognition engine didn't start, stopped, or answered with an error.
+    Engine(String),
+    /// A file couldn't be read or written.
+    File { path: PathBuf, reason: String },
+    /// The webcam couldn't be opened or gave no frame.
+    Camera(String),
+}
+
+impl AppError {
+    pub fn file(path: impl Into<PathBuf>, reason: impl fmt::Display) -> Self {
+        AppError::File {
+            path: path.into(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Heading for toasts and the history panel.
+    pub fn title(&self) -> &'static str {
+        match self {
+            AppError::Database(_) => "Database error",
+            AppError::Engine(_) => "Recognition engine error",
+            AppError::File { .. } => "File error",
+            AppError::Camera(_) => "Camera error",
+        }
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AppError::Database(reason) | AppError::Engine(reason) | AppError::Camera(reason) => {
+                write!(f, "{}", reason)
+            }
+            AppError::File { path, reason } => write!(f, "{}: {}", path.display(), reason),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+/// Shows `error` to the operator, with a Retry button that sends `retry`.
+pub fn report(error: AppError, retry: Option<Message>) -> Task<Message> {
+    Task::done(Message::ShowError(error, retry.map(Box::new)))
+}
@@ -0,0 +1,238 @@
+//! Hierarchical navigable small world graph over `FaceIndex` slots.
+//!
+//! Nodes are slot numbers into the index's flat vector storage. Removed
+//! slots are only tombstoned: they keep routing searches but are never
+//! returned, and the owning index rebuilds the graph when it compacts.
+
+use super::{dot, MaxScored, MinScored};
+use std::collections::{BinaryHeap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HnswParams {
+    /// Links per node on the upper layers (twice this on layer 0).
+    pub m: usize,
+    pub ef_construction: usize,
+    pub ef_search: usize,
+}
+
+impl Default for HnswParams {
+    fn default() -> Self {
+        Self {
+            m: 16,
+            ef_construction: 200,
+            ef_search: 64,
+        }
+    }
+}
+
+pub(super) struct Hnsw {
+    params: HnswParams,
+    // node -> layer -> neighbour nodes
+    links: Vec<Vec<Vec<usize>>>,
+    deleted: Vec<bool>,
+    entry_point: Option<usize>,
+    level_mult: f64,
+    rng_state: u64,
+}
+
+impl Hnsw {
+    pub(super) fn new(params: HnswParams) -> Self {
+        let m = params.m.max(2);
+        Self {
+            params: HnswParams { m, ..params },
+            links: Vec::new(),
+            deleted: Vec::new(),
+            entry_point: None,
+            level_mult: 1.0 / (m as f64).ln(),
+            rng_state: 0x9E37_79B9_7F4A_7C15,
+        }
+    }
+
+    pub(super) fn insert(&mut self, node: usize, vectors: &[f32], dim: usize) {
+        debug_assert_eq!(node, self.links.len(), "nodes are appended in slot order");
+        let level = self.random_level();
+        self.links.push(vec![Vec::new(); level + 1]);
+        self.deleted.push(false);
+
+        let Some(entry) = self.entry_point else {
+            self.entry_point = Some(node);
+            return;
+        };
+
+        let query = &vectors[node * dim..(node + 1) * dim];
+        let top = self.links[entry].len() - 1;
+        let mut nearest = entry;
+
+        for layer in (level + 1..=top).rev() {
+            nearest = self.greedy(query, nearest, layer, vectors, dim);
+        }
+
+        let mut entry_points = vec![nearest];
+        for layer in (0..=level.min(top)).rev() {
+            let found = self.search_layer(
+                query,
+                &entry_points,
+                self.params.ef_construction,
+                layer,
+                vectors,
+                dim,
+            );
+            let max_links = self.max_links(layer);
+            let neighbours: Vec<usize> = found.iter().take(max_links).map(|&(n, _)| n).collect();
+
+            for &neighbour in &neighbours {
+                self.links[neighbour][layer].push(node);
+                if self.links[neighbour][layer].len() > max_links {
+                    self.shrink(neighbour, layer, max_links, vectors, dim);
+                }
+            }
+            self.links[node][layer] = neighbours;
+            entry_points = found.into_iter().map(|(n, _)| n).collect();
+        }
+
+        if level > top {
+            self.entry_point = Some(node);
+        }
+    }
+
+    pub(super) fn mark_deleted(&mut self, node: usize) {
+        if let Some(flag) = self.deleted.get_mut(node) {
+            *flag = true;
+        }
+    }
+
+    /// Approximate top-`k` live nodes as `(node, score)`, best first.
+    pub(super) fn search(
+        &self,
+        query: &[f32],
+        k: usize,
+        vectors: &[f32],
+        dim: usize,
+        is_live: impl Fn(usize) -> bool,
+    ) -> Vec<(usize, f32)> {
+        let Some(entry) = self.entry_point else {
+            return Vec::new();
+        };
+
+        let mut nearest = entry;
+        for layer in (1..self.links[entry].len()).rev() {
+            nearest = self.greedy(query, nearest, layer, vectors, dim);
+        }
+
+        let ef = self.params.ef_search.max(k);
+        self.search_layer(query, &[nearest], ef, 0, vectors, dim)
+            .into_iter()
+            .filter(|&(node, _)| !self.deleted[node] && is_live(node))
+            .take(k)
+            .collect()
+    }
+
+    fn greedy(
+        &self,
+        query: &[f32],
+        start: usize,
+        layer: usize,
+        vectors: &[f32],
+        dim: usize,
+    ) -> usize {
+        let mut current = start;
+        let mut best = dot(query, node_vector(vectors, dim, current));
+
+        loop {
+            let mut improved = false;
+            for &neighbour in &self.links[current][layer] {
+                let score = dot(query, node_vector(vectors, dim, neighbour));
+                if score > best {
+                    best = score;
+                    current = neighbour;
+                    improved = true;
+                }
+            }
+            if !improved {
+                return current;
+            }
+        }
+    }
+
+    /// Beam search on one layer, returning up to `ef` nodes best first.
+    fn search_layer(
+        &self,
+        query: &[f32],
+        entry_points: &[usize],
+        ef: usize,
+        layer: usize,
+        vectors: &[f32],
+        dim: usize,
+    ) -> Vec<(usize, f32)> {
+        let mut visited: HashSet<usize> = entry_points.iter().copied().collect();
+        let mut candidates: BinaryHeap<MaxScored> = BinaryHeap::new();
+        let mut results: BinaryHeap<MinScored> = BinaryHeap::new();
+
+        for &node in entry_points {
+            let score = dot(query, node_vector(vectors, dim, node));
+            candidates.push(MaxScored(score, node));
+            results.push(MinScored(score, node));
+        }
+
+        while let Some(MaxScored(score, node)) = candidates.pop() {
+            if results.len() >= ef && results.peek().is_some_and(|worst| score < worst.0) {
+                break;
+            }
+
+            for &neighbour in self.links[node].get(layer).into_iter().flatten() {
+                if !visited.insert(neighbour) {
+                    continue;
+                }
+                let neighbour_score = dot(query, node_vector(vectors, dim, neighbour));
+                if results.len() < ef
+                    || results
+                        .peek()
+                        .is_some_and(|worst| neighbour_score > worst.0)
+                {
+                    candidates.push(MaxScored(neighbour_score, neighbour));
+                    results.push(MinScored(neighbour_score, neighbour));
+                    if results.len() > ef {
+                        results.pop();
+                    }
+                }
+            }
+        }
+
+        let mut found: Vec<(usize, f32)> = results.into_iter().map(|s| (s.1, s.0)).collect();
+        found.sort_by(|a, b| b.1.total_cmp(&a.1));
+        found
+    }
+
+    fn shrink(&mut self, node: usize, layer: usize, max_links: usize, vectors: &[f32], dim: usize) {
+        let base = node_vector(vectors, dim, node);
+        let mut scored: Vec<(usize, f32)> = self.links[node][layer]
+            .iter()
+            .map(|&n| (n, dot(base, node_vector(vectors, dim, n))))
+            .collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored.truncate(max_links);
+        self.links[node][layer] = scored.into_iter().map(|(n, _)| n).collect();
+    }
+
+    fn max_links(&self, layer: usize) -> usize {
+        if layer == 0 {
+            self.params.m * 2
+        } else {
+            self.params.m
+        }
+    }
+
+    fn random_level(&mut self) -> usize {
+        // xorshift64*, deterministic so rebuilt graphs are reproducible
+        self.rng_state ^= self.rng_state >> 12;
+        self.rng_state ^= self.rng_state << 25;
+        self.rng_state ^= self.rng_state >> 27;
+        let bits = self.rng_state.wrapping_mul(0x2545_F491_4F6C_DD1D) >> 11;
+        let uniform = (bits as f64 + 1.0) / (1u64 << 53) as f64;
+        (-uniform.ln() * self.level_mult).floor() as usize
+    }
+}
+
+fn node_vector(vectors: &[f32], dim: usize, node: usize) -> &[f32] {
+    &vectors[node * dim..(node + 1) * dim]
+}
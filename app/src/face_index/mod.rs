@@ -0,0 +1,401 @@
+//! In-memory similarity search over the face gallery.
+//!
+//! Every stored embedding is L2-normalized on insert, so a dot product is the
+//! cosine similarity the engine used to compute in Python. Small galleries are
+//! searched brute force; large ones can additionally keep an HNSW graph.
+
+mod hnsw;
+
+pub use hnsw::HnswParams;
+
+use hnsw::Hnsw;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::fmt;
+
+/// Minimum similarity for a probe to count as a match (the engine's old default).
+pub const MATCH_THRESHOLD: f32 = 0.4;
+
+/// Similarity at which a new enrollment is probably someone already on file.
+pub const DUPLICATE_THRESHOLD: f32 = 0.55;
+
+/// Output size of the engine's recognition model (buffalo_l / ArcFace).
+pub const EMBEDDING_DIM: usize = 512;
+
+/// Galleries at least this large get an HNSW graph on top of brute force.
+pub const HNSW_MIN_GALLERY: usize = 20_000;
+
+/// Share of dead slots after which storage is compacted and the graph rebuilt.
+const COMPACT_RATIO: f32 = 0.25;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub embedding_id: u32,
+    pub criminal_id: u32,
+    pub photo_id: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SearchHit {
+    pub embedding_id: u32,
+    pub criminal_id: u32,
+    pub photo_id: u32,
+    pub score: f32,
+}
+
+impl SearchHit {
+    /// Whether the hit clears `threshold`; a score exactly on it counts.
+    pub fn matches(&self, threshold: f32) -> bool {
+        self.score >= threshold
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IndexError {
+    DimensionMismatch { expected: usize, got: usize },
+    ZeroVector,
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::DimensionMismatch { expected, got } => {
+                write!(
+                    f,
+                    "embedding has {} dimensions, index expects {}",
+                    got, expected
+                )
+            }
+            IndexError::ZeroVector => write!(f, "embedding has zero length"),
+        }
+    }
+}
+
+impl std::error::Error for IndexError {}
+
+pub struct FaceIndex {
+    dim: usize,
+    // Slot-addressed storage; removed slots stay `None` until compaction so
+    // graph node ids remain stable.
+    vectors: Vec<f32>,
+    slots: Vec<Option<IndexEntry>>,
+    by_embedding: HashMap<u32, usize>,
+    live: usize,
+    hnsw_params: Option<HnswParams>,
+    hnsw: Option<Hnsw>,
+}
+
+impl FaceIndex {
+    /// Brute-force only index for `dim`-dimensional embeddings.
+    pub fn new(dim: usize) -> Self {
+        Self {
+            dim,
+            vectors: Vec::new(),
+            slots: Vec::new(),
+            by_embedding: HashMap::new(),
+            live: 0,
+            hnsw_params: None,
+            hnsw: None,
+        }
+    }
+
+    /// Picks brute force or HNSW depending on how many embeddings will be loaded.
+    pub fn for_gallery_size(dim: usize, expected: usize) -> Self {
+        let index = Self::new(dim);
+        if expected >= HNSW_MIN_GALLERY {
+            index.with_hnsw(HnswParams::default())
+        } else {
+            index
+        }
+    }
+
+    /// Also maintain an HNSW graph and use it for queries.
+    pub fn with_hnsw(mut self, params: HnswParams) -> Self {
+        self.hnsw_params = Some(params);
+        self.rebuild_graph();
+        self
+    }
+
+    pub fn dim(&self) -> usize {
+        self.dim
+    }
+
+    pub fn len(&self) -> usize {
+        self.live
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.live == 0
+    }
+
+    /// Adds or replaces the embedding with `entry.embedding_id`.
+    pub fn insert(&mut self, entry: IndexEntry, vector: &[f32]) -> Result<(), IndexError> {
+        let normalized = self.normalized(vector)?;
+        self.remove(entry.embedding_id);
+
+        let slot = self.slots.len();
+        self.vectors.extend_from_slice(&normalized);
+        self.slots.push(Some(entry));
+        self.by_embedding.insert(entry.embedding_id, slot);
+        self.live += 1;
+
+        if let Some(ref mut graph) = self.hnsw {
+            graph.insert(slot, &self.vectors, self.dim);
+        }
+        Ok(())
+    }
+
+    pub fn remove(&mut self, embedding_id: u32) -> bool {
+        let Some(slot) = self.by_embedding.remove(&embedding_id) else {
+            return false;
+        };
+
+        self.slots[slot] = None;
+        self.live -= 1;
+        if let Some(ref mut graph) = self.hnsw {
+            graph.mark_deleted(slot);
+        }
+        self.maybe_compact();
+        true
+    }
+
+    /// Drops every embedding of one person, returning how many were removed.
+    pub fn remove_criminal(&mut self, criminal_id: u32) -> usize {
+        let ids: Vec<u32> = self
+            .slots
+            .iter()
+            .flatten()
+            .filter(|entry| entry.criminal_id == criminal_id)
+            .map(|entry| entry.embedding_id)
+            .collect();
+
+        for id in &ids {
+            self.remove(*id);
+        }
+        ids.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.vectors.clear();
+        self.slots.clear();
+        self.by_embedding.clear();
+        self.live = 0;
+        self.rebuild_graph();
+    }
+
+    /// The `k` most similar embeddings, best first.
+    pub fn search(&self, query: &[f32], k: usize) -> Result<Vec<SearchHit>, IndexError> {
+        let query = self.normalized(query)?;
+        if k == 0 || self.live == 0 {
+            return Ok(Vec::new());
+        }
+
+        let scored = match self.hnsw {
+            Some(ref graph) => graph.search(&query, k, &self.vectors, self.dim, |slot| {
+                self.slots[slot].is_some()
+            }),
+            None => self.brute_force(&query, k),
+        };
+
+        Ok(scored
+            .into_iter()
+            .filter_map(|(slot, score)| self.hit(slot, score))
+            .collect())
+    }
+
+    /// The `k` most similar people, each scored by their best embedding.
+    pub fn search_identities(&self, query: &[f32], k: usize) -> Result<Vec<SearchHit>, IndexError> {
+        // Over-fetch so several photos of one person don't crowd out others,
+        // widening until k people are found or the gallery is exhausted
+        let mut fetch = k.saturating_mul(8).max(32);
+        loop {
+            let candidates = self.search(query, fetch)?;
+            let exhausted = candidates.len() < fetch;
+
+            let mut best: Vec<SearchHit> = Vec::new();
+            for hit in candidates {
+                if !best.iter().any(|b| b.criminal_id == hit.criminal_id) {
+                    best.push(hit);
+                }
+                if best.len() == k {
+                    break;
+                }
+            }
+            if best.len() == k || exhausted || fetch >= self.live {
+                return Ok(best);
+            }
+            fetch = fetch.saturating_mul(4);
+        }
+    }
+
+    /// Scores against one person's embeddings only, best first.
+    pub fn scores_for_criminal(
+        &self,
+        query: &[f32],
+        criminal_id: u32,
+    ) -> Result<Vec<SearchHit>, IndexError> {
+        let query = self.normalized(query)?;
+        let mut hits: Vec<SearchHit> = self
+            .slots
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.is_some_and(|e| e.criminal_id == criminal_id))
+            .filter_map(|(slot, _)| self.hit(slot, dot(&query, self.vector(slot))))
+            .collect();
+
+        hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+        Ok(hits)
+    }
+
+    fn brute_force(&self, query: &[f32], k: usize) -> Vec<(usize, f32)> {
+        let mut heap: BinaryHeap<MinScored> = BinaryHeap::with_capacity(k + 1);
+
+        for (slot, entry) in self.slots.iter().enumerate() {
+            if entry.is_none() {
+                continue;
+            }
+            let score = dot(query, self.vector(slot));
+            if heap.len() < k {
+                heap.push(MinScored(score, slot));
+            } else if heap.peek().is_some_and(|worst| score > worst.0) {
+                heap.pop();
+                heap.push(MinScored(score, slot));
+            }
+        }
+
+        let mut scored: Vec<(usize, f32)> = heap.into_iter().map(|s| (s.1, s.0)).collect();
+        scored.sort_by(|a, b| b.1.total_cmp(&a.1));
+        scored
+    }
+
+    fn hit(&self, slot: usize, score: f32) -> Option<SearchHit> {
+        self.slots[slot].map(|entry| SearchHit {
+            embedding_id: entry.embedding_id,
+            criminal_id: entry.criminal_id,
+            photo_id: entry.photo_id,
+            score,
+        })
+    }
+
+    fn vector(&self, slot: usize) -> &[f32] {
+        &self.vectors[slot * self.dim..(slot + 1) * self.dim]
+    }
+
+    fn normalized(&self, vector: &[f32]) -> Result<Vec<f32>, IndexError> {
+        if vector.len() != self.dim {
+            return Err(IndexError::DimensionMismatch {
+                expected: self.dim,
+                got: vector.len(),
+            });
+        }
+
+        let norm = dot(vector, vector).sqrt();
+        if norm == 0.0 || !norm.is_finite() {
+            return Err(IndexError::ZeroVector);
+        }
+        Ok(vector.iter().map(|v| v / norm).collect())
+    }
+
+    fn maybe_compact(&mut self) {
+        let dead = self.slots.len() - self.live;
+        if dead == 0 || (dead as f32) < self.slots.len() as f32 * COMPACT_RATIO {
+            return;
+        }
+
+        let mut vectors = Vec::with_capacity(self.live * self.dim);
+        let mut slots = Vec::with_capacity(self.live);
+        self.by_embedding.clear();
+
+        for (slot, entry) in self.slots.iter().enumerate() {
+            if let Some(entry) = entry {
+                self.by_embedding.insert(entry.embedding_id, slots.len());
+                vectors.extend_from_slice(&self.vectors[slot * self.dim..(slot + 1) * self.dim]);
+                slots.push(Some(*entry));
+            }
+        }
+
+        self.vectors = vectors;
+        self.slots = slots;
+        self.rebuild_graph();
+    }
+
+    fn rebuild_graph(&mut self) {
+        self.hnsw = self.hnsw_params.map(|params| {
+            let mut graph = Hnsw::new(params);
+            for slot in 0..self.slots.len() {
+                graph.insert(slot, &self.vectors, self.dim);
+            }
+            graph
+        });
+    }
+}
+
+/// Dot product written as eight independent lanes so LLVM emits packed SIMD
+/// adds/multiplies instead of a serial reduction.
+#[inline]
+pub(crate) fn dot(a: &[f32], b: &[f32]) -> f32 {
+    let mut lanes = [0.0f32; 8];
+    let chunks_a = a.chunks_exact(8);
+    let chunks_b = b.chunks_exact(8);
+    let tail: f32 = chunks_a
+        .remainder()
+        .iter()
+        .zip(chunks_b.remainder())
+        .map(|(x, y)| x * y)
+        .sum();
+
+    for (ca, cb) in chunks_a.zip(chunks_b) {
+        for ((lane, x), y) in lanes.iter_mut().zip(ca).zip(cb) {
+            *lane += x * y;
+        }
+    }
+    lanes.iter().sum::<f32>() + tail
+}
+
+/// Heap item ordered so the lowest score sits on top.
+#[derive(Clone, Copy)]
+pub(crate) struct MinScored(pub f32, pub usize);
+
+impl PartialEq for MinScored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MinScored {}
+
+impl PartialOrd for MinScored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MinScored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0)
+    }
+}
+
+/// Heap item ordered so the highest score sits on top.
+#[derive(Clone, Copy)]
+pub(crate) struct MaxScored(pub f32, pub usize);
+
+impl PartialEq for MaxScored {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MaxScored {}
+
+impl PartialOrd for MaxScored {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for MaxScored {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
@@ -0,0 +1,285 @@
+//! Identification shared by the image, video and webcam find pages.
+//!
+//! A page asks with `Message::Identify(source, path)`. The service sends the
+//! engine the command for that kind of probe, tagged so replies can't be
+//! mistaken for another page's or an older request's, and resolves the reply
+//! into a `Resolved` match or a reason there is none. The app then records
+//! the candidate for review, loads the full record with `load_record` and
+//! answers the page that asked with `Message::Identified`, which `route`
+//! delivers whichever page is on screen.
+//!
+//! Images and webcam frames are embedded by the engine and matched against
+//! `FaceIndex` here; videos are scanned by the engine, which answers with an
+//! untagged `identity` line, so only one video is identified at a time.
+
+use crate::database::{CriminalDB, OffenceEntry};
+use crate::entities::criminal;
+use crate::face_index::FaceIndex;
+use crate::pages::{ImageFindPage, VideoFindPage, WebcamFindPage, NO_FACE};
+use crate::python_process::encode_path;
+use crate::Message;
+use iced::widget::image;
+use iced::Task;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Prefix of the tags on engine requests made here.
+pub const TAG_PREFIX: &str = "find-";
+
+/// Reason given when no one in the gallery is close enough.
+pub const NOT_FOUND: &str = "Not Found";
+
+/// Which find page a probe came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Source {
+    Image,
+    Video,
+    Webcam,
+}
+
+impl Source {
+    /// As stored in `identifications.source` and used by watchlists and notifications.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Source::Image => "image",
+            Source::Video => "video",
+            Source::Webcam => "webcam",
+        }
+    }
+
+    /// The source `as_str` gave.
+    pub fn parse(source: &str) -> Option<Self> {
+        [Source::Image, Source::Video, Source::Webcam]
+            .into_iter()
+            .find(|s| s.as_str() == source)
+    }
+}
+
+/// The pages `route` hands results to.
+pub struct FindPages<'a> {
+    pub image: &'a mut ImageFindPage,
+    pub video: &'a mut VideoFindPage,
+    pub webcam: &'a mut WebcamFindPage,
+}
+
+/// Hands an identification result, or the record made of it, to the page
+/// that asked for it, even if another page is on screen. Other messages are
+/// dropped.
+pub fn route(message: Message, pages: FindPages<'_>, db: Option<Arc<CriminalDB>>) -> Task<Message> {
+    let source = match &message {
+        Message::Identified(source, _) => Some(*source),
+        Message::IdentificationRecorded(Ok(pending)) => Source::parse(&pending.source),
+        _ => None,
+    };
+    match source {
+        Some(Source::Image) => pages.image.update(message, db),
+        Some(Source::Video) => pages.video.update(message, db),
+        Some(Source::Webcam) => pages.webcam.update(message, db),
+        None => Task::none(),
+    }
+}
+
+/// The person a probe matched, before anyone has reviewed it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Candidate {
+    pub criminal_id: u32,
+    pub score: Option<f32>,
+    pub photo_id: Option<u32>, // gallery photo that matched best, when known
+}
+
+/// What became of one request.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resolved {
+    pub source: Source,
+    pub outcome: Result<Candidate, String>, // why nobody matched
+    pub probe: Option<PathBuf>,             // image the match was made on, for the review queue
+}
+
+/// The matched person's full record, for the page that asked.
+#[derive(Debug, Clone)]
+pub struct IdentifiedRecord {
+    pub candidate: Candidate,
+    pub criminal: criminal::Model,
+    pub photos: Vec<image::Handle>, // thumbnails of the registered photos
+    pub aliases: Vec<String>,
+    pub offences: Vec<OffenceEntry>,
+}
+
+struct Request {
+    tag: String,
+    probe: Option<PathBuf>,
+}
+
+/// Requests waiting on the engine, at most one per source; a newer request
+/// from the same page replaces the older one, whose reply is then dropped.
+#[derive(Default)]
+pub struct IdentificationService {
+    next_id: u64,
+    pending: HashMap<Source, Request>,
+}
+
+impl IdentificationService {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts identifying the probe at `path`; returns the engine command.
+    pub fn request(&mut self, source: Source, path: &str) -> String {
+        self.next_id += 1;
+        let tag = format!("{}{}-{}", TAG_PREFIX, source.as_str(), self.next_id);
+        let (command, probe) = match source {
+            // The frame it matched on arrives separately; see `set_probe`
+            Source::Video => (format!("identify video {}", encode_path(path)), None),
+            Source::Image | Source::Webcam => (
+                format!("embed {} {}", tag, encode_path(path)),
+                Some(PathBuf::from(path)),
+            ),
+        };
+        self.pending.insert(source, Request { tag, probe });
+        command
+    }
+
+    /// Whether `source` is waiting on the engine.
+    pub fn is_pending(&self, source: Source) -> bool {
+        self.pending.contains_key(&source)
+    }
+
+    /// Drops `source`'s request, e.g. when its page is left or reset.
+    pub fn cancel(&mut self, source: Source) {
+        self.pending.remove(&source);
+    }
+
+    /// Uses `path` as the probe image of `source`'s open request.
+    pub fn set_probe(&mut self, source: Source, path: &Path) {
+        if let Some(request) = self.pending.get_mut(&source) {
+            request.probe = Some(path.to_path_buf());
+        }
+    }
+
+    /// Gives up on `source`'s request, e.g. when the engine can't read its
+    /// probe; `None` if it has none open.
+    pub fn fail(&mut self, source: Source, reason: &str) -> Option<Resolved> {
+        let request = self.pending.remove(&source)?;
+        Some(Resolved {
+            source,
+            outcome: Err(reason.to_string()),
+            probe: request.probe,
+        })
+    }
+
+    /// Gives up on the request tagged `tag`, e.g. when the engine failed on
+    /// its command; `None` if it isn't (or is no longer) waited on.
+    pub fn fail_tag(&mut self, tag: &str, reason: &str) -> Option<Resolved> {
+        let source = *self
+            .pending
+            .iter()
+            .find(|(_, request)| request.tag == tag)?
+            .0;
+        self.fail(source, reason)
+    }
+
+    /// Gives up on every open request, e.g. when the engine fails or stops.
+    pub fn fail_pending(&mut self, reason: &str) -> Vec<Resolved> {
+        self.pending
+            .drain()
+            .map(|(source, request)| Resolved {
+                source,
+                outcome: Err(reason.to_string()),
+                probe: request.probe,
+            })
+            .collect()
+    }
+
+    /// Whether a `probe` reply with `tag` answers a request made here.
+    pub fn owns_tag(tag: &str) -> bool {
+        tag.starts_with(TAG_PREFIX)
+    }
+
+    /// Resolves the engine's embedding of a probe against `index`; `None`
+    /// for a tag that isn't (or is no longer) waited on.
+    pub fn probe_embedded(
+        &mut self,
+        tag: &str,
+        vector: Option<&[f32]>,
+        index: &FaceIndex,
+        threshold: f32,
+    ) -> Option<Resolved> {
+        let source = *self
+            .pending
+            .iter()
+            .find(|(_, request)| request.tag == tag)?
+            .0;
+        let request = self.pending.remove(&source)?;
+
+        let outcome = match vector {
+            None => Err(NO_FACE.to_string()),
+            Some(vector) => match index.search_identities(vector, 1) {
+                Ok(hits) => hits
+                    .into_iter()
+                    .next()
+                    .filter(|hit| hit.matches(threshold))
+                    .map(|hit| Candidate {
+                        criminal_id: hit.criminal_id,
+                        score: Some(hit.score),
+                        photo_id: Some(hit.photo_id),
+                    })
+                    .ok_or_else(|| NOT_FOUND.to_string()),
+                Err(e) => Err(e.to_string()),
+            },
+        };
+        Some(Resolved {
+            source,
+            outcome,
+            probe: request.probe,
+        })
+    }
+
+    /// Resolves an `identity <id|UNKNOWN> <score>` reply, which only video
+    /// scans get; `None` if no video is being scanned.
+    pub fn identity_reply(&mut self, criminal_id: &str, score: Option<f32>) -> Option<Resolved> {
+        let request = self.pending.remove(&Source::Video)?;
+        let outcome = criminal_id
+            .parse::<u32>()
+            .map(|criminal_id| Candidate {
+                criminal_id,
+                score,
+                photo_id: None,
+            })
+            .map_err(|_| NOT_FOUND.to_string());
+        Some(Resolved {
+            source: Source::Video,
+            outcome,
+            probe: request.probe,
+        })
+    }
+}
+
+/// Everything a find page shows about a match.
+pub async fn load_record(
+    db: Arc<CriminalDB>,
+    candidate: Candidate,
+) -> Result<IdentifiedRecord, String> {
+    let id = candidate.criminal_id;
+    let criminal = db
+        .get_criminal(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Record #{} not found", id))?;
+    let photos = db
+        .get_photo_thumbnails(id)
+        .await
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .map(|(_, jpeg)| image::Handle::from_bytes(jpeg))
+        .collect();
+    let aliases = db.get_aliases(id).await.map_err(|e| e.to_string())?;
+    let offences = db.get_offences(id).await.map_err(|e| e.to_string())?;
+    Ok(IdentifiedRecord {
+        candidate,
+        criminal,
+        photos,
+        aliases,
+        offences,
+    })
+}
@@ -1,14 +1,47 @@
+pub mod alerts;
+pub mod calibration;
 pub mod components;
+pub mod config;
+pub mod connection;
 pub mod database;
+pub mod engine;
 pub mod entities;
+pub mod error;
+pub mod face_index;
+pub mod identify;
+pub mod notify;
+#[cfg(feature = "onnx")]
+pub mod onnx_engine;
+pub mod outbox;
 pub mod pages;
+pub mod photo_store;
+pub mod preprocess;
 pub mod python_process;
+pub mod quality;
+pub mod retention;
+pub mod vault;
 pub mod webcam_task;
+pub mod workspace;
 
 use crate::database::CriminalDB;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+/// A criminal with all of their registered photos.
+pub type CriminalRecord = (
+    entities::criminal::Model,
+    Vec<entities::criminal_photo::Model>,
+);
+
+/// Source and target records picked on the merge page, if found.
+pub type MergeCandidates = (Option<CriminalRecord>, Option<CriminalRecord>);
+
+/// A watchlist entry with the listed person's record.
+pub type WatchlistMember = (entities::watchlist_member::Model, entities::criminal::Model);
+
+/// A criminal with their aliases, as listed by the record browser.
+pub type CriminalSummary = (entities::criminal::Model, Vec<String>);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Page {
     Dashboard,
@@ -17,6 +50,10 @@ pub enum Page {
     ImageFind,
     VideoFind,
     WebcamFind,
+    Merge,
+    Browser,
+    Watchlists,
+    Review,
     SignIn,
 }
 
@@ -25,41 +62,207 @@ pub enum Message {
     None,
     // --- Page Navigation ---
     GoTo(Page),
-    IdentityDataLoadedWithPhotos(
-        entities::criminal::Model,
-        Vec<entities::criminal_photo::Model>,
-    ),
     TickWebcam,
     CaptureWebcamFrame,
-    WebcamFrameCaptured(String), // The path to the temp file
+    WebcamFrameCaptured(Result<workspace::TempFile, error::AppError>), // deleted once the page lets go of it
     ToggleWebcam(bool),
     ResetWebcamSearch,
     ResetForm,
-    DatabaseSaved(u32, Vec<String>),
+    // criminal id, (photo id, path, face box), what couldn't be saved with it
+    DatabaseSaved(
+        u32,
+        Vec<(u32, String, Option<[f32; 4]>)>,
+        Vec<error::AppError>,
+    ),
     // --- Registry Form Inputs ---
     NameChanged(String),
     FathersNameChanged(String),
-    CrimesCountChanged(String),
     LocationChanged(String),
+    AliasesChanged(String),
+    DateOfBirthChanged(String),
+    GenderChanged(String),
+    NationalIdsChanged(String),
+    HeightChanged(String),
+    MarksChanged(String),
+    NotesChanged(String),
+
+    // --- Offence History Editor ---
+    LoadOffenceTypes,
+    OffenceTypesLoaded(Result<Vec<entities::offence_type::Model>, String>),
+    AddOffence,
+    RemoveOffence(usize),
+    OffenceTypeSelected(usize, entities::offence_type::Model),
+    OffenceDateChanged(usize, String),
+    OffenceLocationChanged(usize, String),
+    OffenceCaseChanged(usize, String),
+    OffenceStatusSelected(usize, &'static str),
+    NewOffenceTypeChanged(String),
+    AddOffenceType,
+    OffenceTypeAdded(Result<entities::offence_type::Model, String>),
 
     // --- Image Gallery Logic ---
     OpenFilePicker,
     FilesSelected(Vec<PathBuf>),
+    ImagesPrepared(Vec<Result<preprocess::PreparedImage, String>>), // one per selected file, in order
     NextImage,
     PrevImage,
+    PhotoStatsComputed(u32, Vec<Result<quality::ImageStats, String>>), // analysis generation
+    FacesDetected(String, Vec<quality::FaceBox>),                      // request tag, faces
+    ExcludePhoto(usize),
+    RetryPhotoAnalysis,
+    FaceCropsReady(u32, usize, Vec<iced::widget::image::Handle>), // generation, photo index, crops
+    SelectFace(usize, usize),                                     // photo index, face index
+
+    // --- Image Batch ---
+    OpenFolderPicker,
+    BatchItemPrepared(u32, usize, Result<preprocess::PreparedImage, String>), // batch id, item index
+    ProbeBatchImage(String, String), // request tag, prepared path
+    BatchProbed(String, Result<Option<face_index::SearchHit>, String>), // request tag, best hit
+    BatchRecordLoaded(u32, usize, Option<String>), // batch id, item index, name
+    SortBatch(pages::BatchColumn),
+    BatchMatchesOnly(bool),
+    StopBatch,
+    ExportBatchCsv,
+    BatchExported(Result<PathBuf, String>),
+
+    // --- Probe / Match Comparison ---
+    ProbeFrameSaved(workspace::TempFile), // video frame the engine matched on
+    ComparisonScores(String, Vec<face_index::SearchHit>), // request tag, per-photo hits
+    ComparisonProbeReady(String, Result<iced::widget::image::Handle, String>), // request tag, aligned crop
+    ComparisonPhotosLoaded(
+        String,
+        Result<Vec<(u32, iced::widget::image::Handle)>, String>,
+    ), // request tag, (photo id, crop)
+    SelectComparisonPhoto(u32),
+    ComparisonZoomChanged(f32),
+
+    // --- Duplicate Check Before Enrollment ---
+    CheckDuplicates(Vec<(String, String)>), // (request tag, engine command)
+    DuplicateProbe(String, Option<face_index::SearchHit>), // request tag, best gallery hit
+    DuplicateFound(
+        f32,
+        entities::criminal::Model,
+        Vec<entities::criminal_photo::Model>,
+    ),
+    NoDuplicateFound,
+    DuplicateCheckTimedOut(u32),  // check generation
+    DuplicateCheckFailed(String), // reason, e.g. the engine stopped
+    SaveWithoutDuplicateCheck,
+    JustificationChanged(String),
+    MergeIntoExisting,
+    ForceNewRecord,
+    CancelEnrollment,
+
+    // --- Record Merge ---
+    MergeSourceChanged(String),
+    MergeTargetChanged(String),
+    MergeReasonChanged(String),
+    SwapMergeRecords,
+    LoadMergeRecords,
+    MergeRecordsLoaded(Result<Box<MergeCandidates>, String>),
+    ConfirmMerge,
+    MergeCompleted(Result<database::MergeSummary, String>),
+
+    // --- Record Browser ---
+    BrowserQueryChanged(String),
+    SearchRecords,
+    RecordsFound(String, Result<Vec<CriminalSummary>, String>), // query, results
+    SelectRecord(u32),
+    RecordPhotosLoaded(u32, Result<Vec<iced::widget::image::Handle>, String>), // criminal id, thumbnails
+    PurgeReasonChanged(String),
+    RequestPurge,
+    CancelPurge,
+    ConfirmPurge,
+    RecordPurged(u32, Result<database::PurgeSummary, String>), // criminal id, removed rows
+
+    // --- Watchlists ---
+    LoadWatchlists,
+    WatchlistsLoaded(Result<Vec<entities::watchlist::Model>, String>),
+    WatchlistNameChanged(String),
+    WatchlistPrioritySelected(&'static str),
+    WatchlistDescriptionChanged(String),
+    CreateWatchlist,
+    WatchlistCreated(Result<entities::watchlist::Model, String>),
+    SelectWatchlist(u32),
+    WatchlistMembersLoaded(u32, Result<Vec<WatchlistMember>, String>), // watchlist id, members
+    MemberCriminalIdChanged(String),
+    MemberReasonChanged(String),
+    AddWatchlistMember,
+    RemoveWatchlistMember(u32),
+    WatchlistMembersChanged(Result<(), String>),
+
+    // --- Watchlist Alerts ---
+    CheckWatchlists(u32, String), // criminal id, source ("image", "video", "webcam")
+    AlertRaised(Result<Option<database::AlertEntry>, String>),
+    LoadPendingAlerts,
+    PendingAlertsLoaded(Result<Vec<database::AlertEntry>, String>),
+    AlertNoteChanged(String),
+    AcknowledgeAlert(u32),
+    AlertAcknowledged(u32, Result<(), String>),
+
+    // --- Identification Review ---
+    IdentificationRecorded(Result<entities::identification::Model, String>),
+    ReviewCommentChanged(u32, String), // identification id, comment
+    ReviewIdentification(u32, &'static str, String), // identification id, verdict, comment
+    IdentificationReviewed(Result<entities::identification::Model, String>),
+    LoadIdentifications,
+    IdentificationsLoaded(Result<Vec<database::IdentificationEntry>, String>),
+    ReviewFilterSelected(Option<&'static str>), // None shows every status
+    ExportRejections,
+    RejectionsExported(Result<PathBuf, String>),
+
+    // --- Outbound Notifications ---
+    // criminal id, source, score, probe image, when it was identified
+    IdentificationEvent(
+        u32,
+        String,
+        Option<f32>,
+        Option<Vec<u8>>,
+        chrono::DateTime<chrono::Utc>,
+    ),
+    SendNotification(Box<notify::Notification>),
+    DeliveryAttempted(notify::DeliveryRecord),
+
+    // --- Errors & Toasts ---
+    ShowError(error::AppError, Option<Box<Message>>), // error, message that retries it
+    DismissToast(u64),
+    RetryToast(u64),
+    ExpireToasts,
+    ToggleNotificationHistory,
+    ClearNotificationHistory,
 
     // --- Database Operations ---
     SubmitForm,
+    ConnectDatabase,
     DbConnected(Result<Arc<CriminalDB>, String>),
+    ReconnectDue(u64), // backoff timer of that generation ran out
+    CheckDatabase,
+    DatabaseChecked(Result<(), String>),
+    SyncOutbox,
+    OutboxSynced(outbox::SyncReport),
+    EnrollmentQueued(Result<(), String>), // saved to the outbox while offline
+    IdentificationQueued(Result<(), String>),
+    EnforceRetention,
+    RetentionEnforced(Result<retention::RetentionReport, String>),
+    SweepTempFiles,
+    TempFilesSwept(Result<usize, String>), // how many went
     SaveResult(Result<u32, String>),
+    EnrollmentNotEmbedded(u32, String), // saved record id, why the engine failed
+    UnembeddedPhotosCounted(Result<usize, String>), // registered photos that can't match
 
     // --- PythonProcess model_engine Events ---
     InitializePython,
-    IdentifyCriminalImage(String),
-    IdentifyCriminalVideo(String),
+    RestartEngine,
     PythonInput(String),
     PythonOutput(String),
-    Identity(String),
-    IdentityDataLoaded(entities::criminal::Model), // Success from DB
-    IdentityError(String),
+    Identify(identify::Source, String), // probe path
+    Identified(
+        identify::Source,
+        Result<Box<identify::IdentifiedRecord>, String>, // why nobody matched
+    ),
+    // criminal id, photo id, model name, vector
+    EmbeddingComputed(u32, u32, String, Vec<f32>),
+    GalleryLoaded(Result<Vec<entities::face_embedding::Model>, String>),
+    EmbeddingStored(face_index::IndexEntry, Vec<f32>),
+    ProbeEmbedding(String, Option<Vec<f32>>), // request tag, probe vector
 }
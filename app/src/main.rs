@@ -1,11 +1,31 @@
-use app::database::CriminalDB;
+use app::alerts::AlertCenter;
+use app::components::{FaceComparison, Toasts};
+use app::config::{AppConfig, EngineConfig, MatchingConfig, RetentionConfig};
+use app::connection::{Connection, DbStatus, PING_INTERVAL};
+use app::database::{sealed_data_stored, CriminalDB, NewIdentification};
+use app::engine::{spawn_engine, RecognitionEngine};
+use app::entities::identification;
+use app::error::{self, AppError};
+use app::face_index::{FaceIndex, IndexEntry, EMBEDDING_DIM};
+use app::identify::{self, FindPages, IdentificationService, Resolved, Source};
+use app::notify::{app_delivery_log, delivery_sub, Notification, Notifier, Snapshot};
+use app::outbox;
 use app::pages::*;
-use app::python_process::{python_sub, PythonProcess};
+use app::photo_store::PhotoStore;
+use app::preprocess::IMAGE_EXTENSIONS;
+use app::python_process::{
+    add_command, decode_embedding, decode_path, encode_embedding, encode_path, python_sub,
+    ENGINE_EXITED, ENGINE_LOG_PREFIX,
+};
+use app::quality::FaceBox;
+use app::vault::{self, Vault};
 use app::webcam_task::capture_frame;
+use app::workspace;
 use app::Message;
 use app::Page;
 
 use iced::{Element, Subscription, Task, Theme};
+use std::path::PathBuf;
 use std::sync::Arc;
 
 pub struct GlassmorphismApp {
@@ -14,47 +34,96 @@ pub struct GlassmorphismApp {
     image_find: ImageFindPage,
     video_find: VideoFindPage,
     webcam_find: WebcamFindPage,
-    model_engine: Option<PythonProcess>,
+    merge_page: MergePage,
+    browser: BrowserPage,
+    watchlists: WatchlistsPage,
+    review: ReviewPage,
+    alerts: AlertCenter,
+    toasts: Toasts,
+    notifier: Option<Notifier>,
+    model_engine: Option<Box<dyn RecognitionEngine>>,
+    engine_config: EngineConfig, // to restart the engine from
     db: Option<Arc<CriminalDB>>,
+    database_url: String,
+    connection: Connection, // shown on every page
+    photo_store: Option<PhotoStore>,
+    face_index: FaceIndex,
+    identifier: IdentificationService,
+    matching: MatchingConfig,
+    retention: RetentionConfig,
 }
 
 impl GlassmorphismApp {
     pub fn new() -> (Self, Task<Message>) {
-        let db_url = "mysql://crim_user:@localhost:3306/criminal_recognizer".to_string();
+        let config = AppConfig::load();
 
-        let engine = PythonProcess::spawn(
-            "main.py",
-            "/home/NEW_VOLUME-d/developer/criminal_face_recog/model_engine",
-        )
-        .ok();
+        // Before anything is stored, and before the engine starts so it gets the keys
+        match Vault::load(&config.encryption, || sealed_data_stored(&config)) {
+            Ok(Some(keys)) => {
+                vault::install(keys);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("❌ Encryption keys unavailable: {e}");
+                std::process::exit(1);
+            }
+        }
+
+        // The engine saves its match frames in this run's folder
+        workspace::install(config.storage.workspace_root());
+        // Records made while the database is down wait here
+        outbox::install(config.storage.outbox_dir());
+
+        let (engine, engine_error) = match spawn_engine(&config.engine) {
+            Ok(engine) => (Some(engine), None),
+            Err(e) => (None, Some(e)),
+        };
 
         let app = Self {
             current_page: Page::MainMenu,
             registry_state: RegistryPage::default(),
-            image_find: ImageFindPage::default(),
-            video_find: VideoFindPage::default(),
+            image_find: ImageFindPage::new(config.matching.threshold),
+            video_find: VideoFindPage::new(config.matching.threshold),
             webcam_find: WebcamFindPage::new(),
+            merge_page: MergePage::default(),
+            browser: BrowserPage::default(),
+            watchlists: WatchlistsPage::default(),
+            review: ReviewPage::default(),
+            alerts: AlertCenter::new(config.alerts.clone()),
+            toasts: Toasts::new(),
+            notifier: Notifier::start(config.notifications.clone(), app_delivery_log()),
             model_engine: engine,
+            engine_config: config.engine.clone(),
             db: None,
+            database_url: config.database_url.clone(),
+            connection: Connection::new(outbox::current().len()),
+            photo_store: config.storage.photo_store(),
+            face_index: FaceIndex::new(EMBEDDING_DIM),
+            identifier: IdentificationService::new(),
+            matching: config.matching,
+            retention: config.retention,
+        };
+
+        let engine_failed = match engine_error {
+            Some(e) => error::report(AppError::Engine(e), Some(Message::RestartEngine)),
+            None => Task::none(),
         };
 
         let init_task = Task::batch(vec![
-            Task::perform(
-                async move {
-                    println!("connecting to db");
-                    CriminalDB::new(&db_url)
-                        .await
-                        .map(Arc::new)
-                        .map_err(|e| e.to_string())
-                },
-                Message::DbConnected,
-            ),
+            Task::done(Message::ConnectDatabase),
+            engine_failed,
             Task::perform(
                 async {
                     tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
                 },
                 |_| Message::InitializePython,
             ),
+            Task::done(Message::SweepTempFiles),
+            // Matched suspect photos and webcam frames from older builds
+            Task::perform(
+                tokio::task::spawn_blocking(|| workspace::current().remove_legacy_files()),
+                |_| Message::None,
+            ),
         ]);
 
         (app, init_task)
@@ -70,45 +139,169 @@ impl GlassmorphismApp {
                 if let Some(ref mut engine) = self.model_engine {
                     let _ = engine.send("start");
                     let _h = engine.send("hello");
+                    let _ = engine.send(&self.matching.engine_command());
                 }
                 Task::none()
             }
 
             Message::PythonInput(msg) => {
-                let status_string = self
-                    .model_engine
-                    .as_ref()
-                    .map(|engine| engine.send(&msg))
-                    .map(|res| match res {
-                        Ok(_) => "Success".to_string(),
-                        Err(e) => e.to_string(),
-                    })
-                    .unwrap_or_else(|| "Engine not initialized".to_string());
-                Task::done(Message::PythonOutput(status_string))
+                let Err(e) = self.send_to_engine(&msg) else {
+                    return Task::none();
+                };
+                // The record itself is saved; only its photos are missing from the gallery
+                if let Some(criminal_id) = msg
+                    .strip_prefix("add ")
+                    .and_then(|rest| rest.split_whitespace().next())
+                    .and_then(|id| id.parse().ok())
+                {
+                    let error = AppError::Engine(format!(
+                        "Record #{} was saved but its photos weren't enrolled: {}",
+                        criminal_id, e
+                    ));
+                    return Task::batch(vec![
+                        self.registry_state
+                            .update(Message::SaveResult(Ok(criminal_id)), None),
+                        error::report(error, Some(self.engine_retry(msg))),
+                    ]);
+                }
+                error::report(e, Some(self.engine_retry(msg)))
             }
 
-            Message::IdentifyCriminalImage(path) => {
-                let cmd = format!("identify image {}", path);
-                Task::done(Message::PythonInput(cmd))
+            Message::Identify(source, path) => {
+                let cmd = self.identifier.request(source, &path);
+                let Err(e) = self.send_to_engine(&cmd) else {
+                    return Task::none();
+                };
+                self.identifier.cancel(source);
+                let retry = match self.model_engine {
+                    Some(_) => Message::Identify(source, path),
+                    None => Message::RestartEngine,
+                };
+                Task::batch(vec![
+                    Task::done(Message::Identified(source, Err(e.to_string()))),
+                    error::report(e, Some(retry)),
+                ])
             }
 
-            Message::IdentifyCriminalVideo(path) => {
-                let cmd = format!("identify video {}", path);
-                Task::done(Message::PythonInput(cmd))
-            }
+            Message::RestartEngine => match spawn_engine(&self.engine_config) {
+                Ok(engine) => {
+                    self.model_engine = Some(engine);
+                    // The new engine starts with an empty gallery
+                    Task::batch(vec![
+                        Task::done(Message::InitializePython),
+                        self.reload_gallery(),
+                    ])
+                }
+                Err(e) => error::report(AppError::Engine(e), Some(Message::RestartEngine)),
+            },
             Message::PythonOutput(text) => {
                 eprintln!("[RUST] Python output: {}", text);
 
-                if text.starts_with("added") {
+                if let Some(id) = text.strip_prefix("added") {
+                    // A record synced from the outbox; its entry goes once the
+                    // embeddings are stored
+                    if let Some((id, missing)) = id.trim().parse().ok().and_then(|id| {
+                        outbox::current()
+                            .engine_answered(id)
+                            .map(|missing| (id, missing))
+                    }) {
+                        if missing.is_empty() {
+                            return Task::none();
+                        }
+                        return error::report(
+                            AppError::Engine(format!(
+                                "No face embedding came back for {} photo(s) of record #{}; \
+                                 they stay queued for the next sync",
+                                missing.len(),
+                                id
+                            )),
+                            Some(Message::SyncOutbox),
+                        );
+                    }
                     return self.registry_state.update(Message::SaveResult(Ok(0)), None);
                 }
+                if let Some(log) = text.strip_prefix(ENGINE_LOG_PREFIX) {
+                    return error::report(AppError::Engine(log.trim().to_string()), None);
+                }
+                if text == ENGINE_EXITED {
+                    self.model_engine = None;
+                    let error = AppError::Engine("The recognition engine stopped".to_string());
+                    return self.engine_failed(error, Some(Message::RestartEngine));
+                }
 
                 let parts: Vec<&str> = text.split_whitespace().collect();
-                if parts.get(0) == Some(&"identity") {
-                    if let Some(identity_val) = parts.get(1).map(|s| s.to_string()) {
-                        if matches!(self.current_page, Page::ImageFind | Page::VideoFind) {
-                            return Task::done(Message::Identity(identity_val));
+                if let ["embedding", criminal_id, photo_id, model_name, vector] = parts[..] {
+                    if let (Ok(criminal_id), Ok(photo_id), Some(vector)) = (
+                        criminal_id.parse::<u32>(),
+                        photo_id.parse::<u32>(),
+                        decode_embedding(vector),
+                    ) {
+                        outbox::current().embedding_received(photo_id);
+                        return Task::done(Message::EmbeddingComputed(
+                            criminal_id,
+                            photo_id,
+                            model_name.to_string(),
+                            vector,
+                        ));
+                    }
+                }
+                if let ["probe", tag, vector] = parts[..] {
+                    return Task::done(Message::ProbeEmbedding(
+                        tag.to_string(),
+                        decode_embedding(vector),
+                    ));
+                }
+                if let ["probe_frame", path] = parts[..] {
+                    // Ours from here on: deleted once the match isn't shown
+                    return match workspace::current().adopt(decode_path(path)) {
+                        Some(frame) => {
+                            self.identifier.set_probe(Source::Video, frame.path());
+                            Task::done(Message::ProbeFrameSaved(frame))
+                        }
+                        None => Task::none(),
+                    };
+                }
+                if let ["faces", tag, _count, faces] = parts[..] {
+                    if let Some(faces) = FaceBox::parse_list(faces) {
+                        return Task::done(Message::FacesDetected(tag.to_string(), faces));
+                    }
+                }
+                // A command that failed in the engine; only its request is affected
+                if let ["error", command, target, ref reason @ ..] = parts[..] {
+                    if matches!(command, "add" | "embed" | "analyze" | "identify") {
+                        return self.engine_request_failed(command, target, &reason.join(" "));
+                    }
+                }
+                if let ["error", ref reason @ ..] = parts[..] {
+                    let reason = reason.join(" ");
+                    let error = AppError::Engine(format!("The engine reported: {}", reason));
+                    return match reason.as_str() {
+                        "engine_unavailable" => {
+                            self.engine_failed(error, Some(Message::RestartEngine))
+                        }
+                        // Answers an enrollment photo analysis, which says so itself
+                        _ if reason.starts_with("unreadable_image") => error::report(error, None),
+                        // Answers a video scan; requests from other pages are unaffected
+                        "unreadable_video" | "unsupported_media_type video" => {
+                            let reason = match reason.as_str() {
+                                "unreadable_video" => "The engine couldn't decode this video",
+                                _ => "The recognition engine can't scan videos",
+                            };
+                            let mut tasks =
+                                vec![error::report(AppError::Engine(reason.to_string()), None)];
+                            if let Some(resolved) = self.identifier.fail(Source::Video, reason) {
+                                tasks.push(self.finish_identification(resolved));
+                            }
+                            Task::batch(tasks)
                         }
+                        _ => self.engine_failed(error, None),
+                    };
+                }
+                // Answers a video scan, whichever page is showing
+                if let ["identity", criminal_id, ref rest @ ..] = parts[..] {
+                    let score = rest.first().and_then(|s| s.parse().ok());
+                    if let Some(resolved) = self.identifier.identity_reply(criminal_id, score) {
+                        return self.finish_identification(resolved);
                     }
                 }
                 Task::none()
@@ -120,17 +313,6 @@ impl GlassmorphismApp {
                 Task::perform(
                     async move {
                         match current_page {
-                            Page::ImageFind => {
-                                // Logic for single Video selection
-                                let file = rfd::AsyncFileDialog::new()
-                                    .add_filter("Images", &["jpg", "png", "jpeg", "webm"])
-                                    .pick_file() // Single pick
-                                    .await;
-
-                                file.map(|handle| vec![handle.path().to_path_buf()])
-                                    .unwrap_or_default()
-                            }
-
                             Page::VideoFind => {
                                 // Logic for single Video selection
                                 let file = rfd::AsyncFileDialog::new()
@@ -144,7 +326,7 @@ impl GlassmorphismApp {
                             _ => {
                                 // Logic for multiple Image selection (Registry/Identify)
                                 let files = rfd::AsyncFileDialog::new()
-                                    .add_filter("Images", &["jpg", "png", "jpeg"])
+                                    .add_filter("Images", IMAGE_EXTENSIONS)
                                     .pick_files() // Multiple pick
                                     .await;
 
@@ -162,6 +344,66 @@ impl GlassmorphismApp {
                     Message::FilesSelected,
                 )
             }
+
+            Message::OpenFolderPicker => Task::perform(
+                async {
+                    let Some(folder) = rfd::AsyncFileDialog::new().pick_folder().await else {
+                        return Vec::new();
+                    };
+                    let Ok(entries) = std::fs::read_dir(folder.path()) else {
+                        return Vec::new();
+                    };
+                    let mut files: Vec<PathBuf> = entries
+                        .filter_map(|entry| entry.ok().map(|e| e.path()))
+                        .filter(|path| {
+                            path.extension()
+                                .and_then(|ext| ext.to_str())
+                                .is_some_and(|ext| {
+                                    IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str())
+                                })
+                        })
+                        .collect();
+                    files.sort();
+                    files
+                },
+                Message::FilesSelected,
+            ),
+
+            Message::ProbeBatchImage(tag, path) => match self.model_engine {
+                Some(ref engine) => {
+                    if let Err(e) = engine.send(&format!("embed {} {}", tag, encode_path(&path))) {
+                        return Task::done(Message::BatchProbed(tag, Err(e.to_string())));
+                    }
+                    Task::none()
+                }
+                None => Task::done(Message::BatchProbed(
+                    tag,
+                    Err("Engine not initialized".to_string()),
+                )),
+            },
+
+            Message::ProbeEmbedding(tag, vector) if tag.starts_with("batch-") => {
+                let result = match vector {
+                    Some(v) => self
+                        .face_index
+                        .search_identities(&v, 1)
+                        .map(|hits| hits.into_iter().next())
+                        .map_err(|e| e.to_string()),
+                    None => Err(NO_FACE.to_string()),
+                };
+                self.image_find
+                    .update(Message::BatchProbed(tag, result), self.db.clone())
+            }
+
+            Message::BatchItemPrepared(..)
+            | Message::BatchProbed(..)
+            | Message::BatchRecordLoaded(..)
+            | Message::SortBatch(_)
+            | Message::BatchMatchesOnly(_)
+            | Message::StopBatch
+            | Message::ExportBatchCsv
+            | Message::BatchExported(_) => self.image_find.update(message, self.db.clone()),
+
             Message::GoTo(page) => {
                 if self.current_page == Page::WebcamFind {
                     self.current_page = page;
@@ -169,25 +411,639 @@ impl GlassmorphismApp {
                 }
                 self.current_page = page;
                 self.image_find.selected_image = Vec::new();
+                if page == Page::Browser {
+                    // Refresh so records saved elsewhere show up
+                    return Task::done(Message::SearchRecords);
+                }
+                if page == Page::Watchlists {
+                    return Task::done(Message::LoadWatchlists);
+                }
+                if page == Page::Review {
+                    return Task::done(Message::LoadIdentifications);
+                }
+                if page == Page::Registry {
+                    // The offence type list may have grown since last time
+                    return Task::done(Message::LoadOffenceTypes);
+                }
+                Task::none()
+            }
+
+            Message::ConnectDatabase => {
+                if !self.connection.begin() {
+                    return Task::none();
+                }
+                let db_url = self.database_url.clone();
+                let photo_store = self.photo_store.clone();
+                Task::perform(
+                    async move {
+                        println!("connecting to db");
+                        CriminalDB::new(&db_url)
+                            .await
+                            .map(|db| match photo_store {
+                                Some(store) => db.with_photo_store(store),
+                                None => db,
+                            })
+                            .map(Arc::new)
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::DbConnected,
+                )
+            }
+
+            Message::DbConnected(Err(e)) => {
+                let (delay, generation) = self.connection.failed(e.clone());
+                let retry = Task::perform(tokio::time::sleep(delay), move |_| {
+                    Message::ReconnectDue(generation)
+                });
+                // The badge keeps track from here on
+                if self.connection.failures() > 1 {
+                    return retry;
+                }
+                Task::batch(vec![
+                    retry,
+                    error::report(
+                        AppError::Database(format!(
+                            "Could not connect to the database: {}. New records are kept \
+                             until it's back",
+                            e
+                        )),
+                        Some(Message::ConnectDatabase),
+                    ),
+                ])
+            }
+
+            Message::ReconnectDue(generation) => {
+                if self.connection.is_due(generation) {
+                    return Task::done(Message::ConnectDatabase);
+                }
                 Task::none()
             }
 
+            Message::CheckDatabase => {
+                let Some(db) = self.db.clone() else {
+                    return Task::none();
+                };
+                Task::perform(
+                    async move { db.ping().await.map_err(|e| e.to_string()) },
+                    Message::DatabaseChecked,
+                )
+            }
+
+            Message::DatabaseChecked(Ok(())) => Task::none(),
+
+            Message::DatabaseChecked(Err(e)) => {
+                if self.db.take().is_none() {
+                    return Task::none();
+                }
+                let (delay, generation) = self.connection.lost(e.clone());
+                Task::batch(vec![
+                    Task::perform(tokio::time::sleep(delay), move |_| {
+                        Message::ReconnectDue(generation)
+                    }),
+                    error::report(
+                        AppError::Database(format!(
+                            "Lost the connection to the database: {}. New records are kept \
+                             until it's back",
+                            e
+                        )),
+                        Some(Message::ConnectDatabase),
+                    ),
+                ])
+            }
+
+            Message::SyncOutbox => {
+                let Some(db) = self.db.clone() else {
+                    return Task::none();
+                };
+                if outbox::current().is_empty() {
+                    return Task::none();
+                }
+                Task::perform(
+                    async move { outbox::current().sync(db.as_ref()).await },
+                    Message::OutboxSynced,
+                )
+            }
+
+            Message::OutboxSynced(report) => {
+                self.connection.queued = report.remaining;
+                println!(
+                    "Synced {} queued records and {} identifications, {} left",
+                    report.enrolled.len(),
+                    report.identifications,
+                    report.remaining
+                );
+
+                let mut tasks: Vec<_> = report
+                    .problems
+                    .into_iter()
+                    .map(|problem| error::report(problem, None))
+                    .collect();
+                if let Some(e) = report.failed {
+                    tasks.push(error::report(
+                        AppError::Database(format!(
+                            "Could not sync the records kept while offline: {}",
+                            e
+                        )),
+                        Some(Message::SyncOutbox),
+                    ));
+                }
+                // The engine embeds their photos; each leaves the outbox once stored
+                for saved in report.enrolled {
+                    let command = add_command(saved.criminal_id, &saved.photos);
+                    if let Err(error) = self.send_to_engine(&command) {
+                        tasks.push(error::report(error, Some(self.engine_retry(command))));
+                    }
+                }
+                Task::batch(tasks)
+            }
+
+            Message::EnrollmentQueued(result) => {
+                self.connection.queued = outbox::current().len();
+                let sync = match result {
+                    // The connection came back while it was being queued
+                    Ok(()) if self.db.is_some() => Task::done(Message::SyncOutbox),
+                    _ => Task::none(),
+                };
+                Task::batch(vec![
+                    sync,
+                    self.registry_state
+                        .update(Message::EnrollmentQueued(result), self.db.clone()),
+                ])
+            }
+
+            Message::IdentificationQueued(Ok(())) => {
+                self.connection.queued = outbox::current().len();
+                if self.db.is_some() {
+                    return Task::done(Message::SyncOutbox);
+                }
+                Task::none()
+            }
+
+            Message::IdentificationQueued(Err(e)) => error::report(
+                AppError::File {
+                    path: outbox::current().dir().to_path_buf(),
+                    reason: format!("Could not keep an identification for later: {}", e),
+                },
+                None,
+            ),
+
+            Message::ShowError(..)
+            | Message::DismissToast(_)
+            | Message::RetryToast(_)
+            | Message::ExpireToasts
+            | Message::ToggleNotificationHistory
+            | Message::ClearNotificationHistory => self.toasts.update(message),
+
             Message::DbConnected(Ok(db_arc)) => {
                 println!("SETTING UP DATABASE");
-                self.db = Some(db_arc);
+                self.db = Some(db_arc.clone());
+                self.connection.connected();
                 println!("Connected to the database");
+
+                Task::batch(vec![
+                    Task::perform(
+                        async move {
+                            db_arc
+                                .get_photos_without_embeddings()
+                                .await
+                                .map(|photos| photos.len())
+                                .map_err(|e| e.to_string())
+                        },
+                        Message::UnembeddedPhotosCounted,
+                    ),
+                    Task::done(Message::SyncOutbox),
+                    self.reload_gallery(),
+                    Task::done(Message::LoadPendingAlerts),
+                    Task::done(Message::EnforceRetention),
+                ])
+            }
+
+            Message::GalleryLoaded(Ok(embeddings)) => {
+                if let Some(ref engine) = self.model_engine {
+                    let mut batch = vec!["gallery_clear".to_string()];
+                    batch.extend(embeddings.iter().map(|embedding| {
+                        format!(
+                            "gallery_add {} {} {}",
+                            embedding.criminal_id,
+                            embedding.model_name,
+                            encode_embedding(&embedding.to_vector())
+                        )
+                    }));
+                    batch.push("gallery_commit".to_string());
+                    engine.send_batch(batch);
+                }
+
+                self.face_index = FaceIndex::for_gallery_size(EMBEDDING_DIM, embeddings.len());
+                for embedding in &embeddings {
+                    let entry = IndexEntry {
+                        embedding_id: embedding.embedding_id,
+                        criminal_id: embedding.criminal_id,
+                        photo_id: embedding.photo_id,
+                    };
+                    if let Err(e) = self.face_index.insert(entry, &embedding.to_vector()) {
+                        eprintln!("Skipping embedding {}: {}", embedding.embedding_id, e);
+                    }
+                }
+                println!("Loaded {} face embeddings", self.face_index.len());
                 Task::none()
             }
 
-            Message::Identity(_) | Message::IdentityDataLoaded(_) | Message::IdentityError(_) => {
-                match self.current_page {
-                    Page::ImageFind => self.image_find.update(message, self.db.clone()),
-                    Page::VideoFind => self.video_find.update(message, self.db.clone()),
-                    Page::WebcamFind => self.webcam_find.update(message, self.db.clone()), // Add this
+            Message::UnembeddedPhotosCounted(Ok(0)) => Task::none(),
+
+            // Enrolled before embeddings were kept in SQL, or no face was found
+            Message::UnembeddedPhotosCounted(Ok(count)) => error::report(
+                AppError::Database(format!(
+                    "{} registered photos have no face embedding and can't be matched; \
+                     run `cargo run --release --bin backfill_embeddings`",
+                    count
+                )),
+                None,
+            ),
+
+            Message::UnembeddedPhotosCounted(Err(e)) => error::report(
+                AppError::Database(format!("Could not check the face embeddings: {}", e)),
+                None,
+            ),
+
+            Message::GalleryLoaded(Err(e)) => error::report(
+                AppError::Database(format!("Could not load face embeddings: {}", e)),
+                None,
+            ),
+
+            Message::EmbeddingComputed(criminal_id, photo_id, model_name, vector) => {
+                // A photo synced from the outbox stays queued and is embedded
+                // again on the next sync
+                let Some(db) = self.db.clone() else {
+                    return error::report(
+                        AppError::Database(format!(
+                            "The database went offline before the face embedding of photo #{} \
+                             (record #{}) was stored. Records kept offline retry on the next sync; \
+                             for others run `cargo run --release --bin backfill_embeddings`",
+                            photo_id, criminal_id
+                        )),
+                        None,
+                    );
+                };
+
+                Task::perform(
+                    async move {
+                        let embedding_id = db
+                            .add_face_embedding(photo_id, criminal_id, model_name, &vector)
+                            .await?;
+                        let entry = IndexEntry {
+                            embedding_id,
+                            criminal_id,
+                            photo_id,
+                        };
+                        Ok::<_, sea_orm::DbErr>((entry, vector))
+                    },
+                    |result| match result {
+                        Ok((entry, vector)) => Message::EmbeddingStored(entry, vector),
+                        Err(e) => Message::ShowError(
+                            AppError::Database(format!("Could not store a face embedding: {}", e)),
+                            None,
+                        ),
+                    },
+                )
+            }
+
+            Message::EmbeddingStored(entry, vector) => {
+                if let Err(e) = self.face_index.insert(entry, &vector) {
+                    eprintln!("Failed to index embedding {}: {}", entry.embedding_id, e);
+                }
+                if outbox::current().embedding_stored(entry.criminal_id, entry.photo_id) {
+                    self.connection.queued = outbox::current().len();
+                }
+                Task::none()
+            }
+
+            Message::CheckDuplicates(probes) => match self.model_engine {
+                // Nothing to compare against; let the registry carry on
+                _ if self.face_index.is_empty() => Task::batch(
+                    probes
+                        .into_iter()
+                        .map(|(tag, _)| Task::done(Message::DuplicateProbe(tag, None))),
+                ),
+                Some(ref engine) => {
+                    engine.send_batch(probes.into_iter().map(|(_, cmd)| cmd).collect());
+                    Task::none()
+                }
+                None => self.registry_state.update(
+                    Message::DuplicateCheckFailed(
+                        "The recognition engine isn't running, so the photos couldn't be \
+                         checked for duplicates"
+                            .to_string(),
+                    ),
+                    self.db.clone(),
+                ),
+            },
+
+            Message::ProbeEmbedding(tag, vector) if tag.starts_with("cmp-") => {
+                let scores = match (vector, FaceComparison::criminal_for_tag(&tag)) {
+                    (Some(v), Some(criminal_id)) => self
+                        .face_index
+                        .scores_for_criminal(&v, criminal_id)
+                        .unwrap_or_default(),
+                    _ => Vec::new(),
+                };
+                self.route_comparison(Message::ComparisonScores(tag, scores))
+            }
+
+            Message::FacesDetected(ref tag, _) if tag.starts_with("cmp-") => {
+                self.route_comparison(message)
+            }
+
+            Message::ProbeFrameSaved(_) => self.video_find.update(message, self.db.clone()),
+
+            Message::ComparisonProbeReady(..)
+            | Message::ComparisonPhotosLoaded(..)
+            | Message::SelectComparisonPhoto(_)
+            | Message::ComparisonZoomChanged(_) => self.route_comparison(message),
+
+            Message::ProbeEmbedding(tag, vector) if tag.starts_with("dup-") => {
+                let best = vector.and_then(|v| {
+                    self.face_index
+                        .search_identities(&v, 1)
+                        .ok()
+                        .and_then(|hits| hits.into_iter().next())
+                });
+                self.registry_state
+                    .update(Message::DuplicateProbe(tag, best), self.db.clone())
+            }
+
+            Message::ProbeEmbedding(tag, vector) => {
+                match self.identifier.probe_embedded(
+                    &tag,
+                    vector.as_deref(),
+                    &self.face_index,
+                    self.matching.threshold,
+                ) {
+                    Some(resolved) => self.finish_identification(resolved),
+                    None => Task::none(),
+                }
+            }
+
+            Message::Identified(..) | Message::IdentificationRecorded(Ok(_)) => {
+                let pages = FindPages {
+                    image: &mut self.image_find,
+                    video: &mut self.video_find,
+                    webcam: &mut self.webcam_find,
+                };
+                identify::route(message, pages, self.db.clone())
+            }
+            Message::IdentificationRecorded(Err(e)) => error::report(
+                AppError::Database(format!("Could not record the identification: {}", e)),
+                None,
+            ),
+
+            Message::ReviewIdentification(identification_id, verdict, comment) => {
+                let Some(db) = self.db.clone() else {
+                    return Task::none();
+                };
+                Task::perform(
+                    async move {
+                        db.review_identification(identification_id, verdict, Some(comment))
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::IdentificationReviewed,
+                )
+            }
+
+            Message::IdentificationReviewed(ref result) => {
+                // Watchlist alerts and notifications wait for a confirmation
+                let confirmed = match result {
+                    Ok(reviewed) if reviewed.status == identification::CONFIRMED => {
+                        Task::batch(vec![
+                            Task::done(Message::CheckWatchlists(
+                                reviewed.criminal_id,
+                                reviewed.source.clone(),
+                            )),
+                            Task::done(Message::IdentificationEvent(
+                                reviewed.criminal_id,
+                                reviewed.source.clone(),
+                                reviewed.score,
+                                reviewed.probe.clone(),
+                                reviewed.created_at,
+                            )),
+                        ])
+                    }
                     _ => Task::none(),
+                };
+                Task::batch(vec![
+                    confirmed,
+                    self.image_find.update(message.clone(), self.db.clone()),
+                    self.video_find.update(message.clone(), self.db.clone()),
+                    self.webcam_find.update(message.clone(), self.db.clone()),
+                    self.review.update(message, self.db.clone()),
+                ])
+            }
+
+            Message::ReviewCommentChanged(..) => match self.current_page {
+                Page::ImageFind => self.image_find.update(message, self.db.clone()),
+                Page::VideoFind => self.video_find.update(message, self.db.clone()),
+                Page::WebcamFind => self.webcam_find.update(message, self.db.clone()),
+                Page::Review => self.review.update(message, self.db.clone()),
+                _ => Task::none(),
+            },
+
+            Message::LoadIdentifications
+            | Message::IdentificationsLoaded(_)
+            | Message::ReviewFilterSelected(_)
+            | Message::ExportRejections
+            | Message::RejectionsExported(_) => self.review.update(message, self.db.clone()),
+
+            // Enrollment analysis results land even if the user has moved on
+            Message::PhotoStatsComputed(..)
+            | Message::FacesDetected(..)
+            | Message::FaceCropsReady(..)
+            | Message::DuplicateProbe(..)
+            | Message::DuplicateFound(..)
+            | Message::NoDuplicateFound
+            | Message::DuplicateCheckTimedOut(_)
+            | Message::DuplicateCheckFailed(_)
+            | Message::OffenceTypesLoaded(_)
+            | Message::OffenceTypeAdded(_) => self.registry_state.update(message, self.db.clone()),
+
+            Message::MergeSourceChanged(_)
+            | Message::MergeTargetChanged(_)
+            | Message::MergeReasonChanged(_)
+            | Message::SwapMergeRecords
+            | Message::LoadMergeRecords
+            | Message::MergeRecordsLoaded(_)
+            | Message::ConfirmMerge => self.merge_page.update(message, self.db.clone()),
+
+            Message::MergeCompleted(result) => {
+                // Embeddings changed owner; rebuild both galleries from SQL
+                let resync = if result.is_ok() {
+                    self.reload_gallery()
+                } else {
+                    Task::none()
+                };
+                let page = self
+                    .merge_page
+                    .update(Message::MergeCompleted(result), self.db.clone());
+                Task::batch(vec![page, resync])
+            }
+
+            Message::BrowserQueryChanged(_)
+            | Message::SearchRecords
+            | Message::RecordsFound(..)
+            | Message::SelectRecord(_)
+            | Message::RecordPhotosLoaded(..)
+            | Message::PurgeReasonChanged(_)
+            | Message::RequestPurge
+            | Message::CancelPurge
+            | Message::ConfirmPurge => self.browser.update(message, self.db.clone()),
+
+            Message::RecordPurged(criminal_id, result) => {
+                // Their embeddings are gone; rebuild both galleries from SQL
+                let resync = if result.is_ok() {
+                    self.reload_gallery()
+                } else {
+                    Task::none()
+                };
+                let page = self
+                    .browser
+                    .update(Message::RecordPurged(criminal_id, result), self.db.clone());
+                Task::batch(vec![page, resync])
+            }
+
+            Message::EnforceRetention => {
+                let Some(db) = self.db.clone() else {
+                    return Task::none();
+                };
+                Task::perform(
+                    app::retention::enforce(db, self.retention),
+                    Message::RetentionEnforced,
+                )
+            }
+
+            Message::RetentionEnforced(Ok(report)) => {
+                if !report.is_empty() {
+                    println!("🧹 Retention: {}", report);
                 }
+                Task::none()
+            }
+
+            Message::RetentionEnforced(Err(e)) => error::report(
+                AppError::Database(format!("Retention rules failed: {}", e)),
+                Some(Message::EnforceRetention),
+            ),
+
+            // Runs with or without the database
+            Message::SweepTempFiles => Task::perform(
+                app::retention::sweep_expired(self.retention),
+                Message::TempFilesSwept,
+            ),
+
+            Message::TempFilesSwept(Ok(removed)) => {
+                if removed > 0 {
+                    println!("🧹 Retention: {} temp files removed", removed);
+                }
+                Task::none()
             }
 
+            Message::TempFilesSwept(Err(e)) => {
+                eprintln!("Temp file sweep failed: {}", e);
+                Task::none()
+            }
+
+            Message::LoadWatchlists
+            | Message::WatchlistsLoaded(_)
+            | Message::WatchlistNameChanged(_)
+            | Message::WatchlistPrioritySelected(_)
+            | Message::WatchlistDescriptionChanged(_)
+            | Message::CreateWatchlist
+            | Message::WatchlistCreated(_)
+            | Message::SelectWatchlist(_)
+            | Message::WatchlistMembersLoaded(..)
+            | Message::MemberCriminalIdChanged(_)
+            | Message::MemberReasonChanged(_)
+            | Message::AddWatchlistMember
+            | Message::RemoveWatchlistMember(_)
+            | Message::WatchlistMembersChanged(_) => {
+                self.watchlists.update(message, self.db.clone())
+            }
+
+            // Sent on confirmation, but reports when the person was seen
+            Message::IdentificationEvent(criminal_id, source, score, snapshot, identified_at) => {
+                let (Some(notifier), Some(db)) = (&self.notifier, self.db.clone()) else {
+                    return Task::none();
+                };
+                let include_snapshot = notifier.include_snapshot();
+
+                Task::perform(
+                    async move {
+                        let Some(criminal) = db.get_criminal(criminal_id).await? else {
+                            return Ok(None);
+                        };
+                        let watchlist = db.most_urgent_watchlist(criminal_id).await?;
+                        let snapshot = snapshot
+                            .filter(|_| include_snapshot)
+                            .and_then(|bytes| Snapshot::from_bytes(&bytes));
+                        Ok::<_, sea_orm::DbErr>(Some(Notification {
+                            criminal_id,
+                            name: criminal.name,
+                            score,
+                            source,
+                            time: identified_at,
+                            watchlist: watchlist.as_ref().map(|w| w.name.clone()),
+                            priority: watchlist.map(|w| w.priority),
+                            snapshot,
+                        }))
+                    },
+                    |result| match result {
+                        Ok(Some(notification)) => Message::SendNotification(Box::new(notification)),
+                        Ok(None) => Message::None,
+                        Err(e) => Message::ShowError(
+                            AppError::Database(format!("Could not prepare a notification: {}", e)),
+                            None,
+                        ),
+                    },
+                )
+            }
+
+            Message::SendNotification(notification) => {
+                if let Some(notifier) = &self.notifier {
+                    if notifier.wants(&notification.source, notification.priority.as_deref()) {
+                        notifier.notify(*notification);
+                    }
+                }
+                Task::none()
+            }
+
+            Message::DeliveryAttempted(record) => {
+                println!(
+                    "Notification {} to {} (attempt {}): {}",
+                    record.channel, record.target, record.attempt, record.status
+                );
+                let Some(db) = self.db.clone() else {
+                    return Task::none();
+                };
+                Task::perform(
+                    async move { db.log_delivery(&record).await },
+                    |result| match result {
+                        Ok(_) => Message::None,
+                        Err(e) => Message::ShowError(
+                            AppError::Database(format!(
+                                "Could not log a notification delivery: {}",
+                                e
+                            )),
+                            None,
+                        ),
+                    },
+                )
+            }
+
+            Message::CheckWatchlists(..)
+            | Message::AlertRaised(_)
+            | Message::LoadPendingAlerts
+            | Message::PendingAlertsLoaded(_)
+            | Message::AlertNoteChanged(_)
+            | Message::AcknowledgeAlert(_)
+            | Message::AlertAcknowledged(..) => self.alerts.update(message, self.db.clone()),
+
             Message::FilesSelected(_) => match self.current_page {
                 Page::Registry => self.registry_state.update(message, self.db.clone()),
                 Page::ImageFind => self.image_find.update(message, self.db.clone()),
@@ -211,9 +1067,14 @@ impl GlassmorphismApp {
                     .update(Message::WebcamFrameCaptured(path), self.db.clone())
             }
 
-            Message::ToggleWebcam(on) => self
-                .webcam_find
-                .update(Message::ToggleWebcam(on), self.db.clone()),
+            Message::ToggleWebcam(on) => {
+                if !on {
+                    // A frame still with the engine shouldn't match after the camera is off
+                    self.identifier.cancel(Source::Webcam);
+                }
+                self.webcam_find
+                    .update(Message::ToggleWebcam(on), self.db.clone())
+            }
 
             Message::ResetWebcamSearch => self
                 .webcam_find
@@ -227,7 +1088,246 @@ impl GlassmorphismApp {
         }
     }
 
+    /// Records a match for review and loads the record for the page that asked.
+    fn finish_identification(&self, resolved: Resolved) -> Task<Message> {
+        let source = resolved.source;
+        let candidate = match resolved.outcome {
+            Ok(candidate) => candidate,
+            Err(reason) => return Task::done(Message::Identified(source, Err(reason))),
+        };
+        let Some(db) = self.db.clone() else {
+            // The record can't be shown, but the match is kept for review
+            let error = AppError::Database(format!(
+                "Matched #{} but the database is offline; the match is kept for review \
+                 until it's back",
+                candidate.criminal_id
+            ));
+            return Task::batch(vec![
+                self.record_identification(
+                    candidate.criminal_id,
+                    source.as_str(),
+                    candidate.score,
+                    candidate.photo_id,
+                    resolved.probe,
+                ),
+                Task::done(Message::Identified(source, Err(error.to_string()))),
+                error::report(error, Some(Message::ConnectDatabase)),
+            ]);
+        };
+        println!(
+            "Matched {} ({:?}) from {}",
+            candidate.criminal_id,
+            candidate.score,
+            source.as_str()
+        );
+
+        Task::batch(vec![
+            // Only a candidate until an operator confirms it
+            self.record_identification(
+                candidate.criminal_id,
+                source.as_str(),
+                candidate.score,
+                candidate.photo_id,
+                resolved.probe,
+            ),
+            Task::perform(identify::load_record(db, candidate), move |result| {
+                Message::Identified(source, result.map(Box::new))
+            }),
+        ])
+    }
+
+    fn send_to_engine(&self, command: &str) -> Result<(), AppError> {
+        let engine = self
+            .model_engine
+            .as_ref()
+            .ok_or_else(|| AppError::Engine("The recognition engine isn't running".to_string()))?;
+        engine
+            .send(command)
+            .map_err(|e| AppError::Engine(format!("Could not reach the engine: {}", e)))
+    }
+
+    /// Resends `command`, or restarts the engine first if it's gone.
+    fn engine_retry(&self, command: String) -> Message {
+        match self.model_engine {
+            Some(_) => Message::PythonInput(command),
+            None => Message::RestartEngine,
+        }
+    }
+
+    /// Reports an engine failure and releases the pages waiting on it.
+    fn engine_failed(&mut self, error: AppError, retry: Option<Message>) -> Task<Message> {
+        let reason = error.to_string();
+        let mut tasks: Vec<_> = self
+            .identifier
+            .fail_pending(&reason)
+            .into_iter()
+            .map(|resolved| self.finish_identification(resolved))
+            .collect();
+        // Its embeddings won't come either
+        tasks.push(
+            self.registry_state
+                .update(Message::DuplicateCheckFailed(reason), self.db.clone()),
+        );
+        tasks.push(error::report(error, retry));
+        Task::batch(tasks)
+    }
+
+    /// Answers the one request whose engine command failed: `error <command>
+    /// <tag, record id or media type> <reason>`.
+    fn engine_request_failed(
+        &mut self,
+        command: &str,
+        target: &str,
+        reason: &str,
+    ) -> Task<Message> {
+        let error = AppError::Engine(format!(
+            "The engine failed on \"{} {}\": {}",
+            command, target, reason
+        ));
+        match command {
+            "add" => {
+                let Ok(id) = target.parse() else {
+                    return error::report(error, None);
+                };
+                // Photos of a record synced from the outbox stay queued
+                if outbox::current().engine_answered(id).is_some() {
+                    return error::report(error, Some(Message::SyncOutbox));
+                }
+                self.registry_state.update(
+                    Message::EnrollmentNotEmbedded(id, reason.to_string()),
+                    self.db.clone(),
+                )
+            }
+            "embed" if IdentificationService::owns_tag(target) => {
+                match self.identifier.fail_tag(target, reason) {
+                    Some(resolved) => Task::batch(vec![
+                        error::report(error, None),
+                        self.finish_identification(resolved),
+                    ]),
+                    None => Task::none(),
+                }
+            }
+            "embed" if target.starts_with("dup-") => {
+                let failed = self.registry_state.update(
+                    Message::DuplicateCheckFailed(error.to_string()),
+                    self.db.clone(),
+                );
+                Task::batch(vec![error::report(error, None), failed])
+            }
+            "embed" if target.starts_with("batch-") => self.image_find.update(
+                Message::BatchProbed(target.to_string(), Err(reason.to_string())),
+                self.db.clone(),
+            ),
+            "embed" if target.starts_with("cmp-") => Task::batch(vec![
+                error::report(error, None),
+                self.route_comparison(Message::ComparisonScores(target.to_string(), Vec::new())),
+            ]),
+            // No faces line follows; answer the photo's analysis without any
+            "analyze" => Task::batch(vec![
+                error::report(error, None),
+                Task::done(Message::FacesDetected(target.to_string(), Vec::new())),
+            ]),
+            "identify" => {
+                let mut tasks = vec![error::report(error, None)];
+                let failed =
+                    Source::parse(target).and_then(|source| self.identifier.fail(source, reason));
+                if let Some(resolved) = failed {
+                    tasks.push(self.finish_identification(resolved));
+                }
+                Task::batch(tasks)
+            }
+            _ => error::report(error, None),
+        }
+    }
+
+    /// Stores a match as pending review, with the probe image it came from;
+    /// in the outbox while the database is offline.
+    fn record_identification(
+        &self,
+        criminal_id: u32,
+        source: &str,
+        score: Option<f32>,
+        photo_id: Option<u32>,
+        probe: Option<PathBuf>,
+    ) -> Task<Message> {
+        let source = source.to_string();
+        let captured_at = chrono::Utc::now();
+        let new = async move {
+            let probe = match probe {
+                Some(path) => tokio::fs::read(path).await.and_then(vault::open_input).ok(),
+                None => None,
+            };
+            NewIdentification {
+                criminal_id,
+                photo_id,
+                source,
+                score,
+                probe,
+                captured_at,
+            }
+        };
+
+        let Some(db) = self.db.clone() else {
+            return Task::perform(
+                async move {
+                    outbox::current()
+                        .enqueue_identification(&new.await)
+                        .map_err(|e| e.to_string())
+                },
+                Message::IdentificationQueued,
+            );
+        };
+        Task::perform(
+            async move {
+                db.record_identification(new.await)
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::IdentificationRecorded,
+        )
+    }
+
+    /// Probe comparisons live on the image and video find pages.
+    fn route_comparison(&mut self, message: Message) -> Task<Message> {
+        match self.current_page {
+            Page::ImageFind => self.image_find.update(message, self.db.clone()),
+            Page::VideoFind => self.video_find.update(message, self.db.clone()),
+            _ => Task::none(),
+        }
+    }
+
+    /// Loads every stored embedding; the SQL database owns the gallery.
+    fn reload_gallery(&self) -> Task<Message> {
+        let Some(db) = self.db.clone() else {
+            return Task::none();
+        };
+
+        Task::perform(
+            async move {
+                db.get_all_face_embeddings()
+                    .await
+                    .map_err(|e| e.to_string())
+            },
+            Message::GalleryLoaded,
+        )
+    }
+
     pub fn view(&self) -> Element<'_, Message> {
+        let page = self.page_view();
+        // Unacknowledged watchlist alerts stay on top of whatever page is open
+        let page = match self.alerts.view() {
+            Some(banner) => {
+                iced::widget::column![iced::widget::container(banner).padding([10, 20]), page]
+                    .into()
+            }
+            None => page,
+        };
+        // Errors float over everything, in the bottom-right corner, and the
+        // database status sits in the top-right one
+        iced::widget::stack![page, self.connection.view(), self.toasts.view()].into()
+    }
+
+    fn page_view(&self) -> Element<'_, Message> {
         match self.current_page {
             Page::Dashboard => iced::widget::text("Welcome to Dashboard").into(),
             Page::Registry => self.registry_state.view(),
@@ -235,6 +1335,10 @@ impl GlassmorphismApp {
             Page::ImageFind => self.image_find.view(),
             Page::VideoFind => self.video_find.view(),
             Page::WebcamFind => self.webcam_find.view(),
+            Page::Merge => self.merge_page.view(),
+            Page::Browser => self.browser.view(),
+            Page::Watchlists => self.watchlists.view(),
+            Page::Review => self.review.view(),
             _ => iced::widget::text("New page").into(),
         }
     }
@@ -248,7 +1352,43 @@ impl GlassmorphismApp {
             Subscription::none()
         };
 
-        Subscription::batch(vec![python_sub, webcam_sub])
+        let retention_sub = if self.db.is_some() && self.retention.interval_minutes > 0 {
+            let interval = u64::from(self.retention.interval_minutes) * 60;
+            iced::time::every(std::time::Duration::from_secs(interval))
+                .map(|_| Message::EnforceRetention)
+        } else {
+            Subscription::none()
+        };
+
+        let sweep_sub = if self.retention.interval_minutes > 0 && self.retention.temp_hours > 0 {
+            let interval = u64::from(self.retention.interval_minutes) * 60;
+            iced::time::every(std::time::Duration::from_secs(interval))
+                .map(|_| Message::SweepTempFiles)
+        } else {
+            Subscription::none()
+        };
+
+        let ping_sub = if self.connection.status == DbStatus::Connected {
+            iced::time::every(PING_INTERVAL).map(|_| Message::CheckDatabase)
+        } else {
+            Subscription::none()
+        };
+
+        let toast_sub = if self.toasts.is_expiring() {
+            iced::time::every(std::time::Duration::from_secs(1)).map(|_| Message::ExpireToasts)
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch(vec![
+            python_sub,
+            delivery_sub(),
+            webcam_sub,
+            retention_sub,
+            sweep_sub,
+            ping_sub,
+            toast_sub,
+        ])
     }
 
     pub fn theme(&self) -> Theme {
@@ -257,12 +1397,22 @@ impl GlassmorphismApp {
 }
 
 pub fn main() -> iced::Result {
-    iced::application(
+    let result = iced::application(
         GlassmorphismApp::new,
         GlassmorphismApp::update,
         GlassmorphismApp::view,
     )
     .subscription(GlassmorphismApp::subscription)
     .theme(GlassmorphismApp::theme)
-    .run()
+    .run();
+
+    // Probes and frames don't outlive the app
+    if let Err(e) = workspace::current().clear() {
+        eprintln!(
+            "❌ Could not clear {}: {}",
+            workspace::current().run_dir().display(),
+            e
+        );
+    }
+    result
 }
@@ -0,0 +1,380 @@
+use crate::config::{NotificationConfig, SmtpConfig, WebhookConfig};
+use crate::entities::watchlist;
+use crate::Message;
+use base64::prelude::*;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use iced::futures::stream;
+use iced::Subscription;
+use lettre::message::{header::ContentType, Attachment, MultiPart, SinglePart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Tokio1Executor};
+use serde::Serialize;
+use sha2::Sha256;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+use tokio::sync::{mpsc, Mutex as TokioMutex};
+
+// Outcome of one delivery attempt, as logged
+pub const DELIVERED: &str = "delivered";
+pub const RETRYING: &str = "retrying";
+pub const FAILED: &str = "failed";
+
+/// `sha256=<hex HMAC-SHA256 of the body>`, keyed with the webhook's secret.
+pub const SIGNATURE_HEADER: &str = "X-CFR-Signature";
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// What gets sent out about one identification.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Notification {
+    pub criminal_id: u32,
+    pub name: String,
+    pub score: Option<f32>,
+    // "image", "video" or "webcam"
+    pub source: String,
+    pub time: DateTime<Utc>,
+    pub watchlist: Option<String>,
+    pub priority: Option<String>,
+    pub snapshot: Option<Snapshot>,
+}
+
+/// The probe image, base64-encoded.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Snapshot {
+    pub content_type: String,
+    pub data: String,
+}
+
+impl Snapshot {
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let content_type = match image::guess_format(bytes).ok()? {
+            image::ImageFormat::Png => "image/png",
+            image::ImageFormat::Jpeg => "image/jpeg",
+            image::ImageFormat::WebP => "image/webp",
+            _ => "application/octet-stream",
+        };
+        Some(Self {
+            content_type: content_type.to_string(),
+            data: BASE64_STANDARD.encode(bytes),
+        })
+    }
+}
+
+impl Notification {
+    pub fn subject(&self) -> String {
+        match &self.priority {
+            Some(priority) => format!(
+                "[{}] {} (#{}) identified via {}",
+                watchlist::priority_label(priority).to_uppercase(),
+                self.name,
+                self.criminal_id,
+                self.source
+            ),
+            None => format!(
+                "{} (#{}) identified via {}",
+                self.name, self.criminal_id, self.source
+            ),
+        }
+    }
+
+    fn email_body(&self) -> String {
+        let mut lines = vec![
+            format!("Criminal ID: {}", self.criminal_id),
+            format!("Name: {}", self.name),
+            format!("Source: {}", self.source),
+            format!("Time: {}", self.time.format("%Y-%m-%d %H:%M:%S UTC")),
+        ];
+        if let Some(score) = self.score {
+            lines.push(format!("Match score: {:.2}", score));
+        }
+        if let Some(watchlist) = &self.watchlist {
+            lines.push(format!("Watchlist: {}", watchlist));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Somewhere a notification can be delivered.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Channel {
+    Webhook(WebhookConfig),
+    Email(SmtpConfig),
+}
+
+impl Channel {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Channel::Webhook(_) => "webhook",
+            Channel::Email(_) => "email",
+        }
+    }
+
+    pub fn target(&self) -> String {
+        match self {
+            Channel::Webhook(hook) => hook.url.clone(),
+            Channel::Email(smtp) => smtp.to.join(", "),
+        }
+    }
+}
+
+/// One delivery attempt, handed to the notifier's callback for logging.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeliveryRecord {
+    pub channel: &'static str,
+    pub target: String,
+    pub criminal_id: u32,
+    pub attempt: u32,
+    pub status: &'static str,
+    pub error: Option<String>,
+}
+
+pub type DeliveryCallback = Arc<dyn Fn(DeliveryRecord) + Send + Sync>;
+
+type DeliveryChannel = (
+    mpsc::UnboundedSender<DeliveryRecord>,
+    Arc<TokioMutex<mpsc::UnboundedReceiver<DeliveryRecord>>>,
+);
+
+// The app's notifier reports attempts here; `delivery_sub` drains it.
+static DELIVERY_CHANNEL: OnceLock<DeliveryChannel> = OnceLock::new();
+
+fn delivery_channel() -> &'static DeliveryChannel {
+    DELIVERY_CHANNEL.get_or_init(|| {
+        let (tx, rx) = mpsc::unbounded_channel();
+        (tx, Arc::new(TokioMutex::new(rx)))
+    })
+}
+
+/// Callback that surfaces each attempt to the app as `Message::DeliveryAttempted`.
+pub fn app_delivery_log() -> DeliveryCallback {
+    let tx = delivery_channel().0.clone();
+    Arc::new(move |record| {
+        let _ = tx.send(record);
+    })
+}
+
+pub fn delivery_sub() -> Subscription<Message> {
+    Subscription::run(delivery_stream)
+}
+
+fn delivery_stream() -> impl futures::Stream<Item = Message> + Send + 'static {
+    let rx = delivery_channel().1.clone();
+
+    stream::unfold(rx, |rx| async move {
+        let next = {
+            let mut guard = rx.lock().await;
+            guard.recv().await
+        };
+        next.map(|record| (Message::DeliveryAttempted(record), rx))
+    })
+}
+
+/// Hex HMAC-SHA256 of `body` keyed with `secret`.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts any key length");
+    mac.update(body);
+    hex::encode(mac.finalize().into_bytes())
+}
+
+pub async fn post_webhook(
+    client: &reqwest::Client,
+    hook: &WebhookConfig,
+    notification: &Notification,
+) -> Result<(), String> {
+    let body = serde_json::to_vec(notification).map_err(|e| e.to_string())?;
+    let response = client
+        .post(&hook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(
+            SIGNATURE_HEADER,
+            format!("sha256={}", sign(&hook.secret, &body)),
+        )
+        .body(body)
+        .timeout(WEBHOOK_TIMEOUT)
+        .send()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        Err(format!("HTTP {}", response.status()))
+    }
+}
+
+pub async fn send_email(smtp: &SmtpConfig, notification: &Notification) -> Result<(), String> {
+    let mut builder = lettre::Message::builder()
+        .from(smtp.from.parse().map_err(|e| format!("from: {}", e))?)
+        .subject(notification.subject());
+    for to in &smtp.to {
+        builder = builder.to(to.parse().map_err(|e| format!("to {}: {}", to, e))?);
+    }
+
+    let text = SinglePart::plain(notification.email_body());
+    let email = match &notification.snapshot {
+        Some(snapshot) => {
+            let bytes = BASE64_STANDARD
+                .decode(&snapshot.data)
+                .map_err(|e| e.to_string())?;
+            let content_type =
+                ContentType::parse(&snapshot.content_type).map_err(|e| e.to_string())?;
+            builder.multipart(
+                MultiPart::mixed()
+                    .singlepart(text)
+                    .singlepart(Attachment::new("snapshot".to_string()).body(bytes, content_type)),
+            )
+        }
+        None => builder.singlepart(text),
+    }
+    .map_err(|e| e.to_string())?;
+
+    let mut transport = if smtp.starttls {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&smtp.host)
+            .map_err(|e| e.to_string())?
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&smtp.host)
+    }
+    .port(smtp.port);
+    if !smtp.username.is_empty() {
+        transport = transport.credentials(Credentials::new(
+            smtp.username.clone(),
+            smtp.password.clone(),
+        ));
+    }
+
+    transport
+        .build()
+        .send(email)
+        .await
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+struct Job {
+    channel: Channel,
+    notification: Arc<Notification>,
+    attempt: u32,
+}
+
+/// Queues notifications and delivers them on a background thread, retrying
+/// failures with a doubling delay.
+pub struct Notifier {
+    config: NotificationConfig,
+    queue: mpsc::UnboundedSender<Job>,
+}
+
+impl Notifier {
+    /// Starts the delivery worker; `None` when no sources or channels are configured.
+    pub fn start(config: NotificationConfig, on_attempt: DeliveryCallback) -> Option<Self> {
+        if config.sources.is_empty() || (config.webhooks.is_empty() && config.smtp.is_none()) {
+            return None;
+        }
+
+        let (queue, jobs) = mpsc::unbounded_channel();
+        let requeue = queue.downgrade();
+        let worker_config = config.clone();
+        std::thread::spawn(move || {
+            match tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+            {
+                Ok(runtime) => {
+                    runtime.block_on(run_worker(worker_config, jobs, requeue, on_attempt))
+                }
+                Err(e) => eprintln!("❌ Notification worker failed to start: {e}"),
+            }
+        });
+
+        Some(Self { config, queue })
+    }
+
+    /// Whether an identification from `source` of someone whose most urgent
+    /// watchlist has `priority` should go out.
+    pub fn wants(&self, source: &str, priority: Option<&str>) -> bool {
+        if !self.config.sources.iter().any(|s| s == source) {
+            return false;
+        }
+        if self.config.min_priority.is_empty() {
+            return true;
+        }
+        priority.is_some_and(|p| {
+            watchlist::priority_rank(p) <= watchlist::priority_rank(&self.config.min_priority)
+        })
+    }
+
+    pub fn include_snapshot(&self) -> bool {
+        self.config.include_snapshot
+    }
+
+    /// Queues `notification` for every configured channel.
+    pub fn notify(&self, notification: Notification) {
+        let notification = Arc::new(notification);
+        let channels = self
+            .config
+            .webhooks
+            .iter()
+            .cloned()
+            .map(Channel::Webhook)
+            .chain(self.config.smtp.clone().map(Channel::Email));
+        for channel in channels {
+            let _ = self.queue.send(Job {
+                channel,
+                notification: notification.clone(),
+                attempt: 1,
+            });
+        }
+    }
+}
+
+async fn run_worker(
+    config: NotificationConfig,
+    mut jobs: mpsc::UnboundedReceiver<Job>,
+    requeue: mpsc::WeakUnboundedSender<Job>,
+    on_attempt: DeliveryCallback,
+) {
+    let client = reqwest::Client::new();
+
+    while let Some(job) = jobs.recv().await {
+        let client = client.clone();
+        let requeue = requeue.clone();
+        let on_attempt = on_attempt.clone();
+        let max_attempts = config.max_attempts.max(1);
+        let retry_delay = config.retry_delay_secs;
+
+        // Each delivery runs on its own so a slow relay doesn't hold up the rest
+        tokio::spawn(async move {
+            let result = match &job.channel {
+                Channel::Webhook(hook) => post_webhook(&client, hook, &job.notification).await,
+                Channel::Email(smtp) => send_email(smtp, &job.notification).await,
+            };
+
+            let status = match &result {
+                Ok(()) => DELIVERED,
+                Err(_) if job.attempt < max_attempts => RETRYING,
+                Err(_) => FAILED,
+            };
+            on_attempt(DeliveryRecord {
+                channel: job.channel.kind(),
+                target: job.channel.target(),
+                criminal_id: job.notification.criminal_id,
+                attempt: job.attempt,
+                status,
+                error: result.err(),
+            });
+
+            if status == RETRYING {
+                let delay = retry_delay.saturating_mul(1 << (job.attempt - 1).min(16));
+                tokio::time::sleep(Duration::from_secs(delay)).await;
+                // Gone once the notifier is dropped; nothing left to retry for
+                if let Some(queue) = requeue.upgrade() {
+                    let _ = queue.send(Job {
+                        attempt: job.attempt + 1,
+                        ..job
+                    });
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,95 @@
+//! Five-point similarity alignment onto the ArcFace 112x112 template.
+
+use image::{Rgb, RgbImage};
+
+pub const ALIGNED_SIZE: u32 = 112;
+
+// Canonical landmark positions used to train ArcFace models
+const TEMPLATE: [[f32; 2]; 5] = [
+    [38.2946, 51.6963],
+    [73.5318, 51.5014],
+    [56.0252, 71.7366],
+    [41.5493, 92.3655],
+    [70.7299, 92.2041],
+];
+
+/// Least-squares similarity (rotation + uniform scale + translation) mapping
+/// `landmarks` onto the template, applied as an inverse bilinear warp.
+pub fn align_face(image: &RgbImage, landmarks: &[[f32; 2]; 5]) -> RgbImage {
+    let (a, b, tx, ty) = estimate_similarity(landmarks, &TEMPLATE);
+
+    // Inverse of [[a, -b], [b, a]]
+    let det = a * a + b * b;
+    let (ia, ib) = if det > f32::EPSILON {
+        (a / det, b / det)
+    } else {
+        (1.0, 0.0)
+    };
+
+    RgbImage::from_fn(ALIGNED_SIZE, ALIGNED_SIZE, |x, y| {
+        let dx = x as f32 - tx;
+        let dy = y as f32 - ty;
+        let sx = ia * dx + ib * dy;
+        let sy = -ib * dx + ia * dy;
+        sample_bilinear(image, sx, sy)
+    })
+}
+
+fn estimate_similarity(src: &[[f32; 2]; 5], dst: &[[f32; 2]; 5]) -> (f32, f32, f32, f32) {
+    let n = src.len() as f32;
+    let mean = |pts: &[[f32; 2]; 5]| {
+        let (sx, sy) = pts
+            .iter()
+            .fold((0.0, 0.0), |acc, p| (acc.0 + p[0], acc.1 + p[1]));
+        (sx / n, sy / n)
+    };
+    let (smx, smy) = mean(src);
+    let (dmx, dmy) = mean(dst);
+
+    let mut norm = 0.0;
+    let mut dot = 0.0;
+    let mut cross = 0.0;
+    for (s, d) in src.iter().zip(dst) {
+        let (sx, sy) = (s[0] - smx, s[1] - smy);
+        let (dx, dy) = (d[0] - dmx, d[1] - dmy);
+        norm += sx * sx + sy * sy;
+        dot += sx * dx + sy * dy;
+        cross += sx * dy - sy * dx;
+    }
+
+    let (a, b) = if norm > f32::EPSILON {
+        (dot / norm, cross / norm)
+    } else {
+        (1.0, 0.0)
+    };
+    let tx = dmx - (a * smx - b * smy);
+    let ty = dmy - (b * smx + a * smy);
+    (a, b, tx, ty)
+}
+
+fn sample_bilinear(image: &RgbImage, x: f32, y: f32) -> Rgb<u8> {
+    let (width, height) = image.dimensions();
+    if x < 0.0 || y < 0.0 || x > (width - 1) as f32 || y > (height - 1) as f32 {
+        return Rgb([0, 0, 0]);
+    }
+
+    let x0 = x.floor() as u32;
+    let y0 = y.floor() as u32;
+    let x1 = (x0 + 1).min(width - 1);
+    let y1 = (y0 + 1).min(height - 1);
+    let fx = x - x0 as f32;
+    let fy = y - y0 as f32;
+
+    let p00 = image.get_pixel(x0, y0);
+    let p10 = image.get_pixel(x1, y0);
+    let p01 = image.get_pixel(x0, y1);
+    let p11 = image.get_pixel(x1, y1);
+
+    let mut out = [0u8; 3];
+    for (c, value) in out.iter_mut().enumerate() {
+        let top = p00[c] as f32 * (1.0 - fx) + p10[c] as f32 * fx;
+        let bottom = p01[c] as f32 * (1.0 - fx) + p11[c] as f32 * fx;
+        *value = (top * (1.0 - fy) + bottom * fy).round() as u8;
+    }
+    Rgb(out)
+}
@@ -0,0 +1,149 @@
+//! SCRFD face detector (`det_10g.onnx` from the insightface buffalo packs).
+
+use image::{imageops, RgbImage};
+use std::path::Path;
+use tract_onnx::prelude::*;
+
+const INPUT_SIZE: u32 = 640;
+const STRIDES: [usize; 3] = [8, 16, 32];
+const ANCHORS_PER_CELL: usize = 2;
+const SCORE_THRESHOLD: f32 = 0.5;
+const NMS_THRESHOLD: f32 = 0.4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Detection {
+    /// x1, y1, x2, y2 in source image pixels
+    pub bbox: [f32; 4],
+    pub score: f32,
+    /// Eyes, nose tip, mouth corners
+    pub landmarks: [[f32; 2]; 5],
+}
+
+impl Detection {
+    pub fn width(&self) -> f32 {
+        self.bbox[2] - self.bbox[0]
+    }
+
+    pub fn height(&self) -> f32 {
+        self.bbox[3] - self.bbox[1]
+    }
+
+    fn area(&self) -> f32 {
+        self.width().max(0.0) * self.height().max(0.0)
+    }
+
+    pub fn iou(&self, other: &Detection) -> f32 {
+        let x1 = self.bbox[0].max(other.bbox[0]);
+        let y1 = self.bbox[1].max(other.bbox[1]);
+        let x2 = self.bbox[2].min(other.bbox[2]);
+        let y2 = self.bbox[3].min(other.bbox[3]);
+        let inter = (x2 - x1).max(0.0) * (y2 - y1).max(0.0);
+        let union = self.area() + other.area() - inter;
+        if union <= 0.0 {
+            0.0
+        } else {
+            inter / union
+        }
+    }
+}
+
+pub struct FaceDetector {
+    model: TypedRunnableModel<TypedModel>,
+}
+
+impl FaceDetector {
+    pub fn load(path: &Path) -> TractResult<Self> {
+        let size = INPUT_SIZE as usize;
+        let model = tract_onnx::onnx()
+            .model_for_path(path)?
+            .with_input_fact(0, f32::fact([1, 3, size, size]).into())?
+            .into_optimized()?
+            .into_runnable()?;
+        Ok(Self { model })
+    }
+
+    /// Faces in `image`, highest score first.
+    pub fn detect(&self, image: &RgbImage) -> TractResult<Vec<Detection>> {
+        let (width, height) = image.dimensions();
+        if width == 0 || height == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Letterbox into the top-left corner, like insightface does
+        let scale = (INPUT_SIZE as f32 / width as f32).min(INPUT_SIZE as f32 / height as f32);
+        let new_w = ((width as f32 * scale) as u32).clamp(1, INPUT_SIZE);
+        let new_h = ((height as f32 * scale) as u32).clamp(1, INPUT_SIZE);
+        let resized = imageops::resize(image, new_w, new_h, imageops::FilterType::Triangle);
+
+        let size = INPUT_SIZE as usize;
+        let input: Tensor =
+            tract_ndarray::Array4::from_shape_fn((1, 3, size, size), |(_, c, y, x)| {
+                let value = if (x as u32) < new_w && (y as u32) < new_h {
+                    resized.get_pixel(x as u32, y as u32)[c] as f32
+                } else {
+                    0.0
+                };
+                (value - 127.5) / 128.0
+            })
+            .into();
+
+        let outputs = self.model.run(tvec!(input.into()))?;
+        if outputs.len() < STRIDES.len() * 3 {
+            return Err(TractError::msg(format!(
+                "detector returned {} outputs, expected {}",
+                outputs.len(),
+                STRIDES.len() * 3
+            )));
+        }
+
+        let mut detections = Vec::new();
+        for (level, stride) in STRIDES.iter().copied().enumerate() {
+            let scores = outputs[level].as_slice::<f32>()?;
+            let boxes = outputs[level + STRIDES.len()].as_slice::<f32>()?;
+            let kps = outputs[level + STRIDES.len() * 2].as_slice::<f32>()?;
+
+            let cells = size / stride;
+            for (anchor, &score) in scores.iter().enumerate() {
+                if score < SCORE_THRESHOLD {
+                    continue;
+                }
+                let cell = anchor / ANCHORS_PER_CELL;
+                let cx = ((cell % cells) * stride) as f32;
+                let cy = ((cell / cells) * stride) as f32;
+                let s = stride as f32;
+                let d = &boxes[anchor * 4..anchor * 4 + 4];
+                let k = &kps[anchor * 10..anchor * 10 + 10];
+
+                let mut landmarks = [[0.0f32; 2]; 5];
+                for (i, point) in landmarks.iter_mut().enumerate() {
+                    *point = [(cx + k[i * 2] * s) / scale, (cy + k[i * 2 + 1] * s) / scale];
+                }
+
+                detections.push(Detection {
+                    bbox: [
+                        (cx - d[0] * s) / scale,
+                        (cy - d[1] * s) / scale,
+                        (cx + d[2] * s) / scale,
+                        (cy + d[3] * s) / scale,
+                    ],
+                    score,
+                    landmarks,
+                });
+            }
+        }
+
+        Ok(non_max_suppression(detections))
+    }
+}
+
+fn non_max_suppression(mut detections: Vec<Detection>) -> Vec<Detection> {
+    detections.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    let mut kept: Vec<Detection> = Vec::new();
+    for detection in detections {
+        if kept.iter().all(|k| k.iou(&detection) < NMS_THRESHOLD) {
+            kept.push(detection);
+        }
+    }
+    kept
+}
@@ -0,0 +1,35 @@
+//! ArcFace-style recognizer (`w600k_r50.onnx` from buffalo_l).
+
+use super::align::ALIGNED_SIZE;
+use image::RgbImage;
+use std::path::Path;
+use tract_onnx::prelude::*;
+
+pub struct FaceEmbedder {
+    model: TypedRunnableModel<TypedModel>,
+}
+
+impl FaceEmbedder {
+    pub fn load(path: &Path) -> TractResult<Self> {
+        let size = ALIGNED_SIZE as usize;
+        let model = tract_onnx::onnx()
+            .model_for_path(path)?
+            .with_input_fact(0, f32::fact([1, 3, size, size]).into())?
+            .into_optimized()?
+            .into_runnable()?;
+        Ok(Self { model })
+    }
+
+    /// Embedding of an aligned 112x112 face crop.
+    pub fn embed(&self, face: &RgbImage) -> TractResult<Vec<f32>> {
+        let size = ALIGNED_SIZE as usize;
+        let input: Tensor =
+            tract_ndarray::Array4::from_shape_fn((1, 3, size, size), |(_, c, y, x)| {
+                (face.get_pixel(x as u32, y as u32)[c] as f32 - 127.5) / 127.5
+            })
+            .into();
+
+        let outputs = self.model.run(tvec!(input.into()))?;
+        Ok(outputs[0].as_slice::<f32>()?.to_vec())
+    }
+}
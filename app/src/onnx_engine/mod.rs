@@ -0,0 +1,415 @@
+//! In-process CPU recognition backend.
+//!
+//! Runs the same detector and recognizer as the insightface buffalo_l pack
+//! through `tract`, and answers the same commands as `model_engine/main.py`
+//! on a worker thread, so it drops in wherever `PythonProcess` is used.
+//! Videos are decoded with GStreamer and sampled like the Python engine does.
+
+mod align;
+mod detector;
+mod embedder;
+mod video;
+
+pub use detector::{Detection, FaceDetector};
+pub use embedder::FaceEmbedder;
+
+use crate::config::EngineConfig;
+use crate::engine::RecognitionEngine;
+use crate::face_index::{FaceIndex, IndexEntry, EMBEDDING_DIM, MATCH_THRESHOLD};
+use crate::python_process::{
+    decode_embedding, decode_path, encode_embedding, encode_path, engine_output, ENGINE_LOG_PREFIX,
+};
+use crate::quality::FaceBox;
+use crate::vault;
+use crate::workspace;
+use image::RgbImage;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use tokio::sync::mpsc as tokio_mpsc;
+use tract_onnx::prelude::TractResult;
+use video::FrameSampler;
+
+/// Every how many frames a video is sampled, and how many samples at most.
+const VIDEO_FRAME_SKIP: usize = 5;
+const VIDEO_MAX_FRAMES: usize = 300;
+
+// Numbers the saved video frames; unique for the whole run like workspace files
+static NEXT_FRAME: AtomicU64 = AtomicU64::new(0);
+
+pub struct OnnxEngine {
+    commands: mpsc::Sender<String>,
+}
+
+impl OnnxEngine {
+    /// Starts the worker; models load on that thread so startup isn't blocked.
+    pub fn spawn(config: &EngineConfig) -> Self {
+        let (commands, rx) = mpsc::channel::<String>();
+        let config = config.clone();
+        let out = engine_output();
+
+        thread::spawn(move || {
+            println!("Loading ONNX models on CPU...");
+            let mut worker = match Worker::load(&config, out.clone()) {
+                Ok(worker) => worker,
+                Err(e) => {
+                    eprintln!("❌ ONNX model load failed: {e}");
+                    let _ = out
+                        .blocking_send(format!("{}ONNX model load failed: {e}", ENGINE_LOG_PREFIX));
+                    for _ in rx {
+                        let _ = out.blocking_send("error engine_unavailable".to_string());
+                    }
+                    return;
+                }
+            };
+
+            for line in rx {
+                if !worker.handle(line.trim()) {
+                    break;
+                }
+            }
+        });
+
+        Self { commands }
+    }
+}
+
+impl RecognitionEngine for OnnxEngine {
+    fn send(&self, message: &str) -> std::io::Result<()> {
+        self.commands
+            .send(message.to_string())
+            .map_err(|_| std::io::Error::new(std::io::ErrorKind::BrokenPipe, "ONNX engine stopped"))
+    }
+
+    fn send_batch(&self, messages: Vec<String>) {
+        // The command queue is unbounded, so this never blocks
+        for message in messages {
+            if self.send(&message).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+struct Worker {
+    detector: FaceDetector,
+    embedder: FaceEmbedder,
+    model_name: String,
+    gallery: FaceIndex,
+    next_gallery_id: u32,
+    // Set by the app's [matching] config through `thresholds`
+    threshold: f32,
+    video_min_hits: usize,
+    video_confirm_score: f32,
+    out: tokio_mpsc::Sender<String>,
+}
+
+impl Worker {
+    fn load(config: &EngineConfig, out: tokio_mpsc::Sender<String>) -> TractResult<Self> {
+        Ok(Self {
+            detector: FaceDetector::load(&config.detector_model)?,
+            embedder: FaceEmbedder::load(&config.recognizer_model)?,
+            model_name: config.model_name.clone(),
+            gallery: FaceIndex::new(EMBEDDING_DIM),
+            next_gallery_id: 0,
+            threshold: MATCH_THRESHOLD,
+            video_min_hits: 5,
+            video_confirm_score: 0.6,
+            out,
+        })
+    }
+
+    fn reply(&self, line: String) {
+        let _ = self.out.blocking_send(line);
+    }
+
+    /// Handles one command; returns false on `exit`.
+    fn handle(&mut self, line: &str) -> bool {
+        if line.is_empty() {
+            return true;
+        }
+
+        let recv_msg: Vec<&str> = line.split(' ').collect();
+        match recv_msg[..] {
+            ["start", ..] => self.reply("ready".to_string()),
+
+            ["identify", "image", path, ..] => {
+                let reply = match self.embed_path(Path::new(&decode_path(path))) {
+                    Ok(Some(vector)) => self.identify(&vector),
+                    Ok(None) => "identity UNKNOWN 0.0000".to_string(),
+                    Err(e) => format!("error identify image {e}"),
+                };
+                self.reply(reply);
+            }
+
+            ["identify", "video", path, ..] => {
+                match self.identify_video(Path::new(&decode_path(path))) {
+                    Ok(Some((criminal_id, score, frame))) => {
+                        if let Some(frame) = frame {
+                            let frame = encode_path(&frame.to_string_lossy());
+                            self.reply(format!("probe_frame {}", frame));
+                        }
+                        self.reply(format!("identity {} {:.4}", criminal_id, score));
+                    }
+                    Ok(None) => self.reply("identity UNKNOWN 0.0000".to_string()),
+                    Err(e) => {
+                        eprintln!("[ONNX] identify video {path}: {e}");
+                        self.reply("error unreadable_video".to_string());
+                    }
+                }
+            }
+
+            ["identify", _, _, ..] => self.reply("error unknown_media_type".to_string()),
+            ["identify", ..] => self.reply("error missing_path".to_string()),
+
+            ["embed", tag, path, ref rest @ ..] => {
+                let face_box = rest.first().and_then(|b| parse_box(b));
+                let out = match self.embed_face(Path::new(&decode_path(path)), face_box) {
+                    Ok(Some(vector)) => encode_embedding(&vector),
+                    Ok(None) => "none".to_string(),
+                    Err(e) => {
+                        eprintln!("[ONNX] embed {path}: {e}");
+                        "none".to_string()
+                    }
+                };
+                self.reply(format!("probe {tag} {out}"));
+            }
+
+            ["add", criminal_id, entries, ..] => {
+                // Each entry is "{photo_id}:{x1,y1,x2,y2 or -}:{path}"
+                for entry in entries.split('&') {
+                    let (photo_id, rest) = entry.split_once(':').unwrap_or(("0", entry));
+                    let (face_box, path) = rest.split_once(':').unwrap_or(("-", rest));
+                    let path = decode_path(path);
+                    match self.embed_face(Path::new(&path), parse_box(face_box)) {
+                        Ok(Some(vector)) => {
+                            self.add_to_gallery(criminal_id, &vector);
+                            self.reply(format!(
+                                "embedding {} {} {} {}",
+                                criminal_id,
+                                photo_id,
+                                self.model_name,
+                                encode_embedding(&vector)
+                            ));
+                        }
+                        Ok(None) => eprintln!("[ONNX] No matching face in {path}"),
+                        Err(e) => eprintln!("[ONNX] Failed to load image {path}: {e}"),
+                    }
+                }
+                self.reply(format!("added {criminal_id}"));
+            }
+            ["add", ..] => self.reply("error missing_add_data".to_string()),
+
+            ["analyze", tag, path, ..] => {
+                let faces = match self.detect_path(Path::new(&decode_path(path))) {
+                    Ok(faces) => faces,
+                    Err(e) => {
+                        self.reply(format!("error unreadable_image {tag}"));
+                        eprintln!("[ONNX] analyze {path}: {e}");
+                        Vec::new()
+                    }
+                };
+                self.reply(format!(
+                    "faces {} {} {}",
+                    tag,
+                    faces.len(),
+                    FaceBox::encode_list(&faces)
+                ));
+            }
+            ["analyze", ..] => self.reply("error missing_path".to_string()),
+
+            ["thresholds", threshold, min_hits, confirm_score, ..] => {
+                match (threshold.parse(), min_hits.parse(), confirm_score.parse()) {
+                    (Ok(threshold), Ok(min_hits), Ok(confirm_score)) => {
+                        self.threshold = threshold;
+                        self.video_min_hits = min_hits;
+                        self.video_confirm_score = confirm_score;
+                    }
+                    _ => self.reply("error missing_thresholds".to_string()),
+                }
+            }
+            ["thresholds", ..] => self.reply("error missing_thresholds".to_string()),
+
+            ["gallery_clear", ..] => {
+                self.gallery.clear();
+                self.next_gallery_id = 0;
+            }
+
+            ["gallery_add", criminal_id, model_name, vector, ..] => {
+                if model_name != self.model_name {
+                    eprintln!(
+                        "[ONNX] Skipping {model_name} embedding for {criminal_id} (engine runs {})",
+                        self.model_name
+                    );
+                } else if let Some(vector) = decode_embedding(vector) {
+                    self.add_to_gallery(criminal_id, &vector);
+                }
+            }
+            ["gallery_add", ..] => self.reply("error missing_gallery_data".to_string()),
+
+            ["gallery_commit", ..] => self.reply(format!("gallery_ready {}", self.gallery.len())),
+
+            ["exit", ..] => return false,
+
+            [cmd, ..] => self.reply(format!("info ignored_command {cmd}")),
+            [] => {}
+        }
+        true
+    }
+
+    /// Embedding of the highest-scoring face, or `None` if there is no face.
+    fn embed_path(&self, path: &Path) -> TractResult<Option<Vec<f32>>> {
+        self.embed_face(path, None)
+    }
+
+    /// Embedding of the face overlapping `face_box`, or of the
+    /// highest-scoring face when no box is given.
+    fn embed_face(&self, path: &Path, face_box: Option<[f32; 4]>) -> TractResult<Option<Vec<f32>>> {
+        let image = open_image(path)?;
+        let faces = self.detect(&image)?;
+        let index = match face_box {
+            Some(bbox) => FaceBox::best_match(&faces, bbox),
+            None => (!faces.is_empty()).then_some(0),
+        };
+        let Some(face) = index.map(|i| faces[i]) else {
+            return Ok(None);
+        };
+
+        let aligned = align::align_face(&image, &face.landmarks);
+        self.embedder.embed(&aligned).map(Some)
+    }
+
+    fn detect_path(&self, path: &Path) -> TractResult<Vec<FaceBox>> {
+        self.detect(&open_image(path)?)
+    }
+
+    fn detect(&self, image: &image::RgbImage) -> TractResult<Vec<FaceBox>> {
+        Ok(self
+            .detector
+            .detect(image)?
+            .into_iter()
+            .map(|face| FaceBox {
+                bbox: face.bbox,
+                score: face.score,
+                landmarks: face.landmarks,
+            })
+            .collect())
+    }
+
+    fn identify(&self, vector: &[f32]) -> String {
+        match self.gallery.search_identities(vector, 1) {
+            Ok(hits) => match hits.first() {
+                Some(hit) if hit.matches(self.threshold) => {
+                    format!("identity {} {:.4}", hit.criminal_id, hit.score)
+                }
+                _ => "identity UNKNOWN 0.0000".to_string(),
+            },
+            Err(e) => format!("error identify image {e}"),
+        }
+    }
+
+    /// Scans sampled frames for gallery matches: the first person matched in
+    /// `video_min_hits` faces averaging over `video_confirm_score` wins early,
+    /// otherwise whoever has the best average. Returns the match and the frame
+    /// it scored best in, saved to the workspace for the app to show.
+    fn identify_video(&self, path: &Path) -> TractResult<Option<(u32, f32, Option<PathBuf>)>> {
+        let mut sampler = FrameSampler::open(path, VIDEO_FRAME_SKIP)?;
+        let mut matches: HashMap<u32, Vec<f32>> = HashMap::new();
+        let mut best_frames: HashMap<u32, (f32, RgbImage)> = HashMap::new();
+        let mean = |scores: &Vec<f32>| scores.iter().sum::<f32>() / scores.len() as f32;
+        let confirmed = |scores: &Vec<f32>| {
+            scores.len() >= self.video_min_hits && mean(scores) > self.video_confirm_score
+        };
+
+        for _ in 0..VIDEO_MAX_FRAMES {
+            let Some(frame) = sampler.next_frame()? else {
+                break;
+            };
+            for face in self.detector.detect(&frame)? {
+                let aligned = align::align_face(&frame, &face.landmarks);
+                let vector = self.embedder.embed(&aligned)?;
+                for hit in self.gallery.search_identities(&vector, 5)? {
+                    if !hit.matches(self.threshold) {
+                        break;
+                    }
+                    matches.entry(hit.criminal_id).or_default().push(hit.score);
+                    let best = best_frames.get(&hit.criminal_id).map(|(score, _)| *score);
+                    if best.is_none_or(|best| hit.score > best) {
+                        best_frames.insert(hit.criminal_id, (hit.score, frame.clone()));
+                    }
+                }
+            }
+            if matches.values().any(confirmed) {
+                break;
+            }
+        }
+
+        let best = matches
+            .iter()
+            .find(|(_, scores)| confirmed(scores))
+            .or_else(|| {
+                matches
+                    .iter()
+                    .max_by(|a, b| mean(a.1).total_cmp(&mean(b.1)))
+            });
+        let Some((&criminal_id, scores)) = best else {
+            return Ok(None);
+        };
+        let frame = best_frames
+            .remove(&criminal_id)
+            .and_then(|(_, frame)| save_frame(&frame));
+        Ok(Some((criminal_id, mean(scores), frame)))
+    }
+
+    fn add_to_gallery(&mut self, criminal_id: &str, vector: &[f32]) {
+        let Ok(criminal_id) = criminal_id.parse::<u32>() else {
+            return;
+        };
+
+        let entry = IndexEntry {
+            embedding_id: self.next_gallery_id,
+            criminal_id,
+            photo_id: 0,
+        };
+        self.next_gallery_id += 1;
+        if let Err(e) = self.gallery.insert(entry, vector) {
+            eprintln!("[ONNX] Gallery insert failed: {e}");
+        }
+    }
+}
+
+/// `x1,y1,x2,y2`, or `-` for none.
+fn parse_box(text: &str) -> Option<[f32; 4]> {
+    let values: Vec<f32> = text
+        .split(',')
+        .map(|v| v.parse().ok())
+        .collect::<Option<_>>()?;
+    values.try_into().ok()
+}
+
+/// Writes a video frame into this run's workspace (sealed with encryption
+/// on) for the app to adopt, or `None` if it can't be written.
+fn save_frame(frame: &RgbImage) -> Option<PathBuf> {
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new(&mut jpeg)
+        .encode_image(frame)
+        .ok()?;
+    let dir = workspace::current().run_dir();
+    std::fs::create_dir_all(dir).ok()?;
+    let id = NEXT_FRAME.fetch_add(1, Ordering::Relaxed);
+    let path = dir.join(format!("probe_frame_onnx_{}.jpg", id));
+    match vault::write(&path, jpeg) {
+        Ok(()) => Some(path),
+        Err(e) => {
+            eprintln!("[ONNX] Saving video frame failed: {e}");
+            None
+        }
+    }
+}
+
+/// Decodes an image file, opening it first if it's a sealed temp image.
+fn open_image(path: &Path) -> TractResult<image::RgbImage> {
+    Ok(image::load_from_memory(&vault::read_input(path)?)?.to_rgb8())
+}
@@ -0,0 +1,109 @@
+//! Frame sampling from video files through GStreamer, which the video page
+//! already plays them with.
+
+use gstreamer as gst;
+use gstreamer::prelude::*;
+use gstreamer_app::AppSink;
+use image::{DynamicImage, RgbImage, RgbaImage};
+use std::io;
+use std::path::Path;
+use tract_onnx::prelude::TractResult;
+use url::Url;
+
+/// How long a frame may take to decode before the pipeline is checked for errors.
+const POLL: gst::ClockTime = gst::ClockTime::from_mseconds(200);
+
+/// Decodes a video and hands out every `every`-th frame as RGB.
+pub struct FrameSampler {
+    pipeline: gst::Pipeline,
+    sink: AppSink,
+    every: usize,
+    decoded: usize,
+}
+
+impl FrameSampler {
+    pub fn open(path: &Path, every: usize) -> TractResult<Self> {
+        gst::init()?;
+        let path = path.canonicalize()?;
+        let uri = Url::from_file_path(&path)
+            .map_err(|_| io::Error::other(format!("not a file path: {}", path.display())))?;
+
+        // RGBA rows are never padded, unlike RGB ones
+        let description = format!(
+            "uridecodebin uri=\"{}\" ! videoconvert ! video/x-raw,format=RGBA ! \
+             appsink name=frames sync=false max-buffers=4",
+            uri
+        );
+        let pipeline = gst::parse::launch(&description)?
+            .downcast::<gst::Pipeline>()
+            .map_err(|_| io::Error::other("not a pipeline"))?;
+        let sink = pipeline
+            .by_name("frames")
+            .and_then(|sink| sink.downcast::<AppSink>().ok())
+            .ok_or_else(|| io::Error::other("no frame sink"))?;
+        pipeline.set_state(gst::State::Playing)?;
+
+        Ok(Self {
+            pipeline,
+            sink,
+            every: every.max(1),
+            decoded: 0,
+        })
+    }
+
+    /// The next sampled frame, or `None` at the end of the video.
+    pub fn next_frame(&mut self) -> TractResult<Option<RgbImage>> {
+        loop {
+            let Some(sample) = self.pull()? else {
+                return Ok(None);
+            };
+            self.decoded += 1;
+            if self.decoded.is_multiple_of(self.every) {
+                return Ok(Some(to_rgb(&sample)?));
+            }
+        }
+    }
+
+    fn pull(&self) -> TractResult<Option<gst::Sample>> {
+        loop {
+            if let Some(sample) = self.sink.try_pull_sample(POLL) {
+                return Ok(Some(sample));
+            }
+            if self.sink.is_eos() {
+                return Ok(None);
+            }
+            // A file nothing can decode fails here instead of blocking forever
+            let bus = self
+                .pipeline
+                .bus()
+                .ok_or_else(|| io::Error::other("no bus"))?;
+            if let Some(message) = bus.pop_filtered(&[gst::MessageType::Error]) {
+                if let gst::MessageView::Error(error) = message.view() {
+                    return Err(io::Error::other(error.error().to_string()).into());
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FrameSampler {
+    fn drop(&mut self) {
+        let _ = self.pipeline.set_state(gst::State::Null);
+    }
+}
+
+fn to_rgb(sample: &gst::Sample) -> TractResult<RgbImage> {
+    let size = sample
+        .caps()
+        .and_then(|caps| caps.structure(0))
+        .and_then(|s| Some((s.get::<i32>("width").ok()?, s.get::<i32>("height").ok()?)))
+        .ok_or_else(|| io::Error::other("frame without a size"))?;
+    let buffer = sample
+        .buffer()
+        .ok_or_else(|| io::Error::other("frame without data"))?;
+    let data = buffer.map_readable()?;
+
+    let frame = RgbaImage::from_raw(size.0 as u32, size.1 as u32, data.as_slice().to_vec())
+        .ok_or_else(|| io::Error::other("frame smaller than its size"))?;
+    Ok(DynamicImage::ImageRgba8(frame).to_rgb8())
+}
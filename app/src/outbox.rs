@@ -0,0 +1,557 @@
+//! Write-ahead queue for enrollments and identifications made while the
+//! database is unreachable.
+//!
+//! Each entry is a folder under `[storage] outbox_dir`, named so folders sort
+//! in the order they were queued, holding `entry.json` and the images that go
+//! with it, all sealed like every other file we keep. `sync` writes them to the
+//! database once it is back, oldest first, and stops at the first failure so
+//! nothing lands out of order. An enrollment's photos stay in its folder until
+//! their embeddings are in the database (`embedding_stored`); any the engine
+//! couldn't embed, or that couldn't be stored, go to the engine again on the
+//! next sync. An entry the database rejects while it is otherwise reachable is
+//! moved to `failed/` instead.
+//!
+//! Every enrollment carries a random key that `enroll` records with the rows
+//! it writes, so an entry whose write committed but was never marked saved
+//! (the app stopped, or the reply got lost) is picked up rather than written
+//! again. `sync` takes any `SyncTarget`, which `CriminalDB` is.
+//!
+//! Offline enrollments skip the duplicate check; the merge page joins any
+//! record that turns out to exist already.
+
+use crate::database::{
+    CriminalDB, CriminalDetails, EnrollmentSummary, NewEnrollment, NewIdentification, NewOffence,
+};
+use crate::error::AppError;
+use crate::vault;
+
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::OsRng;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+/// Where the outbox lives when `outbox_dir` isn't configured.
+pub const DEFAULT_DIR: &str = "outbox";
+
+const ENTRY_FILE: &str = "entry.json";
+const PROBE_FILE: &str = "probe";
+// Entries the database rejected; kept out of the queue so they don't block it
+const FAILED_DIR: &str = "failed";
+
+// Records kept while the database is offline; `DEFAULT_DIR` unless configured
+static OUTBOX: OnceLock<Outbox> = OnceLock::new();
+
+/// A new record filled in on the registry page while offline.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Enrollment {
+    pub name: String,
+    pub fathers_name: Option<String>,
+    pub arrested_location: Option<String>,
+    pub details: CriminalDetails,
+    pub aliases: Vec<String>,
+    pub offences: Vec<NewOffence>,
+    // Selected photos and their face boxes; copied into the entry when queued
+    pub photos: Vec<(PathBuf, Option<[f32; 4]>)>,
+    pub captured_at: DateTime<Utc>,
+}
+
+/// A queued enrollment whose rows are written, waiting on its embeddings.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedEnrollment {
+    pub criminal_id: u32,
+    // photo id, path, face box: what the engine's `add` command takes
+    pub photos: Vec<(u32, String, Option<[f32; 4]>)>,
+}
+
+/// What one pass of `sync` got through.
+#[derive(Debug, Clone, Default)]
+pub struct SyncReport {
+    pub enrolled: Vec<SavedEnrollment>, // photos to hand to the engine
+    pub identifications: usize,
+    pub problems: Vec<AppError>, // parts of an entry that couldn't be saved with it
+    pub failed: Option<String>,  // why the pass stopped early
+    pub remaining: usize,
+}
+
+/// Where `sync` writes entries: the database, or a stand-in in tests.
+pub trait SyncTarget: Sync {
+    /// Writes a new record, its outbox key with it, all or nothing.
+    fn write_enrollment(
+        &self,
+        enrollment: NewEnrollment,
+    ) -> impl Future<Output = Result<EnrollmentSummary, String>> + Send;
+
+    /// What an earlier `write_enrollment` with outbox key `key` committed.
+    fn find_enrollment(
+        &self,
+        key: &str,
+    ) -> impl Future<Output = Result<Option<EnrollmentSummary>, String>> + Send;
+
+    fn write_identification(
+        &self,
+        new: NewIdentification,
+    ) -> impl Future<Output = Result<(), String>> + Send;
+
+    /// Whether it answers at all, to tell an outage from an entry it rejects.
+    fn is_reachable(&self) -> impl Future<Output = bool> + Send;
+}
+
+impl SyncTarget for CriminalDB {
+    async fn write_enrollment(
+        &self,
+        enrollment: NewEnrollment,
+    ) -> Result<EnrollmentSummary, String> {
+        self.enroll(None, enrollment)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    async fn find_enrollment(&self, key: &str) -> Result<Option<EnrollmentSummary>, String> {
+        self.synced_enrollment(key).await.map_err(|e| e.to_string())
+    }
+
+    async fn write_identification(&self, new: NewIdentification) -> Result<(), String> {
+        self.record_identification(new)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    async fn is_reachable(&self) -> bool {
+        self.ping().await.is_ok()
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+enum Entry {
+    Enrollment {
+        // Missing in entries queued before keys were; the folder name stands in
+        #[serde(default)]
+        key: Option<String>,
+        enrollment: Box<Enrollment>,
+        saved: Option<SavedEnrollment>,
+    },
+    Identification {
+        criminal_id: u32,
+        photo_id: Option<u32>,
+        source: String,
+        score: Option<f32>,
+        has_probe: bool, // in `PROBE_FILE`
+        captured_at: DateTime<Utc>,
+    },
+}
+
+pub struct Outbox {
+    dir: PathBuf,
+    next_id: AtomicU64,
+    syncing: AtomicBool,
+    // Records whose photos went to the engine this run, with the photos it
+    // hasn't sent an embedding for yet
+    handed_over: Mutex<HashMap<u32, HashSet<u32>>>,
+}
+
+impl Outbox {
+    /// An outbox in `dir`, taken relative to the current folder now: the
+    /// engine reads the photos from another one.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        let dir = dir.into();
+        Self {
+            dir: std::path::absolute(&dir).unwrap_or(dir),
+            next_id: AtomicU64::new(0),
+            syncing: AtomicBool::new(false),
+            handed_over: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Entries waiting, including enrollments the engine hasn't embedded yet.
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Queues `enrollment` with copies of its photos, which may be temp files.
+    pub fn enqueue_enrollment(&self, mut enrollment: Enrollment) -> io::Result<()> {
+        let entry_dir = self.create_entry()?;
+        let result = (|| {
+            for (i, (path, _)) in enrollment.photos.iter_mut().enumerate() {
+                let name = format!("photo_{}.jpg", i);
+                let bytes = vault::read_input(&*path).map_err(|e| with_path(&*path, e))?;
+                vault::write(entry_dir.join(&name), bytes)?;
+                *path = PathBuf::from(name);
+            }
+            write_entry(
+                &entry_dir,
+                &Entry::Enrollment {
+                    key: Some(new_key()),
+                    enrollment: Box::new(enrollment),
+                    saved: None,
+                },
+            )
+        })();
+        if result.is_err() {
+            let _ = std::fs::remove_dir_all(&entry_dir);
+        }
+        result
+    }
+
+    /// Queues a match that couldn't be recorded, probe image and all.
+    pub fn enqueue_identification(&self, new: &NewIdentification) -> io::Result<()> {
+        let entry_dir = self.create_entry()?;
+        let result = (|| {
+            if let Some(probe) = &new.probe {
+                vault::write(entry_dir.join(PROBE_FILE), probe.clone())?;
+            }
+            write_entry(
+                &entry_dir,
+                &Entry::Identification {
+                    criminal_id: new.criminal_id,
+                    photo_id: new.photo_id,
+                    source: new.source.clone(),
+                    score: new.score,
+                    has_probe: new.probe.is_some(),
+                    captured_at: new.captured_at,
+                },
+            )
+        })();
+        if result.is_err() {
+            let _ = std::fs::remove_dir_all(&entry_dir);
+        }
+        result
+    }
+
+    /// Writes the queue to `db`, oldest first. Only one pass runs at a time;
+    /// a second call while one is running returns an empty report.
+    pub async fn sync(&self, db: &impl SyncTarget) -> SyncReport {
+        let mut report = SyncReport::default();
+        if self.syncing.swap(true, Ordering::SeqCst) {
+            report.remaining = self.len();
+            return report;
+        }
+
+        for entry_dir in self.entries() {
+            let entry = match read_entry(&entry_dir) {
+                Ok(entry) => entry,
+                Err(e) => {
+                    // A damaged entry shouldn't hold up the rest
+                    self.set_aside(&entry_dir, e.to_string(), &mut report);
+                    continue;
+                }
+            };
+
+            let result = match entry {
+                Entry::Enrollment {
+                    key,
+                    enrollment,
+                    saved,
+                } => {
+                    let key = key.unwrap_or_else(|| folder_key(&entry_dir));
+                    self.sync_enrollment(db, &entry_dir, key, enrollment, saved, &mut report)
+                        .await
+                }
+                Entry::Identification {
+                    criminal_id,
+                    photo_id,
+                    source,
+                    score,
+                    has_probe,
+                    captured_at,
+                } => {
+                    let probe = if has_probe {
+                        let path = entry_dir.join(PROBE_FILE);
+                        match vault::read(&path) {
+                            Ok(probe) => Some(probe),
+                            Err(e) => {
+                                report.problems.push(AppError::file(path, e));
+                                None
+                            }
+                        }
+                    } else {
+                        None
+                    };
+                    let new = NewIdentification {
+                        criminal_id,
+                        photo_id,
+                        source,
+                        score,
+                        probe,
+                        captured_at,
+                    };
+                    db.write_identification(new).await.map(|()| {
+                        report.identifications += 1;
+                        remove_entry(&entry_dir, &mut report);
+                    })
+                }
+            };
+
+            if let Err(e) = result {
+                if !db.is_reachable().await {
+                    report.failed = Some(e);
+                    break;
+                }
+                // The database is fine, so it's this entry; don't let it block the rest
+                self.set_aside(&entry_dir, e, &mut report);
+            }
+        }
+
+        report.remaining = self.len();
+        self.syncing.store(false, Ordering::SeqCst);
+        report
+    }
+
+    /// Notes an `embedding` line for a photo handed to the engine.
+    pub fn embedding_received(&self, photo_id: u32) {
+        for waiting in self.handed_over.lock().unwrap().values_mut() {
+            waiting.remove(&photo_id);
+        }
+    }
+
+    /// The engine answered the `add` for `criminal_id`. Returns the photos it
+    /// sent no embedding for, or `None` if the record isn't one of ours. They
+    /// stay in the entry and go to the engine again on the next sync.
+    pub fn engine_answered(&self, criminal_id: u32) -> Option<Vec<u32>> {
+        let waiting = self.handed_over.lock().unwrap().remove(&criminal_id)?;
+        let mut missing: Vec<u32> = waiting.into_iter().collect();
+        missing.sort_unstable();
+        Some(missing)
+    }
+
+    /// Drops a photo of `criminal_id` from its entry now that its embedding
+    /// is in the database, and the entry with its last photo. Returns whether
+    /// the record was one of ours.
+    pub fn embedding_stored(&self, criminal_id: u32, photo_id: u32) -> bool {
+        for entry_dir in self.entries() {
+            let Ok(Entry::Enrollment {
+                key,
+                enrollment,
+                saved: Some(mut saved),
+            }) = read_entry(&entry_dir)
+            else {
+                continue;
+            };
+            if saved.criminal_id != criminal_id {
+                continue;
+            }
+            saved.photos.retain(|(id, _, _)| *id != photo_id);
+            let result = if saved.photos.is_empty() {
+                std::fs::remove_dir_all(&entry_dir)
+            } else {
+                let entry = Entry::Enrollment {
+                    key,
+                    enrollment,
+                    saved: Some(saved),
+                };
+                write_entry(&entry_dir, &entry)
+            };
+            if let Err(e) = result {
+                eprintln!("❌ Could not update {}: {}", entry_dir.display(), e);
+            }
+            return true;
+        }
+        false
+    }
+
+    async fn sync_enrollment(
+        &self,
+        db: &impl SyncTarget,
+        entry_dir: &Path,
+        key: String,
+        enrollment: Box<Enrollment>,
+        saved: Option<SavedEnrollment>,
+        report: &mut SyncReport,
+    ) -> Result<(), String> {
+        if let Some(saved) = saved {
+            // Written on an earlier pass; some photos still have no embedding
+            self.hand_over(saved, report);
+            return Ok(());
+        }
+
+        // Photos that can't be read are left out and reported
+        let (mut photos, mut paths) = (Vec::new(), Vec::new());
+        for (file, face_box) in &enrollment.photos {
+            let path = entry_dir.join(file);
+            match vault::read(&path) {
+                Ok(bytes) => {
+                    photos.push((bytes, *face_box));
+                    paths.push((path.to_string_lossy().to_string(), *face_box));
+                }
+                Err(e) => report.problems.push(AppError::file(&path, e)),
+            }
+        }
+        // Written before, but the entry wasn't marked saved
+        let summary = match db.find_enrollment(&key).await? {
+            Some(summary) => summary,
+            None => {
+                let new = NewEnrollment {
+                    name: enrollment.name.clone(),
+                    fathers_name: enrollment.fathers_name.clone(),
+                    arrested_location: enrollment.arrested_location.clone(),
+                    details: enrollment.details.clone(),
+                    aliases: enrollment.aliases.clone(),
+                    offences: enrollment.offences.clone(),
+                    photos,
+                    audit: None,
+                    outbox_entry: Some(key.clone()),
+                };
+                db.write_enrollment(new).await?
+            }
+        };
+        let criminal_id = summary.criminal_id;
+        let photos: Vec<_> = summary
+            .photo_ids
+            .into_iter()
+            .zip(paths)
+            .map(|(photo_id, (path, face_box))| (photo_id, path, face_box))
+            .collect();
+
+        if photos.is_empty() {
+            remove_entry(entry_dir, report);
+            return Ok(());
+        }
+
+        // Keep the photos for the engine, and don't write the record twice
+        let saved = SavedEnrollment {
+            criminal_id,
+            photos,
+        };
+        let entry = Entry::Enrollment {
+            key: Some(key),
+            enrollment,
+            saved: Some(saved.clone()),
+        };
+        if let Err(e) = write_entry(entry_dir, &entry) {
+            report
+                .problems
+                .push(AppError::file(entry_dir.join(ENTRY_FILE), e));
+        }
+        self.hand_over(saved, report);
+        Ok(())
+    }
+
+    /// Passes a saved enrollment's photos to the engine, unless they already
+    /// went this run and it hasn't answered yet.
+    fn hand_over(&self, mut saved: SavedEnrollment, report: &mut SyncReport) {
+        let mut handed_over = self.handed_over.lock().unwrap();
+        if handed_over.contains_key(&saved.criminal_id) {
+            return;
+        }
+        let photo_ids = saved.photos.iter().map(|(id, _, _)| *id).collect();
+        handed_over.insert(saved.criminal_id, photo_ids);
+        // Entries saved while the outbox folder was still taken as relative
+        for (_, path, _) in &mut saved.photos {
+            if let Ok(absolute) = std::path::absolute(&*path) {
+                *path = absolute.to_string_lossy().to_string();
+            }
+        }
+        report.enrolled.push(saved);
+    }
+
+    /// Moves an entry the database won't take into `failed/`, for someone to look at.
+    fn set_aside(&self, entry_dir: &Path, error: String, report: &mut SyncReport) {
+        let failed_dir = self.dir.join(FAILED_DIR);
+        let target = failed_dir.join(entry_dir.file_name().unwrap_or_default());
+        let moved =
+            std::fs::create_dir_all(&failed_dir).and_then(|_| std::fs::rename(entry_dir, &target));
+        let kept = match moved {
+            Ok(()) => format!("moved to {}", target.display()),
+            Err(e) => format!("left in place: {}", e),
+        };
+        report.problems.push(AppError::Database(format!(
+            "Could not sync {}: {}; {}",
+            entry_dir.display(),
+            error,
+            kept
+        )));
+    }
+
+    /// Folders holding a complete entry, oldest first.
+    fn entries(&self) -> Vec<PathBuf> {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return Vec::new();
+        };
+        let mut entries: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.join(ENTRY_FILE).is_file())
+            .collect();
+        entries.sort();
+        entries
+    }
+
+    fn create_entry(&self) -> io::Result<PathBuf> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let name = format!(
+            "{:020}-{}-{}",
+            Utc::now().timestamp_micros(),
+            std::process::id(),
+            id
+        );
+        let entry_dir = self.dir.join(name);
+        std::fs::create_dir_all(&entry_dir)?;
+        Ok(entry_dir)
+    }
+}
+
+/// Keeps offline records in `dir` rather than `DEFAULT_DIR`. The first
+/// `current` settles the folder, so this returns false after it.
+pub fn install(dir: impl Into<PathBuf>) -> bool {
+    OUTBOX.set(Outbox::new(dir)).is_ok()
+}
+
+/// The outbox records queue in while the database is offline.
+pub fn current() -> &'static Outbox {
+    OUTBOX.get_or_init(|| Outbox::new(DEFAULT_DIR))
+}
+
+fn read_entry(entry_dir: &Path) -> io::Result<Entry> {
+    let bytes = vault::read(entry_dir.join(ENTRY_FILE))?;
+    serde_json::from_slice(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Writes `entry.json` through a temp file, so a crash leaves the old one or
+/// none rather than half of it.
+fn write_entry(entry_dir: &Path, entry: &Entry) -> io::Result<()> {
+    let json = serde_json::to_vec(entry).map_err(io::Error::other)?;
+    let temp = entry_dir.join(format!("{}.tmp", ENTRY_FILE));
+    vault::write(&temp, json)?;
+    std::fs::rename(temp, entry_dir.join(ENTRY_FILE))
+}
+
+/// A random key for a new entry, unique across machines sharing a database.
+fn new_key() -> String {
+    let mut bytes = [0; 16];
+    OsRng.fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// The key of an entry queued without one.
+fn folder_key(entry_dir: &Path) -> String {
+    entry_dir
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string()
+}
+
+fn remove_entry(entry_dir: &Path, report: &mut SyncReport) {
+    if let Err(e) = std::fs::remove_dir_all(entry_dir) {
+        report.problems.push(AppError::file(entry_dir, e));
+    }
+}
+
+fn with_path(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+}
@@ -0,0 +1,394 @@
+use crate::components::{GlassButton, GlassInputLabel, GlassTextInput};
+use crate::database::{CriminalDB, PurgeSummary};
+use crate::entities::criminal;
+use crate::{CriminalSummary, Message, Page};
+
+use iced::widget::{button, column, container, image, row, scrollable, space, text};
+use iced::{Alignment, Background, Border, Color, Element, Length, Task, Theme};
+use std::sync::Arc;
+
+const RESULT_LIMIT: u64 = 200;
+
+#[derive(Default)]
+pub struct BrowserPage {
+    pub query: String,
+    pub results: Vec<CriminalSummary>,
+    pub selected: Option<u32>,
+    pub selected_photos: Vec<image::Handle>, // thumbnails
+    pub is_searching: bool,
+    pub error: Option<String>,
+    pub purge_reason: String,
+    pub purge_armed: bool, // asked once, waiting for the second confirmation
+    pub is_purging: bool,
+    pub purged: Option<(u32, PurgeSummary)>,
+}
+
+impl BrowserPage {
+    pub fn update(&mut self, message: Message, db: Option<Arc<CriminalDB>>) -> Task<Message> {
+        match message {
+            Message::BrowserQueryChanged(value) => {
+                self.query = value;
+                return self.search(db);
+            }
+
+            Message::SearchRecords => return self.search(db),
+
+            Message::RecordsFound(query, result) => {
+                // Typing fires a search per keystroke; keep only the latest
+                if query != self.query {
+                    return Task::none();
+                }
+                self.is_searching = false;
+                match result {
+                    Ok(results) => {
+                        self.error = None;
+                        self.results = results;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+
+            Message::SelectRecord(criminal_id) => {
+                self.selected = Some(criminal_id);
+                self.selected_photos = Vec::new();
+                self.purge_reason = String::new();
+                self.purge_armed = false;
+
+                let Some(db) = db else {
+                    return Task::none();
+                };
+                return Task::perform(
+                    async move {
+                        let thumbnails = db
+                            .get_photo_thumbnails(criminal_id)
+                            .await
+                            .map_err(|e| e.to_string())?;
+                        Ok(thumbnails
+                            .into_iter()
+                            .map(|(_, jpeg)| image::Handle::from_bytes(jpeg))
+                            .collect())
+                    },
+                    move |result| Message::RecordPhotosLoaded(criminal_id, result),
+                );
+            }
+
+            Message::RecordPhotosLoaded(criminal_id, result) => {
+                if self.selected != Some(criminal_id) {
+                    return Task::none();
+                }
+                match result {
+                    Ok(photos) => self.selected_photos = photos,
+                    Err(e) => self.error = Some(e),
+                }
+            }
+
+            Message::PurgeReasonChanged(value) => self.purge_reason = value,
+
+            Message::RequestPurge => {
+                if self.purge_reason.trim().is_empty() {
+                    self.error = Some("Give the reason for the purge first".to_string());
+                } else {
+                    self.error = None;
+                    self.purge_armed = true;
+                }
+            }
+
+            Message::CancelPurge => self.purge_armed = false,
+
+            Message::ConfirmPurge => {
+                let (Some(criminal_id), Some(db)) = (self.selected, db) else {
+                    return Task::none();
+                };
+                if !self.purge_armed || self.is_purging {
+                    return Task::none();
+                }
+                let reason = self.purge_reason.clone();
+                self.is_purging = true;
+                self.error = None;
+
+                return Task::perform(
+                    async move {
+                        db.purge_criminal(criminal_id, reason)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    move |result| Message::RecordPurged(criminal_id, result),
+                );
+            }
+
+            Message::RecordPurged(criminal_id, result) => {
+                self.is_purging = false;
+                self.purge_armed = false;
+                match result {
+                    Ok(summary) => {
+                        self.purged = Some((criminal_id, summary));
+                        self.results.retain(|(c, _)| c.criminal_id != criminal_id);
+                        if self.selected == Some(criminal_id) {
+                            self.selected = None;
+                            self.selected_photos = Vec::new();
+                        }
+                        self.purge_reason = String::new();
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    fn search(&mut self, db: Option<Arc<CriminalDB>>) -> Task<Message> {
+        let Some(db) = db else {
+            self.error = Some("Database not connected".to_string());
+            return Task::none();
+        };
+
+        self.is_searching = true;
+        let query = self.query.clone();
+        Task::perform(
+            async move {
+                let result = db
+                    .search_criminals(&query, RESULT_LIMIT)
+                    .await
+                    .map_err(|e| e.to_string());
+                (query, result)
+            },
+            |(query, result)| Message::RecordsFound(query, result),
+        )
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let header = row![
+            GlassButton::new("← Back").on_press(Message::GoTo(Page::MainMenu)),
+            space().width(20.0),
+            GlassInputLabel::new("Record Browser").size(32),
+        ]
+        .align_y(Alignment::Center);
+
+        let search = GlassTextInput::new(
+            "Search name, alias, ID number, marks, notes or #id",
+            &self.query,
+        )
+        .on_input(Message::BrowserQueryChanged)
+        .on_submit(Message::SearchRecords);
+
+        let status: Element<Message> = if let Some(e) = &self.error {
+            text(e)
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.4, 0.4))
+                .into()
+        } else if let Some((criminal_id, summary)) = &self.purged {
+            text(format!(
+                "Purged #{}: {} photos, {} embeddings, {} sightings, {} identifications, {} alerts, {} offences and {} aliases",
+                criminal_id,
+                summary.photos,
+                summary.embeddings,
+                summary.sightings,
+                summary.identifications,
+                summary.alerts,
+                summary.offences,
+                summary.aliases
+            ))
+            .size(12)
+            .color(Color::from_rgb(0.4, 0.9, 0.5))
+            .into()
+        } else {
+            text(if self.is_searching {
+                "Searching...".to_string()
+            } else {
+                format!("{} records", self.results.len())
+            })
+            .size(12)
+            .color(Color::from_rgba(1.0, 1.0, 1.0, 0.4))
+            .into()
+        };
+
+        let list = scrollable(
+            column(
+                self.results
+                    .iter()
+                    .map(|(data, aliases)| self.result_row(data, aliases)),
+            )
+            .spacing(6),
+        )
+        .height(Length::Fill);
+
+        let left = column![
+            search,
+            space().height(8.0),
+            status,
+            space().height(8.0),
+            list
+        ]
+        .width(Length::FillPortion(45));
+
+        let right = container(
+            match self
+                .selected
+                .and_then(|id| self.results.iter().find(|(c, _)| c.criminal_id == id))
+            {
+                Some((data, aliases)) => self.details_view(data, aliases),
+                None => {
+                    container(text("Select a record").color(Color::from_rgba(1.0, 1.0, 1.0, 0.3)))
+                        .center_x(Length::Fill)
+                        .center_y(Length::Fill)
+                        .into()
+                }
+            },
+        )
+        .width(Length::FillPortion(55))
+        .height(Length::Fill)
+        .padding(20);
+
+        column![header, space().height(20.0), row![left, right].spacing(20)]
+            .padding(30)
+            .into()
+    }
+
+    fn result_row<'a>(
+        &self,
+        data: &'a criminal::Model,
+        aliases: &'a [String],
+    ) -> Element<'a, Message> {
+        let selected = self.selected == Some(data.criminal_id);
+        let subtitle = if aliases.is_empty() {
+            format!("{} violations", data.no_of_crimes)
+        } else {
+            format!(
+                "aka {} · {} violations",
+                aliases.join(", "),
+                data.no_of_crimes
+            )
+        };
+
+        button(
+            column![
+                text(format!("#{}  {}", data.criminal_id, data.name))
+                    .size(16)
+                    .color(Color::WHITE),
+                text(subtitle)
+                    .size(12)
+                    .color(Color::from_rgba(1.0, 1.0, 1.0, 0.5)),
+            ]
+            .spacing(2),
+        )
+        .width(Length::Fill)
+        .padding([8, 12])
+        .on_press(Message::SelectRecord(data.criminal_id))
+        .style(move |_theme: &Theme, status: button::Status| {
+            let highlight = selected || matches!(status, button::Status::Hovered);
+            button::Style {
+                background: Some(Background::Color(if highlight {
+                    Color::from_rgba(0.4, 0.9, 0.5, 0.15)
+                } else {
+                    Color::from_rgba(0.05, 0.05, 0.05, 0.3)
+                })),
+                border: Border {
+                    color: Color::from_rgba(0.4, 0.9, 0.5, if selected { 0.5 } else { 0.1 }),
+                    width: 1.0,
+                    radius: 8.0.into(),
+                },
+                ..Default::default()
+            }
+        })
+        .into()
+    }
+
+    fn details_view<'a>(
+        &'a self,
+        data: &'a criminal::Model,
+        aliases: &'a [String],
+    ) -> Element<'a, Message> {
+        let photos = row(self
+            .selected_photos
+            .iter()
+            .map(|photo| image(photo.clone()).width(140.0).height(140.0).into()))
+        .spacing(8);
+
+        let mut fields = vec![
+            ("CRIMINAL ID", data.criminal_id.to_string()),
+            ("NAME", data.name.clone()),
+            (
+                "FATHER'S NAME",
+                data.fathers_name
+                    .clone()
+                    .unwrap_or_else(|| "N/A".to_string()),
+            ),
+            ("VIOLATIONS", data.no_of_crimes.to_string()),
+            (
+                "LAST ARRESTED LOCATION",
+                data.arrested_location
+                    .clone()
+                    .unwrap_or_else(|| "N/A".to_string()),
+            ),
+        ];
+        if !aliases.is_empty() {
+            fields.push(("ALIASES", aliases.join(", ")));
+        }
+        fields.extend(data.attribute_fields());
+
+        scrollable(column![
+            scrollable(photos).direction(scrollable::Direction::Horizontal(
+                scrollable::Scrollbar::default()
+            )),
+            space().height(20.0),
+            column(fields.into_iter().map(|(label, value)| {
+                column![
+                    text(label)
+                        .size(11)
+                        .color(Color::from_rgba(1.0, 1.0, 1.0, 0.4)),
+                    space().height(4.0),
+                    text(value).size(16).color(Color::WHITE),
+                ]
+                .into()
+            }))
+            .spacing(14),
+            space().height(30.0),
+            self.purge_view(data),
+        ])
+        .into()
+    }
+
+    fn purge_view<'a>(&'a self, data: &'a criminal::Model) -> Element<'a, Message> {
+        let action: Element<Message> = if self.is_purging {
+            GlassButton::new("Purging...")
+                .on_press(Message::None)
+                .into()
+        } else if self.purge_armed {
+            column![
+                text(format!(
+                    "Every photo, embedding, sighting, identification, alert and offence of #{} {} will be deleted for good. Audit entries about them lose their details.",
+                    data.criminal_id, data.name
+                ))
+                .size(14)
+                .color(Color::from_rgb(0.9, 0.4, 0.4)),
+                space().height(8.0),
+                row![
+                    GlassButton::new("Purge Permanently").on_press(Message::ConfirmPurge),
+                    GlassButton::new("Cancel").on_press(Message::CancelPurge),
+                ]
+                .spacing(10),
+            ]
+            .into()
+        } else {
+            GlassButton::new("Purge Record...")
+                .on_press(Message::RequestPurge)
+                .into()
+        };
+
+        column![
+            text("PURGE")
+                .size(11)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.4)),
+            space().height(8.0),
+            GlassTextInput::new(
+                "Legal basis for removing this person (required)",
+                &self.purge_reason
+            )
+            .on_input(Message::PurgeReasonChanged),
+            space().height(8.0),
+            action,
+        ]
+        .into()
+    }
+}
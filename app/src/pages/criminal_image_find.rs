@@ -1,79 +1,595 @@
-use crate::components::{GlassButton, GlassImageViewer, GlassInputLabel};
-use crate::database::CriminalDB;
-use crate::entities::criminal;
+use super::review_queue::verdict_panel;
+use crate::components::{
+    file_handle, FaceComparison, GlassButton, GlassImageViewer, GlassInputLabel,
+};
+use crate::database::{CriminalDB, NewIdentification, OffenceEntry};
+use crate::entities::{criminal, identification};
+use crate::error::AppError;
+use crate::face_index::{SearchHit, MATCH_THRESHOLD};
+use crate::identify::{Source, NOT_FOUND};
+use crate::outbox;
+use crate::preprocess::{prepare_image, PreparedImage};
+use crate::vault;
 use crate::{Message, Page};
 
-use iced::widget::{column, container, row, space, text};
-use iced::{Alignment, Color, Element, Length, Task};
+use iced::widget::{button, checkbox, column, container, image, row, scrollable, space, text};
+use iced::{Alignment, Background, Border, Color, Element, Length, Task, Theme};
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 pub struct ImageFindPage {
-    pub selected_image: Vec<String>,
+    pub selected_image: Vec<PreparedImage>,
+    pub selected_handles: Vec<image::Handle>, // selected_image read once for the viewer
     pub is_identifying: bool,
     pub show_details: bool,
     pub identified_data: Option<criminal::Model>,
-    pub not_found: bool, // New field to track search failure
+    pub identified_aliases: Vec<String>,
+    pub identified_offences: Vec<OffenceEntry>,
+    pub not_found: bool,            // New field to track search failure
+    pub load_error: Option<String>, // probe rejected before reaching the engine
+    pub batch: Option<ImageBatch>,  // set while several probes are being worked through
+    next_batch_id: u32,
+    pub comparison: Option<FaceComparison>, // probe next to the matched photos
+    pub pending_review: Option<identification::Model>,
+    pub review_comment: String,
+    pub match_threshold: f32, // from the [matching] config
+}
+
+/// Column the batch results table is sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BatchColumn {
+    File,
+    Criminal,
+    Score,
+    Status,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchStatus {
+    Queued,
+    Identifying,
+    Matched,
+    NoMatch,
+    NoFace,
+    Failed(String),
+    Skipped, // batch was stopped before reaching it
+}
+
+impl BatchStatus {
+    fn label(&self) -> &'static str {
+        match self {
+            BatchStatus::Queued => "Queued",
+            BatchStatus::Identifying => "Identifying",
+            BatchStatus::Matched => "Match (pending review)",
+            BatchStatus::NoMatch => "No match",
+            BatchStatus::NoFace => "No face",
+            BatchStatus::Failed(_) => "Error",
+            BatchStatus::Skipped => "Skipped",
+        }
+    }
+
+    fn color(&self) -> Color {
+        match self {
+            BatchStatus::Matched => Color::from_rgb(0.4, 0.9, 0.5),
+            BatchStatus::Failed(_) => Color::from_rgb(0.9, 0.4, 0.4),
+            BatchStatus::Identifying => Color::from_rgb(0.9, 0.8, 0.4),
+            _ => Color::from_rgba(1.0, 1.0, 1.0, 0.5),
+        }
+    }
+
+    // Matches first when sorting by status
+    fn rank(&self) -> u8 {
+        match self {
+            BatchStatus::Matched => 0,
+            BatchStatus::NoMatch => 1,
+            BatchStatus::NoFace => 2,
+            BatchStatus::Failed(_) => 3,
+            BatchStatus::Identifying => 4,
+            BatchStatus::Queued => 5,
+            BatchStatus::Skipped => 6,
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        matches!(self, BatchStatus::Queued | BatchStatus::Identifying)
+    }
+}
+
+/// One probe image in a batch.
+#[derive(Debug, Clone)]
+pub struct BatchItem {
+    pub file: PathBuf,
+    pub prepared: Option<PreparedImage>, // what the engine reads, until it's been probed
+    pub thumbnail: Option<image::Handle>,
+    pub status: BatchStatus,
+    pub criminal_id: Option<u32>,
+    pub name: Option<String>,
+    pub score: Option<f32>, // best gallery score, even below the match threshold
+}
+
+/// A folder's worth of probes, sent to the engine one at a time.
+#[derive(Debug, Clone)]
+pub struct ImageBatch {
+    pub id: u32,
+    pub items: Vec<BatchItem>,
+    next: usize,
+    pub stopped: bool,
+    pub sort: BatchColumn,
+    pub ascending: bool,
+    pub matches_only: bool,
+    pub export: Option<Result<PathBuf, String>>,
+}
+
+impl ImageBatch {
+    fn new(id: u32, files: Vec<PathBuf>) -> Self {
+        Self {
+            id,
+            items: files
+                .into_iter()
+                .map(|file| BatchItem {
+                    file,
+                    prepared: None,
+                    thumbnail: None,
+                    status: BatchStatus::Queued,
+                    criminal_id: None,
+                    name: None,
+                    score: None,
+                })
+                .collect(),
+            next: 0,
+            stopped: false,
+            sort: BatchColumn::File,
+            ascending: true,
+            matches_only: false,
+            export: None,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.items.iter().any(|item| item.status.is_pending()) && !self.stopped
+    }
+
+    pub fn processed(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| !item.status.is_pending())
+            .count()
+    }
+
+    pub fn matches(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.status == BatchStatus::Matched)
+            .count()
+    }
+
+    /// Indices of the rows to show, filtered and in sort order.
+    pub fn visible_rows(&self) -> Vec<usize> {
+        let mut rows: Vec<usize> = (0..self.items.len())
+            .filter(|&i| !self.matches_only || self.items[i].status == BatchStatus::Matched)
+            .collect();
+        rows.sort_by(|&a, &b| {
+            let (a, b) = (&self.items[a], &self.items[b]);
+            let order = match self.sort {
+                BatchColumn::File => file_name(&a.file).cmp(&file_name(&b.file)),
+                // Unmatched rows go last either way
+                BatchColumn::Criminal => match (&a.name, &b.name) {
+                    (Some(x), Some(y)) => x.to_lowercase().cmp(&y.to_lowercase()),
+                    (Some(_), None) => return Ordering::Less,
+                    (None, Some(_)) => return Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+                BatchColumn::Score => match (a.score, b.score) {
+                    (Some(x), Some(y)) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+                    (Some(_), None) => return Ordering::Less,
+                    (None, Some(_)) => return Ordering::Greater,
+                    (None, None) => Ordering::Equal,
+                },
+                BatchColumn::Status => a.status.rank().cmp(&b.status.rank()),
+            };
+            if self.ascending {
+                order
+            } else {
+                order.reverse()
+            }
+        });
+        rows
+    }
+
+    /// The visible rows as CSV, header first.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("file,criminal_id,name,score,status,detail\n");
+        for i in self.visible_rows() {
+            let item = &self.items[i];
+            let detail = match &item.status {
+                BatchStatus::Failed(e) => e.as_str(),
+                _ => "",
+            };
+            let fields = [
+                item.file.to_string_lossy().to_string(),
+                item.criminal_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default(),
+                item.name.clone().unwrap_or_default(),
+                item.score.map(|s| format!("{:.4}", s)).unwrap_or_default(),
+                item.status.label().to_string(),
+                detail.to_string(),
+            ];
+            let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+            csv.push_str(&row.join(","));
+            csv.push('\n');
+        }
+        csv
+    }
+}
+
+/// `BatchProbed` error for a probe the engine found no face in.
+pub const NO_FACE: &str = "no face detected";
+
+/// Engine request tag for batch `id`, item `index`.
+pub fn batch_tag(id: u32, index: usize) -> String {
+    format!("batch-{}-{}", id, index)
+}
+
+fn parse_batch_tag(tag: &str) -> Option<(u32, usize)> {
+    let (id, index) = tag.strip_prefix("batch-")?.split_once('-')?;
+    Some((id.parse().ok()?, index.parse().ok()?))
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+/// Files the match for review and fills in the candidate's name.
+fn record_batch_match(
+    db: Option<Arc<CriminalDB>>,
+    id: u32,
+    index: usize,
+    hit: SearchHit,
+    probe: Option<PreparedImage>,
+) -> Task<Message> {
+    // Captured now, however long it waits in the outbox
+    let new = move |probe| NewIdentification {
+        criminal_id: hit.criminal_id,
+        photo_id: Some(hit.photo_id),
+        source: "image".to_string(),
+        score: Some(hit.score),
+        probe,
+        captured_at: chrono::Utc::now(),
+    };
+    let Some(db) = db else {
+        return Task::perform(
+            async move {
+                let new = new(read_probe(probe).await);
+                outbox::current()
+                    .enqueue_identification(&new)
+                    .map_err(|e| e.to_string())
+            },
+            Message::IdentificationQueued,
+        );
+    };
+
+    Task::perform(
+        async move {
+            db.record_identification(new(read_probe(probe).await))
+                .await?;
+            db.get_criminal(hit.criminal_id).await
+        },
+        move |result| match result {
+            Ok(model) => Message::BatchRecordLoaded(id, index, model.map(|m| m.name)),
+            Err(e) => Message::ShowError(
+                AppError::Database(format!("Could not record the identification: {}", e)),
+                None,
+            ),
+        },
+    )
+}
+
+async fn read_probe(probe: Option<PreparedImage>) -> Option<Vec<u8>> {
+    let image = probe?;
+    tokio::fs::read(image.path())
+        .await
+        .and_then(vault::open_input)
+        .ok()
+}
+
+pub(super) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
 }
 
 impl Default for ImageFindPage {
     fn default() -> Self {
         Self {
             selected_image: Vec::new(),
+            selected_handles: Vec::new(),
             is_identifying: false,
             show_details: false,
             identified_data: None,
+            identified_aliases: Vec::new(),
+            identified_offences: Vec::new(),
             not_found: false,
+            load_error: None,
+            batch: None,
+            next_batch_id: 0,
+            comparison: None,
+            pending_review: None,
+            review_comment: String::new(),
+            match_threshold: MATCH_THRESHOLD,
         }
     }
 }
 
 impl ImageFindPage {
+    pub fn new(match_threshold: f32) -> Self {
+        Self {
+            match_threshold,
+            ..Self::default()
+        }
+    }
+
     pub fn update(&mut self, message: Message, db: Option<Arc<CriminalDB>>) -> Task<Message> {
         match message {
-            Message::FilesSelected(paths) => {
-                let first_path = paths
-                    .get(0)
-                    .map(|p| p.to_string_lossy().to_string())
-                    .unwrap_or_default();
+            Message::FilesSelected(paths) if paths.len() > 1 => {
+                self.selected_image = Vec::new();
+                self.selected_handles = Vec::new();
+                self.show_details = false;
+                self.identified_data = None;
+                self.not_found = false;
+                self.load_error = None;
+                self.is_identifying = false;
 
-                self.selected_image = paths
-                    .into_iter()
-                    .map(|p| p.to_string_lossy().to_string())
-                    .collect();
+                self.comparison = None;
+                self.pending_review = None;
+                self.next_batch_id += 1;
+                self.batch = Some(ImageBatch::new(self.next_batch_id, paths));
+                return self.advance_batch();
+            }
 
+            Message::FilesSelected(paths) => {
+                let Some(first_path) = paths.into_iter().next() else {
+                    return Task::none();
+                };
+
+                self.batch = None;
+                self.comparison = None;
+                self.pending_review = None;
+                self.review_comment.clear();
+                self.selected_image = Vec::new();
+                self.selected_handles = Vec::new();
                 self.show_details = false;
                 self.identified_data = None;
                 self.not_found = false;
+                self.load_error = None;
                 self.is_identifying = true; // Start "Identifying..." status
 
-                return Task::done(Message::IdentifyCriminalImage(first_path));
+                return Task::perform(
+                    async move {
+                        let result = tokio::task::spawn_blocking(move || {
+                            prepare_image(&first_path).map_err(|e| e.to_string())
+                        })
+                        .await
+                        .unwrap_or_else(|e| Err(e.to_string()));
+                        vec![result]
+                    },
+                    Message::ImagesPrepared,
+                );
             }
 
-            Message::Identity(criminal_id_str) => {
-                let id = criminal_id_str.parse::<u32>().unwrap_or(0);
-
-                if let Some(database) = db {
-                    return Task::perform(
-                        async move { database.get_criminal(id).await },
-                        |result| match result {
-                            Ok(Some(model)) => Message::IdentityDataLoaded(model),
-                            _ => Message::IdentityError("Not Found".to_string()),
-                        },
-                    );
+            Message::ImagesPrepared(results) => match results.into_iter().next() {
+                Some(Ok(image)) => {
+                    let path = image.to_string();
+                    self.selected_handles = vec![file_handle(image.path())];
+                    self.selected_image = vec![image];
+                    return Task::done(Message::Identify(Source::Image, path));
                 }
-            }
+                Some(Err(e)) => {
+                    self.is_identifying = false;
+                    self.load_error = Some(e);
+                }
+                None => self.is_identifying = false,
+            },
 
-            Message::IdentityDataLoaded(model) => {
+            Message::Identified(_, Ok(record)) => {
                 self.is_identifying = false;
                 self.show_details = true;
                 self.not_found = false;
-                self.identified_data = Some(model);
+                let criminal_id = record.criminal.criminal_id;
+                self.identified_data = Some(record.criminal);
+                self.identified_aliases = record.aliases;
+                self.identified_offences = record.offences;
+
+                if let Some(probe) = self.selected_image.first() {
+                    let (comparison, task) = FaceComparison::start(
+                        probe.to_string(),
+                        criminal_id,
+                        self.match_threshold,
+                        db,
+                    );
+                    self.comparison = Some(comparison);
+                    return task;
+                }
             }
 
-            Message::IdentityError(_) => {
+            // Batch matches are reviewed from the queue
+            Message::IdentificationRecorded(Ok(pending)) if self.batch.is_none() => {
+                self.pending_review = Some(pending);
+                self.review_comment.clear();
+            }
+
+            Message::IdentificationReviewed(Ok(reviewed))
+                if self
+                    .pending_review
+                    .as_ref()
+                    .is_some_and(|p| p.identification_id == reviewed.identification_id) =>
+            {
+                self.pending_review = Some(reviewed);
+            }
+
+            Message::ReviewCommentChanged(id, comment)
+                if self
+                    .pending_review
+                    .as_ref()
+                    .is_some_and(|p| p.identification_id == id) =>
+            {
+                self.review_comment = comment;
+            }
+
+            Message::FacesDetected(..)
+            | Message::ComparisonProbeReady(..)
+            | Message::ComparisonScores(..)
+            | Message::ComparisonPhotosLoaded(..)
+            | Message::SelectComparisonPhoto(_)
+            | Message::ComparisonZoomChanged(_) => {
+                if let Some(comparison) = self.comparison.as_mut() {
+                    return comparison.update(message);
+                }
+            }
+
+            Message::Identified(_, Err(reason)) => {
                 self.is_identifying = false;
                 self.show_details = false;
-                self.not_found = true; // Trigger the "NOT FOUND" view
+                self.comparison = None;
+                // Anything else was a failure, reported as a toast
+                self.not_found = reason == NOT_FOUND || reason == NO_FACE;
+            }
+
+            Message::BatchItemPrepared(id, index, result) => {
+                let Some(batch) = self.batch.as_mut().filter(|b| b.id == id) else {
+                    return Task::none();
+                };
+                match result {
+                    Ok(image) => {
+                        batch.items[index].thumbnail = Some(file_handle(image.path()));
+                        let path = image.to_string();
+                        batch.items[index].prepared = Some(image);
+                        return Task::done(Message::ProbeBatchImage(batch_tag(id, index), path));
+                    }
+                    Err(e) => {
+                        batch.items[index].status = BatchStatus::Failed(e);
+                        return self.advance_batch();
+                    }
+                }
+            }
+
+            Message::BatchProbed(tag, result) => {
+                let Some((id, index)) = parse_batch_tag(&tag) else {
+                    return Task::none();
+                };
+                let Some(batch) = self.batch.as_mut().filter(|b| b.id == id) else {
+                    return Task::none();
+                };
+                let item = &mut batch.items[index];
+
+                let matched = match result {
+                    Ok(Some(hit)) => {
+                        item.score = Some(hit.score);
+                        if hit.matches(self.match_threshold) {
+                            item.status = BatchStatus::Matched;
+                            item.criminal_id = Some(hit.criminal_id);
+                            Some(hit)
+                        } else {
+                            item.status = BatchStatus::NoMatch;
+                            None
+                        }
+                    }
+                    Ok(None) => {
+                        item.status = BatchStatus::NoMatch;
+                        None
+                    }
+                    Err(e) if e == NO_FACE => {
+                        item.status = BatchStatus::NoFace;
+                        None
+                    }
+                    Err(e) => {
+                        item.status = BatchStatus::Failed(e);
+                        None
+                    }
+                };
+
+                // The engine is done with it; a match keeps it until it's recorded
+                let probe = item.prepared.take();
+                let next = self.advance_batch();
+                let Some(hit) = matched else {
+                    return next;
+                };
+                return Task::batch(vec![next, record_batch_match(db, id, index, hit, probe)]);
+            }
+
+            Message::BatchRecordLoaded(id, index, name) => {
+                if let Some(batch) = self.batch.as_mut().filter(|b| b.id == id) {
+                    batch.items[index].name = name;
+                }
+            }
+
+            Message::SortBatch(column) => {
+                if let Some(batch) = self.batch.as_mut() {
+                    if batch.sort == column {
+                        batch.ascending = !batch.ascending;
+                    } else {
+                        batch.sort = column;
+                        // Best scores first is what anyone sorting by score wants
+                        batch.ascending = column != BatchColumn::Score;
+                    }
+                }
+            }
+
+            Message::BatchMatchesOnly(only) => {
+                if let Some(batch) = self.batch.as_mut() {
+                    batch.matches_only = only;
+                }
+            }
+
+            Message::StopBatch => {
+                if let Some(batch) = self.batch.as_mut() {
+                    batch.stopped = true;
+                    // The one with the engine finishes; the rest never start
+                    for item in &mut batch.items {
+                        if item.status == BatchStatus::Queued {
+                            item.status = BatchStatus::Skipped;
+                        }
+                    }
+                }
+            }
+
+            Message::ExportBatchCsv => {
+                let Some(batch) = &self.batch else {
+                    return Task::none();
+                };
+                let csv = batch.to_csv();
+
+                return Task::perform(
+                    async move {
+                        let handle = rfd::AsyncFileDialog::new()
+                            .add_filter("CSV", &["csv"])
+                            .set_file_name("batch-results.csv")
+                            .save_file()
+                            .await?;
+                        let path = handle.path().to_path_buf();
+                        Some(
+                            tokio::fs::write(&path, csv)
+                                .await
+                                .map(|_| path)
+                                .map_err(|e| e.to_string()),
+                        )
+                    },
+                    |result| match result {
+                        Some(result) => Message::BatchExported(result),
+                        None => Message::None, // dialog cancelled
+                    },
+                );
+            }
+
+            Message::BatchExported(result) => {
+                if let Some(batch) = self.batch.as_mut() {
+                    batch.export = Some(result);
+                }
             }
 
             _ => {}
@@ -81,18 +597,50 @@ impl ImageFindPage {
         Task::none()
     }
 
-    pub fn view(&self) -> Element<'static, Message> {
+    /// Starts preparing the next queued probe, if any.
+    fn advance_batch(&mut self) -> Task<Message> {
+        let Some(batch) = self.batch.as_mut() else {
+            return Task::none();
+        };
+        if batch.stopped || batch.next >= batch.items.len() {
+            return Task::none();
+        }
+
+        let (id, index) = (batch.id, batch.next);
+        batch.next += 1;
+        batch.items[index].status = BatchStatus::Identifying;
+        let file = batch.items[index].file.clone();
+
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || prepare_image(&file).map_err(|e| e.to_string()))
+                    .await
+                    .unwrap_or_else(|e| Err(e.to_string()))
+            },
+            move |result| Message::BatchItemPrepared(id, index, result),
+        )
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        if let Some(batch) = &self.batch {
+            return self.batch_view(batch);
+        }
+
         let left_content: Element<Message> = if self.selected_image.is_empty() {
             column![
                 GlassInputLabel::new("NO TARGET LOADED").size(20),
                 space().height(15.0),
-                GlassButton::new("Upload Criminal Image").on_press(Message::OpenFilePicker),
+                GlassButton::new("Upload Criminal Image(s)").on_press(Message::OpenFilePicker),
+                space().height(10.0),
+                GlassButton::new("Scan a Folder").on_press(Message::OpenFolderPicker),
             ]
             .align_x(Alignment::Center)
             .into()
+        } else if let Some(comparison) = self.comparison.as_ref().filter(|_| self.show_details) {
+            comparison.view()
         } else {
-            let viewer = GlassImageViewer::new(self.selected_image.clone(), 0);
-            viewer.view(Message::NextImage, Message::PrevImage).into()
+            let viewer = GlassImageViewer::from_handles(self.selected_handles.clone(), 0);
+            viewer.view(Message::NextImage, Message::PrevImage)
         };
 
         let left_side = container(left_content)
@@ -104,11 +652,21 @@ impl ImageFindPage {
         // Branching for the Right Side Layout
         let right_content: Element<Message> = if self.is_identifying {
             self.loading_view()
+        } else if let Some(error) = &self.load_error {
+            self.load_error_view(error.clone())
         } else if self.not_found {
             self.not_found_view()
         } else if self.show_details {
             if let Some(data) = &self.identified_data {
-                self.details_view(data)
+                match &self.pending_review {
+                    Some(pending) => column![
+                        verdict_panel(pending, &self.review_comment),
+                        space().height(20.0),
+                        self.details_view(data),
+                    ]
+                    .into(),
+                    None => self.details_view(data),
+                }
             } else {
                 text("Data synchronization error").into()
             }
@@ -126,6 +684,194 @@ impl ImageFindPage {
         row![left_side, right_side].into()
     }
 
+    fn batch_view(&self, batch: &ImageBatch) -> Element<'static, Message> {
+        let progress = format!(
+            "{} of {} processed · {} matched, pending review{}",
+            batch.processed(),
+            batch.items.len(),
+            batch.matches(),
+            if batch.stopped { " · stopped" } else { "" }
+        );
+
+        let controls: Element<Message> = if batch.is_running() {
+            GlassButton::new("Stop").on_press(Message::StopBatch).into()
+        } else {
+            row![
+                GlassButton::new("Review Matches").on_press(Message::GoTo(Page::Review)),
+                GlassButton::new("New Batch").on_press(Message::OpenFilePicker),
+                GlassButton::new("Scan a Folder").on_press(Message::OpenFolderPicker),
+            ]
+            .spacing(8)
+            .into()
+        };
+
+        let header = row![
+            GlassButton::new("← Back").on_press(Message::GoTo(Page::MainMenu)),
+            space().width(20.0),
+            column![
+                GlassInputLabel::new("Batch Identification").size(28),
+                text(progress)
+                    .size(13)
+                    .color(Color::from_rgba(1.0, 1.0, 1.0, 0.6)),
+            ]
+            .width(Length::Fill),
+            controls,
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        let export_status: Element<Message> = match &batch.export {
+            Some(Ok(path)) => text(format!("Exported to {}", path.display()))
+                .size(12)
+                .color(Color::from_rgb(0.4, 0.9, 0.5))
+                .into(),
+            Some(Err(e)) => text(format!("Export failed: {}", e))
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.4, 0.4))
+                .into(),
+            None => space().width(0.0).into(),
+        };
+
+        let toolbar = row![
+            checkbox(batch.matches_only)
+                .label("Matches only")
+                .on_toggle(Message::BatchMatchesOnly),
+            space().width(Length::Fill),
+            export_status,
+            GlassButton::new("Export CSV").on_press(Message::ExportBatchCsv),
+        ]
+        .spacing(12)
+        .align_y(Alignment::Center);
+
+        let columns = row![
+            space().width(Length::Fixed(64.0)),
+            self.sort_header(batch, "FILE", BatchColumn::File, Length::FillPortion(3)),
+            self.sort_header(
+                batch,
+                "CRIMINAL",
+                BatchColumn::Criminal,
+                Length::FillPortion(3)
+            ),
+            self.sort_header(batch, "SCORE", BatchColumn::Score, Length::FillPortion(1)),
+            self.sort_header(batch, "STATUS", BatchColumn::Status, Length::FillPortion(2)),
+        ]
+        .spacing(12);
+
+        let rows = scrollable(
+            column(
+                batch
+                    .visible_rows()
+                    .into_iter()
+                    .map(|i| self.batch_row(&batch.items[i])),
+            )
+            .spacing(6),
+        )
+        .height(Length::Fill);
+
+        column![
+            header,
+            space().height(16.0),
+            toolbar,
+            space().height(12.0),
+            columns,
+            space().height(6.0),
+            rows
+        ]
+        .padding(30)
+        .into()
+    }
+
+    fn sort_header(
+        &self,
+        batch: &ImageBatch,
+        label: &'static str,
+        column: BatchColumn,
+        width: Length,
+    ) -> Element<'static, Message> {
+        let active = batch.sort == column;
+        let label = match (active, batch.ascending) {
+            (true, true) => format!("{} ▲", label),
+            (true, false) => format!("{} ▼", label),
+            (false, _) => label.to_string(),
+        };
+
+        button(text(label).size(11))
+            .width(width)
+            .padding(0)
+            .on_press(Message::SortBatch(column))
+            .style(
+                move |_theme: &Theme, status: button::Status| button::Style {
+                    background: None,
+                    text_color: if active || matches!(status, button::Status::Hovered) {
+                        Color::WHITE
+                    } else {
+                        Color::from_rgba(1.0, 1.0, 1.0, 0.4)
+                    },
+                    ..Default::default()
+                },
+            )
+            .into()
+    }
+
+    fn batch_row(&self, item: &BatchItem) -> Element<'static, Message> {
+        let thumbnail: Element<Message> = match &item.thumbnail {
+            Some(handle) => image(handle.clone())
+                .width(Length::Fixed(64.0))
+                .height(Length::Fixed(64.0))
+                .into(),
+            None => space().width(64.0).height(64.0).into(),
+        };
+
+        let criminal = match (item.criminal_id, &item.name) {
+            (Some(id), Some(name)) => format!("#{}  {}", id, name),
+            (Some(id), None) => format!("#{}", id),
+            (None, _) => "—".to_string(),
+        };
+        let score = item
+            .score
+            .map(|s| format!("{:.2}", s))
+            .unwrap_or_else(|| "—".to_string());
+        let status = match &item.status {
+            BatchStatus::Failed(e) => format!("{}: {}", item.status.label(), e),
+            other => other.label().to_string(),
+        };
+
+        container(
+            row![
+                thumbnail,
+                text(file_name(&item.file))
+                    .size(14)
+                    .color(Color::WHITE)
+                    .width(Length::FillPortion(3)),
+                text(criminal)
+                    .size(14)
+                    .color(Color::WHITE)
+                    .width(Length::FillPortion(3)),
+                text(score)
+                    .size(14)
+                    .color(Color::from_rgba(1.0, 1.0, 1.0, 0.8))
+                    .width(Length::FillPortion(1)),
+                text(status)
+                    .size(13)
+                    .color(item.status.color())
+                    .width(Length::FillPortion(2)),
+            ]
+            .spacing(12)
+            .align_y(Alignment::Center),
+        )
+        .padding([4, 8])
+        .style(|_theme| container::Style {
+            background: Some(Background::Color(Color::from_rgba(0.05, 0.05, 0.05, 0.3))),
+            border: Border {
+                color: Color::from_rgba(1.0, 1.0, 1.0, 0.1),
+                width: 1.0,
+                radius: 8.0.into(),
+            },
+            ..container::Style::default()
+        })
+        .into()
+    }
+
     fn not_found_view(&self) -> Element<'static, Message> {
         column![
             text("NOT FOUND IN DATABASE")
@@ -144,9 +890,25 @@ impl ImageFindPage {
         .into()
     }
 
+    fn load_error_view(&self, error: String) -> Element<'static, Message> {
+        column![
+            text("UNREADABLE IMAGE")
+                .size(32)
+                .color(Color::from_rgb(0.9, 0.4, 0.4)),
+            space().height(20.0),
+            text(error)
+                .size(14)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.6)),
+            space().height(40.0),
+            GlassButton::new("Try Another Image").on_press(Message::OpenFilePicker),
+        ]
+        .align_x(Alignment::Center)
+        .into()
+    }
+
     fn details_view(&self, data: &criminal::Model) -> Element<'static, Message> {
         column![
-            text("CRIMINAL IDENTIFIED")
+            text("CANDIDATE MATCH")
                 .size(24)
                 .color(Color::from_rgb(0.4, 0.9, 0.5)),
             space().height(20.0),
@@ -163,6 +925,7 @@ impl ImageFindPage {
             space().height(20.0),
             self.info_field("VIOLATIONS", data.no_of_crimes.to_string()),
             space().height(20.0),
+            self.profile_fields(data),
             GlassButton::new("New Search").on_press(Message::OpenFilePicker),
             space().height(10.0),
             GlassButton::new("← Main Menu").on_press(Message::GoTo(Page::MainMenu)),
@@ -171,6 +934,28 @@ impl ImageFindPage {
         .into()
     }
 
+    /// Aliases, offence history and whichever optional attributes the record has.
+    fn profile_fields(&self, data: &criminal::Model) -> Element<'static, Message> {
+        let mut fields = Vec::new();
+        if !self.identified_aliases.is_empty() {
+            fields.push(("ALIASES", self.identified_aliases.join(", ")));
+        }
+        if !self.identified_offences.is_empty() {
+            let history: Vec<String> = self
+                .identified_offences
+                .iter()
+                .map(|o| o.summary())
+                .collect();
+            fields.push(("OFFENCE HISTORY", history.join("\n")));
+        }
+        fields.extend(data.attribute_fields());
+
+        column(fields.into_iter().map(|(label, value)| {
+            column![self.info_field(label, value), space().height(20.0)].into()
+        }))
+        .into()
+    }
+
     fn info_field(&self, label: &'static str, value: String) -> Element<'static, Message> {
         column![
             text(label)
@@ -0,0 +1,320 @@
+use crate::components::{GlassButton, GlassInputLabel, GlassTextInput};
+use crate::database::{CriminalDB, MergeSummary};
+use crate::{CriminalRecord, Message, Page};
+
+use iced::widget::{column, container, image, row, space, text};
+use iced::{Alignment, Background, Border, Color, Element, Length, Task, Theme};
+use std::sync::Arc;
+
+#[derive(Default)]
+pub struct MergePage {
+    pub source_id: String,
+    pub target_id: String,
+    pub reason: String,
+    pub source: Option<CriminalRecord>, // folded in and deleted
+    pub target: Option<CriminalRecord>, // kept
+    pub is_loading: bool,
+    pub is_merging: bool,
+    pub error: Option<String>,
+    pub summary: Option<MergeSummary>,
+}
+
+impl MergePage {
+    pub fn update(&mut self, message: Message, db: Option<Arc<CriminalDB>>) -> Task<Message> {
+        match message {
+            Message::MergeSourceChanged(value) => {
+                self.source_id = value;
+                self.source = None;
+            }
+
+            Message::MergeTargetChanged(value) => {
+                self.target_id = value;
+                self.target = None;
+            }
+
+            Message::MergeReasonChanged(value) => {
+                self.reason = value;
+            }
+
+            Message::SwapMergeRecords => {
+                std::mem::swap(&mut self.source_id, &mut self.target_id);
+                std::mem::swap(&mut self.source, &mut self.target);
+            }
+
+            Message::LoadMergeRecords => {
+                let (Ok(source_id), Ok(target_id)) = (
+                    self.source_id.trim().parse::<u32>(),
+                    self.target_id.trim().parse::<u32>(),
+                ) else {
+                    self.error = Some("Enter two record ids".to_string());
+                    return Task::none();
+                };
+                if source_id == target_id {
+                    self.error = Some("Pick two different records".to_string());
+                    return Task::none();
+                }
+                let Some(db) = db else {
+                    self.error = Some("Database not connected".to_string());
+                    return Task::none();
+                };
+
+                self.is_loading = true;
+                self.error = None;
+                self.summary = None;
+
+                return Task::perform(
+                    async move {
+                        let source = db.get_criminal_with_photos(source_id).await?;
+                        let target = db.get_criminal_with_photos(target_id).await?;
+                        Ok(Box::new((source, target)))
+                    },
+                    |result: Result<_, sea_orm::DbErr>| {
+                        Message::MergeRecordsLoaded(result.map_err(|e| e.to_string()))
+                    },
+                );
+            }
+
+            Message::MergeRecordsLoaded(result) => {
+                self.is_loading = false;
+                match result {
+                    Ok(records) => {
+                        let (source, target) = *records;
+                        if source.is_none() {
+                            self.error = Some(format!("No record #{}", self.source_id.trim()));
+                        } else if target.is_none() {
+                            self.error = Some(format!("No record #{}", self.target_id.trim()));
+                        }
+                        self.source = source;
+                        self.target = target;
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+
+            Message::ConfirmMerge => {
+                let (Some((source, _)), Some((target, _)), Some(db)) =
+                    (&self.source, &self.target, db)
+                else {
+                    return Task::none();
+                };
+
+                let source_id = source.criminal_id;
+                let target_id = target.criminal_id;
+                let reason = (!self.reason.trim().is_empty()).then(|| self.reason.clone());
+                self.is_merging = true;
+                self.error = None;
+
+                return Task::perform(
+                    async move {
+                        db.merge_criminals(source_id, target_id, reason)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::MergeCompleted,
+                );
+            }
+
+            Message::MergeCompleted(result) => {
+                self.is_merging = false;
+                match result {
+                    Ok(summary) => {
+                        self.summary = Some(summary);
+                        self.source = None;
+                        self.source_id = String::new();
+                        self.reason = String::new();
+                    }
+                    Err(e) => self.error = Some(e),
+                }
+            }
+            _ => {}
+        }
+        Task::none()
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let pickers = row![
+            column![
+                GlassInputLabel::new("Duplicate record (removed)").size(12),
+                space().height(8.0),
+                GlassTextInput::new("Record id", &self.source_id)
+                    .on_input(Message::MergeSourceChanged),
+            ]
+            .width(Length::Fill),
+            GlassButton::new("⇄").on_press(Message::SwapMergeRecords),
+            column![
+                GlassInputLabel::new("Record to keep").size(12),
+                space().height(8.0),
+                GlassTextInput::new("Record id", &self.target_id)
+                    .on_input(Message::MergeTargetChanged),
+            ]
+            .width(Length::Fill),
+            GlassButton::new(if self.is_loading {
+                "Loading..."
+            } else {
+                "Load"
+            })
+            .on_press(Message::LoadMergeRecords),
+        ]
+        .spacing(20)
+        .align_y(Alignment::End);
+
+        let cards = row![
+            Self::record_card("WILL BE MERGED AWAY", self.source.as_ref()),
+            container(text("→").size(32))
+                .center_y(Length::Fill)
+                .padding([0, 10]),
+            Self::record_card("WILL BE KEPT", self.target.as_ref()),
+        ]
+        .spacing(10)
+        .height(Length::Fill);
+
+        let action: Element<Message> = if self.is_merging {
+            GlassButton::new("Merging...")
+                .on_press(Message::None)
+                .into()
+        } else if let (Some((source, _)), Some((target, _))) = (&self.source, &self.target) {
+            column![
+                GlassInputLabel::new("Reason").size(12),
+                space().height(8.0),
+                GlassTextInput::new("Why are these the same person?", &self.reason)
+                    .on_input(Message::MergeReasonChanged),
+                space().height(12.0),
+                text(format!(
+                    "#{} {} will be folded into #{} {} and deleted",
+                    source.criminal_id, source.name, target.criminal_id, target.name
+                ))
+                .size(14),
+                space().height(8.0),
+                GlassButton::new("Merge Records").on_press(Message::ConfirmMerge),
+            ]
+            .into()
+        } else {
+            space().height(0.0).into()
+        };
+
+        let status: Element<Message> = if let Some(e) = &self.error {
+            text(e)
+                .size(14)
+                .color(Color::from_rgb(0.9, 0.4, 0.4))
+                .into()
+        } else if let Some(summary) = &self.summary {
+            text(format!(
+                "Merged: moved {} photos, {} embeddings, {} sightings, {} identifications, {} offences and {} aliases",
+                summary.photos,
+                summary.embeddings,
+                summary.sightings,
+                summary.identifications,
+                summary.offences,
+                summary.aliases
+            ))
+            .size(14)
+            .color(Color::from_rgb(0.4, 0.9, 0.5))
+            .into()
+        } else {
+            space().height(0.0).into()
+        };
+
+        column![
+            GlassInputLabel::new("Merge Duplicate Records").size(32),
+            space().height(20.0),
+            pickers,
+            space().height(20.0),
+            cards,
+            space().height(20.0),
+            status,
+            space().height(10.0),
+            action,
+            space().height(10.0),
+            GlassButton::new("← Back").on_press(Message::GoTo(Page::MainMenu)),
+        ]
+        .padding(30)
+        .into()
+    }
+
+    fn record_card<'a>(
+        caption: &'static str,
+        record: Option<&'a CriminalRecord>,
+    ) -> Element<'a, Message> {
+        let body: Element<Message> = match record {
+            Some((data, photos)) => {
+                let photo: Element<Message> = match photos.first() {
+                    Some(photo) => image(image::Handle::from_bytes(photo.preview().to_vec()))
+                        .width(Length::Fill)
+                        .height(200.0)
+                        .into(),
+                    None => {
+                        container(text("No photos").color(Color::from_rgba(1.0, 1.0, 1.0, 0.4)))
+                            .height(200.0)
+                            .center_y(200.0)
+                            .into()
+                    }
+                };
+
+                column![
+                    photo,
+                    space().height(12.0),
+                    Self::info_field("CRIMINAL ID", data.criminal_id.to_string()),
+                    Self::info_field("NAME", data.name.clone()),
+                    Self::info_field(
+                        "FATHER'S NAME",
+                        data.fathers_name
+                            .clone()
+                            .unwrap_or_else(|| "N/A".to_string())
+                    ),
+                    Self::info_field("VIOLATIONS", data.no_of_crimes.to_string()),
+                    Self::info_field(
+                        "LAST ARRESTED LOCATION",
+                        data.arrested_location
+                            .clone()
+                            .unwrap_or_else(|| "N/A".to_string())
+                    ),
+                    Self::info_field(
+                        "LAST SEEN",
+                        data.last_seen
+                            .map(|t| t.format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_else(|| "N/A".to_string())
+                    ),
+                    Self::info_field("PHOTOS", photos.len().to_string()),
+                ]
+                .spacing(10)
+                .into()
+            }
+            None => container(text("No record loaded").color(Color::from_rgba(1.0, 1.0, 1.0, 0.3)))
+                .center_x(Length::Fill)
+                .center_y(Length::Fill)
+                .into(),
+        };
+
+        container(column![
+            text(caption)
+                .size(11)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.4)),
+            space().height(10.0),
+            body,
+        ])
+        .padding(15)
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(|_theme: &Theme| container::Style {
+            background: Some(Background::Color(Color::from_rgba(0.05, 0.05, 0.05, 0.2))),
+            border: Border {
+                color: Color::from_rgba(0.4, 0.9, 0.5, 0.2),
+                width: 1.0,
+                radius: 12.0.into(),
+            },
+            ..Default::default()
+        })
+        .into()
+    }
+
+    fn info_field(label: &'static str, value: String) -> Element<'static, Message> {
+        column![
+            text(label)
+                .size(11)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.4)),
+            space().height(4.0),
+            text(value).size(16).color(Color::WHITE),
+        ]
+        .into()
+    }
+}
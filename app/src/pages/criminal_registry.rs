@@ -1,27 +1,147 @@
 use crate::components::GlassButton;
-use crate::components::GlassImageViewer;
 use crate::components::GlassInputLabel;
 use crate::components::GlassTextInput;
+use crate::components::{file_handle, GlassImageViewer};
 use crate::Page;
 
-use crate::database::CriminalDB;
+use crate::database::{CriminalDB, CriminalDetails, NewEnrollment, NewOffence, PendingAudit};
+use crate::entities::{audit_log, criminal, criminal_photo, offence, offence_type};
+use crate::error::{self, AppError};
+use crate::face_index::{SearchHit, DUPLICATE_THRESHOLD};
+use crate::outbox::{self, Enrollment};
+use crate::preprocess::{load_image, prepare_images, PreparedImage};
+use crate::python_process::{add_command, encode_path, format_box};
+use crate::quality::{FaceBox, ImageStats, PhotoQuality};
+use crate::vault;
 use crate::Message;
+use chrono::{NaiveDate, Utc};
 use iced::{
-    widget::{column, container, row, scrollable, space},
-    Alignment, Element, Length, Task,
+    widget::{button, column, container, image, pick_list, row, scrollable, space, text},
+    Alignment, Background, Border, Color, Element, Length, Task, Theme,
 };
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// How long the engine gets to embed every photo for the duplicate check.
+const DUPLICATE_CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// What we know so far about one selected photo; graded once both halves arrive.
+#[derive(Debug, Clone, Default)]
+pub struct PhotoAnalysis {
+    pub stats: Option<Result<ImageStats, String>>,
+    pub faces: Option<Vec<FaceBox>>,
+    pub chosen_face: Option<usize>, // operator's pick when there are several faces
+    pub face_crops: Vec<image::Handle>,
+}
+
+impl PhotoAnalysis {
+    pub fn quality(&self) -> Option<PhotoQuality> {
+        match (&self.stats, &self.faces) {
+            (Some(Ok(stats)), Some(faces)) => {
+                Some(PhotoQuality::assess(*stats, faces, self.chosen_face))
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether both halves have arrived.
+    pub fn is_complete(&self) -> bool {
+        self.stats.is_some() && self.faces.is_some()
+    }
+
+    /// Box of the suspect's face: the only face, or the chosen one.
+    pub fn face_box(&self) -> Option<[f32; 4]> {
+        let faces = self.faces.as_ref()?;
+        match faces.len() {
+            1 => Some(faces[0].bbox),
+            _ => self.chosen_face.and_then(|i| faces.get(i)).map(|f| f.bbox),
+        }
+    }
+}
+
+/// Existing record the new photos strongly resemble.
+#[derive(Debug, Clone)]
+pub struct DuplicateMatch {
+    pub score: f32,
+    pub criminal: criminal::Model,
+    pub photos: Vec<criminal_photo::Model>,
+}
+
+/// One row of the offences editor, as typed.
+#[derive(Debug, Clone)]
+pub struct OffenceDraft {
+    pub offence_type: Option<offence_type::Model>,
+    pub date: String,
+    pub location: String,
+    pub case_number: String,
+    pub status: &'static str,
+    pub type_error: bool,
+    pub date_error: bool,
+}
+
+impl Default for OffenceDraft {
+    fn default() -> Self {
+        Self {
+            offence_type: None,
+            date: String::new(),
+            location: String::new(),
+            case_number: String::new(),
+            status: offence::OPEN,
+            type_error: false,
+            date_error: false,
+        }
+    }
+}
+
+/// Offence status as offered in the picker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct StatusChoice(&'static str);
+
+impl std::fmt::Display for StatusChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(offence::status_label(self.0))
+    }
+}
 
 pub struct RegistryPage {
     pub name: String,
     pub fathers_name: String,
-    pub no_of_crimes: String,
     pub arrested_location: String,
+    pub aliases: String, // comma-separated
+    pub date_of_birth: String,
+    pub gender: String,
+    pub national_ids: String,
+    pub height_cm: String,
+    pub distinguishing_marks: String,
+    pub notes: String,
+    pub offences: Vec<OffenceDraft>,
+    pub offence_types: Vec<offence_type::Model>, // the managed list
+    pub new_offence_type: String,
+    pub offence_type_error: Option<String>,
     pub name_error: bool,
-    pub selected_images: Vec<String>,
+    pub date_of_birth_error: bool,
+    pub height_error: bool,
+    pub selected_images: Vec<PreparedImage>,
+    pub selected_handles: Vec<image::Handle>, // selected_images read once for the viewer
+    pub photo_errors: Vec<String>,            // files rejected by preprocessing
+    pub is_preparing: bool,
+    pub photo_analysis: Vec<PhotoAnalysis>, // parallel to selected_images
+    pub analysis_generation: u32,           // tags engine replies so stale ones are dropped
     pub current_img_idx: usize,
     pub is_saving: bool,
     pub save_success: bool,
+    pub queued_offline: bool, // saved to the outbox rather than the database
+    pub is_checking: bool,    // searching the gallery for duplicates
+    pub check_generation: u32,
+    pub pending_probes: usize,
+    pub check_error: Option<String>, // why the last duplicate check didn't finish
+    pub best_match: Option<SearchHit>,
+    pub duplicate: Option<DuplicateMatch>,
+    pub justification: String,
+    pub justification_error: bool,
 }
 
 impl Default for RegistryPage {
@@ -29,13 +149,39 @@ impl Default for RegistryPage {
         Self {
             name: String::new(),
             fathers_name: String::new(),
-            no_of_crimes: String::new(),
             arrested_location: String::new(),
+            aliases: String::new(),
+            date_of_birth: String::new(),
+            gender: String::new(),
+            national_ids: String::new(),
+            height_cm: String::new(),
+            distinguishing_marks: String::new(),
+            notes: String::new(),
+            offences: Vec::new(),
+            offence_types: Vec::new(),
+            new_offence_type: String::new(),
+            offence_type_error: None,
             name_error: false,
+            date_of_birth_error: false,
+            height_error: false,
             selected_images: Vec::new(),
+            selected_handles: Vec::new(),
+            photo_errors: Vec::new(),
+            is_preparing: false,
+            photo_analysis: Vec::new(),
+            analysis_generation: 0,
             current_img_idx: 0,
             is_saving: false,
             save_success: false,
+            queued_offline: false,
+            is_checking: false,
+            check_generation: 0,
+            pending_probes: 0,
+            check_error: None,
+            best_match: None,
+            duplicate: None,
+            justification: String::new(),
+            justification_error: false,
         }
     }
 }
@@ -53,65 +199,354 @@ impl RegistryPage {
                 self.fathers_name = value;
             }
 
-            Message::CrimesCountChanged(value) => {
-                self.no_of_crimes = value;
-            }
-
             Message::LocationChanged(value) => {
                 self.arrested_location = value;
             }
 
+            Message::AliasesChanged(value) => {
+                self.aliases = value;
+            }
+
+            Message::DateOfBirthChanged(value) => {
+                self.date_of_birth = value;
+                self.date_of_birth_error = false;
+            }
+
+            Message::GenderChanged(value) => {
+                self.gender = value;
+            }
+
+            Message::NationalIdsChanged(value) => {
+                self.national_ids = value;
+            }
+
+            Message::HeightChanged(value) => {
+                self.height_cm = value;
+                self.height_error = false;
+            }
+
+            Message::MarksChanged(value) => {
+                self.distinguishing_marks = value;
+            }
+
+            Message::NotesChanged(value) => {
+                self.notes = value;
+            }
+
+            Message::LoadOffenceTypes => {
+                let Some(db) = db else {
+                    return Task::none();
+                };
+                return Task::perform(
+                    async move { db.get_offence_types().await.map_err(|e| e.to_string()) },
+                    Message::OffenceTypesLoaded,
+                );
+            }
+
+            Message::OffenceTypesLoaded(result) => match result {
+                Ok(types) => self.offence_types = types,
+                Err(e) => self.offence_type_error = Some(e),
+            },
+
+            Message::AddOffence => self.offences.push(OffenceDraft::default()),
+
+            Message::RemoveOffence(index) if index < self.offences.len() => {
+                self.offences.remove(index);
+            }
+
+            Message::OffenceTypeSelected(index, offence_type) => {
+                if let Some(draft) = self.offences.get_mut(index) {
+                    draft.offence_type = Some(offence_type);
+                    draft.type_error = false;
+                }
+            }
+
+            Message::OffenceDateChanged(index, value) => {
+                if let Some(draft) = self.offences.get_mut(index) {
+                    draft.date = value;
+                    draft.date_error = false;
+                }
+            }
+
+            Message::OffenceLocationChanged(index, value) => {
+                if let Some(draft) = self.offences.get_mut(index) {
+                    draft.location = value;
+                }
+            }
+
+            Message::OffenceCaseChanged(index, value) => {
+                if let Some(draft) = self.offences.get_mut(index) {
+                    draft.case_number = value;
+                }
+            }
+
+            Message::OffenceStatusSelected(index, status) => {
+                if let Some(draft) = self.offences.get_mut(index) {
+                    draft.status = status;
+                }
+            }
+
+            Message::NewOffenceTypeChanged(value) => {
+                self.new_offence_type = value;
+                self.offence_type_error = None;
+            }
+
+            Message::AddOffenceType => {
+                let name = self.new_offence_type.trim().to_string();
+                let Some(db) = db else {
+                    return Task::none();
+                };
+                if name.is_empty() {
+                    return Task::none();
+                }
+                return Task::perform(
+                    async move { db.add_offence_type(&name).await.map_err(|e| e.to_string()) },
+                    Message::OffenceTypeAdded,
+                );
+            }
+
+            Message::OffenceTypeAdded(result) => match result {
+                Ok(added) => {
+                    self.new_offence_type = String::new();
+                    if !self.offence_types.contains(&added) {
+                        self.offence_types.push(added);
+                        self.offence_types.sort_by(|a, b| a.name.cmp(&b.name));
+                    }
+                }
+                Err(e) => self.offence_type_error = Some(e),
+            },
+
             Message::SubmitForm => {
                 if self.name.trim().is_empty() {
                     self.name_error = true;
+                }
+                let date_of_birth = self.date_of_birth.trim();
+                if !date_of_birth.is_empty()
+                    && NaiveDate::parse_from_str(date_of_birth, DATE_FORMAT).is_err()
+                {
+                    self.date_of_birth_error = true;
+                }
+                let height = self.height_cm.trim();
+                if !height.is_empty() && height.parse::<u16>().is_err() {
+                    self.height_error = true;
+                }
+                for draft in &mut self.offences {
+                    draft.type_error = draft.offence_type.is_none();
+                    let date = draft.date.trim();
+                    draft.date_error =
+                        !date.is_empty() && NaiveDate::parse_from_str(date, DATE_FORMAT).is_err();
+                }
+                let offences_invalid = self.offences.iter().any(|d| d.type_error || d.date_error);
+                if self.name_error
+                    || self.date_of_birth_error
+                    || self.height_error
+                    || offences_invalid
+                {
+                    return Task::none();
+                }
+
+                if self.has_blocking_photos() {
                     return Task::none();
                 }
 
+                self.is_saving = true;
                 let Some(db) = db else {
+                    return self.queue_offline();
+                };
+
+                if self.selected_images.is_empty() {
+                    return self.save(db, None, None);
+                }
+
+                // Search the gallery for these faces before creating a record
+                self.check_generation = self.check_generation.wrapping_add(1);
+                self.pending_probes = self.selected_images.len();
+                self.best_match = None;
+                self.is_checking = true;
+                self.check_error = None;
+
+                let probes = self
+                    .selected_images
+                    .iter()
+                    .enumerate()
+                    .map(|(i, path)| {
+                        let tag = format!("dup-{}-{}", self.check_generation, i);
+                        let face_box = self.photo_analysis.get(i).and_then(|a| a.face_box());
+                        let cmd = format!(
+                            "embed {} {} {}",
+                            tag,
+                            encode_path(&path.path().to_string_lossy()),
+                            format_box(face_box)
+                        );
+                        (tag, cmd)
+                    })
+                    .collect();
+                let generation = self.check_generation;
+                return Task::batch(vec![
+                    Task::done(Message::CheckDuplicates(probes)),
+                    Task::perform(tokio::time::sleep(DUPLICATE_CHECK_TIMEOUT), move |_| {
+                        Message::DuplicateCheckTimedOut(generation)
+                    }),
+                ]);
+            }
+
+            Message::DuplicateProbe(tag, hit) => {
+                let Some((generation, _)) = parse_tag(&tag, "dup-") else {
                     return Task::none();
                 };
+                if generation != self.check_generation || !self.is_checking {
+                    return Task::none();
+                }
+
+                if let Some(hit) = hit {
+                    if self.best_match.is_none_or(|best| hit.score > best.score) {
+                        self.best_match = Some(hit);
+                    }
+                }
+                self.pending_probes = self.pending_probes.saturating_sub(1);
+                if self.pending_probes > 0 {
+                    return Task::none();
+                }
+                self.is_checking = false;
 
+                let Some(db) = db else {
+                    return self.queue_offline();
+                };
+                match self.best_match {
+                    Some(hit) if hit.matches(DUPLICATE_THRESHOLD) => {
+                        return Task::perform(
+                            async move { db.get_criminal_with_photos(hit.criminal_id).await },
+                            move |result| match result {
+                                Ok(Some((criminal, photos))) => {
+                                    Message::DuplicateFound(hit.score, criminal, photos)
+                                }
+                                // The matched record is gone; nothing to merge with
+                                _ => Message::NoDuplicateFound,
+                            },
+                        );
+                    }
+                    _ => return self.save(db, None, None),
+                }
+            }
+
+            Message::NoDuplicateFound => {
+                return match db {
+                    Some(db) => self.save(db, None, None),
+                    None => self.queue_offline(),
+                };
+            }
+
+            Message::DuplicateCheckTimedOut(generation)
+                if generation == self.check_generation && self.is_checking =>
+            {
+                let reason = format!(
+                    "The engine didn't check the photos for duplicates within {}s",
+                    DUPLICATE_CHECK_TIMEOUT.as_secs()
+                );
+                self.fail_duplicate_check(reason.clone());
+                return error::report(AppError::Engine(reason), Some(Message::SubmitForm));
+            }
+
+            // The engine failure itself has already been reported
+            Message::DuplicateCheckFailed(reason) if self.is_checking => {
+                self.fail_duplicate_check(reason);
+            }
+
+            Message::SaveWithoutDuplicateCheck => {
+                let Some(reason) = self.check_error.take() else {
+                    return Task::none();
+                };
                 self.is_saving = true;
+                let Some(db) = db else {
+                    return self.queue_offline();
+                };
 
-                let name = self.name.clone();
-                let f_name = (!self.fathers_name.is_empty()).then(|| self.fathers_name.clone());
-                let loc =
-                    (!self.arrested_location.is_empty()).then(|| self.arrested_location.clone());
-                let crimes = self.no_of_crimes.parse::<u32>().unwrap_or(1);
-                let photo_paths = self.selected_images.clone();
+                let audit = PendingAudit {
+                    action: audit_log::SKIPPED_DUPLICATE_CHECK,
+                    related_criminal_id: None,
+                    details: reason,
+                };
+                return self.save(db, None, Some(audit));
+            }
 
-                println!("ATTEMPTING TO ADD TO DATABASE");
-                // UPDATED: Command::perform -> Task::perform
-                return Task::perform(
-                    async move {
-                        // 1. Save to Rust Database
-                        let criminal_id = db
-                            .add_criminal(name, f_name, loc, crimes)
-                            .await
-                            .map_err(|e| e.to_string())?;
+            Message::DuplicateFound(score, criminal, photos) => {
+                self.is_saving = false;
+                self.duplicate = Some(DuplicateMatch {
+                    score,
+                    criminal,
+                    photos,
+                });
+            }
 
-                        // 2. Save photos (preserving existing logic)
-                        for path in &photo_paths {
-                            if let Ok(bytes) = std::fs::read(path) {
-                                let _ = db.add_criminal_photo(criminal_id, bytes).await;
-                            }
-                        }
+            Message::JustificationChanged(value) => {
+                self.justification = value;
+                self.justification_error = false;
+            }
 
-                        Ok((criminal_id, photo_paths))
-                    },
-                    |result| match result {
-                        Ok((id, paths)) => Message::DatabaseSaved(id, paths),
-                        Err(e) => Message::SaveResult(Err(e)),
-                    },
-                );
+            Message::MergeIntoExisting => {
+                let (Some(db), Some(duplicate)) = (db, self.duplicate.take()) else {
+                    return Task::none();
+                };
+                self.is_saving = true;
+
+                let audit = PendingAudit {
+                    action: audit_log::MERGED_ENROLLMENT,
+                    related_criminal_id: None,
+                    details: format!(
+                        "Photos enrolled as \"{}\" added to this record (similarity {:.2})",
+                        self.name.trim(),
+                        duplicate.score
+                    ),
+                };
+                return self.save(db, Some(duplicate.criminal.criminal_id), Some(audit));
+            }
+
+            Message::ForceNewRecord => {
+                if self.justification.trim().is_empty() {
+                    self.justification_error = true;
+                    return Task::none();
+                }
+                let (Some(db), Some(duplicate)) = (db, self.duplicate.take()) else {
+                    return Task::none();
+                };
+                self.is_saving = true;
+
+                let audit = PendingAudit {
+                    action: audit_log::FORCED_NEW_RECORD,
+                    related_criminal_id: Some(duplicate.criminal.criminal_id),
+                    details: format!(
+                        "{} (similarity {:.2})",
+                        self.justification.trim(),
+                        duplicate.score
+                    ),
+                };
+                return self.save(db, None, Some(audit));
             }
 
-            Message::DatabaseSaved(id, paths) => {
-                let paths_str = paths.join("&");
-                let python_cmd = format!("add {} {}", id, paths_str);
+            Message::CancelEnrollment => {
+                self.duplicate = None;
+                self.check_error = None;
+                self.justification = String::new();
+                self.justification_error = false;
+                self.is_saving = false;
+            }
+
+            Message::DatabaseSaved(id, photos, problems) => {
+                let python_cmd = add_command(id, &photos);
 
-                // Use Task::done to hand the command over to the main Python logic
-                return Task::done(Message::PythonInput(python_cmd));
+                // The record is saved either way; say what didn't make it
+                let mut tasks: Vec<_> = problems
+                    .into_iter()
+                    .map(|problem| error::report(problem, None))
+                    .collect();
+                if photos.is_empty() {
+                    // Nothing for the engine to enroll, so no "added" will come
+                    tasks.push(Task::done(Message::SaveResult(Ok(id))));
+                } else {
+                    // Use Task::done to hand the command over to the main Python logic
+                    tasks.push(Task::done(Message::PythonInput(python_cmd)));
+                }
+                return Task::batch(tasks);
             }
 
             Message::SaveResult(Ok(_)) => {
@@ -119,16 +554,164 @@ impl RegistryPage {
                 self.save_success = true;
             }
 
+            Message::EnrollmentQueued(Ok(())) => {
+                self.is_saving = false;
+                self.save_success = true;
+                self.queued_offline = true;
+            }
+
+            Message::EnrollmentQueued(Err(e)) => {
+                self.is_saving = false;
+                return error::report(
+                    AppError::File {
+                        path: outbox::current().dir().to_path_buf(),
+                        reason: format!("Could not keep the record for later: {}", e),
+                    },
+                    Some(Message::SubmitForm),
+                );
+            }
+
+            // Saved, but the engine failed before embedding the photos
+            Message::EnrollmentNotEmbedded(id, reason) => {
+                self.is_saving = false;
+                self.save_success = true;
+                return error::report(
+                    AppError::Engine(format!(
+                        "Record #{} was saved, but its photos couldn't be enrolled for \
+                         matching ({}); run `cargo run --release --bin backfill_embeddings`",
+                        id, reason
+                    )),
+                    None,
+                );
+            }
+
+            Message::SaveResult(Err(e)) => {
+                // Nothing was saved, so submitting again is safe
+                self.is_saving = false;
+                return error::report(
+                    AppError::Database(format!("Could not save the record: {}", e)),
+                    Some(Message::SubmitForm),
+                );
+            }
+
             Message::ResetForm => {
+                // Keep the generations so replies for the old photos are ignored
+                let (analysis, check) = (self.analysis_generation, self.check_generation);
+                let offence_types = std::mem::take(&mut self.offence_types);
                 *self = RegistryPage::default();
+                self.analysis_generation = analysis;
+                self.check_generation = check;
+                self.offence_types = offence_types;
             }
 
             Message::FilesSelected(paths) => {
-                self.selected_images = paths
-                    .into_iter()
-                    .map(|p| p.to_string_lossy().to_string())
+                if paths.is_empty() {
+                    return Task::none();
+                }
+                self.is_preparing = true;
+
+                // Orientation fixes / re-encoding happen off the UI thread
+                return Task::perform(
+                    async move {
+                        tokio::task::spawn_blocking(move || prepare_images(&paths))
+                            .await
+                            .unwrap_or_default()
+                    },
+                    Message::ImagesPrepared,
+                );
+            }
+
+            Message::ImagesPrepared(results) => {
+                self.is_preparing = false;
+                self.photo_errors = Vec::new();
+                self.selected_images = Vec::new();
+
+                for result in results {
+                    match result {
+                        Ok(image) => self.selected_images.push(image),
+                        Err(e) => self.photo_errors.push(e),
+                    }
+                }
+                self.selected_handles = self
+                    .selected_images
+                    .iter()
+                    .map(|image| file_handle(image.path()))
                     .collect();
                 self.current_img_idx = 0;
+
+                return self.start_analysis();
+            }
+
+            Message::PhotoStatsComputed(generation, results) => {
+                if generation != self.analysis_generation {
+                    return Task::none();
+                }
+                for (analysis, stats) in self.photo_analysis.iter_mut().zip(results) {
+                    analysis.stats = Some(stats);
+                }
+            }
+
+            Message::FacesDetected(tag, faces) => {
+                let Some((generation, index)) = parse_tag(&tag, "reg-") else {
+                    return Task::none();
+                };
+                if generation != self.analysis_generation {
+                    return Task::none();
+                }
+                let Some(analysis) = self.photo_analysis.get_mut(index) else {
+                    return Task::none();
+                };
+                analysis.faces = Some(faces.clone());
+                analysis.chosen_face = None;
+
+                // Only group photos need the picker
+                if faces.len() > 1 {
+                    let image = self.selected_images[index].clone();
+                    return Task::perform(
+                        async move {
+                            tokio::task::spawn_blocking(move || {
+                                face_thumbnails(image.path(), &faces)
+                            })
+                            .await
+                            .unwrap_or_default()
+                        },
+                        move |crops| Message::FaceCropsReady(generation, index, crops),
+                    );
+                }
+            }
+
+            Message::FaceCropsReady(generation, index, crops) => {
+                if generation != self.analysis_generation {
+                    return Task::none();
+                }
+                if let Some(analysis) = self.photo_analysis.get_mut(index) {
+                    analysis.face_crops = crops;
+                }
+            }
+
+            Message::RetryPhotoAnalysis => return self.start_analysis(),
+
+            Message::SelectFace(photo, face) => {
+                if let Some(analysis) = self.photo_analysis.get_mut(photo) {
+                    analysis.chosen_face = Some(face);
+                }
+            }
+
+            Message::ExcludePhoto(index) => {
+                if index >= self.selected_images.len() {
+                    return Task::none();
+                }
+                self.selected_images.remove(index);
+                self.selected_handles.remove(index);
+                self.photo_analysis.remove(index);
+
+                // Indices in outstanding engine tags are now off by one
+                if self.photo_analysis.iter().any(|a| a.faces.is_none()) {
+                    return self.start_analysis();
+                }
+                if self.current_img_idx >= self.selected_images.len() {
+                    self.current_img_idx = self.selected_images.len().saturating_sub(1);
+                }
             }
             Message::NextImage => {
                 if !self.selected_images.is_empty() {
@@ -150,7 +733,9 @@ impl RegistryPage {
     }
 
     pub fn view(&self) -> Element<'_, Message> {
-        let top_left_content: Element<Message> = if self.selected_images.is_empty() {
+        let top_left_content: Element<Message> = if self.is_preparing {
+            GlassInputLabel::new("Preparing photos...").size(20).into()
+        } else if self.selected_images.is_empty() {
             column![
                 GlassInputLabel::new("Suspect Images").size(20),
                 space().height(10.0),
@@ -162,11 +747,22 @@ impl RegistryPage {
             self.image_viewer_logic()
         };
 
-        let left_col = column![container(top_left_content)
-            .width(Length::Fill)
-            .height(Length::FillPortion(60))
-            .center_x(Length::Fill)
-            .center_y(Length::Fill),]
+        let photo_errors = column(self.photo_errors.iter().map(|e| {
+            text(format!("Rejected: {}", e))
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.4, 0.4))
+                .into()
+        }))
+        .spacing(4);
+
+        let left_col = column![
+            container(top_left_content)
+                .width(Length::Fill)
+                .height(Length::FillPortion(60))
+                .center_x(Length::Fill)
+                .center_y(Length::Fill),
+            photo_errors,
+        ]
         .width(Length::FillPortion(40));
 
         let right_col = column![
@@ -186,28 +782,91 @@ impl RegistryPage {
                     Message::FathersNameChanged
                 ),
                 space().height(20.0),
-                self.field_group(
-                    "No. of Violations",
-                    &self.no_of_crimes,
-                    Message::CrimesCountChanged
-                ),
-                space().height(20.0),
                 self.field_group(
                     "Last Arrested Location",
                     &self.arrested_location,
                     Message::LocationChanged
                 ),
+                space().height(20.0),
+                self.field_group(
+                    "Aliases (comma-separated)",
+                    &self.aliases,
+                    Message::AliasesChanged
+                ),
+                space().height(20.0),
+                self.field_group(
+                    if self.date_of_birth_error {
+                        "Date of Birth (YYYY-MM-DD) *"
+                    } else {
+                        "Date of Birth (YYYY-MM-DD)"
+                    },
+                    &self.date_of_birth,
+                    Message::DateOfBirthChanged
+                ),
+                space().height(20.0),
+                self.field_group("Gender", &self.gender, Message::GenderChanged),
+                space().height(20.0),
+                self.field_group(
+                    "National ID Numbers",
+                    &self.national_ids,
+                    Message::NationalIdsChanged
+                ),
+                space().height(20.0),
+                self.field_group(
+                    if self.height_error {
+                        "Height (cm) *"
+                    } else {
+                        "Height (cm)"
+                    },
+                    &self.height_cm,
+                    Message::HeightChanged
+                ),
+                space().height(20.0),
+                self.field_group(
+                    "Distinguishing Marks / Tattoos",
+                    &self.distinguishing_marks,
+                    Message::MarksChanged
+                ),
+                space().height(20.0),
+                self.field_group("Notes", &self.notes, Message::NotesChanged),
+                space().height(20.0),
+                self.offences_editor(),
             ])),
             row![GlassButton::new("← Back").on_press(Message::GoTo(Page::MainMenu)),]
                 .padding(10)
                 .align_y(Alignment::Center),
             // Footer Action Area
-            container(if self.is_saving {
-                GlassButton::new("Saving...").on_press(Message::None)
+            container(if let Some(duplicate) = &self.duplicate {
+                self.duplicate_panel(duplicate)
+            } else if let Some(reason) = &self.check_error {
+                self.check_failed_panel(reason)
+            } else if self.is_checking {
+                GlassButton::new("Checking for duplicates...")
+                    .on_press(Message::None)
+                    .into()
+            } else if self.is_saving {
+                GlassButton::new("Saving...").on_press(Message::None).into()
+            } else if self.save_success && self.queued_offline {
+                GlassButton::new("Saved offline, syncs when the database is back (Reset Form)")
+                    .on_press(Message::ResetForm)
+                    .into()
             } else if self.save_success {
-                GlassButton::new("Saved! (Reset Form)").on_press(Message::ResetForm)
+                GlassButton::new("Saved! (Reset Form)")
+                    .on_press(Message::ResetForm)
+                    .into()
+            } else if self.is_analyzing() {
+                // Replies lost to an engine restart would otherwise never come
+                GlassButton::new("Checking Photos... (Click to Retry)")
+                    .on_press(Message::RetryPhotoAnalysis)
+                    .into()
+            } else if self.has_blocking_photos() {
+                GlassButton::new("Exclude Flagged Photos to Save")
+                    .on_press(Message::None)
+                    .into()
             } else {
-                GlassButton::new("Save to Database").on_press(Message::SubmitForm)
+                GlassButton::new("Save to Database")
+                    .on_press(Message::SubmitForm)
+                    .into()
             })
             .width(Length::Fill)
             .height(Length::FillPortion(20))
@@ -219,9 +878,473 @@ impl RegistryPage {
         row![left_col, right_col].into()
     }
 
+    /// The existing record these photos match, and what to do about it.
+    fn duplicate_panel<'a>(&'a self, duplicate: &'a DuplicateMatch) -> Element<'a, Message> {
+        let existing = &duplicate.criminal;
+        let photo: Element<Message> = match duplicate.photos.first() {
+            Some(photo) => image(image::Handle::from_bytes(photo.preview().to_vec()))
+                .width(96.0)
+                .height(96.0)
+                .into(),
+            None => space().width(96.0).into(),
+        };
+
+        let details = column![
+            GlassInputLabel::new("Possible duplicate").size(16),
+            text(format!(
+                "#{} {} ({:.0}% similar)",
+                existing.criminal_id,
+                existing.name,
+                duplicate.score * 100.0
+            ))
+            .size(14),
+            text(format!(
+                "Father's name: {}",
+                existing.fathers_name.as_deref().unwrap_or("-")
+            ))
+            .size(12),
+            text(format!(
+                "Last arrested at: {}",
+                existing.arrested_location.as_deref().unwrap_or("-")
+            ))
+            .size(12),
+        ]
+        .spacing(4);
+
+        column![
+            row![photo, details].spacing(12).align_y(Alignment::Center),
+            space().height(10.0),
+            GlassInputLabel::new(if self.justification_error {
+                "Justification for a new record *"
+            } else {
+                "Justification for a new record"
+            })
+            .size(12),
+            GlassTextInput::new("Why is this a different person?", &self.justification)
+                .on_input(Message::JustificationChanged),
+            space().height(10.0),
+            row![
+                GlassButton::new("Add Photos to Existing").on_press(Message::MergeIntoExisting),
+                GlassButton::new("Create New Anyway").on_press(Message::ForceNewRecord),
+                GlassButton::new("Cancel").on_press(Message::CancelEnrollment),
+            ]
+            .spacing(10),
+        ]
+        .padding(10)
+        .into()
+    }
+
+    fn check_failed_panel<'a>(&'a self, reason: &'a str) -> Element<'a, Message> {
+        column![
+            GlassInputLabel::new("Duplicate check failed").size(16),
+            text(reason).size(12),
+            space().height(10.0),
+            row![
+                GlassButton::new("Retry Check").on_press(Message::SubmitForm),
+                GlassButton::new("Save Without Checking")
+                    .on_press(Message::SaveWithoutDuplicateCheck),
+                GlassButton::new("Cancel").on_press(Message::CancelEnrollment),
+            ]
+            .spacing(10),
+        ]
+        .padding(10)
+        .into()
+    }
+
+    /// Creates a new record (or adds to `existing`) with its photos and
+    /// `audit`, all in one transaction.
+    fn save(
+        &self,
+        db: Arc<CriminalDB>,
+        existing: Option<u32>,
+        audit: Option<PendingAudit>,
+    ) -> Task<Message> {
+        let name = self.name.clone();
+        let f_name = (!self.fathers_name.is_empty()).then(|| self.fathers_name.clone());
+        let loc = (!self.arrested_location.is_empty()).then(|| self.arrested_location.clone());
+        let details = self.details();
+        let offences = self.new_offences();
+        // Adding to an existing record keeps the name typed here as an alias
+        let mut aliases = self.alias_list();
+        if existing.is_some() {
+            aliases.insert(0, self.name.trim().to_string());
+        }
+        let photo_paths: Vec<(String, Option<[f32; 4]>)> = self
+            .selected_images
+            .iter()
+            .enumerate()
+            .map(|(i, path)| {
+                let face_box = self.photo_analysis.get(i).and_then(|a| a.face_box());
+                (path.to_string(), face_box)
+            })
+            .collect();
+
+        Task::perform(
+            async move {
+                // Photos that can't be read are left out and reported
+                let (mut photos, mut saved) = (Vec::new(), Vec::new());
+                let mut problems = Vec::new();
+                for (path, face_box) in photo_paths {
+                    match vault::read_input(&path) {
+                        Ok(bytes) => {
+                            photos.push((bytes, face_box));
+                            saved.push((path, face_box));
+                        }
+                        Err(e) => problems.push(AppError::file(&path, e)),
+                    }
+                }
+
+                let enrollment = NewEnrollment {
+                    name,
+                    fathers_name: f_name,
+                    arrested_location: loc,
+                    details,
+                    aliases,
+                    offences,
+                    photos,
+                    audit,
+                    outbox_entry: None,
+                };
+                let summary = db
+                    .enroll(existing, enrollment)
+                    .await
+                    .map_err(|e| e.to_string())?;
+
+                // Keep the photo ids so the engine's embeddings can be stored against them
+                let saved_photos = summary
+                    .photo_ids
+                    .into_iter()
+                    .zip(saved)
+                    .map(|(photo_id, (path, face_box))| (photo_id, path, face_box))
+                    .collect();
+                Ok((summary.criminal_id, saved_photos, problems))
+            },
+            |result| match result {
+                Ok((id, paths, problems)) => Message::DatabaseSaved(id, paths, problems),
+                Err(e) => Message::SaveResult(Err(e)),
+            },
+        )
+    }
+
+    /// Keeps the record in the outbox until the database is back. Skips the
+    /// duplicate check, which needs the database to look the match up.
+    fn queue_offline(&self) -> Task<Message> {
+        let enrollment = Enrollment {
+            name: self.name.clone(),
+            fathers_name: (!self.fathers_name.is_empty()).then(|| self.fathers_name.clone()),
+            arrested_location: (!self.arrested_location.is_empty())
+                .then(|| self.arrested_location.clone()),
+            details: self.details(),
+            aliases: self.alias_list(),
+            offences: self.new_offences(),
+            photos: self
+                .selected_images
+                .iter()
+                .enumerate()
+                .map(|(i, image)| {
+                    let face_box = self.photo_analysis.get(i).and_then(|a| a.face_box());
+                    (image.path().to_path_buf(), face_box)
+                })
+                .collect(),
+            captured_at: Utc::now(),
+        };
+
+        Task::perform(
+            async move {
+                outbox::current()
+                    .enqueue_enrollment(enrollment)
+                    .map_err(|e| e.to_string())
+            },
+            Message::EnrollmentQueued,
+        )
+    }
+
+    /// The optional attributes as typed; `SubmitForm` has already validated them.
+    fn details(&self) -> CriminalDetails {
+        let text = |value: &str| {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+
+        CriminalDetails {
+            date_of_birth: NaiveDate::parse_from_str(self.date_of_birth.trim(), DATE_FORMAT).ok(),
+            gender: text(&self.gender),
+            national_ids: text(&self.national_ids),
+            height_cm: self.height_cm.trim().parse().ok(),
+            distinguishing_marks: text(&self.distinguishing_marks),
+            notes: text(&self.notes),
+        }
+    }
+
+    /// The offence rows as typed; `SubmitForm` has already validated them.
+    fn new_offences(&self) -> Vec<NewOffence> {
+        let text = |value: &str| {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+
+        self.offences
+            .iter()
+            .filter_map(|draft| {
+                Some(NewOffence {
+                    offence_type_id: draft.offence_type.as_ref()?.offence_type_id,
+                    offence_date: NaiveDate::parse_from_str(draft.date.trim(), DATE_FORMAT).ok(),
+                    location: text(&draft.location),
+                    case_number: text(&draft.case_number),
+                    status: draft.status.to_string(),
+                })
+            })
+            .collect()
+    }
+
+    fn alias_list(&self) -> Vec<String> {
+        self.aliases
+            .split(',')
+            .map(|alias| alias.trim().to_string())
+            .filter(|alias| !alias.is_empty())
+            .collect()
+    }
+
+    /// Gives up on the duplicate check, dropping any late replies, so the
+    /// operator can retry it or save without it.
+    fn fail_duplicate_check(&mut self, reason: String) {
+        self.check_generation = self.check_generation.wrapping_add(1);
+        self.pending_probes = 0;
+        self.best_match = None;
+        self.is_checking = false;
+        self.is_saving = false;
+        self.check_error = Some(reason);
+    }
+
+    /// Measures every selected photo here and asks the engine for its faces.
+    fn start_analysis(&mut self) -> Task<Message> {
+        self.analysis_generation = self.analysis_generation.wrapping_add(1);
+        // A failed check was about the previous photos
+        self.check_error = None;
+        self.photo_analysis = vec![PhotoAnalysis::default(); self.selected_images.len()];
+        if self.current_img_idx >= self.selected_images.len() {
+            self.current_img_idx = self.selected_images.len().saturating_sub(1);
+        }
+        if self.selected_images.is_empty() {
+            return Task::none();
+        }
+
+        let generation = self.analysis_generation;
+        let paths = self.selected_images.clone();
+        let stats_task = Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    paths
+                        .iter()
+                        .map(|image| ImageStats::compute(image.path()))
+                        .collect()
+                })
+                .await
+                .unwrap_or_default()
+            },
+            move |stats| Message::PhotoStatsComputed(generation, stats),
+        );
+
+        let engine_tasks = self
+            .selected_images
+            .iter()
+            .enumerate()
+            .map(|(index, path)| {
+                Task::done(Message::PythonInput(format!(
+                    "analyze reg-{}-{} {}",
+                    generation,
+                    index,
+                    encode_path(&path.path().to_string_lossy())
+                )))
+            });
+
+        Task::batch(std::iter::once(stats_task).chain(engine_tasks))
+    }
+
+    /// Thumbnails of every face on a group photo; clicking one marks it as the suspect.
+    fn face_picker(&self) -> Element<'static, Message> {
+        let photo = self.current_img_idx;
+        let Some(analysis) = self.photo_analysis.get(photo) else {
+            return space().height(0.0).into();
+        };
+        if analysis.face_crops.is_empty() {
+            return space().height(0.0).into();
+        }
+
+        let faces = row(analysis.face_crops.iter().enumerate().map(|(face, crop)| {
+            let selected = analysis.chosen_face == Some(face);
+            button(image(crop.clone()).width(64.0).height(64.0))
+                .padding(3)
+                .on_press(Message::SelectFace(photo, face))
+                .style(move |_theme: &Theme, _status| button::Style {
+                    background: Some(Background::Color(Color::from_rgba(0.0, 0.0, 0.0, 0.4))),
+                    border: Border {
+                        color: if selected {
+                            Color::from_rgb(0.4, 0.9, 0.5)
+                        } else {
+                            Color::from_rgba(1.0, 1.0, 1.0, 0.2)
+                        },
+                        width: if selected { 2.0 } else { 1.0 },
+                        radius: 6.0.into(),
+                    },
+                    ..Default::default()
+                })
+                .into()
+        }))
+        .spacing(8);
+
+        column![
+            space().height(10.0),
+            GlassInputLabel::new("Which face is the suspect?").size(12),
+            space().height(6.0),
+            scrollable(faces).direction(scrollable::Direction::Horizontal(
+                scrollable::Scrollbar::default()
+            )),
+        ]
+        .into()
+    }
+
+    /// Photos with no face, or several faces and no pick, would pollute the
+    /// gallery; so might any that haven't been graded, or couldn't be.
+    fn has_blocking_photos(&self) -> bool {
+        self.photo_analysis.len() != self.selected_images.len()
+            || self
+                .photo_analysis
+                .iter()
+                .any(|analysis| match analysis.quality() {
+                    Some(quality) => quality.grade.is_blocking(),
+                    None => true,
+                })
+    }
+
+    /// Whether some photo is still waiting on its measurements or faces.
+    fn is_analyzing(&self) -> bool {
+        self.photo_analysis
+            .iter()
+            .any(|analysis| !analysis.is_complete())
+    }
+
     fn image_viewer_logic(&self) -> Element<'static, Message> {
-        GlassImageViewer::new(self.selected_images.clone(), self.current_img_idx)
-            .view(Message::NextImage, Message::PrevImage)
+        let viewer =
+            GlassImageViewer::from_handles(self.selected_handles.clone(), self.current_img_idx);
+
+        let viewer = match self.photo_analysis.get(self.current_img_idx) {
+            Some(PhotoAnalysis {
+                stats: Some(Err(e)),
+                ..
+            }) => viewer
+                .badge("UNCHECKED", Color::from_rgb(0.7, 0.7, 0.7))
+                .notes(vec![format!("Could not measure photo: {}", e)]),
+            Some(analysis) => match analysis.quality() {
+                Some(quality) => {
+                    let mut notes = quality.issues.clone();
+                    if let Some((yaw, pitch)) = quality.pose {
+                        notes.push(format!(
+                            "Sharpness {:.0} · Brightness {:.0} · Yaw {:.0}° · Pitch {:.0}°",
+                            quality.stats.sharpness, quality.stats.brightness, yaw, pitch
+                        ));
+                    }
+                    viewer
+                        .badge(quality.grade.label(), quality.grade.color())
+                        .notes(notes)
+                }
+                None => viewer.badge("ANALYZING...", Color::from_rgb(0.7, 0.7, 0.7)),
+            },
+            None => viewer,
+        };
+
+        column![
+            viewer.view(Message::NextImage, Message::PrevImage),
+            self.face_picker(),
+            space().height(10.0),
+            row![
+                text(format!(
+                    "Photo {} of {}",
+                    self.current_img_idx + 1,
+                    self.selected_images.len()
+                ))
+                .size(12),
+                space().width(Length::Fill),
+                GlassButton::new("Exclude Photo")
+                    .on_press(Message::ExcludePhoto(self.current_img_idx)),
+            ]
+            .align_y(Alignment::Center),
+        ]
+        .into()
+    }
+
+    /// One editable row per offence, plus controls to add rows and offence types.
+    fn offences_editor(&self) -> Element<'_, Message> {
+        let statuses: Vec<StatusChoice> = offence::STATUSES.into_iter().map(StatusChoice).collect();
+        let error_color = Color::from_rgb(0.9, 0.4, 0.4);
+
+        let rows = column(self.offences.iter().enumerate().map(|(i, draft)| {
+            let mut problems = Vec::new();
+            if draft.type_error {
+                problems.push("Pick an offence type");
+            }
+            if draft.date_error {
+                problems.push("Date must be YYYY-MM-DD");
+            }
+
+            column![
+                row![
+                    pick_list(
+                        &self.offence_types[..],
+                        draft.offence_type.as_ref(),
+                        move |t| Message::OffenceTypeSelected(i, t)
+                    )
+                    .placeholder("Offence type")
+                    .width(Length::Fill),
+                    pick_list(
+                        statuses.clone(),
+                        Some(StatusChoice(draft.status)),
+                        move |s| { Message::OffenceStatusSelected(i, s.0) }
+                    )
+                    .width(Length::Fixed(140.0)),
+                    GlassButton::new("Remove").on_press(Message::RemoveOffence(i)),
+                ]
+                .spacing(8)
+                .align_y(Alignment::Center),
+                row![
+                    GlassTextInput::new("Date (YYYY-MM-DD)", &draft.date)
+                        .on_input(move |v| Message::OffenceDateChanged(i, v)),
+                    GlassTextInput::new("Location", &draft.location)
+                        .on_input(move |v| Message::OffenceLocationChanged(i, v)),
+                    GlassTextInput::new("Case / FIR No.", &draft.case_number)
+                        .on_input(move |v| Message::OffenceCaseChanged(i, v)),
+                ]
+                .spacing(8),
+                text(problems.join(" · ")).size(12).color(error_color),
+            ]
+            .spacing(6)
+            .into()
+        }))
+        .spacing(14);
+
+        let type_status: Element<Message> = match &self.offence_type_error {
+            Some(e) => text(e.clone()).size(12).color(error_color).into(),
+            None => space().height(0.0).into(),
+        };
+
+        column![
+            GlassInputLabel::new("Offence History").size(12),
+            space().height(8.0),
+            rows,
+            space().height(8.0),
+            GlassButton::new("Add Offence").on_press(Message::AddOffence),
+            space().height(12.0),
+            row![
+                GlassTextInput::new("New offence type", &self.new_offence_type)
+                    .on_input(Message::NewOffenceTypeChanged)
+                    .on_submit(Message::AddOffenceType),
+                GlassButton::new("Add Type").on_press(Message::AddOffenceType),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+            type_status,
+        ]
+        .into()
     }
 
     fn field_group<'a>(
@@ -238,3 +1361,23 @@ impl RegistryPage {
         .into()
     }
 }
+
+fn face_thumbnails(path: &Path, faces: &[FaceBox]) -> Vec<image::Handle> {
+    let Ok(photo) = load_image(path) else {
+        return Vec::new();
+    };
+
+    faces
+        .iter()
+        .map(|face| {
+            let crop = face.thumbnail(&photo, 96);
+            image::Handle::from_rgba(crop.width(), crop.height(), crop.into_raw())
+        })
+        .collect()
+}
+
+/// Splits a `{prefix}{generation}-{index}` engine request tag.
+fn parse_tag(tag: &str, prefix: &str) -> Option<(u32, usize)> {
+    let (generation, index) = tag.strip_prefix(prefix)?.split_once('-')?;
+    Some((generation.parse().ok()?, index.parse().ok()?))
+}
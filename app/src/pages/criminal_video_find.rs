@@ -1,13 +1,19 @@
+use super::review_queue::verdict_panel;
+use crate::components::FaceComparison;
 use crate::components::GlassButton;
 use crate::components::GlassInputLabel;
 use crate::components::VideoViewer;
-use crate::database::CriminalDB;
-use crate::entities::criminal;
+use crate::database::{CriminalDB, OffenceEntry};
+use crate::entities::{criminal, identification};
+use crate::error::{self, AppError};
+use crate::face_index::MATCH_THRESHOLD;
+use crate::identify::{Source, NOT_FOUND};
+use crate::workspace::TempFile;
 use crate::{Message, Page};
 
 use iced::widget::container;
 use iced::{
-    widget::{column, row, text, Space},
+    widget::{column, image, row, text, Space},
     Alignment, Color, Element, Length, Task,
 };
 use iced_video_player::Video;
@@ -19,10 +25,18 @@ pub struct VideoFindPage {
     pub selected_video: Option<String>,
     pub video: Option<Video>,
     pub is_scanning: bool,
+    pub not_found: bool, // the scan finished without a match
     pub show_details: bool,
     pub identified_data: Option<criminal::Model>,
-    pub suspect_photos: Vec<String>, // File paths in project_root/temp_identify
+    pub identified_aliases: Vec<String>,
+    pub identified_offences: Vec<OffenceEntry>,
+    pub suspect_photos: Vec<image::Handle>, // thumbnails of the registered photos
     pub current_photo_index: usize,
+    pub probe_frame: Option<TempFile>, // frame the engine matched on, if it saved one
+    pub comparison: Option<FaceComparison>,
+    pub pending_review: Option<identification::Model>,
+    pub review_comment: String,
+    pub match_threshold: f32, // from the [matching] config
 }
 impl Default for VideoFindPage {
     fn default() -> Self {
@@ -30,15 +44,30 @@ impl Default for VideoFindPage {
             selected_video: None,
             video: None,
             is_scanning: false,
+            not_found: false,
             show_details: false,
             suspect_photos: Vec::new(),
             current_photo_index: 0,
             identified_data: None,
+            identified_aliases: Vec::new(),
+            identified_offences: Vec::new(),
+            probe_frame: None,
+            comparison: None,
+            pending_review: None,
+            review_comment: String::new(),
+            match_threshold: MATCH_THRESHOLD,
         }
     }
 }
 
 impl VideoFindPage {
+    pub fn new(match_threshold: f32) -> Self {
+        Self {
+            match_threshold,
+            ..Self::default()
+        }
+    }
+
     pub fn update(&mut self, message: Message, db: Option<Arc<CriminalDB>>) -> Task<Message> {
         match message {
             Message::FilesSelected(paths) => {
@@ -46,59 +75,91 @@ impl VideoFindPage {
                     let path_str = path.to_string_lossy().to_string();
                     let path_buf = PathBuf::from(&path_str);
                     if let Ok(url) = Url::from_file_path(&path_buf) {
-                        if let Ok(video) = Video::new(&url) {
-                            self.selected_video = Some(path_str.clone());
-                            self.video = Some(video);
-                            self.is_scanning = true;
-                            self.show_details = false;
-                            self.identified_data = None;
-                            self.suspect_photos = Vec::new();
-                            return Task::done(Message::IdentifyCriminalVideo(path_str));
-                        }
+                        let video = match Video::new(&url) {
+                            Ok(video) => video,
+                            Err(e) => return error::report(AppError::file(path_buf, e), None),
+                        };
+                        self.selected_video = Some(path_str.clone());
+                        self.video = Some(video);
+                        self.is_scanning = true;
+                        self.not_found = false;
+                        self.show_details = false;
+                        self.identified_data = None;
+                        self.suspect_photos = Vec::new();
+                        self.probe_frame = None;
+                        self.comparison = None;
+                        self.pending_review = None;
+                        self.review_comment.clear();
+                        return Task::done(Message::Identify(Source::Video, path_str));
                     }
                 }
             }
 
-            Message::Identity(criminal_id_str) => {
-                let id = criminal_id_str.parse::<u32>().unwrap_or(0);
-                if let Some(database) = db {
-                    return Task::perform(
-                        async move { database.get_criminal_with_photos(id).await },
-                        |result| match result {
-                            Ok(Some((model, photos))) => {
-                                Message::IdentityDataLoadedWithPhotos(model, photos)
-                            }
-                            _ => Message::IdentityError("Record not found".to_string()),
-                        },
-                    );
+            Message::ProbeFrameSaved(path) => self.probe_frame = Some(path),
+
+            Message::IdentificationRecorded(Ok(pending)) => {
+                self.pending_review = Some(pending);
+                self.review_comment.clear();
+            }
+
+            Message::IdentificationReviewed(Ok(reviewed))
+                if self
+                    .pending_review
+                    .as_ref()
+                    .is_some_and(|p| p.identification_id == reviewed.identification_id) =>
+            {
+                self.pending_review = Some(reviewed);
+            }
+
+            Message::ReviewCommentChanged(id, comment)
+                if self
+                    .pending_review
+                    .as_ref()
+                    .is_some_and(|p| p.identification_id == id) =>
+            {
+                self.review_comment = comment;
+            }
+
+            Message::FacesDetected(..)
+            | Message::ComparisonProbeReady(..)
+            | Message::ComparisonScores(..)
+            | Message::ComparisonPhotosLoaded(..)
+            | Message::SelectComparisonPhoto(_)
+            | Message::ComparisonZoomChanged(_) => {
+                if let Some(comparison) = self.comparison.as_mut() {
+                    return comparison.update(message);
                 }
             }
 
-            Message::IdentityDataLoadedWithPhotos(model, photos) => {
+            Message::Identified(_, Ok(record)) => {
                 self.is_scanning = false;
                 self.show_details = true;
-                self.identified_data = Some(model);
+                let comparison = self.probe_frame.as_ref().map(|frame| {
+                    FaceComparison::start(
+                        frame.to_string(),
+                        record.criminal.criminal_id,
+                        self.match_threshold,
+                        db.clone(),
+                    )
+                });
+                self.identified_data = Some(record.criminal);
+                self.identified_aliases = record.aliases;
+                self.identified_offences = record.offences;
                 self.current_photo_index = 0;
 
-                // Prepare temp directory
-                let temp_dir = std::path::Path::new("temp_identify");
-                let _ = std::fs::create_dir_all(temp_dir);
+                self.suspect_photos = record.photos;
 
-                // Save photos to disk and store paths
-                self.suspect_photos = photos
-                    .into_iter()
-                    .enumerate()
-                    .filter_map(|(i, photo_model)| {
-                        let file_name = format!("suspect_{}_{}.jpg", photo_model.criminal_id, i);
-                        let file_path = temp_dir.join(file_name);
+                if let Some((comparison, task)) = comparison {
+                    self.comparison = Some(comparison);
+                    return task;
+                }
+            }
 
-                        if std::fs::write(&file_path, photo_model.photo).is_ok() {
-                            Some(file_path.to_string_lossy().to_string())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
+            Message::Identified(_, Err(reason)) => {
+                self.is_scanning = false;
+                // Anything else was a failure, reported as a toast
+                self.not_found = reason == NOT_FOUND;
+                self.probe_frame = None;
             }
 
             Message::NextImage => {
@@ -124,33 +185,44 @@ impl VideoFindPage {
     }
     pub fn view(&self) -> Element<'_, Message> {
         // --- LEFT SIDE: 60% Width ---
-        let left_content: Element<Message> = if self.show_details && !self.suspect_photos.is_empty()
-        {
-            // Replace Video with Image Viewer
-            column![
-                text("DATABASE ARCHIVE: REGISTERED PHOTOS")
-                    .size(14)
-                    .color(Color::from_rgba(1.0, 1.0, 1.0, 0.5)),
-                Space::new().height(10.0),
-                crate::components::GlassImageViewer::new(
-                    self.suspect_photos.clone(),
-                    self.current_photo_index
-                )
-                .view(Message::NextImage, Message::PrevImage)
-            ]
-            .align_x(Alignment::Center)
-            .into()
-        } else if let (Some(ref video), Some(ref path)) = (&self.video, &self.selected_video) {
-            VideoViewer::new(video, path.clone()).view()
-        } else {
-            column![
-                GlassInputLabel::new("NO VIDEO SOURCE").size(20),
-                GlassButton::new("Select Video File").on_press(Message::OpenFilePicker),
-            ]
-            .align_x(Alignment::Center)
-            .spacing(15)
-            .into()
-        };
+        let left_content: Element<Message> =
+            if let Some(comparison) = self.comparison.as_ref().filter(|_| self.show_details) {
+                // The frame the engine matched on next to the archive photos
+                column![
+                    text("PROBE FRAME VS DATABASE ARCHIVE")
+                        .size(14)
+                        .color(Color::from_rgba(1.0, 1.0, 1.0, 0.5)),
+                    Space::new().height(10.0),
+                    comparison.view(),
+                ]
+                .align_x(Alignment::Center)
+                .into()
+            } else if self.show_details && !self.suspect_photos.is_empty() {
+                // Replace Video with Image Viewer
+                column![
+                    text("DATABASE ARCHIVE: REGISTERED PHOTOS")
+                        .size(14)
+                        .color(Color::from_rgba(1.0, 1.0, 1.0, 0.5)),
+                    Space::new().height(10.0),
+                    crate::components::GlassImageViewer::from_handles(
+                        self.suspect_photos.clone(),
+                        self.current_photo_index
+                    )
+                    .view(Message::NextImage, Message::PrevImage)
+                ]
+                .align_x(Alignment::Center)
+                .into()
+            } else if let (Some(ref video), Some(ref path)) = (&self.video, &self.selected_video) {
+                VideoViewer::new(video, path.clone()).view()
+            } else {
+                column![
+                    GlassInputLabel::new("NO VIDEO SOURCE").size(20),
+                    GlassButton::new("Select Video File").on_press(Message::OpenFilePicker),
+                ]
+                .align_x(Alignment::Center)
+                .spacing(15)
+                .into()
+            };
 
         let left_side = container(left_content)
             .width(Length::FillPortion(60))
@@ -161,6 +233,8 @@ impl VideoFindPage {
         // --- RIGHT SIDE: 40% Width ---
         let right_content: Element<Message> = if self.is_scanning {
             self.scanning_view()
+        } else if self.not_found {
+            self.not_found_view()
         } else if self.show_details {
             if let Some(data) = &self.identified_data {
                 self.details_view(data)
@@ -181,9 +255,13 @@ impl VideoFindPage {
     }
     fn details_view(&self, data: &criminal::Model) -> Element<'_, Message> {
         column![
-            text("TARGET IDENTIFIED")
+            text("CANDIDATE MATCH")
                 .size(24)
                 .color(Color::from_rgb(0.4, 0.8, 1.0)),
+            match &self.pending_review {
+                Some(pending) => verdict_panel(pending, &self.review_comment),
+                None => Space::new().height(0.0).into(),
+            },
             self.info_field("CRIMINAL ID", data.criminal_id.to_string()),
             self.info_field("NAME", data.name.clone()),
             self.info_field(
@@ -203,6 +281,7 @@ impl VideoFindPage {
                     .clone()
                     .unwrap_or_else(|| "Unknown".to_string())
             ),
+            self.profile_fields(data),
             GlassButton::new("Scan New Video").on_press(Message::OpenFilePicker),
             GlassButton::new("← Main Menu").on_press(Message::GoTo(Page::MainMenu)),
         ]
@@ -211,6 +290,30 @@ impl VideoFindPage {
         .into()
     }
 
+    /// Aliases, offence history and whichever optional attributes the record has.
+    fn profile_fields(&self, data: &criminal::Model) -> Element<'_, Message> {
+        let mut fields = Vec::new();
+        if !self.identified_aliases.is_empty() {
+            fields.push(("ALIASES", self.identified_aliases.join(", ")));
+        }
+        if !self.identified_offences.is_empty() {
+            let history: Vec<String> = self
+                .identified_offences
+                .iter()
+                .map(|o| o.summary())
+                .collect();
+            fields.push(("OFFENCE HISTORY", history.join("\n")));
+        }
+        fields.extend(data.attribute_fields());
+
+        column(
+            fields
+                .into_iter()
+                .map(|(label, value)| self.info_field(label, value)),
+        )
+        .into()
+    }
+
     fn info_field(&self, label: &'static str, value: String) -> Element<'_, Message> {
         column![
             text(label)
@@ -252,6 +355,24 @@ impl VideoFindPage {
         .into()
     }
 
+    fn not_found_view(&self) -> Element<'_, Message> {
+        column![
+            text("NO MATCH IN VIDEO")
+                .size(22)
+                .color(Color::from_rgb(0.9, 0.4, 0.4)),
+            Space::new().height(10.0),
+            text("No face in the footage matches a registered record.")
+                .size(14)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.6)),
+            Space::new().height(20.0),
+            GlassButton::new("Scan New Video").on_press(Message::OpenFilePicker),
+            GlassButton::new("← Main Menu").on_press(Message::GoTo(Page::MainMenu)),
+        ]
+        .align_x(Alignment::Center)
+        .spacing(10)
+        .into()
+    }
+
     fn awaiting_input_view(&self) -> Element<'_, Message> {
         column![
             text("Awaiting Video Feed").color(Color::from_rgba(1.0, 1.0, 1.0, 0.3)),
@@ -1,9 +1,13 @@
-use crate::components::{GlassButton, GlassImageViewer, GlassInputLabel};
-use crate::database::CriminalDB;
-use crate::entities::criminal;
+use super::review_queue::verdict_panel;
+use crate::components::{file_handle, GlassButton, GlassImageViewer, GlassInputLabel};
+use crate::database::{CriminalDB, OffenceEntry};
+use crate::entities::{criminal, identification};
+use crate::error::{self, AppError};
+use crate::identify::{Source, NOT_FOUND};
+use crate::workspace::TempFile;
 use crate::{Message, Page};
 
-use iced::widget::{column, container, row, space, text};
+use iced::widget::{column, container, image, row, scrollable, space, text};
 use iced::{Alignment, Background, Border, Color, Element, Length, Task, Theme};
 use iced_video_player::{Video, VideoPlayer};
 use std::sync::Arc;
@@ -12,11 +16,17 @@ use url::Url;
 
 pub struct WebcamFindPage {
     pub is_webcam_on: bool,
-    pub last_captured_frame: Option<String>,
+    pub last_captured_frame: Option<TempFile>, // kept until the next capture replaces it
+    pub captured_image: Option<image::Handle>, // last_captured_frame read for display
     pub identified_data: Option<criminal::Model>,
+    pub identified_aliases: Vec<String>,
+    pub identified_offences: Vec<OffenceEntry>,
+    pub identified_photos: Vec<image::Handle>, // thumbnails of the registered photos
     pub is_identifying: bool,
     pub not_found: bool,
     pub video_source: Option<Video>,
+    pub pending_review: Option<identification::Model>,
+    pub review_comment: String,
 }
 
 // ... (Default remains the same) ...
@@ -26,10 +36,16 @@ impl WebcamFindPage {
         Self {
             is_webcam_on: false,
             last_captured_frame: None,
+            captured_image: None,
             identified_data: None,
+            identified_aliases: Vec::new(),
+            identified_offences: Vec::new(),
+            identified_photos: Vec::new(),
             is_identifying: false,
             not_found: false,
             video_source: None,
+            pending_review: None,
+            review_comment: String::new(),
         }
     }
     pub fn update(&mut self, message: Message, _db: Option<Arc<CriminalDB>>) -> Task<Message> {
@@ -37,17 +53,19 @@ impl WebcamFindPage {
             Message::ToggleWebcam(on) => {
                 self.is_webcam_on = on;
                 if on {
-                    let camera_uri = Url::parse("v4l2:///dev/video0").unwrap();
-                    self.video_source = Video::new(&camera_uri).ok();
+                    return self.open_feed();
                 } else {
                     self.video_source = None;
                     self.is_identifying = false;
                 }
             }
 
-            Message::IdentityDataLoaded(model) => {
+            Message::Identified(_, Ok(record)) => {
                 self.is_identifying = false;
-                self.identified_data = Some(model);
+                self.identified_data = Some(record.criminal);
+                self.identified_aliases = record.aliases;
+                self.identified_offences = record.offences;
+                self.identified_photos = record.photos;
                 self.not_found = false;
                 self.is_webcam_on = false;
                 self.video_source = None; // Drops camera handle
@@ -58,35 +76,90 @@ impl WebcamFindPage {
                     return Task::done(Message::CaptureWebcamFrame);
                 }
             }
-            Message::WebcamFrameCaptured(path) => {
-                self.last_captured_frame = Some(path.clone());
+            Message::WebcamFrameCaptured(Err(e)) => {
+                // Stop ticking rather than fail every two seconds
+                self.is_identifying = false;
+                self.is_webcam_on = false;
+                self.video_source = None;
+                return error::report(e, Some(Message::ToggleWebcam(true)));
+            }
+            Message::WebcamFrameCaptured(Ok(frame)) => {
+                self.captured_image = Some(file_handle(frame.path()));
                 self.is_identifying = true;
-                return Task::done(Message::PythonInput(format!("identify {}", path)));
+                let path = frame.to_string();
+                self.last_captured_frame = Some(frame);
+                return Task::done(Message::Identify(Source::Webcam, path));
+            }
+
+            Message::IdentificationRecorded(Ok(pending)) => {
+                self.pending_review = Some(pending);
+                self.review_comment.clear();
+            }
+
+            Message::IdentificationReviewed(Ok(reviewed))
+                if self
+                    .pending_review
+                    .as_ref()
+                    .is_some_and(|p| p.identification_id == reviewed.identification_id) =>
+            {
+                self.pending_review = Some(reviewed);
+            }
+
+            Message::ReviewCommentChanged(id, comment)
+                if self
+                    .pending_review
+                    .as_ref()
+                    .is_some_and(|p| p.identification_id == id) =>
+            {
+                self.review_comment = comment;
             }
 
-            Message::IdentityError(_) => {
+            // Frames without a face are skipped and failures reported as a
+            // toast; the next tick tries again
+            Message::Identified(_, Err(reason)) => {
                 self.is_identifying = false;
-                self.not_found = true;
+                self.not_found = reason == NOT_FOUND;
             }
 
             Message::ResetWebcamSearch => {
                 self.identified_data = None;
+                self.identified_photos.clear();
+                self.pending_review = None;
+                self.review_comment.clear();
                 self.not_found = false;
                 self.is_webcam_on = true;
-                let camera_uri = Url::parse("v4l2:///dev/video0").unwrap();
-                self.video_source = Video::new(&camera_uri).ok();
+                return self.open_feed();
             }
 
-            // ... (TickWebcam, WebcamFrameCaptured, Identity handlers remain the same) ...
             _ => {}
         }
         Task::none()
     }
 
+    /// Starts the live preview. Frames are captured separately, so scanning
+    /// carries on without it.
+    fn open_feed(&mut self) -> Task<Message> {
+        let camera_uri = Url::parse("v4l2:///dev/video0").unwrap();
+        match Video::new(&camera_uri) {
+            Ok(video) => {
+                self.video_source = Some(video);
+                Task::none()
+            }
+            Err(e) => {
+                self.video_source = None;
+                error::report(
+                    AppError::Camera(format!("Could not open the live feed: {}", e)),
+                    Some(Message::ToggleWebcam(true)),
+                )
+            }
+        }
+    }
+
     pub fn view(&self) -> Element<'_, Message> {
         let left_content: Element<Message> = if let Some(_) = &self.identified_data {
-            if let Some(path) = &self.last_captured_frame {
-                GlassImageViewer::new(vec![path.clone()], 0).view(Message::None, Message::None)
+            if let Some(frame) = &self.captured_image {
+                GlassImageViewer::from_handles(vec![frame.clone()], 0)
+                    .view(Message::None, Message::None)
             } else {
                 text("Match frame lost").into()
             }
@@ -186,13 +259,18 @@ impl WebcamFindPage {
         .into()
     }
 
-    fn details_view(&self, data: &criminal::Model) -> Element<'static, Message> {
+    fn details_view(&self, data: &criminal::Model) -> Element<'_, Message> {
         let name = data.name.clone();
         column![
-            text("MATCH CONFIRMED")
+            text("CANDIDATE MATCH")
                 .size(24)
                 .color(Color::from_rgb(0.4, 0.9, 0.5)),
-            space().height(30.0),
+            space().height(10.0),
+            match &self.pending_review {
+                Some(pending) => verdict_panel(pending, &self.review_comment),
+                None => space().height(0.0).into(),
+            },
+            space().height(20.0),
             text(name).size(32).color(Color::WHITE),
             space().height(20.0),
             text(format!("Criminal ID: {}", data.criminal_id))
@@ -200,6 +278,41 @@ impl WebcamFindPage {
                 .color(Color::from_rgba(1.0, 1.0, 1.0, 0.5)),
             space().height(10.0),
             text(format!("Violation History: {} counts", data.no_of_crimes)).size(18),
+            space().height(10.0),
+            scrollable(
+                row(self.identified_photos.iter().map(|photo| {
+                    image(photo.clone())
+                        .width(Length::Fixed(72.0))
+                        .height(Length::Fixed(72.0))
+                        .into()
+                }))
+                .spacing(8),
+            )
+            .direction(scrollable::Direction::Horizontal(
+                scrollable::Scrollbar::default()
+            )),
+            space().height(10.0),
+            column(
+                (!self.identified_aliases.is_empty())
+                    .then(|| ("ALIASES", self.identified_aliases.join(", ")))
+                    .into_iter()
+                    .chain(data.attribute_fields())
+                    .map(|(label, value)| {
+                        text(format!("{}: {}", label, value))
+                            .size(14)
+                            .color(Color::from_rgba(1.0, 1.0, 1.0, 0.7))
+                            .into()
+                    })
+            )
+            .spacing(6),
+            space().height(10.0),
+            column(self.identified_offences.iter().map(|offence| {
+                text(offence.summary())
+                    .size(14)
+                    .color(Color::from_rgba(1.0, 1.0, 1.0, 0.7))
+                    .into()
+            }))
+            .spacing(4),
         ]
         .align_x(Alignment::Start)
         .into()
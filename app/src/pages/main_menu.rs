@@ -27,6 +27,13 @@ impl MainMenu {
 
         let webcam_find_btn =
             GlassButton::new("4. Webcam Search").on_press(Message::GoTo(Page::WebcamFind));
+        let merge_btn =
+            GlassButton::new("5. Merge Duplicate Records").on_press(Message::GoTo(Page::Merge));
+        let browser_btn =
+            GlassButton::new("6. Browse Records").on_press(Message::GoTo(Page::Browser));
+        let watchlists_btn =
+            GlassButton::new("7. Watchlists").on_press(Message::GoTo(Page::Watchlists));
+        let review_btn = GlassButton::new("8. Review Queue").on_press(Message::GoTo(Page::Review));
         let sign_in_btn =
             GlassButton::new("4. Operator Sign-In / Logs").on_press(Message::GoTo(Page::SignIn));
 
@@ -35,6 +42,10 @@ impl MainMenu {
             image_find_btn,
             video_find_btn,
             webcam_find_btn,
+            merge_btn,
+            browser_btn,
+            watchlists_btn,
+            review_btn,
             sign_in_btn
         ]
         .spacing(20)
@@ -1,11 +1,19 @@
+mod criminal_browser;
 mod criminal_image_find;
+mod criminal_merge;
 mod criminal_registry;
 mod criminal_video_find;
 mod criminal_webcam_find;
 mod main_menu;
+mod review_queue;
+mod watchlists;
 
-pub use criminal_image_find::ImageFindPage;
+pub use criminal_browser::BrowserPage;
+pub use criminal_image_find::{BatchColumn, ImageFindPage, NO_FACE};
+pub use criminal_merge::MergePage;
 pub use criminal_registry::RegistryPage;
 pub use criminal_video_find::VideoFindPage;
 pub use criminal_webcam_find::WebcamFindPage;
 pub use main_menu::MainMenu;
+pub use review_queue::{verdict_panel, ReviewPage};
+pub use watchlists::WatchlistsPage;
@@ -0,0 +1,390 @@
+use super::criminal_image_find::csv_field;
+use crate::components::{GlassButton, GlassInputLabel, GlassTextInput};
+use crate::database::{CriminalDB, IdentificationEntry};
+use crate::entities::identification;
+use crate::{Message, Page};
+
+use iced::widget::{button, column, container, image, row, scrollable, space, text};
+use iced::{Alignment, Background, Border, Color, Element, Length, Task, Theme};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+const FILTERS: [(Option<&str>, &str); 5] = [
+    (Some(identification::PENDING), "Pending"),
+    (Some(identification::UNSURE), "Unsure"),
+    (Some(identification::REJECTED), "Rejected"),
+    (Some(identification::CONFIRMED), "Confirmed"),
+    (None, "All"),
+];
+
+/// Engine matches waiting for an operator, and the verdicts already given.
+pub struct ReviewPage {
+    pub entries: Vec<IdentificationEntry>,
+    pub filter: Option<&'static str>,
+    pub comments: HashMap<u32, String>,
+    pub error: Option<String>,
+    pub export: Option<Result<PathBuf, String>>,
+}
+
+impl Default for ReviewPage {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            filter: Some(identification::PENDING),
+            comments: HashMap::new(),
+            error: None,
+            export: None,
+        }
+    }
+}
+
+impl ReviewPage {
+    pub fn update(&mut self, message: Message, db: Option<Arc<CriminalDB>>) -> Task<Message> {
+        match message {
+            Message::LoadIdentifications => {
+                let Some(db) = self.connected(db) else {
+                    return Task::none();
+                };
+                let filter = self.filter;
+                return Task::perform(
+                    async move {
+                        db.get_identifications(filter)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::IdentificationsLoaded,
+                );
+            }
+
+            Message::IdentificationsLoaded(Ok(entries)) => {
+                self.error = None;
+                self.entries = entries;
+            }
+            Message::IdentificationsLoaded(Err(e)) => self.error = Some(e),
+
+            Message::ReviewFilterSelected(filter) => {
+                self.filter = filter;
+                return Task::done(Message::LoadIdentifications);
+            }
+
+            Message::ReviewCommentChanged(id, comment) => {
+                self.comments.insert(id, comment);
+            }
+
+            Message::IdentificationReviewed(Ok(reviewed)) => {
+                self.comments.remove(&reviewed.identification_id);
+                // It may no longer belong under the current filter
+                return Task::done(Message::LoadIdentifications);
+            }
+            Message::IdentificationReviewed(Err(e)) => self.error = Some(e),
+
+            Message::ExportRejections => {
+                let Some(db) = self.connected(db) else {
+                    return Task::none();
+                };
+                return Task::perform(
+                    async move {
+                        let folder = rfd::AsyncFileDialog::new().pick_folder().await?;
+                        let folder = folder.path().to_path_buf();
+                        let result =
+                            match db.get_identifications(Some(identification::REJECTED)).await {
+                                Ok(rejected) => tokio::task::spawn_blocking(move || {
+                                    export_rejections(&folder, &rejected)
+                                })
+                                .await
+                                .unwrap_or_else(|e| Err(e.to_string())),
+                                Err(e) => Err(e.to_string()),
+                            };
+                        Some(result)
+                    },
+                    |result| match result {
+                        Some(result) => Message::RejectionsExported(result),
+                        None => Message::None, // dialog cancelled
+                    },
+                );
+            }
+
+            Message::RejectionsExported(result) => self.export = Some(result),
+
+            _ => {}
+        }
+        Task::none()
+    }
+
+    fn connected(&mut self, db: Option<Arc<CriminalDB>>) -> Option<Arc<CriminalDB>> {
+        if db.is_none() {
+            self.error = Some("Database not connected".to_string());
+        }
+        db
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let header = row![
+            GlassButton::new("← Back").on_press(Message::GoTo(Page::MainMenu)),
+            space().width(20.0),
+            GlassInputLabel::new("Review Queue").size(32),
+            space().width(Length::Fill),
+            GlassButton::new("Export Rejections").on_press(Message::ExportRejections),
+        ]
+        .align_y(Alignment::Center);
+
+        let filters = row(FILTERS.into_iter().map(|(filter, label)| {
+            let active = self.filter == filter;
+            button(text(label).size(13))
+                .padding([6, 14])
+                .on_press(Message::ReviewFilterSelected(filter))
+                .style(
+                    move |_theme: &Theme, status: button::Status| button::Style {
+                        background: Some(Background::Color(
+                            if active || matches!(status, button::Status::Hovered) {
+                                Color::from_rgba(0.4, 0.9, 0.5, 0.2)
+                            } else {
+                                Color::from_rgba(0.05, 0.05, 0.05, 0.3)
+                            },
+                        )),
+                        text_color: if active {
+                            Color::from_rgb(0.4, 0.9, 0.5)
+                        } else {
+                            Color::from_rgba(1.0, 1.0, 1.0, 0.7)
+                        },
+                        border: Border {
+                            color: Color::from_rgba(0.4, 0.9, 0.5, if active { 0.6 } else { 0.2 }),
+                            width: 1.0,
+                            radius: 8.0.into(),
+                        },
+                        ..Default::default()
+                    },
+                )
+                .into()
+        }))
+        .spacing(8);
+
+        let status: Element<Message> = match (&self.error, &self.export) {
+            (Some(e), _) => text(e)
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.4, 0.4))
+                .into(),
+            (None, Some(Ok(path))) => text(format!("Rejections exported to {}", path.display()))
+                .size(12)
+                .color(Color::from_rgb(0.4, 0.9, 0.5))
+                .into(),
+            (None, Some(Err(e))) => text(format!("Export failed: {}", e))
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.4, 0.4))
+                .into(),
+            (None, None) => space().height(0.0).into(),
+        };
+
+        let list: Element<Message> = if self.entries.is_empty() {
+            container(text("Nothing to review").color(Color::from_rgba(1.0, 1.0, 1.0, 0.3)))
+                .center_x(Length::Fill)
+                .padding(40)
+                .into()
+        } else {
+            scrollable(column(self.entries.iter().map(|entry| self.entry_row(entry))).spacing(10))
+                .height(Length::Fill)
+                .into()
+        };
+
+        column![
+            header,
+            space().height(16.0),
+            filters,
+            space().height(8.0),
+            status,
+            space().height(8.0),
+            list
+        ]
+        .padding(30)
+        .into()
+    }
+
+    fn entry_row<'a>(&'a self, entry: &'a IdentificationEntry) -> Element<'a, Message> {
+        let identification = &entry.identification;
+        let thumbnail = |bytes: &Option<Vec<u8>>, label: &'static str| -> Element<'a, Message> {
+            let content: Element<Message> = match bytes {
+                Some(bytes) => image(image::Handle::from_bytes(bytes.clone()))
+                    .width(Length::Fixed(96.0))
+                    .height(Length::Fixed(96.0))
+                    .into(),
+                None => container(
+                    text("—")
+                        .size(12)
+                        .color(Color::from_rgba(1.0, 1.0, 1.0, 0.3)),
+                )
+                .center_x(Length::Fixed(96.0))
+                .center_y(Length::Fixed(96.0))
+                .into(),
+            };
+            column![
+                content,
+                text(label)
+                    .size(10)
+                    .color(Color::from_rgba(1.0, 1.0, 1.0, 0.4))
+            ]
+            .spacing(4)
+            .align_x(Alignment::Center)
+            .into()
+        };
+
+        let details = column![
+            text(format!(
+                "#{}  {}",
+                identification.criminal_id, entry.criminal_name
+            ))
+            .size(18)
+            .color(Color::WHITE),
+            text(format!(
+                "{} · score {} · {}",
+                identification.source,
+                identification
+                    .score
+                    .map(|s| format!("{:.2}", s))
+                    .unwrap_or_else(|| "—".to_string()),
+                identification.created_at.format("%Y-%m-%d %H:%M")
+            ))
+            .size(12)
+            .color(Color::from_rgba(1.0, 1.0, 1.0, 0.5)),
+        ]
+        .spacing(4)
+        .width(Length::Fill);
+
+        let comment = self
+            .comments
+            .get(&identification.identification_id)
+            .map(String::as_str)
+            .unwrap_or_default();
+
+        container(
+            row![
+                thumbnail(&identification.probe, "PROBE"),
+                thumbnail(&entry.candidate_photo, "CANDIDATE"),
+                details,
+                container(verdict_panel(identification, comment)).width(Length::Fixed(360.0)),
+            ]
+            .spacing(16)
+            .align_y(Alignment::Center),
+        )
+        .padding(12)
+        .style(|_theme| container::Style {
+            background: Some(Background::Color(Color::from_rgba(0.05, 0.05, 0.05, 0.3))),
+            border: Border {
+                color: Color::from_rgba(1.0, 1.0, 1.0, 0.1),
+                width: 1.0,
+                radius: 8.0.into(),
+            },
+            ..container::Style::default()
+        })
+        .into()
+    }
+}
+
+/// Confirm / Reject / Unsure with a comment while a verdict is open, the
+/// verdict itself afterwards. Unsure results can still be settled.
+pub fn verdict_panel<'a>(
+    identification: &identification::Model,
+    comment: &'a str,
+) -> Element<'a, Message> {
+    let status = identification.status.as_str();
+    let label = text(identification::status_label(status).to_uppercase())
+        .size(12)
+        .color(verdict_color(status));
+
+    if status != identification::PENDING && status != identification::UNSURE {
+        return column![
+            label,
+            text(identification.comment.clone().unwrap_or_default())
+                .size(12)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.6)),
+        ]
+        .spacing(4)
+        .into();
+    }
+
+    let id = identification.identification_id;
+    let buttons = row(identification::VERDICTS.into_iter().map(|verdict| {
+        let caption = match verdict {
+            identification::CONFIRMED => "Confirm",
+            identification::REJECTED => "Reject",
+            _ => "Unsure",
+        };
+        GlassButton::new(caption)
+            .on_press(Message::ReviewIdentification(
+                id,
+                verdict,
+                comment.to_string(),
+            ))
+            .into()
+    }))
+    .spacing(8);
+
+    column![
+        label,
+        GlassTextInput::new("Comment (why, what you checked)", comment)
+            .on_input(move |c| Message::ReviewCommentChanged(id, c)),
+        buttons,
+    ]
+    .spacing(8)
+    .into()
+}
+
+fn verdict_color(status: &str) -> Color {
+    match status {
+        identification::CONFIRMED => Color::from_rgb(0.4, 0.9, 0.5),
+        identification::REJECTED => Color::from_rgb(0.9, 0.4, 0.4),
+        _ => Color::from_rgb(0.9, 0.7, 0.3),
+    }
+}
+
+/// Writes `rejections.csv` and each probe image into `folder`, labelled as
+/// false positives for threshold tuning.
+fn export_rejections(folder: &Path, rejected: &[IdentificationEntry]) -> Result<PathBuf, String> {
+    let probes = folder.join("probes");
+    std::fs::create_dir_all(&probes).map_err(|e| e.to_string())?;
+
+    let mut csv = String::from(
+        "identification_id,criminal_id,name,source,score,label,comment,probe_file,created_at,reviewed_at\n",
+    );
+    for entry in rejected {
+        let identification = &entry.identification;
+        let probe_file = match &identification.probe {
+            Some(bytes) => {
+                let extension = ::image::guess_format(bytes)
+                    .ok()
+                    .and_then(|f| f.extensions_str().first().copied())
+                    .unwrap_or("bin");
+                let name = format!("{}.{}", identification.identification_id, extension);
+                std::fs::write(probes.join(&name), bytes).map_err(|e| e.to_string())?;
+                format!("probes/{}", name)
+            }
+            None => String::new(),
+        };
+
+        let fields = [
+            identification.identification_id.to_string(),
+            identification.criminal_id.to_string(),
+            entry.criminal_name.clone(),
+            identification.source.clone(),
+            identification
+                .score
+                .map(|s| format!("{:.4}", s))
+                .unwrap_or_default(),
+            "false_positive".to_string(),
+            identification.comment.clone().unwrap_or_default(),
+            probe_file,
+            identification.created_at.to_rfc3339(),
+            identification
+                .reviewed_at
+                .map(|t| t.to_rfc3339())
+                .unwrap_or_default(),
+        ];
+        let row: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&row.join(","));
+        csv.push('\n');
+    }
+
+    let path = folder.join("rejections.csv");
+    std::fs::write(&path, csv).map_err(|e| e.to_string())?;
+    Ok(path)
+}
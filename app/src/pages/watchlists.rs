@@ -0,0 +1,402 @@
+use crate::alerts::priority_color;
+use crate::components::{GlassButton, GlassInputLabel, GlassTextInput};
+use crate::database::CriminalDB;
+use crate::entities::watchlist;
+use crate::{Message, Page, WatchlistMember};
+
+use iced::widget::{button, column, container, pick_list, row, scrollable, space, text};
+use iced::{Alignment, Background, Border, Color, Element, Length, Task, Theme};
+use std::sync::Arc;
+
+/// Watchlist priority as offered in the picker.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct PriorityChoice(&'static str);
+
+impl std::fmt::Display for PriorityChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(watchlist::priority_label(self.0))
+    }
+}
+
+pub struct WatchlistsPage {
+    pub watchlists: Vec<watchlist::Model>,
+    pub selected: Option<u32>,
+    pub members: Vec<WatchlistMember>,
+    pub new_name: String,
+    pub new_priority: &'static str,
+    pub new_description: String,
+    pub member_criminal_id: String,
+    pub member_reason: String,
+    pub error: Option<String>,
+}
+
+impl Default for WatchlistsPage {
+    fn default() -> Self {
+        Self {
+            watchlists: Vec::new(),
+            selected: None,
+            members: Vec::new(),
+            new_name: String::new(),
+            new_priority: watchlist::HIGH,
+            new_description: String::new(),
+            member_criminal_id: String::new(),
+            member_reason: String::new(),
+            error: None,
+        }
+    }
+}
+
+impl WatchlistsPage {
+    pub fn update(&mut self, message: Message, db: Option<Arc<CriminalDB>>) -> Task<Message> {
+        match message {
+            Message::LoadWatchlists => {
+                let Some(db) = self.connected(db) else {
+                    return Task::none();
+                };
+                return Task::perform(
+                    async move { db.get_watchlists().await.map_err(|e| e.to_string()) },
+                    Message::WatchlistsLoaded,
+                );
+            }
+
+            Message::WatchlistsLoaded(result) => match result {
+                Ok(watchlists) => {
+                    self.watchlists = watchlists;
+                    if let (Some(id), Some(db)) = (self.selected, db) {
+                        return load_members(db, id);
+                    }
+                }
+                Err(e) => self.error = Some(e),
+            },
+
+            Message::WatchlistNameChanged(value) => {
+                self.new_name = value;
+                self.error = None;
+            }
+
+            Message::WatchlistPrioritySelected(priority) => {
+                self.new_priority = priority;
+            }
+
+            Message::WatchlistDescriptionChanged(value) => {
+                self.new_description = value;
+            }
+
+            Message::CreateWatchlist => {
+                if self.new_name.trim().is_empty() {
+                    self.error = Some("Give the watchlist a name".to_string());
+                    return Task::none();
+                }
+                let Some(db) = self.connected(db) else {
+                    return Task::none();
+                };
+                let name = self.new_name.trim().to_string();
+                let priority = self.new_priority;
+                let description = self.new_description.trim();
+                let description = (!description.is_empty()).then(|| description.to_string());
+                return Task::perform(
+                    async move {
+                        db.create_watchlist(&name, priority, description)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::WatchlistCreated,
+                );
+            }
+
+            Message::WatchlistCreated(result) => match result {
+                Ok(created) => {
+                    self.new_name = String::new();
+                    self.new_description = String::new();
+                    self.error = None;
+                    self.selected = Some(created.watchlist_id);
+                    return Task::done(Message::LoadWatchlists);
+                }
+                Err(e) => self.error = Some(e),
+            },
+
+            Message::SelectWatchlist(id) => {
+                self.selected = Some(id);
+                self.members = Vec::new();
+                self.error = None;
+                let Some(db) = self.connected(db) else {
+                    return Task::none();
+                };
+                return load_members(db, id);
+            }
+
+            Message::WatchlistMembersLoaded(id, result) => {
+                if self.selected != Some(id) {
+                    return Task::none();
+                }
+                match result {
+                    Ok(members) => self.members = members,
+                    Err(e) => self.error = Some(e),
+                }
+            }
+
+            Message::MemberCriminalIdChanged(value) => {
+                self.member_criminal_id = value;
+                self.error = None;
+            }
+
+            Message::MemberReasonChanged(value) => {
+                self.member_reason = value;
+            }
+
+            Message::AddWatchlistMember => {
+                let Some(watchlist_id) = self.selected else {
+                    return Task::none();
+                };
+                let Ok(criminal_id) = self
+                    .member_criminal_id
+                    .trim()
+                    .trim_start_matches('#')
+                    .parse::<u32>()
+                else {
+                    self.error = Some("Enter a record id".to_string());
+                    return Task::none();
+                };
+                let Some(db) = self.connected(db) else {
+                    return Task::none();
+                };
+                let reason = self.member_reason.trim();
+                let reason = (!reason.is_empty()).then(|| reason.to_string());
+                return Task::perform(
+                    async move {
+                        db.add_watchlist_member(watchlist_id, criminal_id, reason)
+                            .await
+                            .map(|_| ())
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::WatchlistMembersChanged,
+                );
+            }
+
+            Message::RemoveWatchlistMember(member_id) => {
+                let Some(db) = self.connected(db) else {
+                    return Task::none();
+                };
+                return Task::perform(
+                    async move {
+                        db.remove_watchlist_member(member_id)
+                            .await
+                            .map_err(|e| e.to_string())
+                    },
+                    Message::WatchlistMembersChanged,
+                );
+            }
+
+            Message::WatchlistMembersChanged(result) => match result {
+                Ok(()) => {
+                    self.member_criminal_id = String::new();
+                    self.member_reason = String::new();
+                    if let (Some(id), Some(db)) = (self.selected, db) {
+                        return load_members(db, id);
+                    }
+                }
+                Err(e) => self.error = Some(e),
+            },
+
+            _ => {}
+        }
+        Task::none()
+    }
+
+    fn connected(&mut self, db: Option<Arc<CriminalDB>>) -> Option<Arc<CriminalDB>> {
+        if db.is_none() {
+            self.error = Some("Database not connected".to_string());
+        }
+        db
+    }
+
+    pub fn view(&self) -> Element<'_, Message> {
+        let header = row![
+            GlassButton::new("← Back").on_press(Message::GoTo(Page::MainMenu)),
+            space().width(20.0),
+            GlassInputLabel::new("Watchlists").size(32),
+        ]
+        .align_y(Alignment::Center);
+
+        let priorities: Vec<PriorityChoice> = watchlist::PRIORITIES
+            .into_iter()
+            .map(PriorityChoice)
+            .collect();
+        let create = column![
+            GlassInputLabel::new("New Watchlist").size(12),
+            space().height(8.0),
+            GlassTextInput::new("Name, e.g. Wanted - Absconding", &self.new_name)
+                .on_input(Message::WatchlistNameChanged),
+            space().height(8.0),
+            GlassTextInput::new("Description (optional)", &self.new_description)
+                .on_input(Message::WatchlistDescriptionChanged),
+            space().height(8.0),
+            row![
+                pick_list(priorities, Some(PriorityChoice(self.new_priority)), |p| {
+                    Message::WatchlistPrioritySelected(p.0)
+                })
+                .width(Length::Fixed(140.0)),
+                GlassButton::new("Create").on_press(Message::CreateWatchlist),
+            ]
+            .spacing(8)
+            .align_y(Alignment::Center),
+        ];
+
+        let list = scrollable(
+            column(
+                self.watchlists
+                    .iter()
+                    .map(|watchlist| self.watchlist_row(watchlist)),
+            )
+            .spacing(6),
+        )
+        .height(Length::Fill);
+
+        let left = column![create, space().height(20.0), list].width(Length::FillPortion(40));
+
+        let right = container(
+            match self
+                .selected
+                .and_then(|id| self.watchlists.iter().find(|w| w.watchlist_id == id))
+            {
+                Some(watchlist) => self.members_view(watchlist),
+                None => container(
+                    text("Select a watchlist").color(Color::from_rgba(1.0, 1.0, 1.0, 0.3)),
+                )
+                .center_x(Length::Fill)
+                .center_y(Length::Fill)
+                .into(),
+            },
+        )
+        .width(Length::FillPortion(60))
+        .height(Length::Fill)
+        .padding(20);
+
+        let error: Element<Message> = match &self.error {
+            Some(e) => text(e)
+                .size(12)
+                .color(Color::from_rgb(0.9, 0.4, 0.4))
+                .into(),
+            None => space().height(0.0).into(),
+        };
+
+        column![
+            header,
+            space().height(10.0),
+            error,
+            space().height(10.0),
+            row![left, right].spacing(20)
+        ]
+        .padding(30)
+        .into()
+    }
+
+    fn watchlist_row<'a>(&self, watchlist: &'a watchlist::Model) -> Element<'a, Message> {
+        let selected = self.selected == Some(watchlist.watchlist_id);
+        let color = priority_color(&watchlist.priority);
+
+        button(
+            column![
+                text(watchlist.name.as_str()).size(16).color(Color::WHITE),
+                text(watchlist::priority_label(&watchlist.priority).to_uppercase())
+                    .size(11)
+                    .color(color),
+            ]
+            .spacing(2),
+        )
+        .width(Length::Fill)
+        .padding([8, 12])
+        .on_press(Message::SelectWatchlist(watchlist.watchlist_id))
+        .style(move |_theme: &Theme, status: button::Status| {
+            let highlight = selected || matches!(status, button::Status::Hovered);
+            button::Style {
+                background: Some(Background::Color(if highlight {
+                    Color { a: 0.15, ..color }
+                } else {
+                    Color::from_rgba(0.05, 0.05, 0.05, 0.3)
+                })),
+                border: Border {
+                    color: Color {
+                        a: if selected { 0.6 } else { 0.2 },
+                        ..color
+                    },
+                    width: 1.0,
+                    radius: 8.0.into(),
+                },
+                ..Default::default()
+            }
+        })
+        .into()
+    }
+
+    fn members_view<'a>(&'a self, watchlist: &'a watchlist::Model) -> Element<'a, Message> {
+        let add = row![
+            GlassTextInput::new("Record id", &self.member_criminal_id)
+                .on_input(Message::MemberCriminalIdChanged),
+            GlassTextInput::new("Reason, e.g. absconding", &self.member_reason)
+                .on_input(Message::MemberReasonChanged)
+                .on_submit(Message::AddWatchlistMember),
+            GlassButton::new("Add").on_press(Message::AddWatchlistMember),
+        ]
+        .spacing(8)
+        .align_y(Alignment::Center);
+
+        let members = scrollable(
+            column(self.members.iter().map(|(member, criminal)| {
+                row![
+                    column![
+                        text(format!("#{}  {}", criminal.criminal_id, criminal.name))
+                            .size(16)
+                            .color(Color::WHITE),
+                        text(format!(
+                            "{} · added {}",
+                            member.reason.as_deref().unwrap_or("No reason given"),
+                            member.added_at.format("%Y-%m-%d")
+                        ))
+                        .size(12)
+                        .color(Color::from_rgba(1.0, 1.0, 1.0, 0.5)),
+                    ]
+                    .spacing(2)
+                    .width(Length::Fill),
+                    GlassButton::new("Remove")
+                        .on_press(Message::RemoveWatchlistMember(member.member_id)),
+                ]
+                .align_y(Alignment::Center)
+                .into()
+            }))
+            .spacing(10),
+        )
+        .height(Length::Fill);
+
+        column![
+            text(watchlist.name.as_str()).size(24).color(Color::WHITE),
+            text(format!(
+                "{} priority · {} people",
+                watchlist::priority_label(&watchlist.priority),
+                self.members.len()
+            ))
+            .size(13)
+            .color(priority_color(&watchlist.priority)),
+            text(watchlist.description.as_deref().unwrap_or(""))
+                .size(13)
+                .color(Color::from_rgba(1.0, 1.0, 1.0, 0.6)),
+            space().height(16.0),
+            add,
+            space().height(16.0),
+            members,
+        ]
+        .spacing(4)
+        .into()
+    }
+}
+
+fn load_members(db: Arc<CriminalDB>, watchlist_id: u32) -> Task<Message> {
+    Task::perform(
+        async move {
+            db.get_watchlist_members(watchlist_id)
+                .await
+                .map_err(|e| e.to_string())
+        },
+        move |result| Message::WatchlistMembersLoaded(watchlist_id, result),
+    )
+}
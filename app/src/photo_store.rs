@@ -0,0 +1,122 @@
+//! Content-addressed photo files.
+//!
+//! With `[storage] photo_dir` set, enrolled photos are kept here instead of in
+//! `criminal_photos.photo`: each file is named after the SHA-256 of its bytes,
+//! under a folder for the first two hex digits, so the same photo is stored
+//! once however often it's enrolled and a file can be checked against its name.
+//! With encryption on the files are sealed; the name is still the hash of the
+//! plaintext.
+
+use crate::vault;
+use sha2::{Digest, Sha256};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+static NEXT_TEMP_ID: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone)]
+pub struct PhotoStore {
+    root: PathBuf,
+}
+
+impl PhotoStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Hex SHA-256 of `bytes`, the name the photo is stored under.
+    pub fn hash(bytes: &[u8]) -> String {
+        hex::encode(Sha256::digest(bytes))
+    }
+
+    pub fn path(&self, hash: &str) -> PathBuf {
+        self.root.join(&hash[..2.min(hash.len())]).join(hash)
+    }
+
+    pub fn contains(&self, hash: &str) -> bool {
+        self.path(hash).is_file()
+    }
+
+    /// Stores `bytes` unless an identical photo is already there; returns its hash.
+    pub async fn put(&self, bytes: &[u8]) -> io::Result<String> {
+        let hash = Self::hash(bytes);
+        let path = self.path(&hash);
+        if path.is_file() {
+            return Ok(hash);
+        }
+        self.write(&hash, bytes).await?;
+        Ok(hash)
+    }
+
+    /// Writes the photo stored under `hash` again, e.g. sealed with a new key.
+    /// `bytes` must be the photo's plaintext.
+    pub async fn rewrite(&self, hash: &str, bytes: &[u8]) -> io::Result<()> {
+        if Self::hash(bytes) != hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("bytes don't match stored photo {}", hash),
+            ));
+        }
+        self.write(hash, bytes).await
+    }
+
+    /// The photo stored under `hash`, refusing a file whose content no longer
+    /// matches its name.
+    pub async fn get(&self, hash: &str) -> io::Result<Vec<u8>> {
+        let bytes = vault::open(tokio::fs::read(self.path(hash)).await?)?;
+        if Self::hash(&bytes) != hash {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("stored photo {} is corrupt", hash),
+            ));
+        }
+        Ok(bytes)
+    }
+
+    /// Hashes of every stored photo.
+    pub fn hashes(&self) -> io::Result<Vec<String>> {
+        let mut hashes = Vec::new();
+        let shards = match std::fs::read_dir(&self.root) {
+            Ok(shards) => shards,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(hashes),
+            Err(e) => return Err(e),
+        };
+        for shard in shards.flatten().filter(|e| e.path().is_dir()) {
+            for file in std::fs::read_dir(shard.path())?.flatten() {
+                let name = file.file_name().to_string_lossy().to_string();
+                if name.len() == 64 && name.bytes().all(|b| b.is_ascii_hexdigit()) {
+                    hashes.push(name);
+                }
+            }
+        }
+        Ok(hashes)
+    }
+
+    pub async fn remove(&self, hash: &str) -> io::Result<()> {
+        tokio::fs::remove_file(self.path(hash)).await
+    }
+
+    async fn write(&self, hash: &str, bytes: &[u8]) -> io::Result<()> {
+        let path = self.path(hash);
+        let dir = path.parent().unwrap_or(&self.root);
+        tokio::fs::create_dir_all(dir).await?;
+        // Written aside and renamed, so a file under a hash is always complete
+        let temp = dir.join(format!(
+            ".{}-{}-{}.tmp",
+            hash,
+            std::process::id(),
+            NEXT_TEMP_ID.fetch_add(1, Ordering::Relaxed)
+        ));
+        tokio::fs::write(&temp, vault::seal(bytes.to_vec())).await?;
+        if let Err(e) = tokio::fs::rename(&temp, &path).await {
+            let _ = tokio::fs::remove_file(&temp).await;
+            return Err(e);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+//! Normalizes photos before the engine sees them.
+//!
+//! The engine reads paths with `cv2.imread`, which ignores EXIF orientation,
+//! chokes on some formats and is slow on huge images. Everything picked in
+//! the UI goes through `prepare_image` first: corrupt or unsupported files
+//! are rejected here, and anything that needs fixing is re-encoded as JPEG.
+//! Re-encoded copies go in the temp workspace, sealed when encryption is on;
+//! the engines and `load_image` open them.
+
+use crate::vault;
+use crate::workspace::{self, TempFile};
+use image::imageops::FilterType;
+use image::{DynamicImage, ImageFormat};
+use std::fmt;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+/// Extensions offered by the file pickers for photos.
+pub const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png", "webp", "bmp", "tif", "tiff", "gif"];
+
+/// Longest side, in pixels, an image is allowed to keep.
+pub const MAX_DIMENSION: u32 = 2048;
+
+const JPEG_QUALITY: u8 = 92;
+
+/// Longest side, in pixels, of the thumbnail kept with each registered photo.
+pub const THUMBNAIL_SIZE: u32 = 512;
+
+const THUMBNAIL_QUALITY: u8 = 80;
+
+#[derive(Debug)]
+pub enum PreprocessError {
+    Io(PathBuf, std::io::Error),
+    Unsupported(PathBuf, String),
+    Corrupt(PathBuf, String),
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PreprocessError::Io(path, e) => {
+                write!(f, "{}: could not read file ({})", file_name(path), e)
+            }
+            PreprocessError::Unsupported(path, what) => {
+                write!(
+                    f,
+                    "{}: unsupported image format ({})",
+                    file_name(path),
+                    what
+                )
+            }
+            PreprocessError::Corrupt(path, e) => {
+                write!(
+                    f,
+                    "{}: file is damaged or not an image ({})",
+                    file_name(path),
+                    e
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PreprocessError {}
+
+/// An image the engine can read: the picked file itself, or a fixed copy in
+/// the workspace that's deleted once this is dropped.
+#[derive(Debug, Clone)]
+pub enum PreparedImage {
+    Original(PathBuf),
+    Converted(TempFile),
+}
+
+impl PreparedImage {
+    pub fn path(&self) -> &Path {
+        match self {
+            PreparedImage::Original(path) => path,
+            PreparedImage::Converted(file) => file.path(),
+        }
+    }
+}
+
+/// The path, as sent to the engine.
+impl fmt::Display for PreparedImage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path().display())
+    }
+}
+
+/// Decodes `path` with its EXIF orientation applied.
+pub fn load_image(path: &Path) -> Result<DynamicImage, PreprocessError> {
+    load_with_format(path).map(|(image, _, _)| image)
+}
+
+/// Returns an image the engine can read as-is: the original file when it is
+/// already an upright, reasonably sized JPEG/PNG, otherwise a fresh JPEG.
+pub fn prepare_image(path: &Path) -> Result<PreparedImage, PreprocessError> {
+    let (image, format, rotated) = load_with_format(path)?;
+
+    let oversized = image.width().max(image.height()) > MAX_DIMENSION;
+    let engine_readable = matches!(format, ImageFormat::Jpeg | ImageFormat::Png);
+    if engine_readable && !rotated && !oversized {
+        return Ok(PreparedImage::Original(path.to_path_buf()));
+    }
+
+    let image = if oversized {
+        image.resize(MAX_DIMENSION, MAX_DIMENSION, FilterType::Triangle)
+    } else {
+        image
+    };
+
+    let out = workspace::current().allocate("prepared", "jpg");
+    write_jpeg(&image, out.path()).map_err(|e| PreprocessError::Io(out.path().to_path_buf(), e))?;
+    Ok(PreparedImage::Converted(out))
+}
+
+/// `prepare_image` over several files, keeping the order.
+pub fn prepare_images(paths: &[PathBuf]) -> Vec<Result<PreparedImage, String>> {
+    paths
+        .iter()
+        .map(|path| prepare_image(path).map_err(|e| e.to_string()))
+        .collect()
+}
+
+/// An upright JPEG of a stored photo no larger than `THUMBNAIL_SIZE`, for
+/// lists and viewers that don't need the original.
+pub fn make_thumbnail(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    let image = image::load_from_memory(bytes).map_err(|e| e.to_string())?;
+    let image = apply_orientation(image, exif_orientation(bytes));
+    let image = if image.width().max(image.height()) > THUMBNAIL_SIZE {
+        image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+    } else {
+        image
+    };
+
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, THUMBNAIL_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(|e| e.to_string())?;
+    Ok(jpeg)
+}
+
+fn load_with_format(path: &Path) -> Result<(DynamicImage, ImageFormat, bool), PreprocessError> {
+    let bytes = vault::read_input(path).map_err(|e| PreprocessError::Io(path.to_path_buf(), e))?;
+    if bytes.is_empty() {
+        return Err(PreprocessError::Corrupt(
+            path.to_path_buf(),
+            "empty file".to_string(),
+        ));
+    }
+
+    // Trust the content, not the extension (converted HEICs are often misnamed)
+    let format = image::guess_format(&bytes)
+        .map_err(|_| PreprocessError::Unsupported(path.to_path_buf(), sniff_unsupported(&bytes)))?;
+
+    let image = image::load_from_memory_with_format(&bytes, format)
+        .map_err(|e| PreprocessError::Corrupt(path.to_path_buf(), e.to_string()))?;
+
+    let orientation = exif_orientation(&bytes);
+    Ok((
+        apply_orientation(image, orientation),
+        format,
+        orientation > 1,
+    ))
+}
+
+/// EXIF orientation tag (1-8); 1 when absent or unreadable.
+fn exif_orientation(bytes: &[u8]) -> u32 {
+    exif::Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok()
+        .and_then(|data| {
+            data.get_field(exif::Tag::Orientation, exif::In::PRIMARY)
+                .and_then(|field| field.value.get_uint(0))
+        })
+        .filter(|value| (1..=8).contains(value))
+        .unwrap_or(1)
+}
+
+fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+fn write_jpeg(image: &DynamicImage, path: &Path) -> std::io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, JPEG_QUALITY)
+        .encode_image(&image.to_rgb8())
+        .map_err(std::io::Error::other)?;
+    vault::write(path, jpeg)
+}
+
+/// Names formats we recognise but can't decode, for a more useful error.
+fn sniff_unsupported(bytes: &[u8]) -> String {
+    let brand = bytes.get(8..12).unwrap_or_default();
+    if bytes.get(4..8) == Some(b"ftyp") && matches!(brand, b"heic" | b"heix" | b"mif1" | b"hevc") {
+        "HEIC/HEIF, export it as JPEG first".to_string()
+    } else if bytes.get(..4) == Some(&[0x1A, 0x45, 0xDF, 0xA3]) {
+        "video file".to_string()
+    } else {
+        "unknown".to_string()
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.display().to_string())
+}
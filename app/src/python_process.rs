@@ -1,7 +1,12 @@
+use crate::entities::face_embedding;
+use crate::vault;
+use crate::workspace::{self, ENGINE_WORKSPACE_ENV};
 use crate::Message;
+use base64::prelude::*;
 use iced::futures::stream;
 use iced::Subscription;
 
+use std::path::Path;
 use std::sync::{Arc, Mutex as StdMutex, OnceLock};
 use std::{
     io::{BufRead, BufReader, Write},
@@ -11,7 +16,39 @@ use std::{
 
 use tokio::sync::{mpsc, Mutex as TokioMutex};
 
-static PYTHON_RX: OnceLock<Arc<TokioMutex<mpsc::Receiver<String>>>> = OnceLock::new();
+type EngineChannel = (
+    mpsc::Sender<String>,
+    Arc<TokioMutex<mpsc::Receiver<String>>>,
+);
+
+/// Written to the reply channel when the engine's output closes.
+pub const ENGINE_EXITED: &str = "error engine_exited";
+
+/// Prefix of the engine's own error log lines, passed on for the operator.
+pub const ENGINE_LOG_PREFIX: &str = "log ";
+
+/// First line the engine reads: `keys <hex,...>`, or `keys -` without encryption.
+const KEYS_HANDSHAKE: &str = "keys";
+
+// Every engine backend writes its reply lines here; `python_sub` drains it.
+static ENGINE_CHANNEL: OnceLock<EngineChannel> = OnceLock::new();
+
+fn engine_channel() -> &'static EngineChannel {
+    ENGINE_CHANNEL.get_or_init(|| {
+        let (tx, rx) = mpsc::channel::<String>(100);
+        (tx, Arc::new(TokioMutex::new(rx)))
+    })
+}
+
+/// Sender for engine reply lines, surfaced to the app as `Message::PythonOutput`.
+pub fn engine_output() -> mpsc::Sender<String> {
+    engine_channel().0.clone()
+}
+
+/// Next engine reply, for tools that drive the engine without the UI.
+pub async fn next_engine_line() -> Option<String> {
+    engine_channel().1.lock().await.recv().await
+}
 
 pub struct PythonProcess {
     stdin: Arc<StdMutex<ChildStdin>>,
@@ -22,7 +59,7 @@ pub fn python_sub() -> Subscription<Message> {
     Subscription::run(python_stream)
 }
 fn python_stream() -> impl futures::Stream<Item = Message> + Send + 'static {
-    let rx = PYTHON_RX.get().expect("Python RX not initialized").clone();
+    let rx = engine_channel().1.clone();
 
     stream::unfold(rx, |rx| async move {
         // scope the lock tightly
@@ -39,9 +76,23 @@ fn python_stream() -> impl futures::Stream<Item = Message> + Send + 'static {
 }
 
 impl PythonProcess {
-    pub fn spawn(script_path: &str, working_dir: &str) -> std::io::Result<Self> {
-        let mut child = Command::new("prime-run")
-            .arg("python")
+    /// `launcher` wraps the interpreter (e.g. "prime-run" for NVIDIA offload).
+    pub fn spawn(
+        launcher: Option<&str>,
+        script_path: &str,
+        working_dir: &Path,
+    ) -> std::io::Result<Self> {
+        let mut command = match launcher {
+            Some(launcher) => {
+                let mut command = Command::new(launcher);
+                command.arg("python");
+                command
+            }
+            None => Command::new("python"),
+        };
+        command.env(ENGINE_WORKSPACE_ENV, workspace::current().run_dir());
+
+        let mut child = command
             .arg("-u")
             .arg(script_path)
             .current_dir(working_dir)
@@ -50,25 +101,41 @@ impl PythonProcess {
             .stderr(Stdio::piped())
             .spawn()?;
 
-        let stdin = child.stdin.take().unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        // The engine opens sealed temp images and seals the frames it saves.
+        // Keys go over the pipe, where other processes can't read them
+        let keys = vault::current().map_or("-".to_string(), |vault| vault.engine_keys());
+        writeln!(stdin, "{} {}", KEYS_HANDSHAKE, keys)?;
+        stdin.flush()?;
+
         let stdout = child.stdout.take().unwrap();
         let stderr = child.stderr.take().unwrap();
 
-        let (tx, rx) = mpsc::channel::<String>(100);
-        let rx = Arc::new(TokioMutex::new(rx));
-        let _ = PYTHON_RX.set(rx.clone());
+        let tx = engine_output();
+        let log_tx = tx.clone();
 
         thread::spawn(move || {
             let reader = BufReader::new(stdout);
             for line in reader.lines().flatten() {
                 let _ = tx.blocking_send(line);
             }
+            // The process crashed or was killed
+            let _ = tx.blocking_send(ENGINE_EXITED.to_string());
         });
 
         thread::spawn(move || {
             let reader = BufReader::new(stderr);
             for line in reader.lines().flatten() {
                 eprintln!("[PYTHON-ERR] {}", line);
+                // Python's logging writes "LEVEL:logger:message"; info and
+                // warnings stay in the terminal
+                if let Some(message) = line
+                    .strip_prefix("ERROR:")
+                    .or_else(|| line.strip_prefix("CRITICAL:"))
+                {
+                    let message = message.split_once(':').map_or(message, |(_, m)| m);
+                    let _ = log_tx.blocking_send(format!("{}{}", ENGINE_LOG_PREFIX, message));
+                }
             }
         });
 
@@ -83,4 +150,100 @@ impl PythonProcess {
         writeln!(&mut *stdin, "{}", message)?;
         stdin.flush()
     }
+
+    /// Writes a block of commands from a background thread so a large batch
+    /// (e.g. a gallery load while the engine is still starting) can't stall
+    /// the UI on a full pipe. The lock keeps the block contiguous.
+    pub fn send_batch(&self, messages: Vec<String>) {
+        let stdin = self.stdin.clone();
+        thread::spawn(move || {
+            let mut stdin = stdin.lock().unwrap();
+            for message in messages {
+                if writeln!(&mut *stdin, "{}", message).is_err() {
+                    eprintln!("[RUST] Engine pipe closed during batch");
+                    return;
+                }
+            }
+            let _ = stdin.flush();
+        });
+    }
+}
+
+/// `x1,y1,x2,y2` for the engine, or `-` for "first face".
+pub fn format_box(face_box: Option<[f32; 4]>) -> String {
+    face_box
+        .map(|b| format!("{:.1},{:.1},{:.1},{:.1}", b[0], b[1], b[2], b[3]))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// A path as one protocol field: `%`, `&` and whitespace are percent-encoded
+/// so `My Photos/a.jpg` doesn't split into two arguments.
+pub fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c == '%' || c == '&' || c.is_whitespace() {
+            let mut bytes = [0; 4];
+            for byte in c.encode_utf8(&mut bytes).bytes() {
+                encoded.push_str(&format!("%{:02X}", byte));
+            }
+        } else {
+            encoded.push(c);
+        }
+    }
+    encoded
+}
+
+/// Reverses `encode_path`; also takes what the engines send back.
+pub fn decode_path(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| field.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Enrolls a person's saved photos: `add <id> <photo_id>:<box>:<path>&...`.
+/// The engine embeds only the boxed face when one was chosen.
+pub fn add_command(criminal_id: u32, photos: &[(u32, String, Option<[f32; 4]>)]) -> String {
+    let photos = photos
+        .iter()
+        .map(|(photo_id, path, face_box)| {
+            format!(
+                "{}:{}:{}",
+                photo_id,
+                format_box(*face_box),
+                encode_path(path)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("&");
+    format!("add {} {}", criminal_id, photos)
+}
+
+/// Embeddings cross the engine pipe as base64 of little-endian f32.
+pub fn encode_embedding(vector: &[f32]) -> String {
+    BASE64_STANDARD.encode(face_embedding::vector_to_blob(vector))
+}
+
+pub fn decode_embedding(text: &str) -> Option<Vec<f32>> {
+    let bytes = BASE64_STANDARD.decode(text).ok()?;
+    if bytes.is_empty() || bytes.len() % 4 != 0 {
+        return None;
+    }
+    Some(face_embedding::blob_to_vector(&bytes))
 }
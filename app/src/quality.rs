@@ -0,0 +1,384 @@
+//! Enrollment photo checks: face count and size from the engine, blur,
+//! brightness and a rough head pose computed here.
+
+use crate::preprocess::load_image;
+use iced::Color;
+use image::imageops::FilterType;
+use image::{DynamicImage, Rgba, RgbaImage};
+use std::path::Path;
+
+/// Faces smaller than this (shorter box side, px) are too small to enroll well.
+const MIN_FACE_PX: f32 = 80.0;
+const GOOD_FACE_PX: f32 = 112.0;
+/// Variance of the Laplacian on a 512px greyscale copy; lower is blurrier.
+const MIN_SHARPNESS: f32 = 60.0;
+const GOOD_SHARPNESS: f32 = 150.0;
+const MIN_BRIGHTNESS: f32 = 50.0;
+const MAX_BRIGHTNESS: f32 = 210.0;
+/// Degrees away from frontal before a photo is downgraded.
+const MAX_FRONTAL_YAW: f32 = 20.0;
+const MAX_FRONTAL_PITCH: f32 = 20.0;
+
+const STATS_SIZE: u32 = 512;
+
+/// Where the eyes land in an aligned crop, as fractions of its side.
+const ALIGNED_EYE_X: f32 = 0.36;
+const ALIGNED_EYE_Y: f32 = 0.42;
+
+/// One face reported by the engine's `analyze` command.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FaceBox {
+    /// x1, y1, x2, y2 in image pixels
+    pub bbox: [f32; 4],
+    pub score: f32,
+    /// Eyes, nose tip, mouth corners
+    pub landmarks: [[f32; 2]; 5],
+}
+
+impl FaceBox {
+    /// Parses the engine's `x1,y1,x2,y2,score,lx0,ly0,...,lx4,ly4;...` list.
+    pub fn parse_list(text: &str) -> Option<Vec<FaceBox>> {
+        text.split(';')
+            .filter(|face| !face.is_empty() && *face != "-")
+            .map(|face| {
+                let values: Vec<f32> = face
+                    .split(',')
+                    .map(|v| v.parse::<f32>().ok())
+                    .collect::<Option<_>>()?;
+                if values.len() != 15 {
+                    return None;
+                }
+
+                let mut landmarks = [[0.0f32; 2]; 5];
+                for (i, point) in landmarks.iter_mut().enumerate() {
+                    *point = [values[5 + i * 2], values[6 + i * 2]];
+                }
+                Some(FaceBox {
+                    bbox: [values[0], values[1], values[2], values[3]],
+                    score: values[4],
+                    landmarks,
+                })
+            })
+            .collect()
+    }
+
+    /// Inverse of `parse_list`.
+    pub fn encode_list(faces: &[FaceBox]) -> String {
+        if faces.is_empty() {
+            return "-".to_string();
+        }
+
+        faces
+            .iter()
+            .map(|face| {
+                let mut values = face.bbox.to_vec();
+                values.push(face.score);
+                values.extend(face.landmarks.iter().flatten());
+                values
+                    .iter()
+                    .map(|v| format!("{:.2}", v))
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    /// Picks the face in `faces` that best overlaps `bbox`, if any does.
+    pub fn best_match(faces: &[FaceBox], bbox: [f32; 4]) -> Option<usize> {
+        faces
+            .iter()
+            .enumerate()
+            .map(|(index, face)| (index, iou(face.bbox, bbox)))
+            .filter(|&(_, overlap)| overlap > 0.3)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(index, _)| index)
+    }
+
+    /// Square thumbnail of the face with some margin, for the face picker.
+    pub fn thumbnail(&self, image: &DynamicImage, size: u32) -> RgbaImage {
+        face_crop(image, self.bbox, size)
+    }
+
+    /// Square crop rotated and scaled so the eyes sit level at fixed
+    /// positions, for side-by-side comparison with a registered photo.
+    pub fn aligned(&self, image: &DynamicImage, size: u32) -> RgbaImage {
+        let [left_eye, right_eye, ..] = self.landmarks;
+        if distance(left_eye, right_eye) < 1.0 {
+            // Engine sent no landmarks
+            return self.thumbnail(image, size);
+        }
+
+        // Similarity transform taking the output eye positions back to the source
+        let s = size as f32;
+        let dst_left = [s * ALIGNED_EYE_X, s * ALIGNED_EYE_Y];
+        let dst_right = [s * (1.0 - ALIGNED_EYE_X), s * ALIGNED_EYE_Y];
+        let scale = distance(left_eye, right_eye) / distance(dst_left, dst_right);
+        let angle = (right_eye[1] - left_eye[1]).atan2(right_eye[0] - left_eye[0]);
+        let (sin, cos) = (angle.sin() * scale, angle.cos() * scale);
+
+        let source = image.to_rgba8();
+        RgbaImage::from_fn(size, size, |x, y| {
+            let dx = x as f32 - dst_left[0];
+            let dy = y as f32 - dst_left[1];
+            let sx = left_eye[0] + dx * cos - dy * sin;
+            let sy = left_eye[1] + dx * sin + dy * cos;
+            sample_bilinear(&source, sx, sy)
+        })
+    }
+
+    /// Shorter side of the box, in pixels.
+    pub fn size(&self) -> f32 {
+        (self.bbox[2] - self.bbox[0]).min(self.bbox[3] - self.bbox[1])
+    }
+
+    /// Rough (yaw, pitch) in degrees from the five landmarks; (0, 0) is frontal.
+    pub fn pose(&self) -> (f32, f32) {
+        let [left_eye, right_eye, nose, left_mouth, right_mouth] = self.landmarks;
+        let eye_mid = midpoint(left_eye, right_eye);
+        let mouth_mid = midpoint(left_mouth, right_mouth);
+        let interocular = distance(left_eye, right_eye).max(1.0);
+
+        // The nose drifts sideways off the eye-mouth midline as the head turns
+        let midline_x = (eye_mid[0] + mouth_mid[0]) / 2.0;
+        let yaw = ((nose[0] - midline_x) / (interocular * 0.6))
+            .clamp(-1.0, 1.0)
+            .asin()
+            .to_degrees();
+
+        // ...and up or down between eyes and mouth as it tilts (~0.55 frontal)
+        let face_height = (mouth_mid[1] - eye_mid[1]).max(1.0);
+        let pitch = ((nose[1] - eye_mid[1]) / face_height - 0.55) * 90.0;
+
+        (yaw, pitch)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ImageStats {
+    pub sharpness: f32,
+    pub brightness: f32,
+}
+
+impl ImageStats {
+    /// Blur and exposure measurements for the (orientation-corrected) image.
+    pub fn compute(path: &Path) -> Result<Self, String> {
+        let image = load_image(path).map_err(|e| e.to_string())?;
+        let grey = image
+            .resize(STATS_SIZE, STATS_SIZE, FilterType::Triangle)
+            .to_luma8();
+        let (width, height) = grey.dimensions();
+        if width < 3 || height < 3 {
+            return Err("image is too small".to_string());
+        }
+
+        let pixels = grey.as_raw();
+        let brightness = pixels.iter().map(|&p| p as f64).sum::<f64>() / pixels.len() as f64;
+
+        let at = |x: u32, y: u32| pixels[(y * width + x) as usize] as f64;
+        let mut sum = 0.0;
+        let mut sum_sq = 0.0;
+        let mut count = 0.0;
+        for y in 1..height - 1 {
+            for x in 1..width - 1 {
+                let laplacian =
+                    at(x - 1, y) + at(x + 1, y) + at(x, y - 1) + at(x, y + 1) - 4.0 * at(x, y);
+                sum += laplacian;
+                sum_sq += laplacian * laplacian;
+                count += 1.0;
+            }
+        }
+        let mean = sum / count;
+
+        Ok(Self {
+            sharpness: (sum_sq / count - mean * mean) as f32,
+            brightness: brightness as f32,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QualityGrade {
+    Good,
+    Fair,
+    Poor,
+    NoFace,
+    MultipleFaces,
+}
+
+impl QualityGrade {
+    /// Photos that can't be enrolled as-is.
+    pub fn is_blocking(&self) -> bool {
+        matches!(self, QualityGrade::NoFace | QualityGrade::MultipleFaces)
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            QualityGrade::Good => "GOOD",
+            QualityGrade::Fair => "FAIR",
+            QualityGrade::Poor => "POOR",
+            QualityGrade::NoFace => "NO FACE",
+            QualityGrade::MultipleFaces => "MULTIPLE FACES",
+        }
+    }
+
+    pub fn color(&self) -> Color {
+        match self {
+            QualityGrade::Good => Color::from_rgb(0.4, 0.9, 0.5),
+            QualityGrade::Fair => Color::from_rgb(0.95, 0.8, 0.3),
+            QualityGrade::Poor => Color::from_rgb(0.95, 0.55, 0.25),
+            QualityGrade::NoFace | QualityGrade::MultipleFaces => Color::from_rgb(0.9, 0.4, 0.4),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PhotoQuality {
+    pub grade: QualityGrade,
+    pub face_count: usize,
+    pub face_size: Option<f32>,
+    pub pose: Option<(f32, f32)>,
+    pub stats: ImageStats,
+    pub issues: Vec<String>,
+}
+
+impl PhotoQuality {
+    /// Grades a photo from its stats and the faces the engine found in it.
+    /// With several faces, `chosen` is the one the operator picked as the suspect.
+    pub fn assess(stats: ImageStats, faces: &[FaceBox], chosen: Option<usize>) -> Self {
+        let mut issues = Vec::new();
+        let judged = match faces.len() {
+            1 => faces.first(),
+            _ => chosen.and_then(|index| faces.get(index)),
+        };
+
+        let mut grade = match (faces.len(), judged) {
+            (0, _) => QualityGrade::NoFace,
+            (_, Some(_)) => QualityGrade::Good,
+            (_, None) => QualityGrade::MultipleFaces,
+        };
+        // Variants are declared best to worst, so a larger discriminant is worse
+        let mut downgrade = |to: QualityGrade, issue: String| {
+            issues.push(issue);
+            if !grade.is_blocking() && to as u8 > grade as u8 {
+                grade = to;
+            }
+        };
+
+        if let Some(face) = judged {
+            let size = face.size();
+            if size < MIN_FACE_PX {
+                downgrade(QualityGrade::Poor, format!("Face is small ({:.0}px)", size));
+            } else if size < GOOD_FACE_PX {
+                downgrade(
+                    QualityGrade::Fair,
+                    format!("Face is smallish ({:.0}px)", size),
+                );
+            }
+
+            let (yaw, pitch) = face.pose();
+            if yaw.abs() > MAX_FRONTAL_YAW * 2.0 || pitch.abs() > MAX_FRONTAL_PITCH * 2.0 {
+                downgrade(
+                    QualityGrade::Poor,
+                    "Head turned well away from camera".to_string(),
+                );
+            } else if yaw.abs() > MAX_FRONTAL_YAW || pitch.abs() > MAX_FRONTAL_PITCH {
+                downgrade(QualityGrade::Fair, "Not quite frontal".to_string());
+            }
+        }
+
+        if stats.sharpness < MIN_SHARPNESS {
+            downgrade(QualityGrade::Poor, "Blurry".to_string());
+        } else if stats.sharpness < GOOD_SHARPNESS {
+            downgrade(QualityGrade::Fair, "Slightly soft".to_string());
+        }
+
+        if stats.brightness < MIN_BRIGHTNESS {
+            downgrade(QualityGrade::Poor, "Too dark".to_string());
+        } else if stats.brightness > MAX_BRIGHTNESS {
+            downgrade(QualityGrade::Poor, "Overexposed".to_string());
+        }
+
+        match faces.len() {
+            0 => issues.insert(0, "No face detected".to_string()),
+            1 => {}
+            n if judged.is_some() => {
+                issues.insert(0, format!("{} faces detected, using the selected one", n))
+            }
+            n => issues.insert(0, format!("{} faces detected, select the suspect", n)),
+        }
+
+        Self {
+            grade,
+            face_count: faces.len(),
+            face_size: judged.map(FaceBox::size),
+            pose: judged.map(FaceBox::pose),
+            stats,
+            issues,
+        }
+    }
+}
+
+fn midpoint(a: [f32; 2], b: [f32; 2]) -> [f32; 2] {
+    [(a[0] + b[0]) / 2.0, (a[1] + b[1]) / 2.0]
+}
+
+fn distance(a: [f32; 2], b: [f32; 2]) -> f32 {
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2)).sqrt()
+}
+
+fn iou(a: [f32; 4], b: [f32; 4]) -> f32 {
+    let area = |r: [f32; 4]| (r[2] - r[0]).max(0.0) * (r[3] - r[1]).max(0.0);
+    let overlap = [
+        a[0].max(b[0]),
+        a[1].max(b[1]),
+        a[2].min(b[2]),
+        a[3].min(b[3]),
+    ];
+    let inter = area(overlap);
+    let union = area(a) + area(b) - inter;
+    if union > 0.0 {
+        inter / union
+    } else {
+        0.0
+    }
+}
+
+/// Square crop around `bbox` with some margin, resized to `size`.
+pub fn face_crop(image: &DynamicImage, bbox: [f32; 4], size: u32) -> RgbaImage {
+    let [x1, y1, x2, y2] = bbox;
+    let side = (x2 - x1).max(y2 - y1) * 1.3;
+    let cx = (x1 + x2) / 2.0;
+    let cy = (y1 + y2) / 2.0;
+
+    let left = (cx - side / 2.0).max(0.0) as u32;
+    let top = (cy - side / 2.0).max(0.0) as u32;
+    let width = (side as u32).clamp(1, image.width().saturating_sub(left).max(1));
+    let height = (side as u32).clamp(1, image.height().saturating_sub(top).max(1));
+
+    image
+        .crop_imm(left, top, width, height)
+        .resize(size, size, FilterType::Triangle)
+        .to_rgba8()
+}
+
+/// Pixel at a fractional position; transparent outside the image.
+fn sample_bilinear(image: &RgbaImage, x: f32, y: f32) -> Rgba<u8> {
+    let (width, height) = image.dimensions();
+    if x < 0.0 || y < 0.0 || x > (width - 1) as f32 || y > (height - 1) as f32 {
+        return Rgba([0, 0, 0, 0]);
+    }
+
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(width - 1), (y0 + 1).min(height - 1));
+    let (fx, fy) = (x - x0 as f32, y - y0 as f32);
+
+    let mut out = [0u8; 4];
+    for (channel, value) in out.iter_mut().enumerate() {
+        let at = |px: u32, py: u32| image.get_pixel(px, py)[channel] as f32;
+        let top = at(x0, y0) * (1.0 - fx) + at(x1, y0) * fx;
+        let bottom = at(x0, y1) * (1.0 - fx) + at(x1, y1) * fx;
+        *value = (top * (1.0 - fy) + bottom * fy).round() as u8;
+    }
+    Rgba(out)
+}
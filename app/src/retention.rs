@@ -0,0 +1,100 @@
+//! Retention rules, applied at startup and then periodically in the background.
+//!
+//! The database side is `CriminalDB::apply_retention`; this adds the cut-offs
+//! from `[retention]`. The temp images runs that didn't exit cleanly left
+//! behind are swept on their own schedule (`sweep_expired`), since they pile
+//! up whether or not the database is reachable.
+
+use crate::config::RetentionConfig;
+use crate::database::{CriminalDB, RetentionSummary};
+use crate::workspace;
+use chrono::Utc;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// What one pass removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RetentionReport {
+    pub database: RetentionSummary,
+}
+
+impl RetentionReport {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for RetentionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} probe images cleared, {} unconfirmed identifications and {} sightings deleted",
+            self.database.probes_cleared,
+            self.database.unconfirmed_deleted,
+            self.database.sightings_deleted
+        )
+    }
+}
+
+/// Applies the database rules in `config` once.
+pub async fn enforce(
+    db: Arc<CriminalDB>,
+    config: RetentionConfig,
+) -> Result<RetentionReport, String> {
+    let now = Utc::now();
+    let cutoff = |days: u32| (days > 0).then(|| now - chrono::Duration::days(days.into()));
+
+    let database = db
+        .apply_retention(
+            cutoff(config.probe_days),
+            cutoff(config.unconfirmed_days),
+            cutoff(config.sighting_days),
+        )
+        .await
+        .map_err(|e| e.to_string())?;
+
+    Ok(RetentionReport { database })
+}
+
+/// Sweeps temp images older than `config.temp_hours` off the UI thread;
+/// returns how many went.
+pub async fn sweep_expired(config: RetentionConfig) -> Result<usize, String> {
+    if config.temp_hours == 0 {
+        return Ok(0);
+    }
+    let max_age = Duration::from_secs(u64::from(config.temp_hours) * 3600);
+    tokio::task::spawn_blocking(move || sweep_temp_files(max_age))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Deletes temp images older than `max_age` nothing uses any more; returns
+/// how many went.
+pub fn sweep_temp_files(max_age: Duration) -> usize {
+    let mut removed = workspace::current().sweep(max_age);
+
+    // Engines from before the workspace saved their match frames here
+    if let Ok(entries) = std::fs::read_dir(std::env::temp_dir()) {
+        for entry in entries.flatten() {
+            if !entry
+                .file_name()
+                .to_string_lossy()
+                .starts_with("probe_frame_")
+            {
+                continue;
+            }
+            let expired = entry
+                .metadata()
+                .ok()
+                .filter(|meta| meta.is_file())
+                .and_then(|meta| meta.modified().ok())
+                .and_then(|modified| modified.elapsed().ok())
+                .is_some_and(|age| age > max_age);
+            if expired && std::fs::remove_file(entry.path()).is_ok() {
+                removed += 1;
+            }
+        }
+    }
+    removed
+}
@@ -0,0 +1,430 @@
+//! Encryption at rest for photos, probes and embeddings.
+//!
+//! With `[encryption]` configured, photo blobs, thumbnails, probe images and
+//! embedding vectors are sealed with AES-256-GCM before they reach the
+//! database, the photo store or a temp file, and opened again only in memory.
+//! Sealed data starts with `SEALED_MAGIC` and the id of the key that sealed
+//! it, so `rotate_key` can tell what is left to re-encrypt. Once encryption is
+//! on, stored data that isn't sealed is refused: only the tools that seal what
+//! was stored before call `allow_plaintext`. Image files the user picks are
+//! their own and never sealed, so `read_input` takes them as they are.
+//!
+//! The keys are kept one per line as hex, current key first, in a key file or
+//! the OS keyring. The Python engine gets them as the first line on its stdin,
+//! never through its environment, and reads and writes the same format.
+
+use crate::config::EncryptionConfig;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use sha2::{Digest, Sha256};
+use std::io::{self, Read};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+
+/// First bytes of every sealed blob or file.
+pub const SEALED_MAGIC: &[u8; 4] = b"CFE1";
+
+const KEYRING_SERVICE: &str = "criminal-face-recog";
+const KEYRING_USER: &str = "data-keys";
+
+const KEY_LEN: usize = 32;
+const KEY_ID_LEN: usize = 4;
+const NONCE_LEN: usize = 12;
+const HEADER_LEN: usize = SEALED_MAGIC.len() + KEY_ID_LEN + NONCE_LEN;
+
+// Set once at startup by the app and the tools; everything else reads it
+static VAULT: OnceLock<Vault> = OnceLock::new();
+
+// Only `migrate_photos` and `rotate_key` set this
+static PLAINTEXT_ALLOWED: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone)]
+struct DataKey {
+    id: [u8; KEY_ID_LEN],
+    key: [u8; KEY_LEN],
+    cipher: Aes256Gcm,
+}
+
+impl DataKey {
+    fn new(key: [u8; KEY_LEN]) -> Self {
+        let digest = Sha256::digest(key);
+        let mut id = [0; KEY_ID_LEN];
+        id.copy_from_slice(&digest[..KEY_ID_LEN]);
+        Self {
+            id,
+            key,
+            cipher: Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)),
+        }
+    }
+}
+
+/// The data keys: the current one seals, any of them opens.
+#[derive(Clone)]
+pub struct Vault {
+    keys: Vec<DataKey>,
+}
+
+impl std::fmt::Debug for Vault {
+    // Never print key material
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Vault")
+            .field("key_ids", &self.key_ids())
+            .finish()
+    }
+}
+
+impl Vault {
+    /// A vault with one freshly generated key.
+    pub fn generate() -> Self {
+        Self {
+            keys: vec![DataKey::new(Aes256Gcm::generate_key(OsRng).into())],
+        }
+    }
+
+    /// Keys from hex lines, current first. Blank lines and `#` comments are skipped.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut keys = Vec::new();
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let key = hex::decode(line)
+                .ok()
+                .and_then(|bytes| <[u8; KEY_LEN]>::try_from(bytes).ok())
+                .ok_or("data keys must be 64 hex digits each")?;
+            keys.push(DataKey::new(key));
+        }
+        if keys.is_empty() {
+            return Err("no data key found".to_string());
+        }
+        Ok(Self { keys })
+    }
+
+    /// The keys as `parse` reads them.
+    pub fn to_text(&self) -> String {
+        self.keys
+            .iter()
+            .map(|k| hex::encode(k.key) + "\n")
+            .collect()
+    }
+
+    /// A new current key in front of these ones, which still open old data.
+    pub fn rotated(&self) -> Self {
+        let mut keys = Self::generate().keys;
+        keys.extend(self.keys.iter().cloned());
+        Self { keys }
+    }
+
+    /// Only the current key, once nothing is sealed with the others.
+    pub fn current_only(&self) -> Self {
+        Self {
+            keys: self.keys[..1].to_vec(),
+        }
+    }
+
+    /// Hex ids of the keys, current first; safe to show.
+    pub fn key_ids(&self) -> Vec<String> {
+        self.keys.iter().map(|k| hex::encode(k.id)).collect()
+    }
+
+    /// The keys as the Python engine reads them: hex, comma-separated.
+    pub fn engine_keys(&self) -> String {
+        self.keys
+            .iter()
+            .map(|k| hex::encode(k.key))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    pub fn seal(&self, plain: &[u8]) -> Vec<u8> {
+        let current = &self.keys[0];
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        // Only fails on inputs far beyond anything stored here
+        let ciphertext = current
+            .cipher
+            .encrypt(&nonce, plain)
+            .expect("AES-GCM encryption failed");
+
+        let mut sealed = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        sealed.extend_from_slice(SEALED_MAGIC);
+        sealed.extend_from_slice(&current.id);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        sealed
+    }
+
+    /// The plaintext of sealed `data`. Data that was never sealed is an error.
+    pub fn open(&self, data: Vec<u8>) -> io::Result<Vec<u8>> {
+        if !is_sealed(&data) {
+            return Err(invalid(
+                "data is not encrypted; run `rotate_key --no-new-key` to encrypt what was stored before [encryption] was set".to_string(),
+            ));
+        }
+        let id = &data[SEALED_MAGIC.len()..SEALED_MAGIC.len() + KEY_ID_LEN];
+        let key = self.keys.iter().find(|k| k.id == id).ok_or_else(|| {
+            invalid(format!(
+                "sealed with key {}, which is not configured",
+                hex::encode(id)
+            ))
+        })?;
+        let nonce = Nonce::from_slice(&data[HEADER_LEN - NONCE_LEN..HEADER_LEN]);
+        key.cipher
+            .decrypt(nonce, &data[HEADER_LEN..])
+            .map_err(|_| invalid("sealed data is damaged or was tampered with".to_string()))
+    }
+
+    /// `open`, except that data that was never sealed comes back as is.
+    pub fn open_allowing_plaintext(&self, data: Vec<u8>) -> io::Result<Vec<u8>> {
+        if is_sealed(&data) {
+            self.open(data)
+        } else {
+            Ok(data)
+        }
+    }
+
+    /// `data` sealed with the current key, or `None` if it already is. Data
+    /// that was never sealed is sealed too; this is what `rotate_key` does
+    /// to each blob and file.
+    pub fn reseal(&self, data: Vec<u8>) -> io::Result<Option<Vec<u8>>> {
+        if self.is_current(&data) {
+            return Ok(None);
+        }
+        self.open_allowing_plaintext(data)
+            .map(|plain| Some(self.seal(&plain)))
+    }
+
+    /// Whether `data` is sealed with the current key, i.e. needs no rotation.
+    pub fn is_current(&self, data: &[u8]) -> bool {
+        is_sealed(data) && data[SEALED_MAGIC.len()..][..KEY_ID_LEN] == self.keys[0].id
+    }
+
+    /// The configured keys, or `None` when encryption is off. A key file or
+    /// keyring entry that doesn't exist yet is created with a new key, but
+    /// only once `sealed_data_stored` rules out sealed data: a new key can't
+    /// open what the missing one sealed, so then it's an error.
+    pub fn load(
+        config: &EncryptionConfig,
+        sealed_data_stored: impl FnOnce() -> Result<bool, String>,
+    ) -> Result<Option<Self>, String> {
+        if config.keyring {
+            return load_from_keyring(sealed_data_stored).map(Some);
+        }
+        if config.key_file.as_os_str().is_empty() {
+            return Ok(None);
+        }
+
+        match std::fs::read_to_string(&config.key_file) {
+            Ok(text) => Self::parse(&text)
+                .map(Some)
+                .map_err(|e| format!("{}: {}", config.key_file.display(), e)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                let missing = config.key_file.display().to_string();
+                check_nothing_sealed(&missing, sealed_data_stored)?;
+                let vault = Self::generate();
+                vault.save(config)?;
+                println!(
+                    "🔑 Created data key {} in {}; back it up, data sealed with it is lost without it",
+                    vault.key_ids()[0],
+                    config.key_file.display()
+                );
+                Ok(Some(vault))
+            }
+            Err(e) => Err(format!("{}: {}", config.key_file.display(), e)),
+        }
+    }
+
+    /// Writes the keys back where `load` found them.
+    pub fn save(&self, config: &EncryptionConfig) -> Result<(), String> {
+        if config.keyring {
+            return save_to_keyring(&self.to_text());
+        }
+        write_key_file(&config.key_file, &self.to_text())
+            .map_err(|e| format!("{}: {}", config.key_file.display(), e))
+    }
+}
+
+/// Whether `data` was sealed by a vault.
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.len() >= HEADER_LEN && data.starts_with(SEALED_MAGIC)
+}
+
+/// Turns encryption on for this process: from here on `seal`, `open`, `read`
+/// and `write` use `vault`'s keys. They can't be swapped once something is
+/// sealed with them, so a second call is refused and returns false.
+pub fn install(vault: Vault) -> bool {
+    VAULT.set(vault).is_ok()
+}
+
+/// The installed vault, if encryption is on.
+pub fn current() -> Option<&'static Vault> {
+    VAULT.get()
+}
+
+/// `bytes` sealed with the installed vault, or unchanged if there is none.
+pub fn seal(bytes: Vec<u8>) -> Vec<u8> {
+    match current() {
+        Some(vault) => vault.seal(&bytes),
+        None => bytes,
+    }
+}
+
+/// Lets `open` and `read` pass data that was never sealed, for the tools that
+/// encrypt what was stored before encryption was turned on. The app never
+/// calls this.
+pub fn allow_plaintext() {
+    PLAINTEXT_ALLOWED.store(true, Ordering::Relaxed);
+}
+
+/// The plaintext of `bytes`. With a vault installed, data that isn't sealed
+/// is an error unless `allow_plaintext` was called.
+pub fn open(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    match current() {
+        Some(vault) if PLAINTEXT_ALLOWED.load(Ordering::Relaxed) => {
+            vault.open_allowing_plaintext(bytes)
+        }
+        Some(vault) => vault.open(bytes),
+        None if is_sealed(&bytes) => Err(invalid(
+            "data is encrypted but no [encryption] key is configured".to_string(),
+        )),
+        None => Ok(bytes),
+    }
+}
+
+/// `std::fs::read`, opened.
+pub fn read(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    std::fs::read(path).and_then(open)
+}
+
+/// An image file the user picked, as is, or a sealed temp image standing in
+/// for one (a prepared photo or webcam frame), opened.
+pub fn read_input(path: impl AsRef<Path>) -> io::Result<Vec<u8>> {
+    std::fs::read(path).and_then(open_input)
+}
+
+/// `read_input` for bytes already read.
+pub fn open_input(bytes: Vec<u8>) -> io::Result<Vec<u8>> {
+    if is_sealed(&bytes) {
+        open(bytes)
+    } else {
+        Ok(bytes)
+    }
+}
+
+/// `std::fs::write`, sealed.
+pub fn write(path: impl AsRef<Path>, bytes: Vec<u8>) -> io::Result<()> {
+    std::fs::write(path, seal(bytes))
+}
+
+/// Whether any file under `dir` is sealed; a `dir` that doesn't exist holds none.
+pub fn holds_sealed_files(dir: &Path) -> io::Result<bool> {
+    let entries = match std::fs::read_dir(dir) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+        entries => entries?,
+    };
+    for entry in entries {
+        let path = entry?.path();
+        let sealed = if path.is_dir() {
+            holds_sealed_files(&path)?
+        } else {
+            let mut header = Vec::with_capacity(HEADER_LEN);
+            std::fs::File::open(&path)?
+                .take(HEADER_LEN as u64)
+                .read_to_end(&mut header)?;
+            is_sealed(&header)
+        };
+        if sealed {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Before a missing key is replaced: fails if sealed data is stored, or if
+/// that can't be checked.
+fn check_nothing_sealed(
+    missing: &str,
+    sealed_data_stored: impl FnOnce() -> Result<bool, String>,
+) -> Result<(), String> {
+    match sealed_data_stored() {
+        Ok(false) => Ok(()),
+        Ok(true) => Err(format!(
+            "{} is missing but encrypted data is stored; restore the keys from a backup, a new key can't open it",
+            missing
+        )),
+        Err(e) => Err(format!(
+            "{} is missing and stored data could not be checked for encryption: {}",
+            missing, e
+        )),
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+fn write_key_file(path: &Path, text: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    io::Write::write_all(&mut options.open(path)?, text.as_bytes())
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_entry() -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYRING_SERVICE, KEYRING_USER).map_err(|e| format!("keyring: {}", e))
+}
+
+#[cfg(feature = "keyring")]
+fn load_from_keyring(
+    sealed_data_stored: impl FnOnce() -> Result<bool, String>,
+) -> Result<Vault, String> {
+    match keyring_entry()?.get_password() {
+        Ok(text) => Vault::parse(&text).map_err(|e| format!("keyring: {}", e)),
+        Err(keyring::Error::NoEntry) => {
+            let missing = format!("keyring entry {} / {}", KEYRING_SERVICE, KEYRING_USER);
+            check_nothing_sealed(&missing, sealed_data_stored)?;
+            let vault = Vault::generate();
+            save_to_keyring(&vault.to_text())?;
+            println!(
+                "🔑 Created data key {} in the OS keyring ({} / {}); back it up, data sealed with it is lost without it",
+                vault.key_ids()[0],
+                KEYRING_SERVICE,
+                KEYRING_USER
+            );
+            Ok(vault)
+        }
+        Err(e) => Err(format!("keyring: {}", e)),
+    }
+}
+
+#[cfg(feature = "keyring")]
+fn save_to_keyring(text: &str) -> Result<(), String> {
+    keyring_entry()?
+        .set_password(text)
+        .map_err(|e| format!("keyring: {}", e))
+}
+
+#[cfg(not(feature = "keyring"))]
+fn load_from_keyring(
+    _sealed_data_stored: impl FnOnce() -> Result<bool, String>,
+) -> Result<Vault, String> {
+    Err(no_keyring())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn save_to_keyring(_text: &str) -> Result<(), String> {
+    Err(no_keyring())
+}
+
+#[cfg(not(feature = "keyring"))]
+fn no_keyring() -> String {
+    format!(
+        "[encryption] keyring is set but the app was built without the `keyring` feature (entry {} / {})",
+        KEYRING_SERVICE, KEYRING_USER
+    )
+}
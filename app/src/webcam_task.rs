@@ -1,17 +1,15 @@
+use crate::error::AppError;
+use crate::vault;
+use crate::workspace::{self, TempFile};
 use nokhwa::pixel_format::RgbFormat;
 use nokhwa::utils::{CameraIndex, RequestedFormat, RequestedFormatType};
 use nokhwa::Camera;
-use std::path::PathBuf;
 
-pub async fn capture_frame() -> String {
-    // 1. Setup path in your local temp folder
-    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
-    path.push("temp");
-    if !path.exists() {
-        let _ = std::fs::create_dir(&path);
-    }
-    path.push("current_scan.jpg");
-    let path_str = path.to_string_lossy().to_string();
+/// Grabs one frame into a new workspace file; it's deleted once the last
+/// handle to it is dropped.
+pub async fn capture_frame() -> Result<TempFile, AppError> {
+    // 1. A fresh file per frame, so the engine never reads a half-replaced one
+    let file = workspace::current().allocate("frame", "jpg");
 
     // 2. Access the webcam
     let index = CameraIndex::Index(0);
@@ -19,15 +17,32 @@ pub async fn capture_frame() -> String {
         RequestedFormat::new::<RgbFormat>(RequestedFormatType::AbsoluteHighestFrameRate);
 
     // We open the camera, grab one frame, and shut it down to release the device
-    if let Ok(mut camera) = Camera::new(index, requested) {
-        if camera.open_stream().is_ok() {
-            if let Ok(frame) = camera.frame() {
-                let decoded = frame.decode_image::<RgbFormat>().unwrap();
-                // Save the frame as JPG
-                let _ = decoded.save(&path);
-            }
-        }
-    }
+    let mut camera = Camera::new(index, requested)
+        .map_err(|e| AppError::Camera(format!("Could not open the webcam: {}", e)))?;
+    camera
+        .open_stream()
+        .map_err(|e| AppError::Camera(format!("Could not start the webcam: {}", e)))?;
+    let frame = camera
+        .frame()
+        .map_err(|e| AppError::Camera(format!("The webcam gave no frame: {}", e)))?;
+    let decoded = frame
+        .decode_image::<RgbFormat>()
+        .map_err(|e| AppError::Camera(format!("Unreadable webcam frame: {}", e)))?;
 
-    path_str
+    // Save the frame as JPG, sealed when encryption is on
+    let (width, height) = (decoded.width(), decoded.height());
+    let jpeg = image::RgbImage::from_raw(width, height, decoded.into_raw())
+        .and_then(|rgb| encode_jpeg(&rgb))
+        .ok_or_else(|| AppError::Camera("Could not encode the webcam frame".to_string()))?;
+    vault::write(file.path(), jpeg).map_err(|e| AppError::file(file.path(), e))?;
+
+    Ok(file)
+}
+
+fn encode_jpeg(frame: &image::RgbImage) -> Option<Vec<u8>> {
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new(&mut jpeg)
+        .encode_image(frame)
+        .ok()?;
+    Some(jpeg)
 }
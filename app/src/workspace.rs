@@ -0,0 +1,269 @@
+//! Runtime workspace for temp images: prepared probes, webcam frames and the
+//! frames the engine saves for video matches.
+//!
+//! Each run gets its own folder under `[storage] runtime_dir` and every file in
+//! it a unique name, so a frame is never replaced while the engine may still be
+//! reading it. A `TempFile` deletes its file once the last clone is dropped, so
+//! an image lives exactly as long as the result that shows it. The run folder
+//! goes on shutdown, and `sweep` catches whatever a crash left behind.
+//! `remove_legacy_files` clears what builds before the workspace left behind.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+/// Environment variable telling the Python engine where to save its frames.
+pub const ENGINE_WORKSPACE_ENV: &str = "CFR_WORKSPACE";
+
+/// The webcam frame builds before the workspace kept, under the source tree.
+const LEGACY_FRAME: &str = "temp/current_scan.jpg";
+
+/// Where those builds saved matched suspect photos as `suspect_<id>_<n>.jpg`,
+/// under the folder the app ran from.
+const LEGACY_SUSPECT_DIR: &str = "temp_identify";
+
+/// Left in the root once no legacy files remain, so the search stops.
+const LEGACY_MARKER: &str = ".legacy_removed";
+
+// The configured `runtime_dir`, or `default_root` if something needed a file first
+static WORKSPACE: OnceLock<Workspace> = OnceLock::new();
+
+type LiveFiles = Arc<Mutex<HashSet<PathBuf>>>;
+
+pub struct Workspace {
+    root: PathBuf,
+    run_dir: PathBuf,
+    next_id: AtomicU64,
+    live: LiveFiles,
+}
+
+impl Workspace {
+    /// A workspace for this process under `root`; nothing is created until
+    /// the first file is allocated.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        let root = root.into();
+        Self {
+            run_dir: root.join(format!("run-{}", std::process::id())),
+            root,
+            next_id: AtomicU64::new(0),
+            live: LiveFiles::default(),
+        }
+    }
+
+    /// This run's folder, shared with the engine.
+    pub fn run_dir(&self) -> &Path {
+        &self.run_dir
+    }
+
+    /// A new, unused path like `<prefix>_<n>.<extension>`. The file itself is
+    /// left to the caller to write.
+    pub fn allocate(&self, prefix: &str, extension: &str) -> TempFile {
+        // Writing reports the error if this fails
+        let _ = std::fs::create_dir_all(&self.run_dir);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.track(
+            self.run_dir
+                .join(format!("{}_{}.{}", prefix, id, extension)),
+        )
+    }
+
+    /// Takes over a file someone else (the engine) wrote into this run's
+    /// folder. Paths elsewhere aren't ours to delete and give `None`.
+    pub fn adopt(&self, path: impl Into<PathBuf>) -> Option<TempFile> {
+        let path = path.into();
+        let inside = path
+            .canonicalize()
+            .ok()
+            .zip(self.run_dir.canonicalize().ok())
+            .is_some_and(|(path, run_dir)| path.starts_with(run_dir));
+        inside.then(|| self.track(path))
+    }
+
+    /// Deletes files older than `max_age` that nothing holds any more: in this
+    /// run's folder, in folders of runs that didn't shut down cleanly, and
+    /// loose in the root from older builds. Returns how many went.
+    pub fn sweep(&self, max_age: Duration) -> usize {
+        let Ok(entries) = std::fs::read_dir(&self.root) else {
+            return 0;
+        };
+        let live = self.live.lock().unwrap().clone();
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                removed += remove_expired(&path, max_age, &live);
+                if path != self.run_dir {
+                    // Only succeeds once it's empty
+                    let _ = std::fs::remove_dir(&path);
+                }
+            } else if !path.ends_with(LEGACY_MARKER)
+                && is_expired(&path, max_age)
+                && std::fs::remove_file(&path).is_ok()
+            {
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    /// Deletes the temp images builds before the workspace left where they
+    /// wrote them: the webcam frame in this source tree's `temp/` and matched
+    /// suspect photos in `temp_identify/` under the current folder, by their
+    /// known names only. Returns how many went.
+    pub fn remove_legacy_files(&self) -> usize {
+        let working_dir = std::env::current_dir().unwrap_or_default();
+        self.remove_legacy_files_from(Path::new(env!("CARGO_MANIFEST_DIR")), &working_dir)
+    }
+
+    /// `remove_legacy_files` for a build from `source_dir` run in
+    /// `working_dir`. Stops looking once a pass leaves none behind.
+    pub fn remove_legacy_files_from(&self, source_dir: &Path, working_dir: &Path) -> usize {
+        let marker = self.root.join(LEGACY_MARKER);
+        if marker.exists() {
+            return 0;
+        }
+
+        let mut legacy = Vec::new();
+        let frame = source_dir.join(LEGACY_FRAME);
+        if frame.is_file() {
+            legacy.push(frame);
+        }
+        let suspects = working_dir.join(LEGACY_SUSPECT_DIR);
+        if let Ok(entries) = std::fs::read_dir(&suspects) {
+            legacy.extend(
+                entries
+                    .flatten()
+                    .map(|entry| entry.path())
+                    .filter(|path| is_legacy_suspect_photo(path)),
+            );
+        }
+        let removed = legacy
+            .iter()
+            .filter(|path| std::fs::remove_file(path).is_ok())
+            .count();
+        // Only go if nothing else is left in them
+        let _ = std::fs::remove_dir(source_dir.join("temp"));
+        let _ = std::fs::remove_dir(&suspects);
+
+        // Any that couldn't be deleted are tried again next run
+        if removed == legacy.len() {
+            let _ = std::fs::create_dir_all(&self.root);
+            let _ = std::fs::write(marker, b"");
+        }
+        removed
+    }
+
+    /// Deletes this run's folder and everything in it, for shutdown.
+    pub fn clear(&self) -> io::Result<()> {
+        match std::fs::remove_dir_all(&self.run_dir) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        }
+    }
+
+    fn track(&self, path: PathBuf) -> TempFile {
+        self.live.lock().unwrap().insert(path.clone());
+        TempFile(Arc::new(Entry {
+            path,
+            live: self.live.clone(),
+        }))
+    }
+}
+
+/// Puts this process's temp images under `root`. Has to run before the first
+/// file is allocated, which fixes the folder; returns false if that happened.
+pub fn install(root: impl Into<PathBuf>) -> bool {
+    WORKSPACE.set(Workspace::new(root)).is_ok()
+}
+
+/// Where temp images go: the installed workspace, or `default_root`.
+pub fn current() -> &'static Workspace {
+    WORKSPACE.get_or_init(|| Workspace::new(default_root()))
+}
+
+/// Where the workspace lives when `runtime_dir` isn't configured.
+pub fn default_root() -> PathBuf {
+    std::env::temp_dir().join("criminal_face_recog")
+}
+
+/// A file in the workspace, deleted when the last clone is dropped.
+#[derive(Clone)]
+pub struct TempFile(Arc<Entry>);
+
+struct Entry {
+    path: PathBuf,
+    live: LiveFiles,
+}
+
+impl Drop for Entry {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+        self.live.lock().unwrap().remove(&self.path);
+    }
+}
+
+impl TempFile {
+    pub fn path(&self) -> &Path {
+        &self.0.path
+    }
+}
+
+impl AsRef<Path> for TempFile {
+    fn as_ref(&self) -> &Path {
+        self.path()
+    }
+}
+
+impl fmt::Debug for TempFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("TempFile").field(&self.0.path).finish()
+    }
+}
+
+/// The path, as sent to the engine.
+impl fmt::Display for TempFile {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.path.display())
+    }
+}
+
+fn remove_expired(dir: &Path, max_age: Duration, live: &HashSet<PathBuf>) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| !live.contains(path) && is_expired(path, max_age))
+        .filter(|path| std::fs::remove_file(path).is_ok())
+        .count()
+}
+
+/// `suspect_<criminal id>_<n>.jpg`, as older builds named them.
+fn is_legacy_suspect_photo(path: &Path) -> bool {
+    let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+    let Some(numbers) = name
+        .strip_prefix("suspect_")
+        .and_then(|n| n.strip_suffix(".jpg"))
+    else {
+        return false;
+    };
+    numbers.split_once('_').is_some_and(|(id, n)| {
+        [id, n]
+            .iter()
+            .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()))
+    })
+}
+
+fn is_expired(path: &Path, max_age: Duration) -> bool {
+    std::fs::metadata(path)
+        .ok()
+        .filter(|meta| meta.is_file())
+        .and_then(|meta| meta.modified().ok())
+        .and_then(|modified| modified.elapsed().ok())
+        .is_some_and(|age| age > max_age)
+}
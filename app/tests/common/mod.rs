@@ -0,0 +1,11 @@
+//! Helpers shared by the integration tests.
+
+use std::path::PathBuf;
+
+/// A folder for one test under the system temp dir, removed if an earlier run
+/// left it; `suite` and the process id keep parallel runs apart.
+pub fn temp_dir(suite: &str, name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("cfr_{}_{}_{}", suite, name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
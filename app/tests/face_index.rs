@@ -0,0 +1,175 @@
+//! Checks gallery search: HNSW against brute force, one hit per person, and
+//! where the match and duplicate thresholds fall.
+
+use app::face_index::{
+    FaceIndex, HnswParams, IndexEntry, SearchHit, DUPLICATE_THRESHOLD, MATCH_THRESHOLD,
+};
+
+const DIM: usize = 32;
+
+/// Deterministic xorshift so failures reproduce without pulling in `rand`.
+struct Xorshift(u64);
+
+impl Xorshift {
+    fn next_f32(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 40) as f32 / (1u64 << 24) as f32 - 0.5
+    }
+
+    fn vector(&mut self) -> Vec<f32> {
+        (0..DIM).map(|_| self.next_f32()).collect()
+    }
+}
+
+fn entry(embedding_id: u32, criminal_id: u32) -> IndexEntry {
+    IndexEntry {
+        embedding_id,
+        criminal_id,
+        photo_id: embedding_id,
+    }
+}
+
+/// Unit vector whose cosine with the first axis is `cosine`.
+fn at_cosine(cosine: f32) -> Vec<f32> {
+    let mut vector = vec![0.0; DIM];
+    vector[0] = cosine;
+    vector[1] = (1.0 - cosine * cosine).sqrt();
+    vector
+}
+
+fn axis(i: usize) -> Vec<f32> {
+    let mut vector = vec![0.0; DIM];
+    vector[i] = 1.0;
+    vector
+}
+
+fn hit(score: f32) -> SearchHit {
+    SearchHit {
+        embedding_id: 1,
+        criminal_id: 1,
+        photo_id: 1,
+        score,
+    }
+}
+
+#[test]
+fn hnsw_recall_matches_brute_force() {
+    const GALLERY: u32 = 1_000;
+    const QUERIES: usize = 50;
+    const K: usize = 10;
+
+    let mut rng = Xorshift(0x9E37_79B9_7F4A_7C15);
+    let mut exact = FaceIndex::new(DIM);
+    let mut graph = FaceIndex::new(DIM).with_hnsw(HnswParams::default());
+    for id in 0..GALLERY {
+        let vector = rng.vector();
+        exact.insert(entry(id, id), &vector).unwrap();
+        graph.insert(entry(id, id), &vector).unwrap();
+    }
+
+    let mut found = 0;
+    for _ in 0..QUERIES {
+        let query = rng.vector();
+        let truth: Vec<u32> = exact
+            .search(&query, K)
+            .unwrap()
+            .iter()
+            .map(|h| h.embedding_id)
+            .collect();
+        let approx = graph.search(&query, K).unwrap();
+
+        assert_eq!(approx.len(), K);
+        assert!(approx.windows(2).all(|w| w[0].score >= w[1].score));
+        found += approx
+            .iter()
+            .filter(|h| truth.contains(&h.embedding_id))
+            .count();
+    }
+
+    let recall = found as f32 / (QUERIES * K) as f32;
+    assert!(recall >= 0.95, "HNSW recall@{} was {:.3}", K, recall);
+}
+
+#[test]
+fn hnsw_finds_every_enrolled_vector() {
+    let mut rng = Xorshift(42);
+    let mut graph = FaceIndex::new(DIM).with_hnsw(HnswParams::default());
+    let vectors: Vec<Vec<f32>> = (0..500).map(|_| rng.vector()).collect();
+    for (id, vector) in vectors.iter().enumerate() {
+        graph.insert(entry(id as u32, id as u32), vector).unwrap();
+    }
+
+    for (id, vector) in vectors.iter().enumerate() {
+        let best = graph.search(vector, 1).unwrap()[0];
+        assert_eq!(best.embedding_id, id as u32);
+        assert!((best.score - 1.0).abs() < 1e-5);
+    }
+}
+
+#[test]
+fn identities_are_deduplicated_and_ordered() {
+    let mut index = FaceIndex::new(DIM);
+    // Person 1 has three close photos, person 2 one closer photo, person 3 a far one
+    index.insert(entry(10, 1), &at_cosine(0.90)).unwrap();
+    index.insert(entry(11, 1), &at_cosine(0.80)).unwrap();
+    index.insert(entry(12, 1), &at_cosine(0.70)).unwrap();
+    index.insert(entry(20, 2), &at_cosine(0.95)).unwrap();
+    index.insert(entry(30, 3), &at_cosine(0.20)).unwrap();
+
+    let hits = index.search_identities(&axis(0), 3).unwrap();
+    let people: Vec<u32> = hits.iter().map(|h| h.criminal_id).collect();
+    assert_eq!(people, [2, 1, 3]);
+    // Each person is scored by their best photo
+    assert_eq!(hits[1].embedding_id, 10);
+    assert!((hits[1].score - 0.90).abs() < 1e-5);
+    assert!(hits.windows(2).all(|w| w[0].score >= w[1].score));
+
+    let top = index.search_identities(&axis(0), 2).unwrap();
+    assert_eq!(
+        top.iter().map(|h| h.criminal_id).collect::<Vec<_>>(),
+        [2, 1]
+    );
+}
+
+#[test]
+fn identities_are_not_crowded_out_by_many_photos() {
+    let mut index = FaceIndex::new(DIM);
+    // Forty photos of one person all beat the only photo of the next
+    for id in 0..40 {
+        index.insert(entry(id, 1), &at_cosine(0.9)).unwrap();
+    }
+    index.insert(entry(100, 2), &at_cosine(0.5)).unwrap();
+
+    let hits = index.search_identities(&axis(0), 2).unwrap();
+    assert_eq!(
+        hits.iter().map(|h| h.criminal_id).collect::<Vec<_>>(),
+        [1, 2]
+    );
+}
+
+#[test]
+fn thresholds_include_their_boundary() {
+    for threshold in [MATCH_THRESHOLD, DUPLICATE_THRESHOLD] {
+        assert!(hit(threshold).matches(threshold));
+        assert!(hit(threshold.next_up()).matches(threshold));
+        assert!(!hit(threshold.next_down()).matches(threshold));
+    }
+    assert_eq!(MATCH_THRESHOLD, 0.4);
+    assert_eq!(DUPLICATE_THRESHOLD, 0.55);
+}
+
+#[test]
+fn thresholds_split_search_scores() {
+    let mut index = FaceIndex::new(DIM);
+    index.insert(entry(1, 1), &axis(0)).unwrap();
+
+    let score = |cosine: f32| index.search(&at_cosine(cosine), 1).unwrap()[0];
+    assert!(!score(0.39).matches(MATCH_THRESHOLD));
+    assert!(score(0.41).matches(MATCH_THRESHOLD));
+    // A match is not necessarily a duplicate enrollment
+    assert!(!score(0.54).matches(DUPLICATE_THRESHOLD));
+    assert!(score(0.56).matches(DUPLICATE_THRESHOLD));
+    assert!(score(0.56).matches(MATCH_THRESHOLD));
+}
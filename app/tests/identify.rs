@@ -0,0 +1,345 @@
+//! Routes engine replies through the identification service and checks each
+//! find page receives its own results.
+
+use app::database::OffenceEntry;
+use app::entities::criminal;
+use app::face_index::{FaceIndex, IndexEntry};
+use app::identify::{
+    self, Candidate, FindPages, IdentificationService, IdentifiedRecord, Source, NOT_FOUND,
+};
+use app::pages::{ImageFindPage, VideoFindPage, WebcamFindPage, NO_FACE};
+use app::python_process::{add_command, decode_path, encode_path};
+use app::Message;
+use std::path::{Path, PathBuf};
+
+const DIM: usize = 4;
+const THRESHOLD: f32 = 0.4;
+
+/// Two people, one photo each, on orthogonal axes.
+fn sample_index() -> FaceIndex {
+    let mut index = FaceIndex::new(DIM);
+    for (criminal_id, axis) in [(7, 0), (9, 1)] {
+        let mut vector = vec![0.0; DIM];
+        vector[axis] = 1.0;
+        let entry = IndexEntry {
+            embedding_id: criminal_id * 10,
+            criminal_id,
+            photo_id: criminal_id * 100,
+        };
+        index.insert(entry, &vector).unwrap();
+    }
+    index
+}
+
+/// The tag the engine echoes back from an `embed <tag> <path>` command.
+fn tag_of(command: &str) -> String {
+    command.split_whitespace().nth(1).unwrap().to_string()
+}
+
+fn sample_record(criminal_id: u32) -> IdentifiedRecord {
+    IdentifiedRecord {
+        candidate: Candidate {
+            criminal_id,
+            score: Some(0.9),
+            photo_id: Some(criminal_id * 100),
+        },
+        criminal: criminal::Model {
+            criminal_id,
+            name: "Test Subject".to_string(),
+            fathers_name: None,
+            date_of_arrest: chrono::Utc::now(),
+            last_seen: None,
+            no_of_crimes: 0,
+            arrested_location: None,
+            date_of_birth: None,
+            gender: None,
+            national_ids: None,
+            height_cm: None,
+            distinguishing_marks: None,
+            notes: None,
+        },
+        photos: vec![iced::widget::image::Handle::from_bytes(vec![0u8; 4])],
+        aliases: vec!["Tester".to_string()],
+        offences: Vec::<OffenceEntry>::new(),
+    }
+}
+
+fn identified(source: Source, criminal_id: u32) -> Message {
+    Message::Identified(source, Ok(Box::new(sample_record(criminal_id))))
+}
+
+#[test]
+fn webcam_frames_are_embedded_not_sent_as_untyped_identify() {
+    let mut service = IdentificationService::new();
+    let command = service.request(Source::Webcam, "/tmp/frame_1.jpg");
+
+    assert!(command.starts_with("embed find-webcam-"), "{}", command);
+    assert!(command.ends_with(" /tmp/frame_1.jpg"), "{}", command);
+    assert!(IdentificationService::owns_tag(&tag_of(&command)));
+    assert!(service.is_pending(Source::Webcam));
+}
+
+#[test]
+fn videos_are_scanned_by_the_engine() {
+    let mut service = IdentificationService::new();
+    let command = service.request(Source::Video, "/tmp/clip.mp4");
+    assert_eq!(command, "identify video /tmp/clip.mp4");
+}
+
+#[test]
+fn paths_with_spaces_stay_one_field() {
+    let mut service = IdentificationService::new();
+    let image = r"C:\Users\Jo Smith\AppData\Local\Temp\criminal_face_recog\prepared 1.jpg";
+    let command = service.request(Source::Image, image);
+    let fields: Vec<&str> = command.split(' ').collect();
+    assert_eq!(fields.len(), 3, "{}", command);
+    assert_eq!(decode_path(fields[2]), image);
+
+    let video = "/home/u/My Videos/50% & more.mp4";
+    let command = service.request(Source::Video, video);
+    assert_eq!(
+        command,
+        "identify video /home/u/My%20Videos/50%25%20%26%20more.mp4"
+    );
+
+    let add = add_command(3, &[(30, "/home/u/My Photos/a.jpg".to_string(), None)]);
+    assert_eq!(add, "add 3 30:-:/home/u/My%20Photos/a.jpg");
+    assert_eq!(decode_path("/tmp/Temp%20Dir/Ä.jpg"), "/tmp/Temp Dir/Ä.jpg");
+    assert_eq!(decode_path(&encode_path("tab\there ü")), "tab\there ü");
+}
+
+#[test]
+fn an_unreadable_video_fails_only_the_video_scan() {
+    let mut service = IdentificationService::new();
+    service.request(Source::Video, "/tmp/clip.mp4");
+    service.request(Source::Webcam, "/tmp/frame_1.jpg");
+
+    let resolved = service
+        .fail(Source::Video, "The engine couldn't decode this video")
+        .unwrap();
+    assert_eq!(resolved.source, Source::Video);
+    assert!(resolved.outcome.is_err());
+    assert_ne!(resolved.outcome, Err(NOT_FOUND.to_string()));
+
+    assert!(service.is_pending(Source::Webcam));
+    // A late identity reply has nothing left to answer
+    assert!(service.identity_reply("UNKNOWN", Some(0.0)).is_none());
+    assert!(service.fail(Source::Video, "again").is_none());
+}
+
+#[test]
+fn a_failed_command_fails_only_its_request() {
+    let mut service = IdentificationService::new();
+    let image_tag = tag_of(&service.request(Source::Image, "/tmp/probe.jpg"));
+    service.request(Source::Webcam, "/tmp/frame_1.jpg");
+
+    let resolved = service.fail_tag(&image_tag, "out of memory").unwrap();
+    assert_eq!(resolved.source, Source::Image);
+    assert_eq!(resolved.outcome, Err("out of memory".to_string()));
+    assert_eq!(resolved.probe.as_deref(), Some(Path::new("/tmp/probe.jpg")));
+
+    assert!(service.is_pending(Source::Webcam));
+    assert!(service.fail_tag(&image_tag, "again").is_none());
+    assert!(service.fail_tag("dup-1-0", "not ours").is_none());
+}
+
+#[test]
+fn probe_replies_resolve_to_the_page_that_asked() {
+    let index = sample_index();
+    let mut service = IdentificationService::new();
+    let image_tag = tag_of(&service.request(Source::Image, "/tmp/probe.jpg"));
+    let webcam_tag = tag_of(&service.request(Source::Webcam, "/tmp/frame_1.jpg"));
+    assert_ne!(image_tag, webcam_tag);
+
+    // Replies arrive out of order
+    let webcam = service
+        .probe_embedded(&webcam_tag, Some(&[0.0, 1.0, 0.0, 0.0]), &index, THRESHOLD)
+        .unwrap();
+    assert_eq!(webcam.source, Source::Webcam);
+    assert_eq!(webcam.outcome.as_ref().unwrap().criminal_id, 9);
+    assert_eq!(webcam.outcome.as_ref().unwrap().photo_id, Some(900));
+    assert_eq!(webcam.probe.as_deref(), Some(Path::new("/tmp/frame_1.jpg")));
+
+    let image = service
+        .probe_embedded(&image_tag, Some(&[1.0, 0.1, 0.0, 0.0]), &index, THRESHOLD)
+        .unwrap();
+    assert_eq!(image.source, Source::Image);
+    assert_eq!(image.outcome.unwrap().criminal_id, 7);
+
+    assert!(!service.is_pending(Source::Image));
+    assert!(!service.is_pending(Source::Webcam));
+}
+
+#[test]
+fn probes_without_a_face_or_a_close_match_say_why() {
+    let index = sample_index();
+    let mut service = IdentificationService::new();
+
+    let tag = tag_of(&service.request(Source::Webcam, "/tmp/frame_1.jpg"));
+    let resolved = service
+        .probe_embedded(&tag, None, &index, THRESHOLD)
+        .unwrap();
+    assert_eq!(resolved.outcome, Err(NO_FACE.to_string()));
+
+    let tag = tag_of(&service.request(Source::Image, "/tmp/probe.jpg"));
+    let resolved = service
+        .probe_embedded(&tag, Some(&[0.0, 0.0, 1.0, 0.0]), &index, THRESHOLD)
+        .unwrap();
+    assert_eq!(resolved.outcome, Err(NOT_FOUND.to_string()));
+}
+
+#[test]
+fn superseded_and_foreign_replies_are_dropped() {
+    let index = sample_index();
+    let mut service = IdentificationService::new();
+    let old_tag = tag_of(&service.request(Source::Webcam, "/tmp/frame_1.jpg"));
+    let new_tag = tag_of(&service.request(Source::Webcam, "/tmp/frame_2.jpg"));
+    let vector = [1.0, 0.0, 0.0, 0.0];
+
+    assert!(service
+        .probe_embedded(&old_tag, Some(&vector), &index, THRESHOLD)
+        .is_none());
+    assert!(!IdentificationService::owns_tag("batch-3-0"));
+    assert!(service
+        .probe_embedded("batch-3-0", Some(&vector), &index, THRESHOLD)
+        .is_none());
+
+    let resolved = service
+        .probe_embedded(&new_tag, Some(&vector), &index, THRESHOLD)
+        .unwrap();
+    assert_eq!(resolved.probe, Some(PathBuf::from("/tmp/frame_2.jpg")));
+
+    // Nor does a reply count once the request was cancelled
+    let tag = tag_of(&service.request(Source::Webcam, "/tmp/frame_3.jpg"));
+    service.cancel(Source::Webcam);
+    assert!(service
+        .probe_embedded(&tag, Some(&vector), &index, THRESHOLD)
+        .is_none());
+}
+
+#[test]
+fn identity_replies_answer_the_video_scan() {
+    let mut service = IdentificationService::new();
+    assert!(service.identity_reply("7", Some(0.8)).is_none());
+
+    service.request(Source::Video, "/tmp/clip.mp4");
+    service.set_probe(Source::Video, Path::new("/tmp/probe_frame_1.jpg"));
+    let resolved = service.identity_reply("7", Some(0.8)).unwrap();
+    assert_eq!(resolved.source, Source::Video);
+    assert_eq!(
+        resolved.outcome,
+        Ok(Candidate {
+            criminal_id: 7,
+            score: Some(0.8),
+            photo_id: None,
+        })
+    );
+    assert_eq!(
+        resolved.probe,
+        Some(PathBuf::from("/tmp/probe_frame_1.jpg"))
+    );
+
+    service.request(Source::Video, "/tmp/clip.mp4");
+    let resolved = service.identity_reply("UNKNOWN", Some(0.0)).unwrap();
+    assert_eq!(resolved.outcome, Err(NOT_FOUND.to_string()));
+    assert!(service.identity_reply("7", Some(0.8)).is_none());
+}
+
+#[test]
+fn image_page_receives_its_match() {
+    let mut page = ImageFindPage::new(THRESHOLD);
+    page.is_identifying = true;
+    let _ = page.update(identified(Source::Image, 7), None);
+
+    assert!(!page.is_identifying);
+    assert!(!page.not_found);
+    assert_eq!(page.identified_data.as_ref().unwrap().criminal_id, 7);
+    assert_eq!(page.identified_aliases, vec!["Tester".to_string()]);
+
+    let _ = page.update(
+        Message::Identified(Source::Image, Err(NOT_FOUND.to_string())),
+        None,
+    );
+    assert!(page.not_found);
+}
+
+#[test]
+fn video_page_receives_its_match_or_stops_scanning() {
+    let mut page = VideoFindPage::new(THRESHOLD);
+    page.is_scanning = true;
+    let _ = page.update(identified(Source::Video, 9), None);
+
+    assert!(!page.is_scanning);
+    assert!(page.show_details);
+    assert_eq!(page.identified_data.as_ref().unwrap().criminal_id, 9);
+    assert_eq!(page.suspect_photos.len(), 1);
+
+    let mut page = VideoFindPage::new(THRESHOLD);
+    page.is_scanning = true;
+    let _ = page.update(
+        Message::Identified(Source::Video, Err(NOT_FOUND.to_string())),
+        None,
+    );
+    assert!(!page.is_scanning);
+    assert!(page.not_found);
+}
+
+#[test]
+fn webcam_page_receives_the_full_record_and_photos() {
+    let mut page = WebcamFindPage::new();
+    page.is_webcam_on = true;
+    page.is_identifying = true;
+    let _ = page.update(identified(Source::Webcam, 7), None);
+
+    assert!(!page.is_identifying);
+    assert!(!page.is_webcam_on);
+    assert_eq!(page.identified_data.as_ref().unwrap().criminal_id, 7);
+    assert_eq!(page.identified_aliases, vec!["Tester".to_string()]);
+    assert_eq!(page.identified_photos.len(), 1);
+}
+
+#[test]
+fn webcam_page_keeps_scanning_past_frames_without_a_face() {
+    let mut page = WebcamFindPage::new();
+    page.is_webcam_on = true;
+    page.is_identifying = true;
+    let _ = page.update(
+        Message::Identified(Source::Webcam, Err(NO_FACE.to_string())),
+        None,
+    );
+    assert!(!page.is_identifying);
+    assert!(!page.not_found);
+
+    let _ = page.update(
+        Message::Identified(Source::Webcam, Err(NOT_FOUND.to_string())),
+        None,
+    );
+    assert!(page.not_found);
+    assert!(page.identified_data.is_none());
+}
+
+#[test]
+fn results_are_routed_to_the_page_that_asked() {
+    let mut image = ImageFindPage::new(THRESHOLD);
+    let mut video = VideoFindPage::new(THRESHOLD);
+    let mut webcam = WebcamFindPage::new();
+    webcam.is_webcam_on = true;
+    webcam.is_identifying = true;
+
+    let pages = FindPages {
+        image: &mut image,
+        video: &mut video,
+        webcam: &mut webcam,
+    };
+    let _ = identify::route(identified(Source::Webcam, 7), pages, None);
+
+    assert_eq!(webcam.identified_data.as_ref().unwrap().criminal_id, 7);
+    assert!(!webcam.is_identifying);
+    assert!(image.identified_data.is_none());
+    assert!(video.identified_data.is_none());
+
+    for source in [Source::Image, Source::Video, Source::Webcam] {
+        assert_eq!(Source::parse(source.as_str()), Some(source));
+    }
+    assert_eq!(Source::parse("camera"), None);
+}
@@ -0,0 +1,266 @@
+//! Delivers notifications to a local HTTP listener and a local SMTP sink.
+
+use app::config::{NotificationConfig, SmtpConfig, WebhookConfig};
+use app::notify::{
+    self, DeliveryRecord, Notification, Notifier, Snapshot, DELIVERED, RETRYING, SIGNATURE_HEADER,
+};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+fn sample_notification() -> Notification {
+    Notification {
+        criminal_id: 42,
+        name: "Test Subject".to_string(),
+        score: Some(0.81),
+        source: "webcam".to_string(),
+        time: chrono::Utc::now(),
+        watchlist: Some("Wanted".to_string()),
+        priority: Some("critical".to_string()),
+        snapshot: Some(Snapshot {
+            content_type: "image/jpeg".to_string(),
+            data: "/9j/4AAQ".to_string(),
+        }),
+    }
+}
+
+/// A received HTTP request: lower-cased headers and the body.
+struct Request {
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+impl Request {
+    fn header(&self, name: &str) -> Option<&str> {
+        let name = name.to_lowercase();
+        self.headers
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Answers each request with the next status in `statuses` (200 once they run out).
+async fn http_listener(statuses: Vec<u16>) -> (String, Arc<Mutex<Vec<Request>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let log = received.clone();
+    tokio::spawn(async move {
+        let mut statuses = statuses.into_iter();
+        loop {
+            let Ok((stream, _)) = listener.accept().await else {
+                return;
+            };
+            let mut reader = BufReader::new(stream);
+
+            let mut headers = Vec::new();
+            let mut line = String::new();
+            reader.read_line(&mut line).await.unwrap(); // request line
+            loop {
+                line.clear();
+                reader.read_line(&mut line).await.unwrap();
+                let trimmed = line.trim_end();
+                if trimmed.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = trimmed.split_once(':') {
+                    headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+                }
+            }
+            let length = headers
+                .iter()
+                .find(|(n, _)| n == "content-length")
+                .and_then(|(_, v)| v.parse().ok())
+                .unwrap_or(0);
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).await.unwrap();
+            log.lock().unwrap().push(Request { headers, body });
+
+            let status = statuses.next().unwrap_or(200);
+            let response = format!(
+                "HTTP/1.1 {} X\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            );
+            let _ = reader.get_mut().write_all(response.as_bytes()).await;
+        }
+    });
+
+    (url, received)
+}
+
+/// Accepts one message and returns its DATA section.
+async fn smtp_sink() -> (u16, tokio::task::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+
+    let handle = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut reader = BufReader::new(stream);
+        reader
+            .get_mut()
+            .write_all(b"220 sink ESMTP\r\n")
+            .await
+            .unwrap();
+
+        let mut data = String::new();
+        let mut in_data = false;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if reader.read_line(&mut line).await.unwrap() == 0 {
+                return data;
+            }
+            if in_data {
+                if line == ".\r\n" {
+                    in_data = false;
+                    reader.get_mut().write_all(b"250 queued\r\n").await.unwrap();
+                } else {
+                    data.push_str(&line);
+                }
+                continue;
+            }
+
+            let command = line.to_uppercase();
+            let reply: &[u8] = if command.starts_with("EHLO") || command.starts_with("HELO") {
+                b"250 sink\r\n"
+            } else if command.starts_with("DATA") {
+                in_data = true;
+                b"354 go ahead\r\n"
+            } else if command.starts_with("QUIT") {
+                reader.get_mut().write_all(b"221 bye\r\n").await.unwrap();
+                return data;
+            } else {
+                b"250 OK\r\n"
+            };
+            reader.get_mut().write_all(reply).await.unwrap();
+        }
+    });
+
+    (port, handle)
+}
+
+#[test]
+fn signature_is_hmac_sha256() {
+    // RFC 4231, test case 2
+    assert_eq!(
+        notify::sign("Jefe", b"what do ya want for nothing?"),
+        "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+    );
+}
+
+#[tokio::test]
+async fn webhook_payload_is_signed_json() {
+    let (url, received) = http_listener(vec![200]).await;
+    let hook = WebhookConfig {
+        url,
+        secret: "s3cret".to_string(),
+    };
+
+    notify::post_webhook(&reqwest::Client::new(), &hook, &sample_notification())
+        .await
+        .unwrap();
+
+    let received = received.lock().unwrap();
+    let request = &received[0];
+    assert_eq!(
+        request.header(SIGNATURE_HEADER),
+        Some(format!("sha256={}", notify::sign("s3cret", &request.body)).as_str())
+    );
+    let payload: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+    assert_eq!(payload["criminal_id"], 42);
+    assert_eq!(payload["name"], "Test Subject");
+    assert_eq!(payload["source"], "webcam");
+    assert_eq!(payload["snapshot"]["content_type"], "image/jpeg");
+    assert!(payload["time"].is_string());
+}
+
+#[tokio::test]
+async fn failed_webhook_is_retried_and_logged() {
+    let (url, received) = http_listener(vec![500, 503]).await;
+    let config = NotificationConfig {
+        sources: vec!["webcam".to_string()],
+        min_priority: String::new(),
+        max_attempts: 5,
+        retry_delay_secs: 0,
+        webhooks: vec![WebhookConfig {
+            url,
+            secret: "s3cret".to_string(),
+        }],
+        smtp: None,
+        ..NotificationConfig::default()
+    };
+
+    let log: Arc<Mutex<Vec<DeliveryRecord>>> = Arc::new(Mutex::new(Vec::new()));
+    let sink = log.clone();
+    let notifier = Notifier::start(
+        config,
+        Arc::new(move |record| sink.lock().unwrap().push(record)),
+    )
+    .expect("a webhook is configured");
+    notifier.notify(sample_notification());
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    while !log.lock().unwrap().iter().any(|r| r.status == DELIVERED) {
+        assert!(tokio::time::Instant::now() < deadline, "never delivered");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    let log = log.lock().unwrap();
+    let statuses: Vec<(u32, &str)> = log.iter().map(|r| (r.attempt, r.status)).collect();
+    assert_eq!(statuses, vec![(1, RETRYING), (2, RETRYING), (3, DELIVERED)]);
+    assert_eq!(
+        log[0].error.as_deref(),
+        Some("HTTP 500 Internal Server Error")
+    );
+    assert_eq!(log[2].criminal_id, 42);
+    assert_eq!(received.lock().unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn email_goes_through_smtp_relay() {
+    let (port, sink) = smtp_sink().await;
+    let smtp = SmtpConfig {
+        host: "127.0.0.1".to_string(),
+        port,
+        from: "recognizer@localhost".to_string(),
+        to: vec!["field-team@localhost".to_string()],
+        ..SmtpConfig::default()
+    };
+
+    notify::send_email(&smtp, &sample_notification())
+        .await
+        .unwrap();
+
+    let data = sink.await.unwrap();
+    assert!(data.contains("Subject: [CRITICAL] Test Subject (#42) identified via webcam"));
+    assert!(data.contains("To: field-team@localhost"));
+    assert!(data.contains("Content-Disposition: attachment"));
+}
+
+#[test]
+fn notifier_filters_by_source_and_priority() {
+    let config = NotificationConfig {
+        sources: vec!["webcam".to_string()],
+        min_priority: "high".to_string(),
+        webhooks: vec![WebhookConfig {
+            url: "http://127.0.0.1:9/".to_string(),
+            secret: String::new(),
+        }],
+        ..NotificationConfig::default()
+    };
+    let notifier = Notifier::start(config, Arc::new(|_| {})).unwrap();
+
+    assert!(notifier.wants("webcam", Some("critical")));
+    assert!(notifier.wants("webcam", Some("high")));
+    assert!(!notifier.wants("webcam", Some("medium")));
+    assert!(!notifier.wants("webcam", None));
+    assert!(!notifier.wants("image", Some("critical")));
+}
+
+#[test]
+fn nothing_configured_means_no_notifier() {
+    assert!(Notifier::start(NotificationConfig::default(), Arc::new(|_| {})).is_none());
+}
@@ -0,0 +1,370 @@
+//! Queues records in the outbox while the database is down, syncs them to a
+//! stand-in database and checks the reconnection backoff.
+
+mod common;
+
+use app::connection::{backoff, Connection, DbStatus};
+use app::database::{CriminalDetails, EnrollmentSummary, NewEnrollment, NewIdentification};
+use app::outbox::{Enrollment, Outbox, SyncTarget};
+use common::temp_dir;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+#[test]
+fn offline_records_wait_in_the_outbox() {
+    let dir = temp_dir("outbox", "queue");
+    let outbox = Outbox::new(dir.join("outbox"));
+    assert!(outbox.is_empty());
+
+    std::fs::create_dir_all(&dir).unwrap();
+    let photo = dir.join("selected.jpg");
+    std::fs::write(&photo, b"jpeg bytes").unwrap();
+    outbox
+        .enqueue_enrollment(Enrollment {
+            name: "Test Subject".to_string(),
+            fathers_name: None,
+            arrested_location: Some("Harbour".to_string()),
+            details: CriminalDetails::default(),
+            aliases: vec!["Tester".to_string()],
+            offences: Vec::new(),
+            photos: vec![(photo.clone(), Some([1.0, 2.0, 30.0, 40.0]))],
+            captured_at: chrono::Utc::now(),
+        })
+        .unwrap();
+    outbox
+        .enqueue_identification(&NewIdentification {
+            criminal_id: 7,
+            photo_id: Some(700),
+            source: "video".to_string(),
+            score: Some(0.8),
+            probe: Some(b"probe bytes".to_vec()),
+            captured_at: chrono::Utc::now(),
+        })
+        .unwrap();
+    assert_eq!(outbox.len(), 2);
+
+    // The selected photo may be a temp file; the outbox keeps its own copy
+    std::fs::remove_file(&photo).unwrap();
+    let copies = std::fs::read_dir(outbox.dir())
+        .unwrap()
+        .flatten()
+        .filter(|entry| entry.path().join("photo_0.jpg").is_file())
+        .count();
+    assert_eq!(copies, 1);
+
+    // Nothing was written to the database, so nothing is waiting on the engine
+    assert!(outbox.engine_answered(7).is_none());
+    assert!(!outbox.embedding_stored(7, 700));
+    assert_eq!(outbox.len(), 2);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+/// Stands in for the database: keeps what it's sent and fails on request.
+#[derive(Default)]
+struct FakeDb(Mutex<FakeState>);
+
+#[derive(Default)]
+struct FakeState {
+    criminals: Vec<String>, // names; the id is the position plus one
+    photos: Vec<u32>,       // owner of each photo; the id is the position plus one
+    synced: HashMap<String, EnrollmentSummary>,
+    identifications: Vec<u32>,
+    down: bool,
+    // Writes that succeed before the connection drops
+    writes_left: Option<usize>,
+    // The next enrollment commits, but the connection drops before the reply
+    lose_reply: bool,
+    rejected: Vec<u32>, // identifications of these criminals are refused
+}
+
+impl FakeState {
+    fn write(&mut self) -> Result<(), String> {
+        match &mut self.writes_left {
+            _ if self.down => Err("connection refused".to_string()),
+            Some(0) => {
+                self.down = true;
+                Err("connection lost".to_string())
+            }
+            Some(left) => {
+                *left -= 1;
+                Ok(())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl FakeDb {
+    fn state(&self) -> std::sync::MutexGuard<'_, FakeState> {
+        self.0.lock().unwrap()
+    }
+}
+
+impl SyncTarget for FakeDb {
+    async fn write_enrollment(
+        &self,
+        enrollment: NewEnrollment,
+    ) -> Result<EnrollmentSummary, String> {
+        let mut state = self.state();
+        state.write()?;
+        state.criminals.push(enrollment.name);
+        let criminal_id = state.criminals.len() as u32;
+        let mut photo_ids = Vec::new();
+        for _ in enrollment.photos {
+            state.photos.push(criminal_id);
+            photo_ids.push(state.photos.len() as u32);
+        }
+        let summary = EnrollmentSummary {
+            criminal_id,
+            photo_ids,
+        };
+        state
+            .synced
+            .insert(enrollment.outbox_entry.unwrap(), summary.clone());
+        if std::mem::take(&mut state.lose_reply) {
+            state.down = true;
+            return Err("connection lost".to_string());
+        }
+        Ok(summary)
+    }
+
+    async fn find_enrollment(&self, key: &str) -> Result<Option<EnrollmentSummary>, String> {
+        let state = self.state();
+        if state.down {
+            return Err("connection refused".to_string());
+        }
+        Ok(state.synced.get(key).cloned())
+    }
+
+    async fn write_identification(&self, new: NewIdentification) -> Result<(), String> {
+        let mut state = self.state();
+        state.write()?;
+        if state.rejected.contains(&new.criminal_id) {
+            return Err(format!("no criminal {}", new.criminal_id));
+        }
+        state.identifications.push(new.criminal_id);
+        Ok(())
+    }
+
+    async fn is_reachable(&self) -> bool {
+        !self.state().down
+    }
+}
+
+fn queue_enrollment(outbox: &Outbox, dir: &Path, name: &str) {
+    std::fs::create_dir_all(dir).unwrap();
+    let photo = dir.join(format!("{}.jpg", name));
+    std::fs::write(&photo, b"jpeg bytes").unwrap();
+    outbox
+        .enqueue_enrollment(Enrollment {
+            name: name.to_string(),
+            fathers_name: None,
+            arrested_location: None,
+            details: CriminalDetails::default(),
+            aliases: Vec::new(),
+            offences: Vec::new(),
+            photos: vec![(photo, None)],
+            captured_at: chrono::Utc::now(),
+        })
+        .unwrap();
+}
+
+fn queue_identification(outbox: &Outbox, criminal_id: u32) {
+    outbox
+        .enqueue_identification(&NewIdentification {
+            criminal_id,
+            photo_id: None,
+            source: "webcam".to_string(),
+            score: Some(0.7),
+            probe: None,
+            captured_at: chrono::Utc::now(),
+        })
+        .unwrap();
+}
+
+#[tokio::test]
+async fn a_replayed_enrollment_is_not_written_twice() {
+    let dir = temp_dir("outbox", "replay");
+    let outbox = Outbox::new(dir.join("outbox"));
+    queue_enrollment(&outbox, &dir, "Test Subject");
+    let db = FakeDb::default();
+
+    // The write commits, but the reply never arrives
+    db.state().lose_reply = true;
+    let report = outbox.sync(&db).await;
+    assert!(report.failed.is_some());
+    assert!(report.enrolled.is_empty());
+    assert_eq!(outbox.len(), 1);
+
+    db.state().down = false;
+    let report = outbox.sync(&db).await;
+    assert!(report.failed.is_none());
+    assert_eq!(db.state().criminals, ["Test Subject"]);
+    assert_eq!(report.enrolled.len(), 1);
+    assert_eq!(report.enrolled[0].criminal_id, 1);
+    assert_eq!(report.enrolled[0].photos[0].0, 1);
+
+    // Waiting on the engine now; a later pass doesn't write it again either
+    let report = outbox.sync(&db).await;
+    assert!(report.enrolled.is_empty());
+    assert_eq!(db.state().criminals.len(), 1);
+    assert!(outbox.embedding_stored(1, 1));
+    assert!(outbox.is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn photos_stay_queued_until_their_embeddings_are_stored() {
+    // Relative, like the default outbox folder; the engine runs in another one
+    let dir = Path::new("target").join(format!("cfr_outbox_relative_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let outbox = Outbox::new(dir.join("outbox"));
+    assert!(outbox.dir().is_absolute());
+    queue_enrollment(&outbox, &dir, "Test Subject");
+    let db = FakeDb::default();
+
+    let report = outbox.sync(&db).await;
+    let photos = &report.enrolled[0].photos;
+    assert_eq!(photos.len(), 1);
+    assert!(Path::new(&photos[0].1).is_absolute(), "{}", photos[0].1);
+    assert!(Path::new(&photos[0].1).is_file());
+
+    // "added" without an embedding: the photo goes to the engine again
+    assert_eq!(outbox.engine_answered(1), Some(vec![1]));
+    assert_eq!(outbox.len(), 1);
+    let report = outbox.sync(&db).await;
+    assert_eq!(report.enrolled.len(), 1);
+    assert_eq!(db.state().criminals.len(), 1);
+
+    outbox.embedding_received(1);
+    assert_eq!(outbox.engine_answered(1), Some(Vec::new()));
+    assert_eq!(outbox.len(), 1);
+    assert!(outbox.embedding_stored(1, 1));
+    assert!(outbox.is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn sync_stops_at_an_outage_and_resumes_in_order() {
+    let dir = temp_dir("outbox", "partial");
+    let outbox = Outbox::new(&dir);
+    for criminal_id in [1, 2, 3] {
+        queue_identification(&outbox, criminal_id);
+    }
+    let db = FakeDb::default();
+
+    db.state().writes_left = Some(1);
+    let report = outbox.sync(&db).await;
+    assert_eq!(report.identifications, 1);
+    assert_eq!(report.failed.as_deref(), Some("connection lost"));
+    assert_eq!(report.remaining, 2);
+    assert_eq!(db.state().identifications, [1]);
+
+    db.state().down = false;
+    db.state().writes_left = None;
+    let report = outbox.sync(&db).await;
+    assert_eq!(report.identifications, 2);
+    assert_eq!(report.remaining, 0);
+    assert_eq!(db.state().identifications, [1, 2, 3]);
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[tokio::test]
+async fn rejected_and_damaged_entries_are_moved_to_failed() {
+    let dir = temp_dir("outbox", "failed");
+    let outbox = Outbox::new(&dir);
+    let damaged = dir.join("0-damaged");
+    std::fs::create_dir_all(&damaged).unwrap();
+    std::fs::write(damaged.join("entry.json"), b"{ not json").unwrap();
+    for criminal_id in [1, 404, 3] {
+        queue_identification(&outbox, criminal_id);
+    }
+    let db = FakeDb::default();
+    db.state().rejected.push(404);
+
+    let report = outbox.sync(&db).await;
+    assert!(report.failed.is_none());
+    assert_eq!(report.identifications, 2);
+    assert_eq!(report.remaining, 0);
+    assert_eq!(report.problems.len(), 2);
+    assert!(report.problems[1].to_string().contains("no criminal 404"));
+    assert_eq!(db.state().identifications, [1, 3]);
+
+    let failed: Vec<PathBuf> = std::fs::read_dir(dir.join("failed"))
+        .unwrap()
+        .flatten()
+        .map(|entry| entry.path())
+        .collect();
+    assert_eq!(failed.len(), 2);
+    assert!(failed
+        .iter()
+        .all(|entry| entry.join("entry.json").is_file()));
+    assert!(dir.join("failed").join("0-damaged").is_dir());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn a_missing_photo_queues_nothing() {
+    let dir = temp_dir("outbox", "missing");
+    let outbox = Outbox::new(&dir);
+    let result = outbox.enqueue_enrollment(Enrollment {
+        name: "Test Subject".to_string(),
+        fathers_name: None,
+        arrested_location: None,
+        details: CriminalDetails::default(),
+        aliases: Vec::new(),
+        offences: Vec::new(),
+        photos: vec![(dir.join("gone.jpg"), None)],
+        captured_at: chrono::Utc::now(),
+    });
+    assert!(result.is_err());
+    assert!(outbox.is_empty());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn reconnection_backs_off_up_to_a_minute() {
+    assert_eq!(backoff(1), Duration::from_secs(2));
+    assert_eq!(backoff(2), Duration::from_secs(4));
+    assert_eq!(backoff(5), Duration::from_secs(32));
+    assert_eq!(backoff(6), Duration::from_secs(60));
+    assert_eq!(backoff(1000), Duration::from_secs(60));
+}
+
+#[test]
+fn only_the_latest_retry_timer_reconnects() {
+    let mut connection = Connection::new(0);
+    assert!(connection.begin());
+    assert!(!connection.begin(), "one attempt at a time");
+
+    let (delay, first) = connection.failed("refused".to_string());
+    assert_eq!(delay, Duration::from_secs(2));
+    assert!(matches!(connection.status, DbStatus::Offline { .. }));
+    assert!(connection.is_due(first));
+
+    // "Reconnect now" fails again before the first timer runs out
+    assert!(connection.begin());
+    assert!(!connection.is_due(first));
+    let (delay, second) = connection.failed("refused".to_string());
+    assert_eq!(delay, Duration::from_secs(4));
+    assert!(!connection.is_due(first));
+    assert!(connection.is_due(second));
+
+    assert!(connection.begin());
+    connection.connected();
+    assert_eq!(connection.status, DbStatus::Connected);
+    assert!(!connection.begin());
+    assert!(!connection.is_due(second));
+
+    // A dropped connection retries from the shortest wait again
+    let (delay, _) = connection.lost("gone away".to_string());
+    assert_eq!(delay, Duration::from_secs(2));
+}
@@ -0,0 +1,208 @@
+//! Checks an enrollment waits for every photo to be graded and for the
+//! duplicate check, and is released when that check fails.
+
+use app::face_index::SearchHit;
+use app::pages::RegistryPage;
+use app::preprocess::PreparedImage;
+use app::quality::{FaceBox, ImageStats};
+use app::Message;
+use iced::widget::image::Handle;
+use std::path::PathBuf;
+
+fn face() -> FaceBox {
+    FaceBox {
+        bbox: [40.0, 40.0, 240.0, 240.0],
+        score: 0.9,
+        landmarks: [
+            [100.0, 110.0],
+            [180.0, 110.0],
+            [140.0, 150.0],
+            [110.0, 195.0],
+            [170.0, 195.0],
+        ],
+    }
+}
+
+fn stats() -> ImageStats {
+    ImageStats {
+        sharpness: 400.0,
+        brightness: 120.0,
+    }
+}
+
+/// A valid form with two photos whose analysis has just been requested.
+fn page_with_photos() -> RegistryPage {
+    let mut page = RegistryPage {
+        name: "Test Subject".to_string(),
+        selected_images: vec![
+            PreparedImage::Original(PathBuf::from("/tmp/front.jpg")),
+            PreparedImage::Original(PathBuf::from("/tmp/side.jpg")),
+        ],
+        selected_handles: vec![Handle::from_bytes(vec![0u8; 4]); 2],
+        ..RegistryPage::default()
+    };
+    let _ = page.update(Message::RetryPhotoAnalysis, None);
+    page
+}
+
+fn faces_found(page: &mut RegistryPage, index: usize, faces: Vec<FaceBox>) {
+    let tag = format!("reg-{}-{}", page.analysis_generation, index);
+    let _ = page.update(Message::FacesDetected(tag, faces), None);
+}
+
+fn submit(page: &mut RegistryPage) -> bool {
+    let _ = page.update(Message::SubmitForm, None);
+    page.is_saving
+}
+
+#[test]
+fn photos_without_analysis_block_saving() {
+    let mut page = page_with_photos();
+    assert!(!submit(&mut page));
+
+    // Measured, but the engine hasn't answered
+    let generation = page.analysis_generation;
+    let _ = page.update(
+        Message::PhotoStatsComputed(generation, vec![Ok(stats()), Ok(stats())]),
+        None,
+    );
+    assert!(!submit(&mut page));
+
+    // One photo still waits on its faces
+    faces_found(&mut page, 0, vec![face()]);
+    assert!(!submit(&mut page));
+
+    faces_found(&mut page, 1, vec![face()]);
+    assert!(submit(&mut page));
+}
+
+#[test]
+fn photos_that_could_not_be_measured_block_saving() {
+    let mut page = page_with_photos();
+    let generation = page.analysis_generation;
+    let _ = page.update(
+        Message::PhotoStatsComputed(
+            generation,
+            vec![Ok(stats()), Err("file is damaged".to_string())],
+        ),
+        None,
+    );
+    faces_found(&mut page, 0, vec![face()]);
+    faces_found(&mut page, 1, vec![face()]);
+    assert!(!submit(&mut page));
+
+    let _ = page.update(Message::ExcludePhoto(1), None);
+    assert!(submit(&mut page));
+}
+
+#[test]
+fn stale_analysis_does_not_unblock_a_new_selection() {
+    let mut page = page_with_photos();
+    let old = page.analysis_generation;
+    let _ = page.update(Message::RetryPhotoAnalysis, None);
+
+    let _ = page.update(
+        Message::PhotoStatsComputed(old, vec![Ok(stats()), Ok(stats())]),
+        None,
+    );
+    for index in 0..2 {
+        let tag = format!("reg-{}-{}", old, index);
+        let _ = page.update(Message::FacesDetected(tag, vec![face()]), None);
+    }
+    assert!(!submit(&mut page));
+}
+
+/// A page waiting on the engine to embed both photos for the duplicate check.
+fn checking_page() -> RegistryPage {
+    let mut page = page_with_photos();
+    page.check_generation = 3;
+    page.pending_probes = 2;
+    page.is_checking = true;
+    page.is_saving = true;
+    page
+}
+
+fn hit(score: f32) -> SearchHit {
+    SearchHit {
+        embedding_id: 1,
+        criminal_id: 7,
+        photo_id: 70,
+        score,
+    }
+}
+
+#[test]
+fn a_duplicate_check_times_out() {
+    let mut page = checking_page();
+    let _ = page.update(Message::DuplicateProbe("dup-3-0".to_string(), None), None);
+
+    // An older check's timer changes nothing
+    let _ = page.update(Message::DuplicateCheckTimedOut(2), None);
+    assert!(page.is_checking);
+
+    let _ = page.update(Message::DuplicateCheckTimedOut(3), None);
+    assert!(!page.is_checking);
+    assert!(!page.is_saving);
+    assert_eq!(page.pending_probes, 0);
+    assert!(page.check_error.is_some());
+
+    // The late reply belongs to the abandoned check
+    let _ = page.update(
+        Message::DuplicateProbe("dup-3-1".to_string(), Some(hit(0.9))),
+        None,
+    );
+    assert!(page.best_match.is_none());
+    assert!(page.duplicate.is_none());
+}
+
+#[test]
+fn an_engine_failure_releases_the_duplicate_check() {
+    let mut page = checking_page();
+    let _ = page.update(
+        Message::DuplicateCheckFailed("The engine stopped".to_string()),
+        None,
+    );
+    assert!(!page.is_checking);
+    assert_eq!(page.check_error.as_deref(), Some("The engine stopped"));
+
+    let _ = page.update(Message::CancelEnrollment, None);
+    assert!(page.check_error.is_none());
+    assert!(!page.is_saving);
+}
+
+#[test]
+fn a_failed_duplicate_check_can_be_skipped() {
+    let mut page = checking_page();
+    let _ = page.update(
+        Message::DuplicateCheckFailed("The engine stopped".to_string()),
+        None,
+    );
+    let _ = page.update(Message::SaveWithoutDuplicateCheck, None);
+    assert!(page.is_saving);
+    assert!(page.check_error.is_none());
+
+    // Only after a failure
+    let mut page = checking_page();
+    page.is_saving = false;
+    let _ = page.update(Message::SaveWithoutDuplicateCheck, None);
+    assert!(!page.is_saving);
+}
+
+#[test]
+fn an_engine_failure_while_enrolling_ends_the_save() {
+    let mut page = checking_page();
+    let _ = page.update(
+        Message::DuplicateCheckFailed("The engine stopped".to_string()),
+        None,
+    );
+    let _ = page.update(Message::SaveWithoutDuplicateCheck, None);
+    assert!(page.is_saving);
+
+    // The record is in the database; only its embeddings are missing
+    let _ = page.update(
+        Message::EnrollmentNotEmbedded(5, "out of memory".to_string()),
+        None,
+    );
+    assert!(!page.is_saving);
+    assert!(page.save_success);
+}
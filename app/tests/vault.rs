@@ -0,0 +1,158 @@
+//! Checks sealing at rest: round trips, wrong and rotated keys, tampering,
+//! unsealed data and what happens when the key file is missing.
+
+mod common;
+
+use app::config::EncryptionConfig;
+use app::vault::{self, Vault, SEALED_MAGIC};
+use common::temp_dir;
+use std::path::Path;
+
+const PHOTO: &[u8] = b"\xFF\xD8\xFF\xE0 jpeg bytes of a suspect photo";
+
+fn key_file(dir: &Path) -> EncryptionConfig {
+    EncryptionConfig {
+        key_file: dir.join("data.keys"),
+        keyring: false,
+    }
+}
+
+#[test]
+fn sealed_data_opens_to_the_original() {
+    let keys = Vault::generate();
+    let sealed = keys.seal(PHOTO);
+
+    assert!(vault::is_sealed(&sealed));
+    assert!(sealed.starts_with(SEALED_MAGIC));
+    assert!(!sealed.windows(PHOTO.len()).any(|w| w == PHOTO));
+    assert_eq!(keys.open(sealed).unwrap(), PHOTO);
+    // A fresh nonce every time
+    assert_ne!(keys.seal(PHOTO), keys.seal(PHOTO));
+}
+
+#[test]
+fn another_key_cannot_open_it() {
+    let sealed = Vault::generate().seal(PHOTO);
+    let error = Vault::generate().open(sealed).unwrap_err();
+    assert!(error.to_string().contains("not configured"), "{error}");
+}
+
+#[test]
+fn tampered_data_is_refused() {
+    let keys = Vault::generate();
+    let sealed = keys.seal(PHOTO);
+
+    let mut flipped = sealed.clone();
+    *flipped.last_mut().unwrap() ^= 1;
+    assert!(keys.open(flipped).is_err());
+
+    let mut truncated = sealed.clone();
+    truncated.truncate(sealed.len() - 1);
+    assert!(keys.open(truncated).is_err());
+}
+
+#[test]
+fn unsealed_data_is_refused_unless_allowed() {
+    let keys = Vault::generate();
+    assert!(keys.open(PHOTO.to_vec()).is_err());
+    assert_eq!(keys.open_allowing_plaintext(PHOTO.to_vec()).unwrap(), PHOTO);
+
+    let sealed = keys.seal(PHOTO);
+    assert_eq!(keys.open_allowing_plaintext(sealed).unwrap(), PHOTO);
+}
+
+#[test]
+fn rotation_seals_with_the_new_key_and_still_opens_old_data() {
+    let old = Vault::generate();
+    let before = old.seal(PHOTO);
+
+    let rotated = old.rotated();
+    assert_eq!(rotated.key_ids().len(), 2);
+    assert_eq!(rotated.key_ids()[1], old.key_ids()[0]);
+    assert_ne!(rotated.key_ids()[0], old.key_ids()[0]);
+
+    // Old data opens with the key its id names, new data uses the current key
+    assert!(!rotated.is_current(&before));
+    assert_eq!(rotated.open(before.clone()).unwrap(), PHOTO);
+    let after = rotated.seal(PHOTO);
+    assert!(rotated.is_current(&after));
+    assert!(old.open(after.clone()).is_err());
+
+    // Once everything is resealed only the new key is kept
+    let current = rotated.current_only();
+    assert_eq!(current.key_ids(), rotated.key_ids()[..1]);
+    assert_eq!(current.open(after).unwrap(), PHOTO);
+    assert!(current.open(before).is_err());
+}
+
+#[test]
+fn reseal_moves_everything_to_the_current_key() {
+    let old = Vault::generate();
+    let rotated = old.rotated();
+
+    let current = rotated.seal(PHOTO);
+    assert_eq!(rotated.reseal(current).unwrap(), None);
+
+    // Under the old key, and stored before encryption was on
+    for stored in [old.seal(PHOTO), PHOTO.to_vec()] {
+        let resealed = rotated.reseal(stored).unwrap().unwrap();
+        assert!(rotated.is_current(&resealed));
+        assert_eq!(rotated.current_only().open(resealed).unwrap(), PHOTO);
+    }
+
+    // Sealed with a key that's gone fails instead of being sealed again
+    assert!(rotated.reseal(Vault::generate().seal(PHOTO)).is_err());
+}
+
+#[test]
+fn keys_survive_the_key_file() {
+    let keys = Vault::generate().rotated();
+    let parsed = Vault::parse(&format!("# data keys\n\n{}", keys.to_text())).unwrap();
+    assert_eq!(parsed.key_ids(), keys.key_ids());
+    assert_eq!(parsed.open(keys.seal(PHOTO)).unwrap(), PHOTO);
+
+    assert!(Vault::parse("").is_err());
+    assert!(Vault::parse("abcd").is_err());
+}
+
+#[test]
+fn a_missing_key_is_created_only_when_nothing_is_sealed() {
+    let dir = temp_dir("vault", "load");
+    let config = key_file(&dir);
+
+    let error = Vault::load(&config, || Ok(true)).unwrap_err();
+    assert!(error.contains("restore the keys"), "{error}");
+    let error = Vault::load(&config, || Err("database offline".to_string())).unwrap_err();
+    assert!(error.contains("database offline"), "{error}");
+    assert!(!config.key_file.exists());
+
+    let created = Vault::load(&config, || Ok(false)).unwrap().unwrap();
+    assert!(config.key_file.exists());
+    // Found next time, without checking again
+    let loaded = Vault::load(&config, || panic!("the key file exists"))
+        .unwrap()
+        .unwrap();
+    assert_eq!(loaded.key_ids(), created.key_ids());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn sealed_files_are_found_in_nested_folders() {
+    let dir = temp_dir("vault", "files");
+    assert!(!vault::holds_sealed_files(&dir).unwrap());
+
+    std::fs::create_dir_all(dir.join("entry")).unwrap();
+    std::fs::write(dir.join("plain.jpg"), PHOTO).unwrap();
+    std::fs::write(dir.join("short"), b"CF").unwrap();
+    assert!(!vault::holds_sealed_files(&dir).unwrap());
+
+    std::fs::write(
+        dir.join("entry").join("photo_0.jpg"),
+        Vault::generate().seal(PHOTO),
+    )
+    .unwrap();
+    assert!(vault::holds_sealed_files(&dir).unwrap());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
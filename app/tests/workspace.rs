@@ -0,0 +1,64 @@
+//! Checks that the legacy temp image cleanup looks where older builds wrote,
+//! deletes only the names they used, and stops once they are gone.
+
+mod common;
+
+use app::workspace::Workspace;
+use common::temp_dir;
+
+#[test]
+fn legacy_files_are_removed_by_name_once() {
+    let dir = temp_dir("workspace", "legacy");
+    let (root, source, working) = (dir.join("root"), dir.join("app"), dir.join("cwd"));
+    std::fs::create_dir_all(source.join("temp")).unwrap();
+    std::fs::create_dir_all(working.join("temp_identify")).unwrap();
+    for file in [
+        source.join("temp/current_scan.jpg"),
+        working.join("temp_identify/suspect_12_0.jpg"),
+        working.join("temp_identify/suspect_12_1.jpg"),
+        // Not names older builds wrote
+        working.join("temp_identify/suspect_notes.txt"),
+        working.join("temp_identify/suspect_x_1.jpg"),
+        source.join("notes.jpg"),
+    ] {
+        std::fs::write(file, b"jpeg").unwrap();
+    }
+
+    let workspace = Workspace::new(&root);
+    assert_eq!(workspace.remove_legacy_files_from(&source, &working), 3);
+    assert!(!source.join("temp").exists());
+    assert!(working.join("temp_identify/suspect_notes.txt").exists());
+    assert!(working.join("temp_identify/suspect_x_1.jpg").exists());
+    assert!(source.join("notes.jpg").exists());
+
+    // Once done, a new frame by the old name is left alone
+    std::fs::create_dir_all(source.join("temp")).unwrap();
+    std::fs::write(source.join("temp/current_scan.jpg"), b"jpeg").unwrap();
+    let workspace = Workspace::new(&root);
+    assert_eq!(workspace.remove_legacy_files_from(&source, &working), 0);
+    assert!(source.join("temp/current_scan.jpg").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
+
+#[test]
+fn photos_left_behind_are_tried_again() {
+    let dir = temp_dir("workspace", "retry");
+    let (root, source, working) = (dir.join("root"), dir.join("app"), dir.join("cwd"));
+    // Legacy names in the workspace root aren't where older builds wrote
+    std::fs::create_dir_all(root.join("temp")).unwrap();
+    std::fs::write(root.join("temp/current_scan.jpg"), b"jpeg").unwrap();
+    // Can't be deleted as a file, so the first pass leaves it
+    std::fs::create_dir_all(working.join("temp_identify/suspect_3_0.jpg")).unwrap();
+
+    let workspace = Workspace::new(&root);
+    assert_eq!(workspace.remove_legacy_files_from(&source, &working), 0);
+    assert!(root.join("temp/current_scan.jpg").exists());
+
+    std::fs::remove_dir(working.join("temp_identify/suspect_3_0.jpg")).unwrap();
+    std::fs::write(working.join("temp_identify/suspect_3_0.jpg"), b"jpeg").unwrap();
+    assert_eq!(workspace.remove_legacy_files_from(&source, &working), 1);
+    assert!(!working.join("temp_identify").exists());
+
+    let _ = std::fs::remove_dir_all(&dir);
+}
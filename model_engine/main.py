@@ -1,85 +1,234 @@
+import base64
 import insightface
 import numpy as np
 import cv2
 import logging
 import contextlib
+import hashlib
 import os
 from sklearn.cluster import KMeans
 import sys
+import tempfile
 import time
+from urllib.parse import quote, unquote
 
 time.sleep(0.1)
 logger = logging.getLogger(__name__)
 
+# Files sealed by the app (app/src/vault.rs): magic, key id, nonce, AES-GCM ciphertext
+SEALED_MAGIC = b"CFE1"
+KEY_ID_LEN = 4
+NONCE_LEN = 12
+
+
+def load_data_keys():
+    """
+    (key id, key) pairs from the "keys <hex,...>" line the app writes first on
+    stdin, current first. Empty ("keys -") when the app runs without
+    encryption. The keys never pass through the environment, where other
+    processes could read them.
+    """
+    parts = sys.stdin.readline().split()
+    if len(parts) != 2 or parts[0] != "keys":
+        sys.exit("expected the keys handshake as the first line on stdin")
+    keys = []
+    if parts[1] != "-":
+        for text in parts[1].split(","):
+            key = bytes.fromhex(text)
+            keys.append((hashlib.sha256(key).digest()[:KEY_ID_LEN], key))
+    return keys
+
+
+DATA_KEYS = load_data_keys()
+if DATA_KEYS:
+    from cryptography.hazmat.primitives.ciphers.aead import AESGCM
+
 
 def cosine_similarity(a, b):
     return np.dot(a, b) / (np.linalg.norm(a) * np.linalg.norm(b))
 
 
+def encode_embedding(emb):
+    """
+    Embeddings travel to and from the Rust side as base64 of little-endian f32.
+    """
+    return base64.b64encode(np.asarray(emb, dtype="<f4").tobytes()).decode("ascii")
+
+
+def encode_faces(faces):
+    """
+    "x1,y1,x2,y2,score,lx0,ly0,...,lx4,ly4" per face, joined with ";",
+    or "-" when there are none.
+    """
+    if not faces:
+        return "-"
+
+    encoded = []
+    for face in faces:
+        values = list(face.bbox) + [face.det_score]
+        if face.kps is not None:
+            values += list(np.asarray(face.kps).flatten())
+        else:
+            values += [0.0] * 10
+        encoded.append(",".join(f"{float(v):.2f}" for v in values))
+    return ";".join(encoded)
+
+
+def box_iou(a, b):
+    x1, y1 = max(a[0], b[0]), max(a[1], b[1])
+    x2, y2 = min(a[2], b[2]), min(a[3], b[3])
+    inter = max(0.0, x2 - x1) * max(0.0, y2 - y1)
+    area_a = max(0.0, a[2] - a[0]) * max(0.0, a[3] - a[1])
+    area_b = max(0.0, b[2] - b[0]) * max(0.0, b[3] - b[1])
+    union = area_a + area_b - inter
+    return inter / union if union > 0 else 0.0
+
+
+def pick_face(faces, face_box):
+    """
+    The face overlapping `face_box` best, or the first face when no box was
+    chosen. None if the box matches nothing.
+    """
+    if face_box is None:
+        return faces[0]
+
+    best = max(faces, key=lambda f: box_iou(f.bbox, face_box))
+    return best if box_iou(best.bbox, face_box) > 0.3 else None
+
+
+def open_sealed(data):
+    """
+    The plaintext of a sealed file's bytes; anything else comes back as is.
+    """
+    if not data.startswith(SEALED_MAGIC):
+        return data
+    key_id = data[len(SEALED_MAGIC) : len(SEALED_MAGIC) + KEY_ID_LEN]
+    header = len(SEALED_MAGIC) + KEY_ID_LEN + NONCE_LEN
+    for known_id, key in DATA_KEYS:
+        if known_id == key_id:
+            nonce = data[header - NONCE_LEN : header]
+            return AESGCM(key).decrypt(nonce, data[header:], None)
+    raise ValueError(f"sealed with unknown key {key_id.hex()}")
+
+
+def seal(data):
+    """
+    `data` sealed with the current key, or unchanged without encryption.
+    """
+    if not DATA_KEYS:
+        return data
+    key_id, key = DATA_KEYS[0]
+    nonce = os.urandom(NONCE_LEN)
+    return SEALED_MAGIC + key_id + nonce + AESGCM(key).encrypt(nonce, data, None)
+
+
+def read_image(path):
+    """
+    cv2.imread that also opens sealed temp images. None if unreadable.
+    """
+    try:
+        with open(path, "rb") as f:
+            data = open_sealed(f.read())
+    except Exception as e:
+        logger.error("Failed to read %s: %s", path, e)
+        return None
+    return cv2.imdecode(np.frombuffer(data, dtype=np.uint8), cv2.IMREAD_COLOR)
+
+
+def save_frame(frame):
+    """
+    Writes a video frame to a temporary JPEG (sealed with encryption on) and
+    returns its path, or None.
+    """
+    ok, jpeg = cv2.imencode(".jpg", frame)
+    if not ok:
+        return None
+    # The app's workspace deletes it once the match is no longer shown
+    workspace = os.environ.get("CFR_WORKSPACE") or None
+    if workspace:
+        os.makedirs(workspace, exist_ok=True)
+    fd, path = tempfile.mkstemp(prefix="probe_frame_", suffix=".jpg", dir=workspace)
+    with os.fdopen(fd, "wb") as f:
+        f.write(seal(jpeg.tobytes()))
+    return path
+
+
+def decode_path(field):
+    """ A path field: "%", "&" and whitespace arrive percent-encoded. """
+    return unquote(field)
+
+
+def encode_path(path):
+    return quote(path, safe="/\\:")
+
+
+def parse_box(text):
+    """ "x1,y1,x2,y2" or "-" for none. """
+    if text in ("", "-"):
+        return None
+    return [float(v) for v in text.split(",")]
+
+
+def decode_embedding(text):
+    return np.frombuffer(base64.b64decode(text), dtype="<f4").astype(np.float32)
+
+
 class FaceRecognizer:
     def __init__(
         self,
-        db_path="faces_db.npz",
+        model_name="buffalo_l",
         max_embeddings_per_person=10,
         max_centroids=3,
     ):
-        self.db_path = db_path
+        self.model_name = model_name
         self.max_embeddings = max_embeddings_per_person
         self.max_centroids = max_centroids
 
+        # Overridden by the app's [matching] config through "thresholds"
+        self.threshold = 0.4
+        self.video_min_hits = 5
+        self.video_confirm_score = 0.6
+
         print("Initializing AI models (silently)...", flush=True)
         with open(os.devnull, "w") as fnull:
             with contextlib.redirect_stdout(fnull), contextlib.redirect_stderr(fnull):
                 self.app = insightface.app.FaceAnalysis(
-                    name="buffalo_l", providers=["CUDAExecutionProvider"]
+                    name=self.model_name, providers=["CUDAExecutionProvider"]
                 )
                 self.app.prepare(ctx_id=0, det_size=(640, 640))
 
         # name -> List[np.ndarray]
+        # The gallery is owned by the Rust side (face_embeddings table) and
+        # pushed in with gallery_add; nothing is persisted here.
         self.embeddings = {}
 
         # name -> np.ndarray (centroids or average)
         self.representations = {}
 
-        self.load_db()
-
     # =========================
-    # Persistence
+    # Gallery sync
     # =========================
 
-    def load_db(self):
-        if not os.path.exists(self.db_path):
-            logger.info("No local face DB found. Starting fresh.")
-            return
-
-        logger.info("Loading face DB.")
-        data = np.load(self.db_path, allow_pickle=True)
-
+    def gallery_clear(self):
         self.embeddings = {}
+        self.representations = {}
 
-        for name in data.files:
-            arr = data[name]
-
-            # 🔒 Normalize old DB formats
-            if arr.ndim == 1:
-                # Old format: single embedding
-                self.embeddings[name] = [arr.astype(np.float32)]
-            elif arr.ndim == 2:
-                # New format: multiple embeddings
-                self.embeddings[name] = [
-                    arr[i].astype(np.float32) for i in range(arr.shape[0])
-                ]
-            else:
-                logger.warning("Invalid embedding shape for %s: %s", name, arr.shape)
+    def gallery_add(self, name, model_name, emb):
+        if model_name != self.model_name:
+            logger.warning(
+                "Skipping %s embedding for %s (engine runs %s)",
+                model_name,
+                name,
+                self.model_name,
+            )
+            return False
 
-        self._rebuild_representations()
+        self.embeddings.setdefault(name, []).append(emb)
+        return True
 
-    def save_db(self):
+    def gallery_commit(self):
         self._rebuild_representations()
-        np.savez(
-            self.db_path,
-            **{name: np.stack(embs) for name, embs in self.embeddings.items()},
-        )
+        return sum(len(embs) for embs in self.embeddings.values())
 
     # =========================
     # Internal helpers
@@ -131,18 +280,28 @@ class FaceRecognizer:
     # Public API
     # =========================
 
-    def add_person(self, name, image_path):
-        img = cv2.imread(image_path)
+    def add_person(self, name, image_path, face_box=None):
+        """
+        Returns the new embedding so the caller can hand it to the Rust side,
+        or None when the photo is unreadable or has no face. `face_box` is
+        the operator's pick when the photo shows several people.
+        """
+        img = read_image(image_path)
         if img is None:
             logger.error("Failed to load image: %s", image_path)
-            return False
+            return None
 
         faces = self.app.get(img)
         if not faces:
             logger.warning("No face detected in %s", image_path)
-            return False
+            return None
 
-        emb = faces[0].embedding
+        face = pick_face(faces, face_box)
+        if face is None:
+            logger.warning("Selected face not found in %s", image_path)
+            return None
+
+        emb = face.embedding
 
         if name not in self.embeddings:
             self.embeddings[name] = []
@@ -151,10 +310,39 @@ class FaceRecognizer:
         self._prune_embeddings(name)
         print("ADDED PERSON", flush=True)
 
-        return True
+        return emb
+
+    def embed(self, image_path, face_box=None):
+        """
+        Embedding of the first face in the image (or the one at `face_box`),
+        or None. Matching against the gallery happens on the Rust side.
+        """
+        img = read_image(image_path)
+        if img is None:
+            return None
+
+        faces = self.app.get(img)
+        if not faces:
+            return None
 
-    def identify(self, image_path, threshold=0.4):
-        img = cv2.imread(image_path)
+        face = pick_face(faces, face_box)
+        return face.embedding if face is not None else None
+
+    def analyze(self, image_path):
+        """
+        Every detected face, for enrollment quality checks. None if the
+        image can't be read.
+        """
+        img = read_image(image_path)
+        if img is None:
+            return None
+
+        return self.app.get(img)
+
+    def identify(self, image_path, threshold=None):
+        if threshold is None:
+            threshold = self.threshold
+        img = read_image(image_path)
         if img is None:
             return None, 0
 
@@ -179,19 +367,25 @@ class FaceRecognizer:
     def identify_from_video(
         self,
         video_path,
-        threshold=0.4,
+        threshold=None,
         frame_skip=5,
         max_frames=300,
     ):
+        if threshold is None:
+            threshold = self.threshold
+        # None when the video can't be read, unlike a scan without a match
         if not os.path.exists(video_path):
             logger.error("Video not found: %s", video_path)
-            return None, 0
+            return None
 
         cap = cv2.VideoCapture(video_path)
         if not cap.isOpened():
-            return None, 0
+            logger.error("Video can't be decoded: %s", video_path)
+            return None
 
         matches = {}
+        # Best-scoring frame per person, shown next to the match for verification
+        best_frames = {}
         frame_count = 0
         processed = 0
 
@@ -219,29 +413,33 @@ class FaceRecognizer:
                         sim = cosine_similarity(emb, rep)
                         if sim >= threshold:
                             matches.setdefault(name, []).append(sim)
+                            if sim > best_frames.get(name, (0, None))[0]:
+                                best_frames[name] = (sim, frame.copy())
 
             # Early strong exit
             for name, sims in matches.items():
-                if len(sims) >= 5 and np.mean(sims) > 0.6:
+                if (
+                    len(sims) >= self.video_min_hits
+                    and np.mean(sims) > self.video_confirm_score
+                ):
                     cap.release()
-                    return name, float(np.mean(sims))
+                    return name, float(np.mean(sims)), save_frame(best_frames[name][1])
 
         cap.release()
 
         if not matches:
-            return None, 0
+            return None, 0, None
 
         best_name = max(matches, key=lambda k: np.mean(matches[k]))
         best_score = float(np.mean(matches[best_name]))
 
-        return best_name, best_score
+        return best_name, best_score, save_frame(best_frames[best_name][1])
 
 
 # print("Adding brad...".upper())
 # fr.add_person("morgan", "morgan_1.jpg")
 # fr.add_person("brad", "brad_pitt.webp")
 # fr.add_person("morgan", "morgan_2.jpg")
-# fr.save_db()
 # print("Attempting to identify")
 # print(fr.identify("morgan_1.jpg"))
 
@@ -279,13 +477,19 @@ for line in sys.stdin:
                 continue
 
             media_type = recv_msg[1]
-            media_path = recv_msg[2]
+            media_path = decode_path(recv_msg[2])
 
             if media_type == "image":
                 criminal_id, confidence = fr.identify(media_path)
             elif media_type == "video":
                 print("media_path is ", media_path, flush=True)
-                criminal_id, confidence = fr.identify_from_video(media_path)
+                result = fr.identify_from_video(media_path)
+                if result is None:
+                    print("error unreadable_video", flush=True)
+                    continue
+                criminal_id, confidence, frame_path = result
+                if frame_path:
+                    print(f"probe_frame {encode_path(frame_path)}", flush=True)
             else:
                 print("error unknown_media_type", flush=True)
                 continue
@@ -300,13 +504,68 @@ for line in sys.stdin:
                 continue
 
             criminal_id = recv_msg[1]
-            photo_locations = recv_msg[2].split("&")
+            # Each entry is "{photo_id}:{x1,y1,x2,y2 or -}:{path}"
+            photo_entries = recv_msg[2].split("&")
+
+            for entry in photo_entries:
+                photo_id, _, rest = entry.partition(":")
+                face_box, _, loc = rest.partition(":")
+                emb = fr.add_person(criminal_id, decode_path(loc), parse_box(face_box))
+                if emb is not None:
+                    print(
+                        f"embedding {criminal_id} {photo_id} {fr.model_name} "
+                        f"{encode_embedding(emb)}",
+                        flush=True,
+                    )
+
+            fr.gallery_commit()
+            print(f"added {criminal_id}", flush=True)
 
-            for loc in photo_locations:
-                fr.add_person(criminal_id, loc)
+        elif cmd == "embed":
+            if len(recv_msg) < 3:
+                print("error missing_path", flush=True)
+                continue
 
-            fr.save_db()
-            print(f"added {criminal_id}", flush=True)
+            tag = recv_msg[1]
+            face_box = parse_box(recv_msg[3]) if len(recv_msg) > 3 else None
+            emb = fr.embed(decode_path(recv_msg[2]), face_box)
+            out = encode_embedding(emb) if emb is not None else "none"
+            print(f"probe {tag} {out}", flush=True)
+
+        elif cmd == "analyze":
+            if len(recv_msg) < 3:
+                print("error missing_path", flush=True)
+                continue
+
+            tag = recv_msg[1]
+            faces = fr.analyze(decode_path(recv_msg[2]))
+            if faces is None:
+                print(f"error unreadable_image {tag}", flush=True)
+                faces = []
+            print(f"faces {tag} {len(faces)} {encode_faces(faces)}", flush=True)
+
+        elif cmd == "thresholds":
+            if len(recv_msg) < 4:
+                print("error missing_thresholds", flush=True)
+                continue
+
+            fr.threshold = float(recv_msg[1])
+            fr.video_min_hits = int(recv_msg[2])
+            fr.video_confirm_score = float(recv_msg[3])
+
+        elif cmd == "gallery_clear":
+            fr.gallery_clear()
+
+        elif cmd == "gallery_add":
+            if len(recv_msg) < 4:
+                print("error missing_gallery_data", flush=True)
+                continue
+
+            fr.gallery_add(recv_msg[1], recv_msg[2], decode_embedding(recv_msg[3]))
+
+        elif cmd == "gallery_commit":
+            count = fr.gallery_commit()
+            print(f"gallery_ready {count}", flush=True)
 
         elif cmd == "exit":
             break
@@ -315,4 +574,8 @@ for line in sys.stdin:
             print(f"info ignored_command {cmd}", flush=True)
 
     except Exception as e:
-        print(f"error {str(e)}", flush=True)
+        # Names the command and its tag (or record id) so the app fails only
+        # that request; one line, whatever the message holds
+        target = recv_msg[1] if len(recv_msg) > 1 else "-"
+        reason = " ".join(str(e).split()) or type(e).__name__
+        print(f"error {cmd} {target} {reason}", flush=True)
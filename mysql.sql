@@ -4,9 +4,17 @@ CREATE TABLE criminals (
     fathers_name VARCHAR(255) DEFAULT NULL,
     date_of_arrest DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
     last_seen DATETIME DEFAULT NULL,
-    no_of_crimes INT(10) UNSIGNED NOT NULL DEFAULT 1,
+    no_of_crimes INT(10) UNSIGNED NOT NULL DEFAULT 0, -- number of offences rows
     arrested_location VARCHAR(255) DEFAULT NULL,
-    PRIMARY KEY (criminal_id)
+    date_of_birth DATE DEFAULT NULL,
+    gender VARCHAR(16) DEFAULT NULL,
+    national_ids VARCHAR(255) DEFAULT NULL,
+    height_cm SMALLINT(5) UNSIGNED DEFAULT NULL,
+    distinguishing_marks TEXT DEFAULT NULL,
+    notes TEXT DEFAULT NULL,
+    PRIMARY KEY (criminal_id),
+    KEY name (name),
+    KEY national_ids (national_ids)
 ) ENGINE=InnoDB
 DEFAULT CHARSET=utf8mb4
 COLLATE=utf8mb4_unicode_ci;
@@ -14,9 +22,17 @@ COLLATE=utf8mb4_unicode_ci;
 CREATE TABLE criminal_photos (
     photo_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
     criminal_id INT(10) UNSIGNED NOT NULL,
-    photo LONGBLOB NOT NULL,
+    photo LONGBLOB NULL, -- NULL when kept in the photo store
+    photo_hash CHAR(64) NULL, -- SHA-256, the file name in the photo store
+    photo_size INT(10) UNSIGNED NULL,
+    thumbnail MEDIUMBLOB NULL, -- small JPEG, filled in on first use for older photos
+    face_x1 FLOAT NULL,
+    face_y1 FLOAT NULL,
+    face_x2 FLOAT NULL,
+    face_y2 FLOAT NULL,
     PRIMARY KEY (photo_id),
     KEY criminal_id (criminal_id),
+    KEY photo_hash (photo_hash),
     CONSTRAINT fk_criminal_photos_criminals
         FOREIGN KEY (criminal_id)
         REFERENCES criminals (criminal_id)
@@ -39,3 +55,216 @@ END$$
 
 DELIMITER ;
 
+
+-- One row per registered photo the engine found a face in. Photos enrolled
+-- before this table existed get theirs with
+--   cargo run --release --bin backfill_embeddings
+CREATE TABLE face_embeddings (
+    embedding_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    photo_id INT(10) UNSIGNED NOT NULL,
+    criminal_id INT(10) UNSIGNED NOT NULL,
+    model_name VARCHAR(64) NOT NULL,
+    vector BLOB NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (embedding_id),
+    KEY photo_id (photo_id),
+    KEY criminal_id (criminal_id),
+    CONSTRAINT fk_face_embeddings_photos
+        FOREIGN KEY (photo_id)
+        REFERENCES criminal_photos (photo_id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_face_embeddings_criminals
+        FOREIGN KEY (criminal_id)
+        REFERENCES criminals (criminal_id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;
+
+
+CREATE TABLE audit_log (
+    audit_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    action VARCHAR(64) NOT NULL,
+    criminal_id INT(10) UNSIGNED NULL,
+    related_criminal_id INT(10) UNSIGNED NULL,
+    details TEXT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (audit_id),
+    KEY criminal_id (criminal_id),
+    KEY related_criminal_id (related_criminal_id)
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;
+
+
+CREATE TABLE sightings (
+    sighting_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    criminal_id INT(10) UNSIGNED NOT NULL,
+    source VARCHAR(16) NOT NULL,
+    score FLOAT NULL,
+    seen_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (sighting_id),
+    KEY criminal_id (criminal_id),
+    CONSTRAINT fk_sightings_criminals
+        FOREIGN KEY (criminal_id)
+        REFERENCES criminals (criminal_id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;
+
+
+CREATE TABLE criminal_aliases (
+    alias_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    criminal_id INT(10) UNSIGNED NOT NULL,
+    alias VARCHAR(255) NOT NULL,
+    PRIMARY KEY (alias_id),
+    KEY criminal_id (criminal_id),
+    KEY alias (alias),
+    CONSTRAINT fk_criminal_aliases_criminals
+        FOREIGN KEY (criminal_id)
+        REFERENCES criminals (criminal_id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;
+
+
+CREATE TABLE offence_types (
+    offence_type_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    name VARCHAR(128) NOT NULL,
+    PRIMARY KEY (offence_type_id),
+    UNIQUE KEY name (name)
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;
+
+INSERT INTO offence_types (name) VALUES
+    ('Theft'), ('Burglary'), ('Robbery'), ('Assault'), ('Fraud'),
+    ('Drug Offence'), ('Homicide'), ('Kidnapping'), ('Cybercrime'), ('Vandalism');
+
+
+CREATE TABLE offences (
+    offence_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    criminal_id INT(10) UNSIGNED NOT NULL,
+    offence_type_id INT(10) UNSIGNED NOT NULL,
+    offence_date DATE DEFAULT NULL,
+    location VARCHAR(255) DEFAULT NULL,
+    case_number VARCHAR(64) DEFAULT NULL,
+    status VARCHAR(32) NOT NULL DEFAULT 'open',
+    PRIMARY KEY (offence_id),
+    KEY criminal_id (criminal_id),
+    KEY case_number (case_number),
+    CONSTRAINT fk_offences_criminals
+        FOREIGN KEY (criminal_id)
+        REFERENCES criminals (criminal_id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_offences_offence_types
+        FOREIGN KEY (offence_type_id)
+        REFERENCES offence_types (offence_type_id)
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;
+
+
+CREATE TABLE watchlists (
+    watchlist_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    name VARCHAR(128) NOT NULL,
+    priority VARCHAR(16) NOT NULL DEFAULT 'medium',
+    description TEXT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (watchlist_id),
+    UNIQUE KEY name (name)
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;
+
+
+CREATE TABLE watchlist_members (
+    member_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    watchlist_id INT(10) UNSIGNED NOT NULL,
+    criminal_id INT(10) UNSIGNED NOT NULL,
+    reason VARCHAR(255) DEFAULT NULL,
+    added_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (member_id),
+    UNIQUE KEY watchlist_criminal (watchlist_id, criminal_id),
+    KEY criminal_id (criminal_id),
+    CONSTRAINT fk_watchlist_members_watchlists
+        FOREIGN KEY (watchlist_id)
+        REFERENCES watchlists (watchlist_id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_watchlist_members_criminals
+        FOREIGN KEY (criminal_id)
+        REFERENCES criminals (criminal_id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;
+
+
+CREATE TABLE alerts (
+    alert_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    criminal_id INT(10) UNSIGNED NOT NULL,
+    watchlist_id INT(10) UNSIGNED NOT NULL,
+    source VARCHAR(16) NOT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    acknowledged_at DATETIME NULL,
+    acknowledgement_note TEXT NULL,
+    PRIMARY KEY (alert_id),
+    KEY criminal_id (criminal_id),
+    KEY acknowledged_at (acknowledged_at),
+    CONSTRAINT fk_alerts_criminals
+        FOREIGN KEY (criminal_id)
+        REFERENCES criminals (criminal_id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_alerts_watchlists
+        FOREIGN KEY (watchlist_id)
+        REFERENCES watchlists (watchlist_id)
+        ON DELETE CASCADE
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;
+
+
+CREATE TABLE notification_deliveries (
+    delivery_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    channel VARCHAR(16) NOT NULL,
+    target VARCHAR(1024) NOT NULL,
+    criminal_id INT(10) UNSIGNED NOT NULL,
+    attempt INT(10) UNSIGNED NOT NULL,
+    status VARCHAR(16) NOT NULL,
+    error TEXT NULL,
+    attempted_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (delivery_id),
+    KEY criminal_id (criminal_id),
+    KEY status (status)
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;
+
+
+CREATE TABLE identifications (
+    identification_id INT(10) UNSIGNED NOT NULL AUTO_INCREMENT,
+    criminal_id INT(10) UNSIGNED NOT NULL,
+    photo_id INT(10) UNSIGNED NULL,
+    source VARCHAR(16) NOT NULL,
+    score FLOAT NULL,
+    probe LONGBLOB NULL,
+    status VARCHAR(16) NOT NULL DEFAULT 'pending',
+    comment TEXT NULL,
+    created_at DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    reviewed_at DATETIME NULL,
+    PRIMARY KEY (identification_id),
+    KEY criminal_id (criminal_id),
+    KEY status (status),
+    CONSTRAINT fk_identifications_criminals
+        FOREIGN KEY (criminal_id)
+        REFERENCES criminals (criminal_id)
+        ON DELETE CASCADE,
+    CONSTRAINT fk_identifications_photos
+        FOREIGN KEY (photo_id)
+        REFERENCES criminal_photos (photo_id)
+        ON DELETE SET NULL
+) ENGINE=InnoDB
+DEFAULT CHARSET=utf8mb4
+COLLATE=utf8mb4_unicode_ci;